@@ -583,3 +583,14 @@ pub static LOGS: [&sct::Log; 27] = [
     },
 
 ];
+
+/// Logs in `LOGS` which are no longer trusted.  These are the logs
+/// with a `final_sth`: they are frozen, and SCTs they issued after the
+/// final STH's timestamp don't count towards a `sct::Policy`.
+pub static DISQUALIFIED_LOGS: [sct::DisqualifiedLog; 1] = [
+    // Google 'Aviator' log
+    sct::DisqualifiedLog {
+        id: [ 0x68, 0xf6, 0x98, 0xf8, 0x1f, 0x64, 0x82, 0xbe, 0x3a, 0x8c, 0xee, 0xb9, 0x28, 0x1d, 0x4c, 0xfc, 0x71, 0x51, 0x5d, 0x67, 0x93, 0xd4, 0x44, 0xd1, 0x0a, 0x67, 0xac, 0xbb, 0x4f, 0x4f, 0xfb, 0xc4 ],
+        disqualified_at: 1480512258330,
+    },
+];
@@ -163,8 +163,9 @@
 //! [`Builder`]: struct.Builder.html
 //! [`Error`]: ../struct.Error.html
 
-use {SendStream, RecvStream, ReleaseCapacity};
+use {SendStream, RecvStream, ReleaseCapacity, Stats};
 use codec::{Codec, RecvError, SendError, UserError};
+use ext::Protocol;
 use frame::{Headers, Pseudo, Reason, Settings, StreamId};
 use proto;
 
@@ -580,6 +581,27 @@ where
                 (response, stream)
             })
     }
+
+    /// Returns whether the [extended CONNECT protocol][1] is enabled or not.
+    ///
+    /// This setting is configured by the server peer by sending the
+    /// [`SETTINGS_ENABLE_CONNECT_PROTOCOL` parameter][2] in a `SETTINGS`
+    /// frame. Until the server's initial `SETTINGS` frame has been processed
+    /// by the [`Connection`], this returns `false`.
+    ///
+    /// When enabled, requests may carry a [`Protocol`] extension to open an
+    /// extended CONNECT stream. The resulting [`SendStream`] and response
+    /// body may be combined into a [`Tunnel`] for byte-oriented I/O.
+    ///
+    /// [1]: https://datatracker.ietf.org/doc/html/rfc8441#section-4
+    /// [2]: https://datatracker.ietf.org/doc/html/rfc8441#section-3
+    /// [`Connection`]: struct.Connection.html
+    /// [`Protocol`]: ../ext/struct.Protocol.html
+    /// [`SendStream`]: ../struct.SendStream.html
+    /// [`Tunnel`]: ../struct.Tunnel.html
+    pub fn is_extended_connect_protocol_enabled(&self) -> bool {
+        self.inner.is_extended_connect_protocol_enabled()
+    }
 }
 
 impl<B> fmt::Debug for SendRequest<B>
@@ -1243,6 +1265,18 @@ where
         assert!(size <= proto::MAX_WINDOW_SIZE);
         self.inner.set_target_window_size(size);
     }
+
+    /// Returns a snapshot of the connection's state.
+    ///
+    /// The snapshot includes stream counts, cumulative frame and byte
+    /// counters, the connection level flow control windows, the lengths of
+    /// the internal capacity queues, `GOAWAY` state and HPACK table usage.
+    /// See [`Stats`] for details on each value.
+    ///
+    /// [`Stats`]: ../struct.Stats.html
+    pub fn stats(&self) -> Stats {
+        self.inner.stats()
+    }
 }
 
 impl<T, B> Future for Connection<T, B>
@@ -1380,6 +1414,7 @@ impl Peer {
                 uri,
                 headers,
                 version,
+                mut extensions,
                 ..
             },
             _,
@@ -1391,6 +1426,16 @@ impl Peer {
         // and `path`.
         let mut pseudo = Pseudo::request(method, uri);
 
+        // The `:protocol` pseudo-header is only valid on extended CONNECT
+        // requests (RFC 8441).
+        if let Some(protocol) = extensions.remove::<Protocol>() {
+            if !is_connect {
+                return Err(UserError::MalformedHeaders.into());
+            }
+
+            pseudo.set_protocol(protocol);
+        }
+
         if pseudo.scheme.is_none() {
             // If the scheme is not set, then there are a two options.
             //
@@ -54,6 +54,10 @@ pub enum UserError {
 
     /// Calls `SendResponse::poll_reset` after having called `send_response`.
     PollResetAfterSendResponse,
+
+    /// Request submitted with a `:protocol` pseudo-header, but the peer has
+    /// not enabled the extended CONNECT protocol.
+    ExtendedConnectProtocolDisabled,
 }
 
 // ===== impl RecvError =====
@@ -134,6 +138,7 @@ impl error::Error for UserError {
             MalformedHeaders => "malformed headers",
             MissingUriSchemeAndAuthority => "request URI missing scheme and authority",
             PollResetAfterSendResponse => "poll_reset after send_response is illegal",
+            ExtendedConnectProtocolDisabled => "extended CONNECT protocol not enabled by peer",
         }
     }
 }
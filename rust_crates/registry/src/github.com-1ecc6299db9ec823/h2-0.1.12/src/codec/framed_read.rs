@@ -3,6 +3,7 @@ use frame::{self, Frame, Kind, Reason};
 use frame::{DEFAULT_MAX_FRAME_SIZE, DEFAULT_SETTINGS_HEADER_TABLE_SIZE, MAX_MAX_FRAME_SIZE};
 
 use hpack;
+use Stats;
 
 use futures::*;
 
@@ -26,6 +27,12 @@ pub struct FramedRead<T> {
     max_header_list_size: usize,
 
     partial: Option<Partial>,
+
+    /// Total number of bytes of frames read, including frame headers
+    bytes_received: u64,
+
+    /// Total number of RST_STREAM frames read
+    num_resets_received: u64,
 }
 
 /// Partially loaded headers frame
@@ -51,6 +58,8 @@ impl<T> FramedRead<T> {
             hpack: hpack::Decoder::new(DEFAULT_SETTINGS_HEADER_TABLE_SIZE),
             max_header_list_size: DEFAULT_SETTINGS_MAX_HEADER_LIST_SIZE,
             partial: None,
+            bytes_received: 0,
+            num_resets_received: 0,
         }
     }
 
@@ -111,8 +120,9 @@ impl<T> FramedRead<T> {
                         });
                     },
                     Err(e) => {
-                        debug!("connection error PROTOCOL_ERROR -- failed HPACK decoding; err={:?}", e);
-                        return Err(Connection(Reason::PROTOCOL_ERROR));
+                        let reason = error_reason(&e);
+                        debug!("connection error {:?} -- failed HPACK decoding; err={:?}", reason, e);
+                        return Err(Connection(reason));
                     }
                 }
 
@@ -136,24 +146,27 @@ impl<T> FramedRead<T> {
                 let res = frame::Settings::load(head, &bytes[frame::HEADER_LEN..]);
 
                 res.map_err(|e| {
-                    debug!("connection error PROTOCOL_ERROR -- failed to load SETTINGS frame; err={:?}", e);
-                    Connection(Reason::PROTOCOL_ERROR)
+                    let reason = error_reason(&e);
+                    debug!("connection error {:?} -- failed to load SETTINGS frame; err={:?}", reason, e);
+                    Connection(reason)
                 })?.into()
             },
             Kind::Ping => {
                 let res = frame::Ping::load(head, &bytes[frame::HEADER_LEN..]);
 
                 res.map_err(|e| {
-                    debug!("connection error PROTOCOL_ERROR -- failed to load PING frame; err={:?}", e);
-                    Connection(Reason::PROTOCOL_ERROR)
+                    let reason = error_reason(&e);
+                    debug!("connection error {:?} -- failed to load PING frame; err={:?}", reason, e);
+                    Connection(reason)
                 })?.into()
             },
             Kind::WindowUpdate => {
                 let res = frame::WindowUpdate::load(head, &bytes[frame::HEADER_LEN..]);
 
                 res.map_err(|e| {
-                    debug!("connection error PROTOCOL_ERROR -- failed to load WINDOW_UPDATE frame; err={:?}", e);
-                    Connection(Reason::PROTOCOL_ERROR)
+                    let reason = error_reason(&e);
+                    debug!("connection error {:?} -- failed to load WINDOW_UPDATE frame; err={:?}", reason, e);
+                    Connection(reason)
                 })?.into()
             },
             Kind::Data => {
@@ -171,11 +184,18 @@ impl<T> FramedRead<T> {
             },
             Kind::Reset => {
                 let res = frame::Reset::load(head, &bytes[frame::HEADER_LEN..]);
-                res.map_err(|_| Connection(Reason::PROTOCOL_ERROR))?.into()
+                let frame = res.map_err(|e| Connection(error_reason(&e)))?;
+                self.num_resets_received += 1;
+                frame.into()
             },
             Kind::GoAway => {
+                if !head.stream_id().is_zero() {
+                    debug!("connection error PROTOCOL_ERROR -- GOAWAY frame on stream {:?}", head.stream_id());
+                    return Err(Connection(Reason::PROTOCOL_ERROR));
+                }
+
                 let res = frame::GoAway::load(&bytes[frame::HEADER_LEN..]);
-                res.map_err(|_| Connection(Reason::PROTOCOL_ERROR))?.into()
+                res.map_err(|e| Connection(error_reason(&e)))?.into()
             },
             Kind::PushPromise => {
                 header_block!(PushPromise, head, bytes)
@@ -198,7 +218,7 @@ impl<T> FramedRead<T> {
                             reason: Reason::PROTOCOL_ERROR,
                         });
                     },
-                    Err(_) => return Err(Connection(Reason::PROTOCOL_ERROR)),
+                    Err(e) => return Err(Connection(error_reason(&e))),
                 }
             },
             Kind::Continuation => {
@@ -257,8 +277,9 @@ impl<T> FramedRead<T> {
                         });
                     },
                     Err(e) => {
-                        debug!("connection error PROTOCOL_ERROR -- failed HPACK decoding; err={:?}", e);
-                        return Err(Connection(Reason::PROTOCOL_ERROR));
+                        let reason = error_reason(&e);
+                        debug!("connection error {:?} -- failed HPACK decoding; err={:?}", reason, e);
+                        return Err(Connection(reason));
                     },
                 }
 
@@ -307,6 +328,14 @@ impl<T> FramedRead<T> {
     pub fn set_max_header_list_size(&mut self, val: usize) {
         self.max_header_list_size = val;
     }
+
+    /// Records the receive side counters and HPACK decoder state.
+    pub fn fill_stats(&self, stats: &mut Stats) {
+        stats.bytes_received = self.bytes_received;
+        stats.num_resets_received = self.num_resets_received;
+        stats.hpack_decoder_table_size = self.hpack.table_size();
+        stats.hpack_decoder_table_max_size = self.hpack.max_table_size();
+    }
 }
 
 impl<T> Stream for FramedRead<T>
@@ -325,6 +354,11 @@ where
             };
 
             trace!("poll; bytes={}B", bytes.len());
+
+            // The frame header is not skipped, so this counts the header and
+            // the payload, matching `FramedWrite::bytes_sent`.
+            self.bytes_received += bytes.len() as u64;
+
             if let Some(frame) = self.decode_frame(bytes)? {
                 debug!("received; frame={:?}", frame);
                 return Ok(Async::Ready(Some(frame)));
@@ -333,6 +367,25 @@ where
     }
 }
 
+/// Returns the connection error code for a frame that failed to load.
+fn error_reason(err: &frame::Error) -> Reason {
+    use hpack::DecoderError::*;
+
+    match *err {
+        // The payload length does not match the frame type
+        frame::Error::BadFrameSize |
+        frame::Error::InvalidPayloadLength |
+        frame::Error::InvalidPayloadAckSettings => Reason::FRAME_SIZE_ERROR,
+        // The header block decoded, but contained an invalid field. The HPACK
+        // decoder stopped early, so this still has to be a connection error.
+        frame::Error::Hpack(InvalidUtf8) |
+        frame::Error::Hpack(InvalidStatusCode) |
+        frame::Error::Hpack(InvalidPseudoheader) => Reason::PROTOCOL_ERROR,
+        frame::Error::Hpack(_) => Reason::COMPRESSION_ERROR,
+        _ => Reason::PROTOCOL_ERROR,
+    }
+}
+
 fn map_err(err: io::Error) -> RecvError {
     use tokio_io::codec::length_delimited::FrameTooBig;
 
@@ -390,3 +443,60 @@ impl<T> From<Continuable> for Frame<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hpack::DecoderError;
+
+    #[test]
+    fn test_frame_size_errors() {
+        for err in &[
+            frame::Error::BadFrameSize,
+            frame::Error::InvalidPayloadLength,
+            frame::Error::InvalidPayloadAckSettings,
+        ] {
+            assert_eq!(error_reason(err), Reason::FRAME_SIZE_ERROR, "{:?}", err);
+        }
+    }
+
+    #[test]
+    fn test_hpack_errors() {
+        for err in &[
+            DecoderError::InvalidRepresentation,
+            DecoderError::InvalidIntegerPrefix,
+            DecoderError::InvalidTableIndex,
+            DecoderError::InvalidHuffmanCode,
+            DecoderError::InvalidMaxDynamicSize,
+            DecoderError::IntegerOverflow,
+            DecoderError::NeedMore(hpack::NeedMore::UnexpectedEndOfStream),
+        ] {
+            let err = frame::Error::Hpack(*err);
+            assert_eq!(error_reason(&err), Reason::COMPRESSION_ERROR, "{:?}", err);
+        }
+
+        // These decode fine, but the header block is malformed.
+        for err in &[
+            DecoderError::InvalidUtf8,
+            DecoderError::InvalidStatusCode,
+            DecoderError::InvalidPseudoheader,
+        ] {
+            let err = frame::Error::Hpack(*err);
+            assert_eq!(error_reason(&err), Reason::PROTOCOL_ERROR, "{:?}", err);
+        }
+    }
+
+    #[test]
+    fn test_other_errors() {
+        for err in &[
+            frame::Error::TooMuchPadding,
+            frame::Error::InvalidSettingValue,
+            frame::Error::InvalidWindowUpdateValue,
+            frame::Error::InvalidStreamId,
+            frame::Error::MalformedMessage,
+            frame::Error::InvalidDependencyId,
+        ] {
+            assert_eq!(error_reason(err), Reason::PROTOCOL_ERROR, "{:?}", err);
+        }
+    }
+}
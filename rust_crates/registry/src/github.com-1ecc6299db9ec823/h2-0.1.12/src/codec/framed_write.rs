@@ -1,7 +1,8 @@
 use codec::UserError;
 use codec::UserError::*;
-use frame::{self, Frame, FrameSize};
+use frame::{self, Frame, FrameSize, Reason};
 use hpack;
+use Stats;
 
 use bytes::{Buf, BufMut, BytesMut};
 use futures::*;
@@ -30,6 +31,16 @@ pub struct FramedWrite<T, B> {
 
     /// Max frame size, this is specified by the peer
     max_frame_size: FrameSize,
+
+    /// Total number of bytes of frames written to `inner`, including frame
+    /// headers
+    bytes_sent: u64,
+
+    /// Total number of RST_STREAM frames buffered
+    num_resets_sent: u64,
+
+    /// Number of RST_STREAM frames buffered with `REFUSED_STREAM`
+    num_refused_streams: u64,
 }
 
 #[derive(Debug)]
@@ -63,6 +74,9 @@ where
             next: None,
             last_data_frame: None,
             max_frame_size: frame::DEFAULT_MAX_FRAME_SIZE,
+            bytes_sent: 0,
+            num_resets_sent: 0,
+            num_refused_streams: 0,
         }
     }
 
@@ -156,6 +170,12 @@ where
                 unimplemented!();
             },
             Frame::Reset(v) => {
+                self.num_resets_sent += 1;
+
+                if v.reason() == Reason::REFUSED_STREAM {
+                    self.num_refused_streams += 1;
+                }
+
                 v.encode(self.buf.get_mut());
                 trace!("encoded reset; rem={:?}", self.buf.remaining());
             },
@@ -174,11 +194,13 @@ where
                     Some(Next::Data(ref mut frame)) => {
                         trace!("  -> queued data frame");
                         let mut buf = Buf::by_ref(&mut self.buf).chain(frame.payload_mut());
-                        try_ready!(self.inner.write_buf(&mut buf));
+                        let n = try_ready!(self.inner.write_buf(&mut buf));
+                        self.bytes_sent += n as u64;
                     },
                     _ => {
                         trace!("  -> not a queued data frame");
-                        try_ready!(self.inner.write_buf(&mut self.buf));
+                        let n = try_ready!(self.inner.write_buf(&mut self.buf));
+                        self.bytes_sent += n as u64;
                     },
                 }
             }
@@ -243,6 +265,15 @@ impl<T, B> FramedWrite<T, B> {
         self.max_frame_size = val as FrameSize;
     }
 
+    /// Records the send side counters and HPACK encoder state.
+    pub fn fill_stats(&self, stats: &mut Stats) {
+        stats.bytes_sent = self.bytes_sent;
+        stats.num_resets_sent = self.num_resets_sent;
+        stats.num_refused_streams = self.num_refused_streams;
+        stats.hpack_encoder_table_size = self.hpack.table_size();
+        stats.hpack_encoder_table_max_size = self.hpack.max_table_size();
+    }
+
     /// Retrieve the last data frame that has been sent
     pub fn take_last_data_frame(&mut self) -> Option<frame::Data<B>> {
         self.last_data_frame.take()
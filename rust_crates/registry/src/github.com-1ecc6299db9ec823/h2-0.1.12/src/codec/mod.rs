@@ -8,6 +8,7 @@ use self::framed_read::FramedRead;
 use self::framed_write::FramedWrite;
 
 use frame::{self, Data, Frame};
+use Stats;
 
 use futures::*;
 
@@ -106,6 +107,12 @@ impl<T, B> Codec<T, B> {
         self.inner.get_mut().get_mut()
     }
 
+    /// Records the codec's counters and HPACK table usage.
+    pub(crate) fn fill_stats(&self, stats: &mut Stats) {
+        self.inner.fill_stats(stats);
+        self.inner.get_ref().fill_stats(stats);
+    }
+
     /// Takes the data payload value that was fully written to the socket
     pub(crate) fn take_last_data_frame(&mut self) -> Option<Data<B>> {
         self.framed_write().take_last_data_frame()
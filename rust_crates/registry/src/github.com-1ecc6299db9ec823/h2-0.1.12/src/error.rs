@@ -49,6 +49,14 @@ impl Error {
             _ => None,
         }
     }
+
+    /// Converts this error into an `io::Error`, preserving I/O errors as-is.
+    pub(crate) fn into_io(self) -> io::Error {
+        match self.kind {
+            Kind::Io(e) => e,
+            kind => io::Error::new(io::ErrorKind::Other, Error { kind }),
+        }
+    }
 }
 
 impl From<proto::Error> for Error {
@@ -0,0 +1,95 @@
+//! Extensions specific to the HTTP/2.0 protocol.
+//!
+//! Values defined here are stored in the [`Extensions`] of an `http`
+//! `Request` to carry HTTP/2.0 specific information that has no equivalent
+//! in the `http` crate.
+//!
+//! [`Extensions`]: https://docs.rs/http/0.1/http/struct.Extensions.html
+
+use bytes::Bytes;
+use string::String;
+
+use std::fmt;
+
+/// Represents the `:protocol` pseudo-header used by the [Extended CONNECT
+/// Protocol].
+///
+/// To send an extended CONNECT request, insert a `Protocol` into the
+/// extensions of a `CONNECT` request before passing it to
+/// [`SendRequest::send_request`]. The peer must have advertised
+/// `SETTINGS_ENABLE_CONNECT_PROTOCOL`, see
+/// [`SendRequest::is_extended_connect_protocol_enabled`].
+///
+/// On the server, requests received with a `:protocol` pseudo-header have a
+/// `Protocol` in their extensions. Servers only accept such requests after
+/// calling [`server::Builder::enable_connect_protocol`].
+///
+/// # Examples
+///
+/// ```
+/// # extern crate h2;
+/// # extern crate http;
+/// use h2::ext::Protocol;
+/// use http::{Method, Request};
+///
+/// # pub fn main() {
+/// let mut request = Request::builder()
+///     .method(Method::CONNECT)
+///     .uri("https://example.com/chat")
+///     .body(())
+///     .unwrap();
+///
+/// request.extensions_mut().insert(Protocol::from_static("websocket"));
+/// # }
+/// ```
+///
+/// [Extended CONNECT Protocol]: https://tools.ietf.org/html/rfc8441
+/// [`SendRequest::send_request`]: ../client/struct.SendRequest.html#method.send_request
+/// [`SendRequest::is_extended_connect_protocol_enabled`]: ../client/struct.SendRequest.html#method.is_extended_connect_protocol_enabled
+/// [`server::Builder::enable_connect_protocol`]: ../server/struct.Builder.html#method.enable_connect_protocol
+#[derive(Clone, Eq, PartialEq)]
+pub struct Protocol {
+    value: String<Bytes>,
+}
+
+impl Protocol {
+    /// Converts a static string to a protocol name.
+    pub fn from_static(value: &'static str) -> Self {
+        Protocol {
+            value: unsafe { String::from_utf8_unchecked(Bytes::from_static(value.as_bytes())) },
+        }
+    }
+
+    /// Returns a str representation of the header.
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    pub(crate) fn from_shared(value: String<Bytes>) -> Self {
+        Protocol { value }
+    }
+
+    pub(crate) fn into_inner(self) -> String<Bytes> {
+        self.value
+    }
+}
+
+impl<'a> From<&'a str> for Protocol {
+    fn from(value: &'a str) -> Self {
+        Protocol {
+            value: unsafe { String::from_utf8_unchecked(Bytes::from(value)) },
+        }
+    }
+}
+
+impl AsRef<[u8]> for Protocol {
+    fn as_ref(&self) -> &[u8] {
+        self.value.as_ref()
+    }
+}
+
+impl fmt::Debug for Protocol {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), fmt)
+    }
+}
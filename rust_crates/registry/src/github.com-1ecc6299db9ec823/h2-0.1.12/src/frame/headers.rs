@@ -1,4 +1,5 @@
 use super::{StreamDependency, StreamId};
+use ext::Protocol;
 use frame::{Error, Frame, Head, Kind};
 use hpack;
 
@@ -67,6 +68,7 @@ pub struct Pseudo {
     pub scheme: Option<String<Bytes>>,
     pub authority: Option<String<Bytes>>,
     pub path: Option<String<Bytes>>,
+    pub protocol: Option<Protocol>,
 
     // Response
     pub status: Option<StatusCode>,
@@ -236,6 +238,10 @@ impl Headers {
         (self.header_block.pseudo, self.header_block.fields)
     }
 
+    pub fn pseudo(&self) -> &Pseudo {
+        &self.header_block.pseudo
+    }
+
     #[cfg(feature = "unstable")]
     pub fn pseudo_mut(&mut self) -> &mut Pseudo {
         &mut self.header_block.pseudo
@@ -467,6 +473,7 @@ impl Pseudo {
             scheme: None,
             authority: None,
             path: Some(to_string(path)),
+            protocol: None,
             status: None,
         };
 
@@ -492,6 +499,7 @@ impl Pseudo {
             scheme: None,
             authority: None,
             path: None,
+            protocol: None,
             status: Some(status),
         }
     }
@@ -503,6 +511,10 @@ impl Pseudo {
     pub fn set_authority(&mut self, authority: String<Bytes>) {
         self.authority = Some(authority);
     }
+
+    pub fn set_protocol(&mut self, protocol: Protocol) {
+        self.protocol = Some(protocol);
+    }
 }
 
 fn to_string(src: Bytes) -> String<Bytes> {
@@ -586,6 +598,10 @@ impl Iterator for Iter {
                 return Some(Path(path));
             }
 
+            if let Some(protocol) = pseudo.protocol.take() {
+                return Some(Protocol(protocol.into_inner()));
+            }
+
             if let Some(status) = pseudo.status.take() {
                 return Some(Status(status));
             }
@@ -782,6 +798,7 @@ impl HeaderBlock {
                 Method(v) => set_pseudo!(method, v),
                 Scheme(v) => set_pseudo!(scheme, v),
                 Path(v) => set_pseudo!(path, v),
+                Protocol(v) => set_pseudo!(protocol, ::ext::Protocol::from_shared(v)),
                 Status(v) => set_pseudo!(status, v),
             }
         });
@@ -832,6 +849,7 @@ impl HeaderBlock {
         pseudo_size!(status) +
         pseudo_size!(authority) +
         pseudo_size!(path) +
+        pseudo_size!(protocol) +
         self.fields.iter()
             .map(|(name, value)| decoded_header_size(name.as_str().len(), value.len()))
             .sum::<usize>()
@@ -167,6 +167,16 @@ impl Decoder {
         self.max_size_update = Some(size);
     }
 
+    /// Returns the current size of the dynamic table
+    pub fn table_size(&self) -> usize {
+        self.table.size()
+    }
+
+    /// Returns the maximum size of the dynamic table
+    pub fn max_table_size(&self) -> usize {
+        self.table.max_size
+    }
+
     /// Decodes the headers found in the given buffer.
     pub fn decode<F>(&mut self, src: &mut Cursor<&mut BytesMut>, mut f: F) -> Result<(), DecoderError>
     where
@@ -71,6 +71,16 @@ impl Encoder {
         }
     }
 
+    /// Returns the current size of the dynamic table
+    pub fn table_size(&self) -> usize {
+        self.table.size()
+    }
+
+    /// Returns the maximum size of the dynamic table
+    pub fn max_table_size(&self) -> usize {
+        self.table.max_size()
+    }
+
     /// Encode a set of headers into the provide buffer
     pub fn encode<I>(
         &mut self,
@@ -105,6 +105,11 @@ impl Table {
         self.max_size
     }
 
+    /// Returns the table size
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
     /// Gets the header stored in the table
     pub fn resolve<'a>(&'a self, index: &'a Index) -> &'a Header {
         use self::Index::*;
@@ -613,11 +618,6 @@ impl Table {
     pub fn len(&self) -> usize {
         self.slots.len()
     }
-
-    /// Returns the table size
-    pub fn size(&self) -> usize {
-        self.size
-    }
 }
 
 impl Index {
@@ -735,6 +735,8 @@ fn index_static(header: &Header) -> Option<(usize, bool)> {
             "/index.html" => Some((5, true)),
             _ => Some((4, false)),
         },
+        // `:protocol` is not part of the static table.
+        Header::Protocol(_) => None,
         Header::Status(ref v) => match u16::from(*v) {
             200 => Some((8, true)),
             204 => Some((9, true)),
@@ -181,7 +181,7 @@ fn gen_header(g: &mut StdRng) -> Header<Option<HeaderName>> {
     use http::{Method, StatusCode};
 
     if g.gen_weighted_bool(10) {
-        match g.next_u32() % 5 {
+        match g.next_u32() % 6 {
             0 => {
                 let value = gen_string(g, 4, 20);
                 Header::Authority(to_shared(value))
@@ -229,6 +229,14 @@ fn gen_header(g: &mut StdRng) -> Header<Option<HeaderName>> {
 
                 Header::Status(StatusCode::from_u16(status).unwrap())
             },
+            5 => {
+                let value = match g.next_u32() % 2 {
+                    0 => "websocket".to_string(),
+                    _ => gen_string(g, 4, 20),
+                };
+
+                Header::Protocol(to_shared(value))
+            },
             _ => unreachable!(),
         }
     } else {
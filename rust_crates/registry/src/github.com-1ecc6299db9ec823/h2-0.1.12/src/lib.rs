@@ -124,11 +124,19 @@ mod frame;
 pub mod frame;
 
 pub mod client;
+pub mod ext;
 pub mod server;
 mod share;
+mod stats;
+mod tunnel;
+
+#[cfg(test)]
+mod test;
 
 pub use error::{Error, Reason};
 pub use share::{SendStream, StreamId, RecvStream, ReleaseCapacity};
+pub use stats::Stats;
+pub use tunnel::Tunnel;
 
 #[cfg(feature = "unstable")]
 pub use codec::{Codec, RecvError, SendError, UserError};
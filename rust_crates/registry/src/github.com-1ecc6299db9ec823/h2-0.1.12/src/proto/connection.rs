@@ -1,4 +1,4 @@
-use {client, frame, proto, server};
+use {client, frame, proto, server, Stats};
 use codec::RecvError;
 use frame::{Reason, StreamId};
 
@@ -91,6 +91,9 @@ where
             remote_max_initiated: config.settings
                 .max_concurrent_streams()
                 .map(|max| max as usize),
+            local_extended_connect_protocol_enabled: config.settings
+                .is_extended_connect_protocol_enabled()
+                .unwrap_or(false),
         });
         Connection {
             state: State::Open,
@@ -108,6 +111,19 @@ where
         self.streams.set_target_connection_window_size(size);
     }
 
+    /// Returns a snapshot of the connection's state.
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats::default();
+
+        self.codec.fill_stats(&mut stats);
+        self.streams.fill_stats(&mut stats);
+
+        stats.go_away_sent = self.go_away.going_away_reason();
+        stats.go_away_received = self.go_away.received_reason();
+
+        stats
+    }
+
     /// Returns `Ready` when the connection is ready to receive a frame.
     ///
     /// Returns `RecvError` as this may raise errors that are caused by delayed
@@ -315,6 +331,7 @@ where
                     // until they are all EOS. Once they are, State should
                     // transition to GoAway.
                     self.streams.recv_go_away(&frame)?;
+                    self.go_away.recv_go_away(&frame);
                     self.error = Some(frame.reason());
                 },
                 Some(Ping(frame)) => {
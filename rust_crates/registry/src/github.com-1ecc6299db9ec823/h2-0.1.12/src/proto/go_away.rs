@@ -16,6 +16,9 @@ pub(super) struct GoAway {
 
     /// A GOAWAY frame that must be buffered in the Codec immediately.
     pending: Option<frame::GoAway>,
+
+    /// Records the error code of the last GOAWAY received from the peer.
+    received: Option<Reason>,
 }
 
 /// Keeps a memory of any GOAWAY frames we've sent before.
@@ -46,6 +49,7 @@ impl GoAway {
             close_now: false,
             going_away: None,
             pending: None,
+            received: None,
         }
     }
 
@@ -94,6 +98,16 @@ impl GoAway {
             .map(|g| g.reason)
     }
 
+    /// Record a GOAWAY frame received from the peer.
+    pub fn recv_go_away(&mut self, f: &frame::GoAway) {
+        self.received = Some(f.reason());
+    }
+
+    /// Return the Reason of the last GOAWAY received, if any.
+    pub fn received_reason(&self) -> Option<Reason> {
+        self.received
+    }
+
     /// Returns if the connection should close now, or wait until idle.
     pub fn should_close_now(&self) -> bool {
         self.pending.is_none() && self.close_now
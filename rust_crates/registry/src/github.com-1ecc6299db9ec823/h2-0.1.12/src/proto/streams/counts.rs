@@ -1,4 +1,5 @@
 use super::*;
+use Stats;
 
 use std::usize;
 
@@ -104,6 +105,15 @@ impl Counts {
         self.num_reset_streams += 1;
     }
 
+    /// Records the stream concurrency counters.
+    pub fn fill_stats(&self, stats: &mut Stats) {
+        stats.num_local_streams = self.num_send_streams;
+        stats.num_remote_streams = self.num_recv_streams;
+        stats.max_local_streams = self.max_send_streams;
+        stats.max_remote_streams = self.max_recv_streams;
+        stats.num_pending_reset_streams = self.num_reset_streams;
+    }
+
     pub fn apply_remote_settings(&mut self, settings: &frame::Settings) {
         if let Some(val) = settings.max_concurrent_streams() {
             self.max_send_streams = val as usize;
@@ -59,4 +59,7 @@ pub struct Config {
 
     /// Maximum number of remote initiated streams
     pub remote_max_initiated: Option<usize>,
+
+    /// If the local peer accepts the extended CONNECT protocol
+    pub local_extended_connect_protocol_enabled: bool,
 }
@@ -97,6 +97,11 @@ impl Prioritize {
         }
     }
 
+    /// Returns the connection level send window
+    pub fn connection_window_size(&self) -> WindowSize {
+        self.flow.window_size()
+    }
+
     /// Queue a frame to be sent to the remote
     pub fn queue_frame<B>(
         &mut self,
@@ -54,6 +54,9 @@ pub(super) struct Recv {
 
     /// If push promises are allowed to be recevied.
     is_push_enabled: bool,
+
+    /// If extended CONNECT requests are allowed to be received.
+    is_extended_connect_protocol_enabled: bool,
 }
 
 #[derive(Debug)]
@@ -107,6 +110,7 @@ impl Recv {
             buffer: Buffer::new(),
             refused: None,
             is_push_enabled: config.local_push_enabled,
+            is_extended_connect_protocol_enabled: config.local_extended_connect_protocol_enabled,
         }
     }
 
@@ -115,6 +119,11 @@ impl Recv {
         self.init_window_sz
     }
 
+    /// Returns the connection level receive window
+    pub fn connection_window_size(&self) -> WindowSize {
+        self.flow.window_size()
+    }
+
     /// Returns the ID of the last processed stream
     pub fn last_processed_id(&self) -> StreamId {
         self.last_processed_id
@@ -216,6 +225,17 @@ impl Recv {
             };
         }
 
+        if frame.pseudo().protocol.is_some() && !self.is_extended_connect_protocol_enabled {
+            // > A CONNECT request that includes the `:protocol` pseudo-header
+            // > but was sent to a peer that did not advertise support MUST be
+            // > treated as malformed.
+            trace!("recv_headers; :protocol received without SETTINGS_ENABLE_CONNECT_PROTOCOL");
+            return Err(RecvError::Stream {
+                id: stream.id,
+                reason: Reason::PROTOCOL_ERROR,
+            }.into());
+        }
+
         let message = counts.peer().convert_poll_message(frame)?;
 
         // Push the frame onto the stream's recv buffer
@@ -430,12 +450,16 @@ impl Recv {
         let is_ignoring_frame = stream.state.is_local_reset();
 
         if !is_ignoring_frame && !stream.state.is_recv_streaming() {
-            // TODO: There are cases where this can be a stream error of
-            // STREAM_CLOSED instead...
+            // Receiving a DATA frame after the peer closed its side of the
+            // stream is a STREAM_CLOSED error, otherwise receiving a DATA frame
+            // when not expecting one is a protocol error.
+            let reason = if stream.state.is_recv_closed() {
+                Reason::STREAM_CLOSED
+            } else {
+                Reason::PROTOCOL_ERROR
+            };
 
-            // Receiving a DATA frame when not expecting one is a protocol
-            // error.
-            return Err(RecvError::Connection(Reason::PROTOCOL_ERROR));
+            return Err(RecvError::Connection(reason));
         }
 
         trace!(
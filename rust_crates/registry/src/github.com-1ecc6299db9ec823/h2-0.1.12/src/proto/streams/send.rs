@@ -24,6 +24,9 @@ pub(super) struct Send {
 
     /// Prioritization layer
     prioritize: Prioritize,
+
+    /// If the remote peer accepts extended CONNECT requests
+    is_extended_connect_protocol_enabled: bool,
 }
 
 /// A value to detect which public API has called `poll_reset`.
@@ -40,6 +43,7 @@ impl Send {
             init_window_sz: config.remote_init_window_sz,
             next_stream_id: Ok(config.local_next_stream_id),
             prioritize: Prioritize::new(config),
+            is_extended_connect_protocol_enabled: false,
         }
     }
 
@@ -48,6 +52,17 @@ impl Send {
         self.init_window_sz
     }
 
+    /// Returns true if the remote peer advertised support for the extended
+    /// CONNECT protocol.
+    pub fn is_extended_connect_protocol_enabled(&self) -> bool {
+        self.is_extended_connect_protocol_enabled
+    }
+
+    /// Returns the connection level send window
+    pub fn connection_window_size(&self) -> WindowSize {
+        self.prioritize.connection_window_size()
+    }
+
     pub fn open(&mut self) -> Result<StreamId, UserError> {
         let stream_id = self.ensure_next_stream_id()?;
         self.next_stream_id = stream_id.next_id();
@@ -348,6 +363,20 @@ impl Send {
         counts: &mut Counts,
         task: &mut Option<Task>,
     ) -> Result<(), RecvError> {
+        if let Some(enabled) = settings.is_extended_connect_protocol_enabled() {
+            // Per RFC 8441 §3:
+            //
+            // > A sender MUST NOT send a SETTINGS_ENABLE_CONNECT_PROTOCOL
+            // > parameter with the value of 0 after previously sending a value
+            // > of 1.
+            if self.is_extended_connect_protocol_enabled && !enabled {
+                debug!("recv_settings; SETTINGS_ENABLE_CONNECT_PROTOCOL disabled after being enabled");
+                return Err(RecvError::Connection(Reason::PROTOCOL_ERROR));
+            }
+
+            self.is_extended_connect_protocol_enabled = enabled;
+        }
+
         // Applies an update to the remote endpoint's initial window size.
         //
         // Per RFC 7540 §6.9.2:
@@ -211,6 +211,8 @@ impl State {
                 self.inner = Closed(Cause::EndStream);
                 Ok(())
             },
+            // The peer already closed its side of the stream.
+            HalfClosedRemote(..) | Closed(..) => Err(RecvError::Connection(Reason::STREAM_CLOSED)),
             _ => Err(RecvError::Connection(Reason::PROTOCOL_ERROR)),
         }
     }
@@ -379,6 +381,28 @@ impl State {
         }
     }
 
+    /// Returns true if both halves of the stream are open.
+    pub fn is_open(&self) -> bool {
+        match self.inner {
+            Open { .. } => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_half_closed_local(&self) -> bool {
+        match self.inner {
+            HalfClosedLocal(..) => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_half_closed_remote(&self) -> bool {
+        match self.inner {
+            HalfClosedRemote(..) => true,
+            _ => false,
+        }
+    }
+
     pub fn is_recv_closed(&self) -> bool {
         match self.inner {
             Closed(..) | HalfClosedRemote(..) => true,
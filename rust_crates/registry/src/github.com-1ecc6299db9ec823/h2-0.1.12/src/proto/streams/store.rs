@@ -197,10 +197,15 @@ impl Store {
         self.ids.len()
     }
 
-    #[cfg(feature = "unstable")]
     pub fn num_wired_streams(&self) -> usize {
         self.slab.len()
     }
+
+    /// Iterates over every stream held in memory, including closed streams
+    /// that have not yet been released.
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = &'a Stream> + 'a {
+        self.slab.iter().map(|(_, &(_, ref stream))| stream)
+    }
 }
 
 impl Drop for Store {
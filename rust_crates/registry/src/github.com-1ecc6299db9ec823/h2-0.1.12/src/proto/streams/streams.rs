@@ -1,5 +1,6 @@
 use {client, proto, server};
 use codec::{Codec, RecvError, SendError, UserError};
+use ext::Protocol;
 use frame::{self, Frame, Reason};
 use proto::{peer, Peer, Open, WindowSize};
 use super::{Buffer, Config, Counts, Prioritized, Recv, Send, Stream, StreamId};
@@ -11,6 +12,8 @@ use futures::{task, Async, Poll};
 use http::{HeaderMap, Request, Response};
 use tokio_io::AsyncWrite;
 
+use Stats;
+
 use std::{fmt, io};
 use std::sync::{Arc, Mutex};
 
@@ -227,7 +230,14 @@ where
                 }
 
                 trace!("recv_data; stream not found: {:?}", id);
-                return Err(RecvError::Connection(Reason::PROTOCOL_ERROR));
+
+                // DATA on an idle stream is a protocol error, otherwise the
+                // stream has already been closed.
+                me.actions
+                    .ensure_not_idle(me.counts.peer(), id)
+                    .map_err(RecvError::Connection)?;
+
+                return Err(RecvError::Connection(Reason::STREAM_CLOSED));
             },
         };
 
@@ -604,6 +614,12 @@ where
                 return Err(UserError::UnexpectedFrameType.into());
             }
 
+            if request.extensions().get::<Protocol>().is_some()
+                && !me.actions.send.is_extended_connect_protocol_enabled()
+            {
+                return Err(UserError::ExtendedConnectProtocolDisabled.into());
+            }
+
             let stream_id = me.actions.send.open()?;
 
             let mut stream = Stream::new(
@@ -760,6 +776,11 @@ where
         me.store.num_active_streams()
     }
 
+    pub fn is_extended_connect_protocol_enabled(&self) -> bool {
+        let me = self.inner.lock().unwrap();
+        me.actions.send.is_extended_connect_protocol_enabled()
+    }
+
     pub fn has_streams_or_other_references(&self) -> bool {
         if Arc::strong_count(&self.inner) > 1 {
             return true;
@@ -778,6 +799,48 @@ where
         let me = self.inner.lock().unwrap();
         me.store.num_wired_streams()
     }
+
+    /// Records stream states, flow control windows and queue lengths.
+    pub fn fill_stats(&self, stats: &mut Stats) {
+        let me = self.inner.lock().unwrap();
+
+        me.counts.fill_stats(stats);
+
+        stats.num_active_streams = me.store.num_active_streams();
+        stats.num_wired_streams = me.store.num_wired_streams();
+        stats.send_window = me.actions.send.connection_window_size();
+        stats.recv_window = me.actions.recv.connection_window_size();
+
+        for stream in me.store.iter() {
+            if stream.state.is_open() {
+                stats.num_open_streams += 1;
+            } else if stream.state.is_half_closed_local() {
+                stats.num_half_closed_local_streams += 1;
+            } else if stream.state.is_half_closed_remote() {
+                stats.num_half_closed_remote_streams += 1;
+            }
+
+            if stream.is_pending_send_capacity {
+                stats.num_pending_send_capacity += 1;
+            }
+
+            if stream.is_pending_send {
+                stats.num_pending_send += 1;
+            }
+
+            if stream.is_pending_open {
+                stats.num_pending_open += 1;
+            }
+
+            if stream.is_pending_accept {
+                stats.num_pending_accept += 1;
+            }
+
+            if stream.is_pending_window_update {
+                stats.num_pending_window_updates += 1;
+            }
+        }
+    }
 }
 
 // no derive because we don't need B and P to be Clone.
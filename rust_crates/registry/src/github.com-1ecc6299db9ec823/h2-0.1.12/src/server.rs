@@ -132,14 +132,14 @@
 //! [`SendStream`]: ../struct.SendStream.html
 //! [`TcpListener`]: https://docs.rs/tokio-core/0.1/tokio_core/net/struct.TcpListener.html
 
-use {SendStream, RecvStream, ReleaseCapacity};
+use {SendStream, RecvStream, ReleaseCapacity, Stats};
 use codec::{Codec, RecvError};
 use frame::{self, Reason, Settings, StreamId};
 use proto::{self, Config, Prioritized};
 
 use bytes::{Buf, Bytes, IntoBuf};
 use futures::{self, Async, Future, Poll};
-use http::{Request, Response};
+use http::{Method, Request, Response};
 use std::{convert, fmt, io, mem};
 use std::time::Duration;
 use tokio_io::{AsyncRead, AsyncWrite};
@@ -410,6 +410,18 @@ where
         self.connection.set_target_window_size(size);
     }
 
+    /// Returns a snapshot of the connection's state.
+    ///
+    /// The snapshot includes stream counts, cumulative frame and byte
+    /// counters, the connection level flow control windows, the lengths of
+    /// the internal capacity queues, `GOAWAY` state and HPACK table usage.
+    /// See [`Stats`] for details on each value.
+    ///
+    /// [`Stats`]: ../struct.Stats.html
+    pub fn stats(&self) -> Stats {
+        self.connection.stats()
+    }
+
     /// Returns `Ready` when the underlying connection has closed.
     ///
     /// If any new inbound streams are received during a call to `poll_close`,
@@ -847,6 +859,45 @@ impl Builder {
         self
     }
 
+    /// Enables the [extended CONNECT protocol].
+    ///
+    /// This advertises `SETTINGS_ENABLE_CONNECT_PROTOCOL` to the client,
+    /// allowing it to send `CONNECT` requests carrying a `:protocol`
+    /// pseudo-header, for example to bootstrap WebSockets over HTTP/2.0.
+    /// Such requests are surfaced with a [`Protocol`] in their extensions.
+    ///
+    /// When this is not enabled, requests carrying a `:protocol`
+    /// pseudo-header are reset with `PROTOCOL_ERROR`.
+    ///
+    /// [extended CONNECT protocol]: https://tools.ietf.org/html/rfc8441
+    /// [`Protocol`]: ../ext/struct.Protocol.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate h2;
+    /// # extern crate tokio_io;
+    /// # use tokio_io::*;
+    /// # use h2::server::*;
+    /// #
+    /// # fn doc<T: AsyncRead + AsyncWrite>(my_io: T)
+    /// # -> Handshake<T>
+    /// # {
+    /// // `server_fut` is a future representing the completion of the HTTP/2.0
+    /// // handshake.
+    /// let server_fut = Builder::new()
+    ///     .enable_connect_protocol()
+    ///     .handshake(my_io);
+    /// # server_fut
+    /// # }
+    /// #
+    /// # pub fn main() {}
+    /// ```
+    pub fn enable_connect_protocol(&mut self) -> &mut Self {
+        self.settings.set_enable_connect_protocol(Some(1));
+        self
+    }
+
     /// Creates a new configured HTTP/2.0 server backed by `io`.
     ///
     /// It is expected that `io` already be in an appropriate state to commence
@@ -1215,11 +1266,13 @@ impl proto::Peer for Peer {
 
         b.version(Version::HTTP_2);
 
-        if let Some(method) = pseudo.method {
+        let is_connect = if let Some(method) = pseudo.method {
+            let is_connect = method == Method::CONNECT;
             b.method(method);
+            is_connect
         } else {
             malformed!("malformed headers: missing method");
-        }
+        };
 
         // Specifying :status for a request is a protocol error
         if pseudo.status.is_some() {
@@ -1241,6 +1294,20 @@ impl proto::Peer for Peer {
                 .or_else(|_| malformed!("malformed headers: malformed authority"))?);
         }
 
+        // An extended CONNECT request (RFC 8441) must use the CONNECT method
+        // and include the `:path` pseudo-header.
+        if let Some(ref protocol) = pseudo.protocol {
+            if !is_connect {
+                malformed!("malformed headers: :protocol on non-CONNECT request");
+            }
+
+            if pseudo.path.is_none() {
+                malformed!("malformed headers: extended CONNECT missing path");
+            }
+
+            b.extension(protocol.clone());
+        }
+
         if let Some(path) = pseudo.path {
             // This cannot be empty
             if path.is_empty() {
@@ -0,0 +1,242 @@
+use frame::Reason;
+
+/// A point-in-time snapshot of the state of an HTTP/2.0 connection.
+///
+/// A `Stats` value is obtained by calling `stats` on either a
+/// [`client::Connection`] or a [`server::Connection`]. The snapshot is not
+/// updated after it has been taken; call `stats` again to observe the latest
+/// values.
+///
+/// Counters such as [`bytes_sent`] are cumulative over the lifetime of the
+/// connection. All other values reflect the state of the connection at the
+/// time the snapshot was taken.
+///
+/// [`client::Connection`]: client/struct.Connection.html
+/// [`server::Connection`]: server/struct.Connection.html
+/// [`bytes_sent`]: #method.bytes_sent
+#[derive(Debug, Clone, Default)]
+pub struct Stats {
+    pub(crate) num_active_streams: usize,
+    pub(crate) num_wired_streams: usize,
+    pub(crate) num_local_streams: usize,
+    pub(crate) num_remote_streams: usize,
+    pub(crate) max_local_streams: usize,
+    pub(crate) max_remote_streams: usize,
+    pub(crate) num_open_streams: usize,
+    pub(crate) num_half_closed_local_streams: usize,
+    pub(crate) num_half_closed_remote_streams: usize,
+    pub(crate) num_pending_reset_streams: usize,
+    pub(crate) num_resets_sent: u64,
+    pub(crate) num_resets_received: u64,
+    pub(crate) num_refused_streams: u64,
+    pub(crate) bytes_sent: u64,
+    pub(crate) bytes_received: u64,
+    pub(crate) send_window: u32,
+    pub(crate) recv_window: u32,
+    pub(crate) num_pending_send_capacity: usize,
+    pub(crate) num_pending_send: usize,
+    pub(crate) num_pending_open: usize,
+    pub(crate) num_pending_accept: usize,
+    pub(crate) num_pending_window_updates: usize,
+    pub(crate) go_away_sent: Option<Reason>,
+    pub(crate) go_away_received: Option<Reason>,
+    pub(crate) hpack_encoder_table_size: usize,
+    pub(crate) hpack_encoder_table_max_size: usize,
+    pub(crate) hpack_decoder_table_size: usize,
+    pub(crate) hpack_decoder_table_max_size: usize,
+}
+
+impl Stats {
+    /// Returns the number of active streams.
+    ///
+    /// An active stream is a stream that has not yet transitioned to a closed
+    /// state.
+    pub fn num_active_streams(&self) -> usize {
+        self.num_active_streams
+    }
+
+    /// Returns the number of streams that are held in memory.
+    ///
+    /// A wired stream is a stream that is either active or is closed but must
+    /// stay in memory for some reason. For example, there are still outstanding
+    /// userspace handles pointing to the slot.
+    pub fn num_wired_streams(&self) -> usize {
+        self.num_wired_streams
+    }
+
+    /// Returns the number of locally initiated streams counted against the
+    /// peer's `SETTINGS_MAX_CONCURRENT_STREAMS` limit.
+    pub fn num_local_streams(&self) -> usize {
+        self.num_local_streams
+    }
+
+    /// Returns the number of remotely initiated streams counted against the
+    /// local `SETTINGS_MAX_CONCURRENT_STREAMS` limit.
+    pub fn num_remote_streams(&self) -> usize {
+        self.num_remote_streams
+    }
+
+    /// Returns the maximum number of concurrent locally initiated streams, as
+    /// advertised by the peer.
+    pub fn max_local_streams(&self) -> usize {
+        self.max_local_streams
+    }
+
+    /// Returns the maximum number of concurrent remotely initiated streams
+    /// accepted by this endpoint.
+    pub fn max_remote_streams(&self) -> usize {
+        self.max_remote_streams
+    }
+
+    /// Returns the number of streams in the "open" state.
+    pub fn num_open_streams(&self) -> usize {
+        self.num_open_streams
+    }
+
+    /// Returns the number of streams in the "half-closed (local)" state.
+    ///
+    /// These streams have finished sending but are still receiving data from
+    /// the peer.
+    pub fn num_half_closed_local_streams(&self) -> usize {
+        self.num_half_closed_local_streams
+    }
+
+    /// Returns the number of streams in the "half-closed (remote)" state.
+    ///
+    /// The peer has finished sending on these streams, but the local end is
+    /// still sending.
+    pub fn num_half_closed_remote_streams(&self) -> usize {
+        self.num_half_closed_remote_streams
+    }
+
+    /// Returns the number of locally reset streams that are kept around in
+    /// order to ignore frames the peer sent before receiving the reset.
+    ///
+    /// See [`max_concurrent_reset_streams`] for how this is bounded.
+    ///
+    /// [`max_concurrent_reset_streams`]: server/struct.Builder.html#method.max_concurrent_reset_streams
+    pub fn num_pending_reset_streams(&self) -> usize {
+        self.num_pending_reset_streams
+    }
+
+    /// Returns the total number of `RST_STREAM` frames sent to the peer.
+    ///
+    /// This includes streams refused with `REFUSED_STREAM`.
+    pub fn num_resets_sent(&self) -> u64 {
+        self.num_resets_sent
+    }
+
+    /// Returns the total number of `RST_STREAM` frames received from the peer.
+    pub fn num_resets_received(&self) -> u64 {
+        self.num_resets_received
+    }
+
+    /// Returns the total number of streams refused by this endpoint with a
+    /// `REFUSED_STREAM` error code.
+    pub fn num_refused_streams(&self) -> u64 {
+        self.num_refused_streams
+    }
+
+    /// Returns the total number of bytes of frames written to the underlying
+    /// I/O.
+    ///
+    /// This includes frame headers and all frame types, not just `DATA`
+    /// payloads. The connection preface is not counted. Bytes are counted the
+    /// same way as [`bytes_received`], so the two values can be compared.
+    ///
+    /// [`bytes_received`]: #method.bytes_received
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent
+    }
+
+    /// Returns the total number of bytes of frames read from the underlying
+    /// I/O.
+    ///
+    /// This includes frame headers and all frame types, not just `DATA`
+    /// payloads. The connection preface is not counted.
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received
+    }
+
+    /// Returns the current connection level send window, in octets.
+    ///
+    /// This is the amount of data the peer is willing to accept across all
+    /// streams before it sends more `WINDOW_UPDATE` frames. A value of zero
+    /// means that all outbound data is stalled on the peer.
+    pub fn send_window(&self) -> u32 {
+        self.send_window
+    }
+
+    /// Returns the current connection level receive window, in octets.
+    ///
+    /// This is the amount of data the peer may still send across all streams
+    /// before this endpoint sends more `WINDOW_UPDATE` frames.
+    pub fn recv_window(&self) -> u32 {
+        self.recv_window
+    }
+
+    /// Returns the number of streams waiting to be assigned connection level
+    /// send capacity.
+    pub fn num_pending_send_capacity(&self) -> usize {
+        self.num_pending_send_capacity
+    }
+
+    /// Returns the number of streams with frames queued to be written to the
+    /// connection.
+    pub fn num_pending_send(&self) -> usize {
+        self.num_pending_send
+    }
+
+    /// Returns the number of locally initiated streams waiting to be opened
+    /// because the peer's concurrency limit has been reached.
+    pub fn num_pending_open(&self) -> usize {
+        self.num_pending_open
+    }
+
+    /// Returns the number of remotely initiated streams that have not yet been
+    /// accepted by the application.
+    pub fn num_pending_accept(&self) -> usize {
+        self.num_pending_accept
+    }
+
+    /// Returns the number of streams waiting to send a `WINDOW_UPDATE` frame.
+    pub fn num_pending_window_updates(&self) -> usize {
+        self.num_pending_window_updates
+    }
+
+    /// Returns the error code of the last `GOAWAY` frame sent to the peer, if
+    /// any.
+    pub fn go_away_sent(&self) -> Option<Reason> {
+        self.go_away_sent
+    }
+
+    /// Returns the error code of the last `GOAWAY` frame received from the
+    /// peer, if any.
+    pub fn go_away_received(&self) -> Option<Reason> {
+        self.go_away_received
+    }
+
+    /// Returns the current size, in octets, of the HPACK dynamic table used to
+    /// encode outbound header blocks.
+    pub fn hpack_encoder_table_size(&self) -> usize {
+        self.hpack_encoder_table_size
+    }
+
+    /// Returns the maximum size, in octets, of the HPACK dynamic table used to
+    /// encode outbound header blocks.
+    pub fn hpack_encoder_table_max_size(&self) -> usize {
+        self.hpack_encoder_table_max_size
+    }
+
+    /// Returns the current size, in octets, of the HPACK dynamic table used to
+    /// decode inbound header blocks.
+    pub fn hpack_decoder_table_size(&self) -> usize {
+        self.hpack_decoder_table_size
+    }
+
+    /// Returns the maximum size, in octets, of the HPACK dynamic table used to
+    /// decode inbound header blocks.
+    pub fn hpack_decoder_table_max_size(&self) -> usize {
+        self.hpack_decoder_table_max_size
+    }
+}
@@ -0,0 +1,681 @@
+//! Conformance tests for the server side of RFC 7540, organized by section in
+//! the same way as h2spec.
+
+use Reason;
+use server;
+
+use super::mock::*;
+
+// ===== 3.5. HTTP/2 Connection Preface =====
+
+#[test]
+fn s3_5_invalid_preface() {
+    let mut srv = Server::new();
+
+    srv.send(b"INVALID CONNECTION PREFACE\r\n\r\n");
+    srv.poll();
+
+    match srv.result() {
+        Some(&Err(ref e)) => assert_eq!(e.reason(), Some(Reason::PROTOCOL_ERROR)),
+        res => panic!("unexpected result; {:?}", res),
+    }
+}
+
+// ===== 4.1. Frame Format =====
+
+#[test]
+fn s4_1_unknown_frame_type_is_ignored() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(0x16, 0, 0, b"unknown"));
+    srv.assert_open();
+}
+
+#[test]
+fn s4_1_undefined_flags_are_ignored() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(PING, 0x16, 0, &[0; 8]));
+
+    let frames = srv.recv();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].kind, PING);
+    assert_eq!(frames[0].flags, ACK);
+}
+
+#[test]
+fn s4_1_reserved_bit_is_ignored() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(HEADERS, END_HEADERS | END_STREAM, 1 | 1 << 31, GET_REQUEST));
+    srv.assert_open();
+
+    assert_eq!(srv.num_requests(), 1);
+}
+
+// ===== 4.2. Frame Size =====
+
+#[test]
+fn s4_2_data_frame_exceeds_max_frame_size() {
+    let mut srv = Server::handshake();
+
+    srv.send(&post(1));
+    srv.send(&data(1, 0, &[0; 16_385]));
+
+    srv.assert_go_away(Reason::FRAME_SIZE_ERROR);
+}
+
+#[test]
+fn s4_2_headers_frame_exceeds_max_frame_size() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(HEADERS, END_HEADERS, 1, &[0; 16_385]));
+
+    srv.assert_go_away(Reason::FRAME_SIZE_ERROR);
+}
+
+// ===== 4.3. Header Compression and Decompression =====
+
+#[test]
+fn s4_3_invalid_header_block() {
+    let mut srv = Server::handshake();
+
+    // Index 0 is never valid.
+    srv.send(&frame(HEADERS, END_HEADERS | END_STREAM, 1, &[0x80]));
+
+    srv.assert_go_away(Reason::COMPRESSION_ERROR);
+}
+
+#[test]
+fn s4_3_truncated_header_block() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(HEADERS, END_HEADERS | END_STREAM, 1, &GET_REQUEST[..6]));
+
+    srv.assert_go_away(Reason::COMPRESSION_ERROR);
+}
+
+// ===== 5.1. Stream States =====
+
+#[test]
+fn s5_1_idle_data() {
+    let mut srv = Server::handshake();
+
+    srv.send(&data(1, END_STREAM, b"hello"));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+#[test]
+fn s5_1_idle_rst_stream() {
+    let mut srv = Server::handshake();
+
+    srv.send(&reset(1, Reason::CANCEL));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+#[test]
+fn s5_1_idle_window_update() {
+    let mut srv = Server::handshake();
+
+    srv.send(&window_update(1, 100));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+#[test]
+fn s5_1_idle_continuation() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(CONTINUATION, END_HEADERS, 1, GET_REQUEST));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+#[test]
+fn s5_1_half_closed_remote_data() {
+    let mut srv = Server::handshake();
+
+    srv.send(&get(1));
+    srv.send(&data(1, 0, b"hello"));
+
+    srv.assert_go_away(Reason::STREAM_CLOSED);
+}
+
+#[test]
+fn s5_1_half_closed_remote_headers() {
+    let mut srv = Server::handshake();
+
+    srv.send(&get(1));
+    srv.send(&get(1));
+
+    srv.assert_go_away(Reason::STREAM_CLOSED);
+}
+
+#[test]
+fn s5_1_closed_data_after_end_stream() {
+    let mut srv = Server::handshake();
+
+    srv.send(&get(1));
+    assert!(srv.recv().is_empty());
+
+    srv.respond(0);
+    assert_eq!(srv.recv().len(), 1);
+
+    srv.send(&data(1, 0, b"hello"));
+
+    srv.assert_go_away(Reason::STREAM_CLOSED);
+}
+
+#[test]
+fn s5_1_1_even_stream_id() {
+    let mut srv = Server::handshake();
+
+    srv.send(&get(2));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+#[test]
+fn s5_1_1_decreasing_stream_id() {
+    let mut srv = Server::handshake();
+
+    srv.send(&get(5));
+    srv.send(&get(3));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+#[test]
+fn s5_1_2_exceeds_max_concurrent_streams() {
+    let mut srv = Server::handshake_with(server::Builder::new().max_concurrent_streams(1));
+
+    srv.send(&post(1));
+    srv.send(&post(3));
+
+    srv.assert_reset(3, Reason::REFUSED_STREAM);
+    assert_eq!(srv.num_requests(), 1);
+}
+
+// ===== 5.3. Stream Priority =====
+
+#[test]
+fn s5_3_1_headers_depends_on_itself() {
+    let mut srv = Server::handshake();
+
+    let mut payload = vec![0, 0, 0, 1, 16];
+    payload.extend_from_slice(GET_REQUEST);
+    srv.send(&frame(HEADERS, END_HEADERS | END_STREAM | PRIORITY_FLAG, 1, &payload));
+
+    srv.assert_reset(1, Reason::PROTOCOL_ERROR);
+}
+
+#[test]
+fn s5_3_1_priority_depends_on_itself() {
+    let mut srv = Server::handshake();
+
+    srv.send(&post(1));
+    srv.send(&priority(1, 1, 16));
+
+    srv.assert_reset(1, Reason::PROTOCOL_ERROR);
+}
+
+// ===== 5.5. Extending HTTP/2 =====
+
+#[test]
+fn s5_5_unknown_frame_in_header_block() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(HEADERS, END_STREAM, 1, &GET_REQUEST[..3]));
+    srv.send(&frame(0x16, 0, 1, b"unknown"));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+// ===== 6.1. DATA =====
+
+#[test]
+fn s6_1_data_on_stream_zero() {
+    let mut srv = Server::handshake();
+
+    srv.send(&data(0, END_STREAM, b"hello"));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+#[test]
+fn s6_1_data_too_much_padding() {
+    let mut srv = Server::handshake();
+
+    srv.send(&post(1));
+    srv.send(&frame(DATA, PADDED | END_STREAM, 1, &[6, b'h', b'e', b'l', b'l', b'o']));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+// ===== 6.2. HEADERS =====
+
+#[test]
+fn s6_2_headers_on_stream_zero() {
+    let mut srv = Server::handshake();
+
+    srv.send(&get(0));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+#[test]
+fn s6_2_headers_too_much_padding() {
+    let mut srv = Server::handshake();
+
+    let mut payload = vec![GET_REQUEST.len() as u8 + 1];
+    payload.extend_from_slice(GET_REQUEST);
+    srv.send(&frame(HEADERS, END_HEADERS | END_STREAM | PADDED, 1, &payload));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+#[test]
+fn s6_2_headers_followed_by_other_frame() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(HEADERS, END_STREAM, 1, GET_REQUEST));
+    srv.send(&data(1, END_STREAM, b"hello"));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+// ===== 6.3. PRIORITY =====
+
+#[test]
+fn s6_3_priority_on_stream_zero() {
+    let mut srv = Server::handshake();
+
+    srv.send(&priority(0, 1, 16));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+#[test]
+fn s6_3_priority_on_idle_stream() {
+    let mut srv = Server::handshake();
+
+    srv.send(&priority(3, 0, 16));
+    srv.assert_open();
+
+    // The idle stream can still be opened.
+    srv.send(&get(3));
+    srv.assert_open();
+    assert_eq!(srv.num_requests(), 1);
+}
+
+// ===== 6.4. RST_STREAM =====
+
+#[test]
+fn s6_4_rst_stream_on_stream_zero() {
+    let mut srv = Server::handshake();
+
+    srv.send(&reset(0, Reason::CANCEL));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+#[test]
+fn s6_4_rst_stream_invalid_length() {
+    let mut srv = Server::handshake();
+
+    srv.send(&post(1));
+    srv.send(&frame(RST_STREAM, 0, 1, &[0, 0, 0, 0, 8]));
+
+    srv.assert_go_away(Reason::FRAME_SIZE_ERROR);
+}
+
+// ===== 6.5. SETTINGS =====
+
+#[test]
+fn s6_5_ack_with_payload() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(SETTINGS, ACK, 0, &[0, 2, 0, 0, 0, 0]));
+
+    srv.assert_go_away(Reason::FRAME_SIZE_ERROR);
+}
+
+#[test]
+fn s6_5_settings_on_stream() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(SETTINGS, 0, 1, &[0, 2, 0, 0, 0, 0]));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+#[test]
+fn s6_5_invalid_length() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(SETTINGS, 0, 0, &[0, 2, 0, 0, 0]));
+
+    srv.assert_go_away(Reason::FRAME_SIZE_ERROR);
+}
+
+#[test]
+fn s6_5_2_invalid_enable_push() {
+    let mut srv = Server::handshake();
+
+    srv.send(&settings(&[(SETTINGS_ENABLE_PUSH, 2)]));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+#[test]
+fn s6_5_2_max_frame_size_too_small() {
+    let mut srv = Server::handshake();
+
+    srv.send(&settings(&[(SETTINGS_MAX_FRAME_SIZE, 16_383)]));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+#[test]
+fn s6_5_2_max_frame_size_too_large() {
+    let mut srv = Server::handshake();
+
+    srv.send(&settings(&[(SETTINGS_MAX_FRAME_SIZE, 1 << 24)]));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+#[test]
+fn s6_5_2_unknown_setting_is_ignored() {
+    let mut srv = Server::handshake();
+
+    srv.send(&settings(&[(0xff, 1)]));
+
+    let frames = srv.recv();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].kind, SETTINGS);
+    assert_eq!(frames[0].flags, ACK);
+}
+
+// ===== 6.7. PING =====
+
+#[test]
+fn s6_7_ping_is_acknowledged() {
+    let mut srv = Server::handshake();
+
+    srv.send(&ping(*b"conform!"));
+
+    let frames = srv.recv();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].kind, PING);
+    assert_eq!(frames[0].flags, ACK);
+    assert_eq!(frames[0].payload, b"conform!");
+}
+
+#[test]
+fn s6_7_ping_ack_is_not_acknowledged() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(PING, ACK, 0, b"conform!"));
+
+    assert!(srv.recv().is_empty());
+    srv.assert_open();
+}
+
+#[test]
+fn s6_7_ping_on_stream() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(PING, 0, 1, &[0; 8]));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+#[test]
+fn s6_7_ping_invalid_length() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(PING, 0, 0, &[0; 6]));
+
+    srv.assert_go_away(Reason::FRAME_SIZE_ERROR);
+}
+
+// ===== 6.8. GOAWAY =====
+
+#[test]
+fn s6_8_go_away_on_stream() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(GOAWAY, 0, 1, &[0, 0, 0, 0, 0, 0, 0, 0]));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+#[test]
+fn s6_8_go_away_invalid_length() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(GOAWAY, 0, 0, &[0, 0, 0, 0]));
+
+    srv.assert_go_away(Reason::FRAME_SIZE_ERROR);
+}
+
+#[test]
+fn s6_8_go_away_closes_connection() {
+    let mut srv = Server::handshake();
+
+    srv.send(&go_away(0, Reason::NO_ERROR));
+    srv.close();
+    srv.poll();
+
+    match srv.result() {
+        Some(&Ok(())) => {},
+        res => panic!("unexpected result; {:?}", res),
+    }
+}
+
+// ===== 6.9. WINDOW_UPDATE =====
+
+#[test]
+fn s6_9_zero_increment_on_connection() {
+    let mut srv = Server::handshake();
+
+    srv.send(&window_update(0, 0));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+#[test]
+fn s6_9_zero_increment_on_stream() {
+    let mut srv = Server::handshake();
+
+    srv.send(&post(1));
+    srv.send(&window_update(1, 0));
+
+    // A stream error may be treated as a connection error (Section 5.4.1).
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+#[test]
+fn s6_9_invalid_length() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(WINDOW_UPDATE, 0, 0, &[0, 0, 1]));
+
+    srv.assert_go_away(Reason::FRAME_SIZE_ERROR);
+}
+
+#[test]
+fn s6_9_1_connection_window_overflow() {
+    let mut srv = Server::handshake();
+
+    srv.send(&window_update(0, (1 << 31) - 1));
+
+    srv.assert_go_away(Reason::FLOW_CONTROL_ERROR);
+}
+
+#[test]
+fn s6_9_1_stream_window_overflow() {
+    let mut srv = Server::handshake();
+
+    srv.send(&post(1));
+    srv.send(&window_update(1, (1 << 31) - 1));
+
+    srv.assert_reset(1, Reason::FLOW_CONTROL_ERROR);
+}
+
+// ===== 6.10. CONTINUATION =====
+
+#[test]
+fn s6_10_continuation_completes_header_block() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(HEADERS, END_STREAM, 1, &GET_REQUEST[..3]));
+    srv.send(&frame(CONTINUATION, 0, 1, &GET_REQUEST[3..6]));
+    srv.send(&frame(CONTINUATION, END_HEADERS, 1, &GET_REQUEST[6..]));
+
+    srv.assert_open();
+    assert_eq!(srv.num_requests(), 1);
+}
+
+#[test]
+fn s6_10_continuation_on_other_stream() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(HEADERS, END_STREAM, 1, &GET_REQUEST[..3]));
+    srv.send(&frame(CONTINUATION, END_HEADERS, 3, &GET_REQUEST[3..]));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+#[test]
+fn s6_10_continuation_after_end_headers() {
+    let mut srv = Server::handshake();
+
+    srv.send(&post(1));
+    srv.send(&frame(CONTINUATION, END_HEADERS, 1, GET_REQUEST));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+// ===== 8.1. HTTP Request/Response Exchange =====
+
+fn request_with(extra: &[u8]) -> Vec<u8> {
+    let mut block = GET_REQUEST.to_vec();
+    block.extend_from_slice(extra);
+    frame(HEADERS, END_HEADERS | END_STREAM, 1, &block)
+}
+
+/// A literal header field without indexing, with a literal name.
+fn literal(name: &[u8], value: &[u8]) -> Vec<u8> {
+    let mut dst = vec![0x00, name.len() as u8];
+    dst.extend_from_slice(name);
+    dst.push(value.len() as u8);
+    dst.extend_from_slice(value);
+    dst
+}
+
+#[test]
+fn s8_1_2_uppercase_header_name() {
+    let mut srv = Server::handshake();
+
+    srv.send(&request_with(&literal(b"X-Test", b"ok")));
+
+    // The header block cannot be decoded past the invalid field, so the
+    // stream error is treated as a connection error (Section 5.4.1).
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+    assert_eq!(srv.num_requests(), 0);
+}
+
+#[test]
+fn s8_1_2_1_unknown_pseudo_header() {
+    let mut srv = Server::handshake();
+
+    srv.send(&request_with(&literal(b":test", b"ok")));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+    assert_eq!(srv.num_requests(), 0);
+}
+
+#[test]
+fn s8_1_2_1_pseudo_header_after_regular_header() {
+    let mut srv = Server::handshake();
+
+    let mut block = literal(b"x-test", b"ok");
+    block.push(0x82);
+    block.push(0x86);
+    block.push(0x84);
+    srv.send(&frame(HEADERS, END_HEADERS | END_STREAM, 1, &block));
+
+    srv.assert_reset(1, Reason::PROTOCOL_ERROR);
+    assert_eq!(srv.num_requests(), 0);
+}
+
+#[test]
+fn s8_1_2_2_connection_specific_header() {
+    let mut srv = Server::handshake();
+
+    srv.send(&request_with(&literal(b"connection", b"keep-alive")));
+
+    srv.assert_reset(1, Reason::PROTOCOL_ERROR);
+    assert_eq!(srv.num_requests(), 0);
+}
+
+#[test]
+fn s8_1_2_2_te_other_than_trailers() {
+    let mut srv = Server::handshake();
+
+    srv.send(&request_with(&literal(b"te", b"gzip")));
+
+    srv.assert_reset(1, Reason::PROTOCOL_ERROR);
+    assert_eq!(srv.num_requests(), 0);
+}
+
+#[test]
+fn s8_1_2_3_missing_method() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(HEADERS, END_HEADERS | END_STREAM, 1, &GET_REQUEST[1..]));
+
+    srv.assert_reset(1, Reason::PROTOCOL_ERROR);
+    assert_eq!(srv.num_requests(), 0);
+}
+
+#[test]
+fn s8_1_2_3_duplicate_pseudo_header() {
+    let mut srv = Server::handshake();
+
+    srv.send(&request_with(&[0x84]));
+
+    srv.assert_reset(1, Reason::PROTOCOL_ERROR);
+    assert_eq!(srv.num_requests(), 0);
+}
+
+#[test]
+fn s8_1_2_6_content_length_mismatch() {
+    let mut srv = Server::handshake();
+
+    let mut block = GET_REQUEST.to_vec();
+    block.extend_from_slice(&literal(b"content-length", b"1"));
+    srv.send(&frame(HEADERS, END_HEADERS, 1, &block));
+    srv.send(&data(1, END_STREAM, b"hello"));
+
+    srv.assert_reset(1, Reason::PROTOCOL_ERROR);
+}
+
+// ===== 8.2. Server Push =====
+
+#[test]
+fn s8_2_push_promise_from_client() {
+    let mut srv = Server::handshake();
+
+    srv.send(&post(1));
+
+    let mut payload = vec![0, 0, 0, 2];
+    payload.extend_from_slice(GET_REQUEST);
+    srv.send(&frame(PUSH_PROMISE, END_HEADERS, 1, &payload));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
@@ -0,0 +1,74 @@
+//! Connection error codes sent for frames that fail to load, and for DATA
+//! received on streams that are already closed.
+
+use Reason;
+
+use super::mock::*;
+
+// ===== Frame load errors =====
+
+#[test]
+fn priority_invalid_length_is_frame_size_error() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(PRIORITY, 0, 1, &[0, 0, 0, 0]));
+
+    srv.assert_go_away(Reason::FRAME_SIZE_ERROR);
+}
+
+#[test]
+fn settings_ack_with_payload_is_frame_size_error() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(SETTINGS, ACK, 0, &[0, 4, 0, 0, 0xff, 0xff]));
+
+    srv.assert_go_away(Reason::FRAME_SIZE_ERROR);
+}
+
+#[test]
+fn hpack_integer_overflow_is_compression_error() {
+    let mut srv = Server::handshake();
+
+    // An indexed field whose index never terminates.
+    let block = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f];
+    srv.send(&frame(HEADERS, END_HEADERS | END_STREAM, 1, &block));
+
+    srv.assert_go_away(Reason::COMPRESSION_ERROR);
+}
+
+#[test]
+fn go_away_on_stream_is_protocol_error() {
+    let mut srv = Server::handshake();
+
+    srv.send(&get(1));
+    srv.send(&frame(GOAWAY, 0, 1, &[0, 0, 0, 0, 0, 0, 0, 0]));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+// ===== DATA on closed streams =====
+
+#[test]
+fn data_on_reset_stream_is_stream_closed() {
+    let mut srv = Server::handshake();
+
+    srv.send(&post(1));
+    srv.send(&reset(1, Reason::CANCEL));
+    srv.assert_open();
+
+    srv.send(&data(1, 0, b"hello"));
+
+    srv.assert_go_away(Reason::STREAM_CLOSED);
+}
+
+#[test]
+fn data_on_idle_stream_after_open_stream_is_protocol_error() {
+    let mut srv = Server::handshake();
+
+    srv.send(&post(1));
+    srv.assert_open();
+
+    srv.send(&data(3, 0, b"hello"));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
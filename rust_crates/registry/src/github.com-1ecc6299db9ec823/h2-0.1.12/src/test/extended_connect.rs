@@ -0,0 +1,156 @@
+//! Client and server tests for the extended CONNECT protocol (RFC 8441).
+
+use {server, Reason, Tunnel};
+use ext::Protocol;
+
+use http::{Method, Request, Response, StatusCode};
+use tokio_io::io::{read_exact, write_all};
+
+use std::io::Read;
+
+use super::mock::*;
+
+const SETTINGS_ENABLE_CONNECT_PROTOCOL: u16 = 0x8;
+
+/// `CONNECT https://example.com/chat` with `:protocol websocket`, encoded
+/// using only the static table and literals without indexing.
+const EXTENDED_CONNECT_REQUEST: &'static [u8] = &[
+    0x02, 0x07, // :method, literal without indexing
+    b'C', b'O', b'N', b'N', b'E', b'C', b'T',
+    0x87, // :scheme https
+    0x04, 0x05, // :path, literal without indexing
+    b'/', b'c', b'h', b'a', b't',
+    0x01, 0x0b, // :authority, literal without indexing
+    b'e', b'x', b'a', b'm', b'p', b'l', b'e', b'.', b'c', b'o', b'm',
+    0x00, 0x09, // new name, literal without indexing
+    b':', b'p', b'r', b'o', b't', b'o', b'c', b'o', b'l',
+    0x09,
+    b'w', b'e', b'b', b's', b'o', b'c', b'k', b'e', b't',
+];
+
+fn extended_connect() -> Request<()> {
+    let mut request = Request::builder()
+        .method(Method::CONNECT)
+        .uri("https://example.com/chat")
+        .body(())
+        .unwrap();
+
+    request.extensions_mut().insert(Protocol::from_static("websocket"));
+    request
+}
+
+#[test]
+fn extended_connect_round_trip() {
+    let mut pair = Pair::new(server::Builder::new().enable_connect_protocol());
+
+    assert!(pair.client().is_extended_connect_protocol_enabled());
+
+    let (response, client_send) = pair.client()
+        .send_request(extended_connect(), false)
+        .unwrap();
+
+    let (request, mut respond) = pair.accept();
+
+    assert_eq!(request.method(), Method::CONNECT);
+    assert_eq!(request.uri().path(), "/chat");
+    assert_eq!(
+        request.extensions().get::<Protocol>(),
+        Some(&Protocol::from_static("websocket"))
+    );
+
+    let server_send = respond.send_response(Response::new(()), false).unwrap();
+
+    let response = pair.run(response).unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let client = Tunnel::new(client_send, response.into_body());
+    let server = Tunnel::new(server_send, request.into_body());
+
+    let (client, _) = pair.run(write_all(client, b"hello")).unwrap();
+    let (server, buf) = pair.run(read_exact(server, [0; 5])).unwrap();
+    assert_eq!(&buf, b"hello");
+
+    let (_server, _) = pair.run(write_all(server, b"world")).unwrap();
+    let (_client, buf) = pair.run(read_exact(client, [0; 5])).unwrap();
+    assert_eq!(&buf, b"world");
+}
+
+#[test]
+fn zero_length_read_keeps_buffered_data() {
+    let mut pair = Pair::new(server::Builder::new().enable_connect_protocol());
+
+    let (response, client_send) = pair.client()
+        .send_request(extended_connect(), false)
+        .unwrap();
+    let (request, mut respond) = pair.accept();
+    let server_send = respond.send_response(Response::new(()), false).unwrap();
+    let response = pair.run(response).unwrap();
+
+    let client = Tunnel::new(client_send, response.into_body());
+    let server = Tunnel::new(server_send, request.into_body());
+
+    let (_client, _) = pair.run(write_all(client, b"hello")).unwrap();
+    let (mut server, buf) = pair.run(read_exact(server, [0; 2])).unwrap();
+    assert_eq!(&buf, b"he");
+
+    // Part of the chunk is still buffered; an empty read must not look like
+    // the end of the stream or lose it.
+    assert_eq!(server.read(&mut []).unwrap(), 0);
+    let (mut server, buf) = pair.run(read_exact(server, [0; 3])).unwrap();
+    assert_eq!(&buf, b"llo");
+
+    // Nothing is buffered now, and the stream is still open.
+    assert_eq!(server.read(&mut []).unwrap(), 0);
+    assert!(!server.get_ref().1.is_end_stream());
+}
+
+#[test]
+fn client_rejects_protocol_when_not_enabled() {
+    let mut pair = Pair::new(&server::Builder::new());
+
+    assert!(!pair.client().is_extended_connect_protocol_enabled());
+    assert!(pair.client().send_request(extended_connect(), false).is_err());
+}
+
+#[test]
+fn server_rejects_protocol_when_not_enabled() {
+    let mut srv = Server::handshake();
+
+    srv.send(&frame(HEADERS, END_HEADERS, 1, EXTENDED_CONNECT_REQUEST));
+
+    srv.assert_reset(1, Reason::PROTOCOL_ERROR);
+    assert_eq!(srv.num_requests(), 0);
+}
+
+#[test]
+fn server_accepts_protocol_when_enabled() {
+    let mut srv = Server::handshake_with(server::Builder::new().enable_connect_protocol());
+
+    srv.send(&frame(HEADERS, END_HEADERS, 1, EXTENDED_CONNECT_REQUEST));
+
+    srv.assert_open();
+    assert_eq!(srv.num_requests(), 1);
+}
+
+#[test]
+fn client_rejects_setting_disabled_after_enabled() {
+    let mut client = Client::handshake_with(&[(SETTINGS_ENABLE_CONNECT_PROTOCOL, 1)]);
+
+    assert!(client.send_request().is_extended_connect_protocol_enabled());
+
+    client.send(&settings(&[(SETTINGS_ENABLE_CONNECT_PROTOCOL, 0)]));
+
+    client.assert_go_away(Reason::PROTOCOL_ERROR);
+}
+
+#[test]
+fn server_rejects_setting_disabled_after_enabled() {
+    let mut srv = Server::handshake();
+
+    srv.send(&settings(&[(SETTINGS_ENABLE_CONNECT_PROTOCOL, 1)]));
+    srv.assert_open();
+
+    srv.send(&settings(&[(SETTINGS_ENABLE_CONNECT_PROTOCOL, 0)]));
+
+    srv.assert_go_away(Reason::PROTOCOL_ERROR);
+}
@@ -0,0 +1,358 @@
+extern crate quickcheck;
+extern crate rand;
+
+use Reason;
+
+use super::mock::*;
+
+use self::quickcheck::{Arbitrary, Gen, QuickCheck, TestResult};
+use self::rand::{Rng, SeedableRng, StdRng};
+
+const MAX_CHUNK: usize = 64;
+
+#[test]
+fn frame_sequence_fuzz() {
+    fn prop(fuzz: FuzzFrames) -> TestResult {
+        fuzz.run();
+        TestResult::from_bool(true)
+    }
+
+    QuickCheck::new()
+        .tests(200)
+        .quickcheck(prop as fn(FuzzFrames) -> TestResult)
+}
+
+/// A sequence of frames that are all valid in the state they are sent in,
+/// optionally followed by a single frame that violates the protocol.
+#[derive(Debug, Clone)]
+struct FuzzFrames {
+    // The magic seed that makes the test case reproducible
+    seed: [usize; 4],
+
+    frames: Vec<Action>,
+
+    // The protocol violation sent after all valid frames, if any
+    violation: Option<Violation>,
+
+    // The sizes of the writes used to deliver the frames
+    chunks: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+enum Action {
+    Ping([u8; 8]),
+    Settings(Vec<(u16, u32)>),
+    Request { id: u32, end_stream: bool },
+    Data { id: u32, len: usize, end_stream: bool },
+    WindowUpdate { id: u32, increment: u32 },
+    Priority { id: u32, dependency: u32 },
+    Reset { id: u32 },
+    Unknown { kind: u8, id: u32, len: usize },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Violation {
+    DataOnStreamZero,
+    PingOnStream,
+    PingLength,
+    SettingsOnStream,
+    SettingsLength,
+    SettingsValue,
+    EvenStreamId,
+    InvalidHeaderBlock,
+    UnexpectedContinuation,
+    ZeroWindowUpdate,
+    WindowOverflow,
+    ResetOnStreamZero,
+    GoAwayOnStream,
+    FrameTooLarge,
+}
+
+impl FuzzFrames {
+    fn new(seed: [usize; 4]) -> FuzzFrames {
+        // Seed the RNG
+        let mut rng = StdRng::from_seed(&seed);
+
+        let mut frames = vec![];
+
+        // Streams that were opened and may still receive frames
+        let mut open: Vec<u32> = vec![];
+        // Streams on which the client may still send DATA
+        let mut sending: Vec<u32> = vec![];
+        let mut next_id = 1;
+
+        for _ in 0..rng.gen_range(0, 100) {
+            let action = match rng.gen_range(0, 9) {
+                0 => {
+                    let mut payload = [0; 8];
+                    rng.fill_bytes(&mut payload);
+                    Action::Ping(payload)
+                },
+                1 => {
+                    let mut values = vec![];
+
+                    for _ in 0..rng.gen_range(0, 4) {
+                        values.push(match rng.gen_range(0, 4) {
+                            // SETTINGS_HEADER_TABLE_SIZE
+                            0 => (0x1, rng.gen_range(0, 8192)),
+                            // SETTINGS_ENABLE_PUSH
+                            1 => (0x2, rng.gen_range(0, 2)),
+                            // SETTINGS_MAX_FRAME_SIZE
+                            2 => (0x5, rng.gen_range(1 << 14, 1 << 24)),
+                            // Unknown settings are ignored
+                            _ => (rng.gen_range(0x10, 0xffff), rng.gen()),
+                        });
+                    }
+
+                    Action::Settings(values)
+                },
+                2 | 3 => {
+                    let id = next_id;
+                    let end_stream: bool = rng.gen();
+                    next_id += 2;
+
+                    open.push(id);
+
+                    if !end_stream {
+                        sending.push(id);
+                    }
+
+                    Action::Request { id, end_stream }
+                },
+                4 if !sending.is_empty() => {
+                    let i = rng.gen_range(0, sending.len());
+                    let id = sending[i];
+                    let end_stream = rng.gen_weighted_bool(4);
+
+                    if end_stream {
+                        sending.remove(i);
+                    }
+
+                    Action::Data { id, len: rng.gen_range(0, 256), end_stream }
+                },
+                5 => {
+                    let id = if open.is_empty() || rng.gen() {
+                        0
+                    } else {
+                        *rng.choose(&open).unwrap()
+                    };
+
+                    Action::WindowUpdate { id, increment: rng.gen_range(1, 1 << 16) }
+                },
+                6 => {
+                    let id = rng.gen_range(0, next_id + 10) | 1;
+                    let mut dependency = rng.gen_range(0, next_id + 10);
+
+                    if dependency == id {
+                        dependency = 0;
+                    }
+
+                    Action::Priority { id, dependency }
+                },
+                7 if !open.is_empty() => {
+                    let i = rng.gen_range(0, open.len());
+                    let id = open.remove(i);
+                    sending.retain(|&s| s != id);
+
+                    Action::Reset { id }
+                },
+                _ => Action::Unknown {
+                    kind: rng.gen_range(0x10, 0xff),
+                    id: rng.gen_range(0, next_id + 10),
+                    len: rng.gen_range(0, 64),
+                },
+            };
+
+            frames.push(action);
+        }
+
+        let violation = if rng.gen() {
+            Some(match rng.gen_range(0, 14) {
+                0 => Violation::DataOnStreamZero,
+                1 => Violation::PingOnStream,
+                2 => Violation::PingLength,
+                3 => Violation::SettingsOnStream,
+                4 => Violation::SettingsLength,
+                5 => Violation::SettingsValue,
+                6 => Violation::EvenStreamId,
+                7 => Violation::InvalidHeaderBlock,
+                8 => Violation::UnexpectedContinuation,
+                9 => Violation::ZeroWindowUpdate,
+                10 => Violation::WindowOverflow,
+                11 => Violation::ResetOnStreamZero,
+                12 => Violation::GoAwayOnStream,
+                _ => Violation::FrameTooLarge,
+            })
+        } else {
+            None
+        };
+
+        let mut chunks = vec![];
+
+        for _ in 0..rng.gen_range(0, 100) {
+            chunks.push(rng.gen_range(1, MAX_CHUNK));
+        }
+
+        FuzzFrames {
+            seed,
+            frames,
+            violation,
+            chunks,
+        }
+    }
+
+    fn run(self) {
+        let mut srv = Server::handshake();
+        let mut chunks = self.chunks;
+
+        let mut input = vec![];
+        let mut expect_pings = vec![];
+        let mut expect_settings_acks = 0;
+        let mut expect_requests = 0;
+
+        for action in &self.frames {
+            match *action {
+                Action::Ping(payload) => {
+                    input.extend(ping(payload));
+                    expect_pings.push(payload);
+                },
+                Action::Settings(ref values) => {
+                    input.extend(settings(values));
+                    expect_settings_acks += 1;
+                },
+                Action::Request { id, end_stream } => {
+                    input.extend(if end_stream { get(id) } else { post(id) });
+                    expect_requests += 1;
+                },
+                Action::Data { id, len, end_stream } => {
+                    let flags = if end_stream { END_STREAM } else { 0 };
+                    input.extend(data(id, flags, &vec![0; len]));
+                },
+                Action::WindowUpdate { id, increment } => {
+                    input.extend(window_update(id, increment));
+                },
+                Action::Priority { id, dependency } => {
+                    input.extend(priority(id, dependency, 16));
+                },
+                Action::Reset { id } => {
+                    input.extend(reset(id, Reason::CANCEL));
+                },
+                Action::Unknown { kind, id, len } => {
+                    input.extend(frame(kind, 0, id, &vec![0; len]));
+                },
+            }
+        }
+
+        let expect_reason = self.violation.map(|violation| {
+            let (bytes, reason) = violation.encode();
+            input.extend(bytes);
+            reason
+        });
+
+        // Deliver the input in arbitrarily sized writes, driving the
+        // connection after each one.
+        let mut frames = vec![];
+        let mut pos = 0;
+
+        while pos < input.len() {
+            let n = ::std::cmp::min(chunks.pop().unwrap_or(MAX_CHUNK), input.len() - pos);
+
+            srv.send(&input[pos..pos + n]);
+            frames.extend(srv.recv());
+
+            pos += n;
+        }
+
+        frames.extend(srv.recv());
+
+        // No stream is ever reset by the server, as every frame is valid.
+        assert!(
+            frames.iter().all(|f| f.kind != RST_STREAM),
+            "unexpected RST_STREAM; frames={:?}",
+            frames
+        );
+
+        let pings: Vec<_> = frames
+            .iter()
+            .filter(|f| f.kind == PING && f.flags & ACK == ACK)
+            .map(|f| f.payload.clone())
+            .collect();
+
+        let expect_pings: Vec<_> = expect_pings.iter().map(|p| p.to_vec()).collect();
+
+        let settings_acks = frames
+            .iter()
+            .filter(|f| f.kind == SETTINGS && f.flags & ACK == ACK)
+            .count();
+
+        match expect_reason {
+            Some(reason) => {
+                // Frames received just before the violation may not have been
+                // acted on before the connection was closed.
+                assert!(expect_pings.starts_with(&pings), "pings={:?}", pings);
+                assert!(settings_acks <= expect_settings_acks);
+                assert!(srv.num_requests() <= expect_requests);
+
+                let go_away: Vec<_> = frames.iter().filter(|f| f.kind == GOAWAY).collect();
+
+                assert_eq!(go_away.len(), 1, "frames={:?}", frames);
+                assert_eq!(go_away[0].reason(), reason, "violation={:?}", self.violation);
+                assert_eq!(frames.last().unwrap().kind, GOAWAY);
+
+                match srv.result() {
+                    Some(&Err(ref e)) => assert_eq!(e.reason(), Some(reason)),
+                    res => panic!("expected connection error {:?}; got {:?}", reason, res),
+                }
+            },
+            None => {
+                assert_eq!(pings, expect_pings);
+                assert_eq!(settings_acks, expect_settings_acks);
+                assert_eq!(srv.num_requests(), expect_requests);
+
+                assert!(frames.iter().all(|f| f.kind != GOAWAY), "frames={:?}", frames);
+                srv.assert_open();
+            },
+        }
+    }
+}
+
+impl Violation {
+    /// Returns the frame committing the violation and the error code the
+    /// connection must be closed with.
+    fn encode(self) -> (Vec<u8>, Reason) {
+        use self::Violation::*;
+
+        match self {
+            DataOnStreamZero => (data(0, 0, b"hello"), Reason::PROTOCOL_ERROR),
+            PingOnStream => (frame(PING, 0, 1, &[0; 8]), Reason::PROTOCOL_ERROR),
+            PingLength => (frame(PING, 0, 0, &[0; 7]), Reason::FRAME_SIZE_ERROR),
+            SettingsOnStream => (frame(SETTINGS, 0, 1, &[]), Reason::PROTOCOL_ERROR),
+            SettingsLength => (frame(SETTINGS, 0, 0, &[0; 5]), Reason::FRAME_SIZE_ERROR),
+            SettingsValue => (settings(&[(0x2, 2)]), Reason::PROTOCOL_ERROR),
+            // Even stream IDs are reserved for the server
+            EvenStreamId => (get(1 << 30), Reason::PROTOCOL_ERROR),
+            InvalidHeaderBlock => (
+                frame(HEADERS, END_HEADERS | END_STREAM, (1 << 30) + 1, &[0x80]),
+                Reason::COMPRESSION_ERROR,
+            ),
+            UnexpectedContinuation => (
+                frame(CONTINUATION, END_HEADERS, 1, GET_REQUEST),
+                Reason::PROTOCOL_ERROR,
+            ),
+            ZeroWindowUpdate => (window_update(0, 0), Reason::PROTOCOL_ERROR),
+            WindowOverflow => (window_update(0, (1 << 31) - 1), Reason::FLOW_CONTROL_ERROR),
+            ResetOnStreamZero => (reset(0, Reason::CANCEL), Reason::PROTOCOL_ERROR),
+            GoAwayOnStream => (
+                frame(GOAWAY, 0, 1, &[0; 8]),
+                Reason::PROTOCOL_ERROR,
+            ),
+            FrameTooLarge => (data(1, 0, &[0; 16_385]), Reason::FRAME_SIZE_ERROR),
+        }
+    }
+}
+
+impl Arbitrary for FuzzFrames {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        FuzzFrames::new(quickcheck::Rng::gen(g))
+    }
+}
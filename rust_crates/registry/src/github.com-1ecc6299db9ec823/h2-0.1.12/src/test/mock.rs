@@ -0,0 +1,688 @@
+//! In-process harness for driving a server `Connection` with raw frames.
+//!
+//! Frames are written byte for byte by the tests, so that invalid frames can
+//! be expressed just as easily as valid ones. The frames written by the server
+//! are parsed with a minimal decoder that does not share any code with the
+//! codec under test.
+
+use {client, server, Reason, RecvStream, Stats};
+use server::{Handshake, SendResponse};
+
+use bytes::Bytes;
+use futures::{Async, Future, Poll, Stream};
+use futures::executor::{self, Notify, NotifyHandle, Spawn};
+use http::{Request, Response};
+use tokio_io::{AsyncRead, AsyncWrite};
+
+use std::{cmp, io};
+use std::sync::{Arc, Mutex};
+
+pub const PREFACE: &'static [u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+// Frame types
+pub const DATA: u8 = 0x0;
+pub const HEADERS: u8 = 0x1;
+pub const PRIORITY: u8 = 0x2;
+pub const RST_STREAM: u8 = 0x3;
+pub const SETTINGS: u8 = 0x4;
+pub const PUSH_PROMISE: u8 = 0x5;
+pub const PING: u8 = 0x6;
+pub const GOAWAY: u8 = 0x7;
+pub const WINDOW_UPDATE: u8 = 0x8;
+pub const CONTINUATION: u8 = 0x9;
+
+// Frame flags
+pub const END_STREAM: u8 = 0x1;
+pub const ACK: u8 = 0x1;
+pub const END_HEADERS: u8 = 0x4;
+pub const PADDED: u8 = 0x8;
+pub const PRIORITY_FLAG: u8 = 0x20;
+
+// Setting identifiers
+pub const SETTINGS_ENABLE_PUSH: u16 = 0x2;
+pub const SETTINGS_MAX_FRAME_SIZE: u16 = 0x5;
+
+/// `GET http://example.com/`, encoded using only the static table and literals
+/// without indexing so that the HPACK decoder state is never modified.
+pub const GET_REQUEST: &'static [u8] = &[
+    0x82, // :method GET
+    0x86, // :scheme http
+    0x84, // :path /
+    0x01, 0x0b, // :authority, literal without indexing
+    b'e', b'x', b'a', b'm', b'p', b'l', b'e', b'.', b'c', b'o', b'm',
+];
+
+/// Builds a raw frame.
+pub fn frame(kind: u8, flags: u8, stream_id: u32, payload: &[u8]) -> Vec<u8> {
+    let len = payload.len();
+    assert!(len < 1 << 24, "payload too large");
+
+    let mut dst = Vec::with_capacity(9 + len);
+    dst.push((len >> 16) as u8);
+    dst.push((len >> 8) as u8);
+    dst.push(len as u8);
+    dst.push(kind);
+    dst.push(flags);
+    dst.extend_from_slice(&u32_bytes(stream_id));
+    dst.extend_from_slice(payload);
+    dst
+}
+
+pub fn settings(values: &[(u16, u32)]) -> Vec<u8> {
+    let mut payload = vec![];
+
+    for &(id, val) in values {
+        payload.push((id >> 8) as u8);
+        payload.push(id as u8);
+        payload.extend_from_slice(&u32_bytes(val));
+    }
+
+    frame(SETTINGS, 0, 0, &payload)
+}
+
+pub fn settings_ack() -> Vec<u8> {
+    frame(SETTINGS, ACK, 0, &[])
+}
+
+pub fn ping(payload: [u8; 8]) -> Vec<u8> {
+    frame(PING, 0, 0, &payload)
+}
+
+/// A request with an empty body on `stream_id`.
+pub fn get(stream_id: u32) -> Vec<u8> {
+    frame(HEADERS, END_HEADERS | END_STREAM, stream_id, GET_REQUEST)
+}
+
+/// A request on `stream_id` that is followed by a body.
+pub fn post(stream_id: u32) -> Vec<u8> {
+    frame(HEADERS, END_HEADERS, stream_id, GET_REQUEST)
+}
+
+pub fn data(stream_id: u32, flags: u8, payload: &[u8]) -> Vec<u8> {
+    frame(DATA, flags, stream_id, payload)
+}
+
+pub fn priority(stream_id: u32, dependency: u32, weight: u8) -> Vec<u8> {
+    let mut payload = u32_bytes(dependency).to_vec();
+    payload.push(weight);
+    frame(PRIORITY, 0, stream_id, &payload)
+}
+
+pub fn reset(stream_id: u32, reason: Reason) -> Vec<u8> {
+    frame(RST_STREAM, 0, stream_id, &u32_bytes(reason.into()))
+}
+
+pub fn go_away(last_stream_id: u32, reason: Reason) -> Vec<u8> {
+    let mut payload = u32_bytes(last_stream_id).to_vec();
+    payload.extend_from_slice(&u32_bytes(reason.into()));
+    frame(GOAWAY, 0, 0, &payload)
+}
+
+pub fn window_update(stream_id: u32, increment: u32) -> Vec<u8> {
+    frame(WINDOW_UPDATE, 0, stream_id, &u32_bytes(increment))
+}
+
+fn u32_bytes(val: u32) -> [u8; 4] {
+    [(val >> 24) as u8, (val >> 16) as u8, (val >> 8) as u8, val as u8]
+}
+
+/// A frame written by the server.
+#[derive(Debug, Clone)]
+pub struct RawFrame {
+    pub kind: u8,
+    pub flags: u8,
+    pub stream_id: u32,
+    pub payload: Vec<u8>,
+}
+
+impl RawFrame {
+    /// The error code of a `RST_STREAM` or `GOAWAY` frame.
+    pub fn reason(&self) -> Reason {
+        let offset = match self.kind {
+            RST_STREAM => 0,
+            GOAWAY => 4,
+            kind => panic!("frame type {} has no error code", kind),
+        };
+
+        read_u32(&self.payload[offset..offset + 4]).into()
+    }
+}
+
+fn read_u32(src: &[u8]) -> u32 {
+    (src[0] as u32) << 24 | (src[1] as u32) << 16 | (src[2] as u32) << 8 | src[3] as u32
+}
+
+/// Parses all complete frames in `src`, leaving any trailing partial frame.
+fn parse_frames(src: &mut Vec<u8>) -> Vec<RawFrame> {
+    let mut frames = vec![];
+    let mut pos = 0;
+
+    while src.len() - pos >= 9 {
+        let len = (src[pos] as usize) << 16 | (src[pos + 1] as usize) << 8 | src[pos + 2] as usize;
+
+        if src.len() - pos < 9 + len {
+            break;
+        }
+
+        frames.push(RawFrame {
+            kind: src[pos + 3],
+            flags: src[pos + 4],
+            stream_id: read_u32(&src[pos + 5..pos + 9]) & !(1 << 31),
+            payload: src[pos + 9..pos + 9 + len].to_vec(),
+        });
+
+        pos += 9 + len;
+    }
+
+    src.drain(..pos);
+    frames
+}
+
+// ===== Mock I/O =====
+
+#[derive(Debug, Default)]
+struct Pipe {
+    /// Bytes written by the test, not yet read by the server
+    input: Vec<u8>,
+
+    /// Bytes written by the server, not yet inspected by the test
+    output: Vec<u8>,
+
+    /// Set when the test closes its write half
+    eof: bool,
+
+    /// Total number of bytes written by the test
+    num_input: usize,
+
+    /// Total number of bytes written by the server
+    num_output: usize,
+}
+
+/// An in-memory transport. Reads return `WouldBlock` until more input is
+/// written or the transport is closed.
+#[derive(Debug, Clone, Default)]
+pub struct Mock {
+    pipe: Arc<Mutex<Pipe>>,
+
+    /// Set on the other end of the pipe, which reads the output and writes
+    /// the input.
+    is_peer: bool,
+}
+
+impl Mock {
+    /// Returns both ends of a new in-memory transport.
+    pub fn pair() -> (Mock, Mock) {
+        let mock = Mock::default();
+        let peer = Mock {
+            pipe: mock.pipe.clone(),
+            is_peer: true,
+        };
+
+        (mock, peer)
+    }
+}
+
+impl io::Read for Mock {
+    fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+        let mut pipe = self.pipe.lock().unwrap();
+        let eof = pipe.eof;
+
+        let src = if self.is_peer { &mut pipe.output } else { &mut pipe.input };
+
+        if src.is_empty() {
+            if eof {
+                return Ok(0);
+            }
+
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+
+        let n = cmp::min(dst.len(), src.len());
+        dst[..n].copy_from_slice(&src[..n]);
+        src.drain(..n);
+
+        Ok(n)
+    }
+}
+
+impl AsyncRead for Mock {}
+
+impl io::Write for Mock {
+    fn write(&mut self, src: &[u8]) -> io::Result<usize> {
+        let mut pipe = self.pipe.lock().unwrap();
+
+        if self.is_peer {
+            pipe.input.extend_from_slice(src);
+            pipe.num_input += src.len();
+        } else {
+            pipe.output.extend_from_slice(src);
+            pipe.num_output += src.len();
+        }
+
+        Ok(src.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for Mock {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        Ok(Async::Ready(()))
+    }
+}
+
+// ===== Server harness =====
+
+pub struct Noop;
+
+impl Notify for Noop {
+    fn notify(&self, _: usize) {}
+}
+
+enum State {
+    Handshaking(Handshake<Mock>),
+    Open(server::Connection<Mock, Bytes>),
+    Closed,
+}
+
+/// Drives the server connection, holding on to every accepted request so that
+/// streams are not implicitly canceled.
+struct Driver {
+    state: State,
+    requests: Vec<(Request<RecvStream>, SendResponse<Bytes>)>,
+    result: Option<Result<(), ::Error>>,
+}
+
+impl Future for Driver {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<(), ()> {
+        loop {
+            let res = match self.state {
+                State::Handshaking(ref mut handshake) => match handshake.poll() {
+                    Ok(Async::Ready(conn)) => Ok(Some(conn)),
+                    Ok(Async::NotReady) => return Ok(Async::NotReady),
+                    Err(e) => Err(e),
+                },
+                State::Open(ref mut conn) => match conn.poll() {
+                    Ok(Async::Ready(Some(request))) => {
+                        self.requests.push(request);
+                        continue;
+                    },
+                    Ok(Async::Ready(None)) => Ok(None),
+                    Ok(Async::NotReady) => return Ok(Async::NotReady),
+                    Err(e) => Err(e),
+                },
+                State::Closed => return Ok(Async::Ready(())),
+            };
+
+            self.state = match res {
+                Ok(Some(conn)) => State::Open(conn),
+                Ok(None) => {
+                    self.result = Some(Ok(()));
+                    State::Closed
+                },
+                Err(e) => {
+                    self.result = Some(Err(e));
+                    State::Closed
+                },
+            };
+        }
+    }
+}
+
+/// A server connection under test.
+pub struct Server {
+    io: Mock,
+    driver: Spawn<Driver>,
+    notify: NotifyHandle,
+}
+
+impl Server {
+    pub fn new() -> Server {
+        Server::with_builder(&server::Builder::new())
+    }
+
+    pub fn with_builder(builder: &server::Builder) -> Server {
+        let io = Mock::default();
+        let driver = Driver {
+            state: State::Handshaking(builder.handshake(io.clone())),
+            requests: vec![],
+            result: None,
+        };
+
+        Server {
+            io,
+            driver: executor::spawn(driver),
+            notify: NotifyHandle::from(Arc::new(Noop)),
+        }
+    }
+
+    /// Creates a server and completes the connection preface, consuming the
+    /// server's initial SETTINGS frame and the acknowledgement of ours.
+    pub fn handshake() -> Server {
+        Server::handshake_with(&server::Builder::new())
+    }
+
+    pub fn handshake_with(builder: &server::Builder) -> Server {
+        let mut srv = Server::with_builder(builder);
+
+        srv.send(PREFACE);
+        srv.send(&settings(&[]));
+
+        let frames = srv.recv();
+        assert!(frames.len() >= 2, "expected SETTINGS and SETTINGS ACK; frames={:?}", frames);
+        assert_eq!(frames[0].kind, SETTINGS);
+        assert_eq!(frames[0].flags & ACK, 0);
+        assert!(
+            frames.iter().any(|f| f.kind == SETTINGS && f.flags & ACK == ACK),
+            "server did not acknowledge SETTINGS; frames={:?}",
+            frames
+        );
+
+        srv.send(&settings_ack());
+        assert!(srv.recv().is_empty());
+
+        srv
+    }
+
+    /// Writes raw bytes to the server.
+    pub fn send(&mut self, src: &[u8]) {
+        let mut pipe = self.io.pipe.lock().unwrap();
+        pipe.input.extend_from_slice(src);
+        pipe.num_input += src.len();
+    }
+
+    /// Total number of bytes written to and by the server, in that order.
+    pub fn num_bytes(&self) -> (usize, usize) {
+        let pipe = self.io.pipe.lock().unwrap();
+        (pipe.num_input, pipe.num_output)
+    }
+
+    /// A snapshot of the open connection's stats.
+    pub fn stats(&self) -> Stats {
+        match self.driver.get_ref().state {
+            State::Open(ref conn) => conn.stats(),
+            _ => panic!("connection is not open"),
+        }
+    }
+
+    /// Closes the client's write half.
+    pub fn close(&mut self) {
+        self.io.pipe.lock().unwrap().eof = true;
+    }
+
+    /// Drives the connection until it can make no further progress.
+    pub fn poll(&mut self) {
+        // The connection may yield after notifying itself, so poll a few times
+        // rather than relying on the notification.
+        for _ in 0..8 {
+            if let Ok(Async::Ready(())) = self.driver.poll_future_notify(&self.notify, 0) {
+                return;
+            }
+        }
+    }
+
+    /// Drives the connection and returns the frames written by the server.
+    pub fn recv(&mut self) -> Vec<RawFrame> {
+        self.poll();
+        parse_frames(&mut self.io.pipe.lock().unwrap().output)
+    }
+
+    /// Number of requests accepted so far.
+    pub fn num_requests(&self) -> usize {
+        self.driver.get_ref().requests.len()
+    }
+
+    /// Responds to the `n`th accepted request with an empty `200 OK`.
+    pub fn respond(&mut self, n: usize) {
+        let respond = &mut self.driver.get_mut().requests[n].1;
+        respond.send_response(Response::new(()), true).unwrap();
+    }
+
+    /// The error the connection terminated with, if it has terminated.
+    pub fn result(&self) -> Option<&Result<(), ::Error>> {
+        self.driver.get_ref().result.as_ref()
+    }
+
+    /// Asserts that the connection was closed with a GOAWAY frame carrying
+    /// `reason`.
+    pub fn assert_go_away(&mut self, reason: Reason) {
+        let frames = self.recv();
+
+        let go_away = frames
+            .iter()
+            .find(|f| f.kind == GOAWAY)
+            .unwrap_or_else(|| panic!("expected GOAWAY({:?}); frames={:?}", reason, frames));
+
+        assert_eq!(go_away.stream_id, 0);
+        assert_eq!(go_away.reason(), reason, "frames={:?}", frames);
+        assert!(
+            frames.iter().skip_while(|f| f.kind != GOAWAY).skip(1).next().is_none(),
+            "frames written after GOAWAY; frames={:?}",
+            frames
+        );
+
+        match self.result() {
+            Some(&Err(ref e)) => assert_eq!(e.reason(), Some(reason)),
+            res => panic!("expected connection error {:?}; got {:?}", reason, res),
+        }
+    }
+
+    /// Asserts that `stream_id` was reset with `reason` while the connection
+    /// remains usable.
+    pub fn assert_reset(&mut self, stream_id: u32, reason: Reason) {
+        let frames = self.recv();
+
+        let reset = frames
+            .iter()
+            .find(|f| f.kind == RST_STREAM && f.stream_id == stream_id)
+            .unwrap_or_else(|| panic!("expected RST_STREAM({:?}) on stream {}; frames={:?}", reason, stream_id, frames));
+
+        assert_eq!(reset.reason(), reason, "frames={:?}", frames);
+        assert!(frames.iter().all(|f| f.kind != GOAWAY), "frames={:?}", frames);
+
+        self.assert_open();
+    }
+
+    /// Asserts that the connection is still alive by round tripping a PING.
+    pub fn assert_open(&mut self) {
+        let payload = *b"h2 alive";
+        self.send(&ping(payload));
+
+        let frames = self.recv();
+
+        assert!(self.result().is_none(), "connection closed; result={:?}", self.result());
+        assert!(
+            frames.iter().any(|f| f.kind == PING && f.flags & ACK == ACK && f.payload == payload),
+            "no PING ACK; frames={:?}",
+            frames
+        );
+        assert!(frames.iter().all(|f| f.kind != GOAWAY), "frames={:?}", frames);
+    }
+}
+
+// ===== Client harness =====
+
+/// A client connection under test. The test plays the part of the server.
+pub struct Client {
+    io: Mock,
+    send_request: client::SendRequest<Bytes>,
+    conn: Spawn<client::Connection<Mock, Bytes>>,
+    result: Option<Result<(), ::Error>>,
+    notify: NotifyHandle,
+}
+
+impl Client {
+    /// Creates a client and completes the connection preface, sending
+    /// `values` in the server's initial SETTINGS frame.
+    pub fn handshake_with(values: &[(u16, u32)]) -> Client {
+        let io = Mock::default();
+        let (send_request, conn) = client::handshake(io.clone()).wait().unwrap();
+
+        let mut client = Client {
+            io,
+            send_request,
+            conn: executor::spawn(conn),
+            result: None,
+            notify: NotifyHandle::from(Arc::new(Noop)),
+        };
+
+        client.poll();
+
+        {
+            let mut pipe = client.io.pipe.lock().unwrap();
+            assert!(pipe.output.starts_with(PREFACE), "expected client preface");
+            pipe.output.drain(..PREFACE.len());
+        }
+
+        let frames = client.recv();
+        assert_eq!(frames.len(), 1, "expected SETTINGS; frames={:?}", frames);
+        assert_eq!(frames[0].kind, SETTINGS);
+
+        client.send(&settings(values));
+        client.send(&settings_ack());
+
+        let frames = client.recv();
+        assert!(
+            frames.iter().any(|f| f.kind == SETTINGS && f.flags & ACK == ACK),
+            "client did not acknowledge SETTINGS; frames={:?}",
+            frames
+        );
+
+        client
+    }
+
+    /// Writes raw bytes to the client.
+    pub fn send(&mut self, src: &[u8]) {
+        let mut pipe = self.io.pipe.lock().unwrap();
+        pipe.input.extend_from_slice(src);
+        pipe.num_input += src.len();
+    }
+
+    /// Drives the connection until it can make no further progress.
+    pub fn poll(&mut self) {
+        if self.result.is_some() {
+            return;
+        }
+
+        for _ in 0..8 {
+            match self.conn.poll_future_notify(&self.notify, 0) {
+                Ok(Async::NotReady) => {},
+                Ok(Async::Ready(())) => {
+                    self.result = Some(Ok(()));
+                    return;
+                },
+                Err(e) => {
+                    self.result = Some(Err(e));
+                    return;
+                },
+            }
+        }
+    }
+
+    /// Drives the connection and returns the frames written by the client.
+    pub fn recv(&mut self) -> Vec<RawFrame> {
+        self.poll();
+        parse_frames(&mut self.io.pipe.lock().unwrap().output)
+    }
+
+    pub fn send_request(&mut self) -> &mut client::SendRequest<Bytes> {
+        &mut self.send_request
+    }
+
+    /// Asserts that the connection was closed with a GOAWAY frame carrying
+    /// `reason`.
+    pub fn assert_go_away(&mut self, reason: Reason) {
+        let frames = self.recv();
+
+        let go_away = frames
+            .iter()
+            .find(|f| f.kind == GOAWAY)
+            .unwrap_or_else(|| panic!("expected GOAWAY({:?}); frames={:?}", reason, frames));
+
+        assert_eq!(go_away.reason(), reason, "frames={:?}", frames);
+
+        match self.result {
+            Some(Err(ref e)) => assert_eq!(e.reason(), Some(reason)),
+            ref res => panic!("expected connection error {:?}; got {:?}", reason, res),
+        }
+    }
+}
+
+// ===== Client and server pair =====
+
+/// A client and a server connected to each other over an in-memory
+/// transport.
+pub struct Pair {
+    client: client::SendRequest<Bytes>,
+    client_conn: Spawn<client::Connection<Mock, Bytes>>,
+    server: Spawn<Driver>,
+    notify: NotifyHandle,
+}
+
+impl Pair {
+    /// Connects a client to a server built with `builder` and exchanges the
+    /// initial SETTINGS frames.
+    pub fn new(builder: &server::Builder) -> Pair {
+        let (client_io, server_io) = Mock::pair();
+
+        let (client, client_conn) = client::handshake(client_io).wait().unwrap();
+        let server = Driver {
+            state: State::Handshaking(builder.handshake(server_io)),
+            requests: vec![],
+            result: None,
+        };
+
+        let mut pair = Pair {
+            client,
+            client_conn: executor::spawn(client_conn),
+            server: executor::spawn(server),
+            notify: NotifyHandle::from(Arc::new(Noop)),
+        };
+
+        pair.poll();
+        pair
+    }
+
+    pub fn client(&mut self) -> &mut client::SendRequest<Bytes> {
+        &mut self.client
+    }
+
+    /// Drives both connections until they can make no further progress.
+    pub fn poll(&mut self) {
+        for _ in 0..8 {
+            let _ = self.client_conn.poll_future_notify(&self.notify, 0);
+            let _ = self.server.poll_future_notify(&self.notify, 0);
+        }
+    }
+
+    /// Drives both connections until `f` completes.
+    pub fn run<F: Future>(&mut self, f: F) -> Result<F::Item, F::Error> {
+        let mut f = executor::spawn(f);
+
+        for _ in 0..100 {
+            match f.poll_future_notify(&self.notify, 0) {
+                Ok(Async::Ready(v)) => return Ok(v),
+                Ok(Async::NotReady) => {},
+                Err(e) => return Err(e),
+            }
+
+            self.poll();
+        }
+
+        panic!("future did not complete");
+    }
+
+    /// Removes the oldest request accepted by the server.
+    pub fn accept(&mut self) -> (Request<RecvStream>, SendResponse<Bytes>) {
+        self.poll();
+
+        let requests = &mut self.server.get_mut().requests;
+        assert!(!requests.is_empty(), "no request accepted");
+        requests.remove(0)
+    }
+}
@@ -0,0 +1,6 @@
+mod conformance;
+mod error_codes;
+mod extended_connect;
+mod fuzz;
+mod mock;
+mod stats;
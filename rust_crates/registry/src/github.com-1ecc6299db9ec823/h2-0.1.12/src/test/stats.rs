@@ -0,0 +1,52 @@
+//! Tests for the counters reported by `Connection::stats`.
+
+use Reason;
+use server;
+
+use super::mock::*;
+
+#[test]
+fn byte_counters_match_frames_on_the_wire() {
+    let mut srv = Server::handshake();
+
+    srv.send(&get(1));
+    srv.poll();
+    srv.respond(0);
+
+    let frames = srv.recv();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].kind, HEADERS);
+
+    srv.assert_open();
+
+    // The connection preface is not a frame, so it is not counted. Everything
+    // else, frame headers included, is counted the same way in both
+    // directions.
+    let (num_input, num_output) = srv.num_bytes();
+    let stats = srv.stats();
+
+    assert_eq!(stats.bytes_received(), (num_input - PREFACE.len()) as u64);
+    assert_eq!(stats.bytes_sent(), num_output as u64);
+
+    // SETTINGS, SETTINGS ACK, HEADERS and PING
+    let received = 9 + 9 + (9 + GET_REQUEST.len()) + (9 + 8);
+    assert_eq!(stats.bytes_received(), received as u64);
+}
+
+#[test]
+fn reset_counters() {
+    let mut srv = Server::handshake_with(server::Builder::new().max_concurrent_streams(1));
+
+    srv.send(&post(1));
+    srv.send(&post(3));
+    srv.assert_reset(3, Reason::REFUSED_STREAM);
+
+    srv.send(&reset(1, Reason::CANCEL));
+    srv.assert_open();
+
+    let stats = srv.stats();
+
+    assert_eq!(stats.num_resets_sent(), 1);
+    assert_eq!(stats.num_refused_streams(), 1);
+    assert_eq!(stats.num_resets_received(), 1);
+}
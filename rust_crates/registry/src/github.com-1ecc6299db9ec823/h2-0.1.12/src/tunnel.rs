@@ -0,0 +1,172 @@
+use {RecvStream, SendStream};
+
+use bytes::Bytes;
+use futures::{Async, Poll, Stream};
+use tokio_io::{AsyncRead, AsyncWrite};
+
+use std::{cmp, fmt, io};
+
+/// A bidirectional byte stream carried over a single HTTP/2.0 stream.
+///
+/// # Overview
+///
+/// A `Tunnel` combines the [`SendStream`] and [`RecvStream`] halves of an
+/// established `CONNECT` or extended `CONNECT` stream into a single value
+/// implementing `AsyncRead` and `AsyncWrite`. This allows protocols such as
+/// WebSockets or TCP proxy tunnels to share one HTTP/2.0 connection with
+/// regular requests.
+///
+/// On the client, the tunnel is created once the response to the `CONNECT`
+/// request has been received with a 2xx status, using the [`SendStream`]
+/// returned by `send_request` and the response body. On the server, it is
+/// created from the request body and the [`SendStream`] returned by
+/// `send_response`.
+///
+/// # Flow control
+///
+/// Reading from the tunnel releases the consumed capacity back to the peer.
+/// Writing to the tunnel requests send capacity on behalf of the caller and
+/// only writes as much data as there is capacity assigned, so writes may be
+/// shorter than the provided buffer.
+///
+/// Shutting down the write half sends an empty `DATA` frame with the
+/// `END_STREAM` flag set. Dropping the tunnel without shutting it down resets
+/// the stream.
+///
+/// [`SendStream`]: struct.SendStream.html
+/// [`RecvStream`]: struct.RecvStream.html
+pub struct Tunnel {
+    send: SendStream<Bytes>,
+    recv: RecvStream,
+
+    /// Data received but not yet read by the caller
+    buf: Bytes,
+
+    /// Set once the send half has been closed
+    is_send_closed: bool,
+}
+
+impl Tunnel {
+    /// Creates a new `Tunnel` from the two halves of an established stream.
+    pub fn new(send: SendStream<Bytes>, recv: RecvStream) -> Self {
+        Tunnel {
+            send,
+            recv,
+            buf: Bytes::new(),
+            is_send_closed: false,
+        }
+    }
+
+    /// Returns references to the underlying stream halves.
+    pub fn get_ref(&self) -> (&SendStream<Bytes>, &RecvStream) {
+        (&self.send, &self.recv)
+    }
+
+    /// Returns mutable references to the underlying stream halves.
+    pub fn get_mut(&mut self) -> (&mut SendStream<Bytes>, &mut RecvStream) {
+        (&mut self.send, &mut self.recv)
+    }
+
+    /// Consumes the tunnel, returning the underlying stream halves.
+    ///
+    /// Any data that was received but not yet read is returned as well.
+    pub fn into_parts(self) -> (SendStream<Bytes>, RecvStream, Bytes) {
+        (self.send, self.recv, self.buf)
+    }
+}
+
+impl io::Read for Tunnel {
+    fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+        // A zero-length read says nothing about the end of the stream, so
+        // don't touch the buffer or poll for more data.
+        if dst.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            if !self.buf.is_empty() {
+                let n = cmp::min(dst.len(), self.buf.len());
+
+                dst[..n].copy_from_slice(&self.buf[..n]);
+                self.buf.advance(n);
+
+                self.recv
+                    .release_capacity()
+                    .release_capacity(n)
+                    .map_err(::Error::into_io)?;
+
+                return Ok(n);
+            }
+
+            match self.recv.poll() {
+                Ok(Async::Ready(Some(chunk))) => self.buf = chunk,
+                Ok(Async::Ready(None)) => return Ok(0),
+                Ok(Async::NotReady) => return Err(io::ErrorKind::WouldBlock.into()),
+                Err(e) => return Err(e.into_io()),
+            }
+        }
+    }
+}
+
+impl AsyncRead for Tunnel {}
+
+impl io::Write for Tunnel {
+    fn write(&mut self, src: &[u8]) -> io::Result<usize> {
+        if src.is_empty() {
+            return Ok(0);
+        }
+
+        if self.is_send_closed {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+
+        self.send.reserve_capacity(src.len());
+
+        let capacity = loop {
+            match self.send.poll_capacity() {
+                Ok(Async::Ready(Some(0))) => continue,
+                Ok(Async::Ready(Some(capacity))) => break capacity,
+                Ok(Async::Ready(None)) => return Err(io::ErrorKind::BrokenPipe.into()),
+                Ok(Async::NotReady) => return Err(io::ErrorKind::WouldBlock.into()),
+                Err(e) => return Err(e.into_io()),
+            }
+        };
+
+        let n = cmp::min(capacity, src.len());
+
+        self.send
+            .send_data(Bytes::from(&src[..n]), false)
+            .map_err(::Error::into_io)?;
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // Frames are flushed to the socket by the `Connection`.
+        Ok(())
+    }
+}
+
+impl AsyncWrite for Tunnel {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        if !self.is_send_closed {
+            self.send
+                .send_data(Bytes::new(), true)
+                .map_err(::Error::into_io)?;
+
+            self.is_send_closed = true;
+        }
+
+        Ok(Async::Ready(()))
+    }
+}
+
+impl fmt::Debug for Tunnel {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Tunnel")
+            .field("send", &self.send)
+            .field("recv", &self.recv)
+            .field("buffered", &self.buf.len())
+            .finish()
+    }
+}
@@ -347,5 +347,9 @@ fn check_per_nonce_max_bytes(alg: &Algorithm, in_out_len: usize)
 }
 
 pub mod chacha20_poly1305_openssh;
+
+#[cfg(feature = "use_heap")]
+pub mod stream;
+
 mod chacha20_poly1305;
 mod aes_gcm;
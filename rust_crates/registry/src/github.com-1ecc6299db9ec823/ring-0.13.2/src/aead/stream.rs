@@ -0,0 +1,297 @@
+// Copyright 2018 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Streaming AEAD encryption of messages too large to hold in memory.
+//!
+//! This implements the STREAM construction from [Online
+//! Authenticated-Encryption and its Nonce-Reuse Misuse-Resistance]. The
+//! plaintext is split into segments of `segment_len` bytes, except for the
+//! last segment, which may be shorter (or even empty). Each segment is sealed
+//! separately with the same key and associated data, and the stream is the
+//! concatenation of the sealed segments, each followed by its tag.
+//!
+//! The nonce of each segment is `nonce_prefix||counter||last`, where
+//! `nonce_prefix` is `NONCE_PREFIX_LEN` bytes chosen by the caller, `counter`
+//! is the index of the segment as a 32-bit big-endian integer, and `last` is
+//! the byte 1 for the last segment and 0 otherwise. Since the index is in the
+//! nonce, segments that are reordered fail to open, and since the last segment
+//! is marked, a stream that is truncated at a segment boundary fails to open
+//! too.
+//!
+//! `nonce_prefix` must be unique for every stream sealed with the same key.
+//!
+//! Only available in `use_heap` mode.
+//!
+//! [Online Authenticated-Encryption and its Nonce-Reuse Misuse-Resistance]:
+//!     https://eprint.iacr.org/2015/189.pdf
+
+use {error, polyfill};
+use core;
+use std;
+use std::io;
+use super::{check_per_nonce_max_bytes, open_in_place, seal_in_place,
+            OpeningKey, SealingKey, NONCE_LEN, TAG_LEN};
+
+/// The length of the nonce prefix of a stream.
+pub const NONCE_PREFIX_LEN: usize = NONCE_LEN - 4 - 1;
+
+/// Seals everything written to it as a stream, writing the sealed stream to
+/// an inner writer.
+///
+/// `finish()` must be called after the last plaintext is written, to seal the
+/// last segment. A `SealingWriter` that is dropped without calling `finish()`
+/// leaves a stream that will fail to open.
+pub struct SealingWriter<W: io::Write> {
+    key: SealingKey,
+    nonces: Nonces,
+    ad: std::vec::Vec<u8>,
+    segment_len: usize,
+    buf: std::vec::Vec<u8>,
+    inner: W,
+}
+
+impl<W: io::Write> SealingWriter<W> {
+    /// Constructs a new `SealingWriter`.
+    ///
+    /// `nonce_prefix` must be `NONCE_PREFIX_LEN` bytes long, and
+    /// `segment_len` must be non-zero and no larger than the algorithm's
+    /// per-nonce limit.
+    pub fn new(key: SealingKey, nonce_prefix: &[u8], ad: &[u8],
+               segment_len: usize, inner: W)
+               -> Result<SealingWriter<W>, error::Unspecified> {
+        let nonces = Nonces::new(nonce_prefix)?;
+        check_segment_len(key.algorithm(), segment_len)?;
+        Ok(SealingWriter {
+            key,
+            nonces,
+            ad: ad.to_vec(),
+            segment_len,
+            buf: std::vec::Vec::with_capacity(segment_len + TAG_LEN),
+            inner,
+        })
+    }
+
+    /// Seals the last segment, flushes the inner writer, and returns it.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.seal_segment(true)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn seal_segment(&mut self, last: bool) -> io::Result<()> {
+        let nonce = self.nonces.next(last).map_err(invalid_data)?;
+        let in_out_len = self.buf.len() + TAG_LEN;
+        self.buf.resize(in_out_len, 0);
+        let out_len = seal_in_place(&self.key, &nonce, &self.ad, &mut self.buf,
+                                    TAG_LEN).map_err(invalid_data)?;
+        self.inner.write_all(&self.buf[..out_len])?;
+        self.buf.clear();
+        Ok(())
+    }
+}
+
+impl<W: io::Write> io::Write for SealingWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+
+        // A full segment can only be sealed once it is known not to be the
+        // last one, i.e. once there is more plaintext to follow it.
+        if self.buf.len() == self.segment_len {
+            self.seal_segment(false)?;
+        }
+
+        let n = core::cmp::min(data.len(), self.segment_len - self.buf.len());
+        self.buf.extend_from_slice(&data[..n]);
+        Ok(n)
+    }
+
+    /// Flushes the inner writer. Buffered plaintext that doesn't fill a
+    /// segment yet isn't written.
+    fn flush(&mut self) -> io::Result<()> { self.inner.flush() }
+}
+
+/// Opens a stream read from an inner reader.
+///
+/// Reads fail with an error of kind `io::ErrorKind::InvalidData` if a
+/// segment fails to open, including when the stream was truncated, reordered,
+/// or extended. Plaintext is only returned after the segment it is in has
+/// been authenticated, but a stream that fails to open may already have
+/// returned plaintext from earlier segments.
+pub struct OpeningReader<R: io::Read> {
+    key: OpeningKey,
+    nonces: Nonces,
+    ad: std::vec::Vec<u8>,
+    segment_len: usize,
+
+    // The current segment. After it has been opened, `buf[pos..plaintext_len]`
+    // is the plaintext that hasn't been read yet.
+    buf: std::vec::Vec<u8>,
+    pos: usize,
+    plaintext_len: usize,
+
+    // The first byte of the next segment, which has to be read to know
+    // whether the current segment is the last one.
+    lookahead: Option<u8>,
+    finished: bool,
+
+    // Set once a segment fails to open. The rest of the stream can't be
+    // trusted after that, so every later read fails too.
+    failed: bool,
+
+    inner: R,
+}
+
+impl<R: io::Read> OpeningReader<R> {
+    /// Constructs a new `OpeningReader`.
+    ///
+    /// `nonce_prefix`, `ad`, and `segment_len` must be the same as the ones
+    /// the stream was sealed with.
+    pub fn new(key: OpeningKey, nonce_prefix: &[u8], ad: &[u8],
+               segment_len: usize, inner: R)
+               -> Result<OpeningReader<R>, error::Unspecified> {
+        let nonces = Nonces::new(nonce_prefix)?;
+        check_segment_len(key.algorithm(), segment_len)?;
+        Ok(OpeningReader {
+            key,
+            nonces,
+            ad: ad.to_vec(),
+            segment_len,
+            buf: std::vec::Vec::with_capacity(segment_len + TAG_LEN + 1),
+            pos: 0,
+            plaintext_len: 0,
+            lookahead: None,
+            finished: false,
+            failed: false,
+            inner,
+        })
+    }
+
+    /// Returns the inner reader.
+    pub fn into_inner(self) -> R { self.inner }
+
+    fn open_segment(&mut self) -> io::Result<()> {
+        let sealed_segment_len = self.segment_len + TAG_LEN;
+
+        self.buf.clear();
+        if let Some(b) = self.lookahead.take() {
+            self.buf.push(b);
+        }
+        let mut filled = self.buf.len();
+        self.buf.resize(sealed_segment_len + 1, 0);
+        while filled < self.buf.len() {
+            match self.inner.read(&mut self.buf[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {},
+                Err(e) => return Err(e),
+            }
+        }
+        self.buf.truncate(filled);
+
+        let last = filled <= sealed_segment_len;
+        if !last {
+            self.lookahead = self.buf.pop();
+        }
+
+        let opened = self.nonces.next(last).and_then(|nonce| {
+            open_in_place(&self.key, &nonce, &self.ad, 0, &mut self.buf)
+                .map(|plaintext| plaintext.len())
+        });
+        self.plaintext_len = match opened {
+            Ok(plaintext_len) => plaintext_len,
+            Err(err) => {
+                self.failed = true;
+                return Err(invalid_data(err));
+            },
+        };
+        self.pos = 0;
+        self.finished = last;
+        Ok(())
+    }
+}
+
+impl<R: io::Read> io::Read for OpeningReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.failed {
+            return Err(invalid_data(error::Unspecified));
+        }
+        while self.pos == self.plaintext_len {
+            if self.finished || out.is_empty() {
+                return Ok(0);
+            }
+            self.open_segment()?;
+        }
+
+        let n = core::cmp::min(out.len(), self.plaintext_len - self.pos);
+        out[..n].copy_from_slice(&self.buf[self.pos..(self.pos + n)]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+fn check_segment_len(alg: &super::Algorithm, segment_len: usize)
+                     -> Result<(), error::Unspecified> {
+    if segment_len == 0 {
+        return Err(error::Unspecified);
+    }
+    check_per_nonce_max_bytes(alg, segment_len)
+}
+
+fn invalid_data(err: error::Unspecified) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+// The nonces of the segments of a stream, in order.
+struct Nonces {
+    prefix: [u8; NONCE_PREFIX_LEN],
+    counter: u32,
+    finished: bool,
+}
+
+impl Nonces {
+    fn new(prefix: &[u8]) -> Result<Nonces, error::Unspecified> {
+        let prefix = slice_as_array_ref!(prefix, NONCE_PREFIX_LEN)?;
+        Ok(Nonces {
+            prefix: *prefix,
+            counter: 0,
+            finished: false,
+        })
+    }
+
+    fn next(&mut self, last: bool)
+            -> Result<[u8; NONCE_LEN], error::Unspecified> {
+        if self.finished {
+            return Err(error::Unspecified);
+        }
+
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce[..NONCE_PREFIX_LEN].copy_from_slice(&self.prefix);
+        nonce[NONCE_PREFIX_LEN..(NONCE_LEN - 1)]
+            .copy_from_slice(&polyfill::slice::be_u8_from_u32(self.counter));
+        nonce[NONCE_LEN - 1] = last as u8;
+
+        // The counter must not wrap around; a stream that would need more
+        // segments than that can't be sealed.
+        match self.counter.checked_add(1) {
+            Some(counter) => self.counter = counter,
+            None if last => {},
+            None => return Err(error::Unspecified),
+        }
+        self.finished = last;
+
+        Ok(nonce)
+    }
+}
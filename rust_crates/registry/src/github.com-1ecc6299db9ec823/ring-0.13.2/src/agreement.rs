@@ -76,7 +76,9 @@
 
 
 
-use {ec, error, rand};
+use {ec, error, pkcs8, rand};
+#[cfg(feature = "use_heap")]
+use {der_writer, std};
 use untrusted;
 
 
@@ -146,6 +148,105 @@ impl<'a> EphemeralPrivateKey {
     }
 }
 
+/// A static private key for use with `agree_static`. Unlike an
+/// `EphemeralPrivateKey`, a `StaticPrivateKey` can be used for any number of
+/// key agreements, and it can be serialized as a PKCS#8 document so that it
+/// can be stored and used again later.
+pub struct StaticPrivateKey {
+    key_pair: ec::KeyPair,
+    alg: &'static Algorithm,
+}
+
+derive_debug_from_field!(StaticPrivateKey, alg);
+
+impl StaticPrivateKey {
+    /// Generate a new static private key for the given algorithm.
+    ///
+    /// The private key can't be serialized; use
+    /// `StaticPrivateKey::generate_pkcs8()` for a key that needs to be stored.
+    pub fn generate(alg: &'static Algorithm, rng: &rand::SecureRandom)
+                    -> Result<StaticPrivateKey, error::Unspecified> {
+        let private_key = ec::PrivateKey::generate(&alg.i.curve, rng)?;
+        Self::from_private_key(alg, private_key)
+    }
+
+    /// Generates a new static private key for the given algorithm and
+    /// returns it serialized as a PKCS#8 document.
+    ///
+    /// P-256 and P-384 keys are serialized as unencrypted PKCS#8 v1
+    /// documents holding an `ECPrivateKey` ([RFC 5915]), exactly like the
+    /// documents generated by `ECDSAKeyPair::generate_pkcs8()`. X25519 keys
+    /// are serialized as unencrypted PKCS#8 v2 documents ([RFC 5958]) as
+    /// described in [RFC 8410].
+    ///
+    /// Use `StaticPrivateKey::from_pkcs8()` to deserialize the document.
+    ///
+    /// [RFC 5915]: https://tools.ietf.org/html/rfc5915
+    /// [RFC 5958]: https://tools.ietf.org/html/rfc5958
+    /// [RFC 8410]: https://tools.ietf.org/html/rfc8410
+    pub fn generate_pkcs8(alg: &'static Algorithm, rng: &rand::SecureRandom)
+                          -> Result<pkcs8::Document, error::Unspecified> {
+        let key = Self::generate(alg, rng)?;
+        let curve = alg.i.curve;
+        Ok(pkcs8::wrap_key(alg.i.pkcs8_template,
+                           key.key_pair.private_key.bytes(curve),
+                           key.public_key()))
+    }
+
+    /// Constructs a static private key by parsing an unencrypted PKCS#8
+    /// document.
+    ///
+    /// Documents generated by `StaticPrivateKey::generate_pkcs8()` are
+    /// accepted. P-256 and P-384 documents must be PKCS#8 v1 documents
+    /// containing an `ECPrivateKey` that includes the public key. X25519
+    /// documents may be PKCS#8 v1 or v2; if the public key is present then it
+    /// is checked against the private key.
+    pub fn from_pkcs8(alg: &'static Algorithm, input: untrusted::Input)
+                      -> Result<StaticPrivateKey, error::Unspecified> {
+        let key_pair =
+            (alg.i.key_pair_from_pkcs8)(alg.i.curve, alg.i.pkcs8_template,
+                                        input)?;
+        Ok(StaticPrivateKey { key_pair, alg })
+    }
+
+    fn from_private_key(alg: &'static Algorithm, private_key: ec::PrivateKey)
+                        -> Result<StaticPrivateKey, error::Unspecified> {
+        let mut public_key = [0u8; PUBLIC_KEY_MAX_LEN];
+        private_key.compute_public_key(
+            &alg.i.curve, &mut public_key[..alg.i.curve.public_key_len])?;
+        Ok(StaticPrivateKey {
+            key_pair: ec::KeyPair { private_key, public_key },
+            alg,
+        })
+    }
+
+    /// The key exchange algorithm.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm { self.alg }
+
+    /// The public key, encoded in the standard form for the algorithm.
+    #[inline]
+    pub fn public_key(&self) -> &[u8] {
+        &self.key_pair.public_key[..self.alg.i.curve.public_key_len]
+    }
+
+    /// Returns the public key encoded as a DER `SubjectPublicKeyInfo`
+    /// ([RFC 5280 Section 4.1]), as used in X.509 certificates.
+    ///
+    /// P-256 and P-384 keys use the id-ecPublicKey algorithm identifier and a
+    /// named curve ([RFC 5480]). X25519 keys use the id-X25519 algorithm
+    /// identifier ([RFC 8410]).
+    ///
+    /// [RFC 5280 Section 4.1]: https://tools.ietf.org/html/rfc5280#section-4.1
+    /// [RFC 5480]: https://tools.ietf.org/html/rfc5480
+    /// [RFC 8410]: https://tools.ietf.org/html/rfc8410
+    #[cfg(feature = "use_heap")]
+    pub fn public_key_spki(&self) -> std::vec::Vec<u8> {
+        der_writer::subject_public_key_info(
+            self.alg.i.pkcs8_template.alg_id_value(), self.public_key())
+    }
+}
+
 /// Performs a key agreement with an ephemeral private key and the given public
 /// key.
 ///
@@ -176,16 +277,45 @@ pub fn agree_ephemeral<F, R, E>(my_private_key: EphemeralPrivateKey,
                                 peer_public_key: untrusted::Input,
                                 error_value: E, kdf: F) -> Result<R, E>
                                 where F: FnOnce(&[u8]) -> Result<R, E> {
+    agree_(&my_private_key.private_key, my_private_key.alg,
+           peer_public_key_alg, peer_public_key, error_value, kdf)
+}
+
+/// Performs a key agreement with a static private key and the given public
+/// key.
+///
+/// Unlike `agree_ephemeral`, `agree_static` borrows `my_private_key`, so the
+/// same private key can be used for any number of key agreements. This is
+/// what is needed when the private key is a long-term key, e.g. the
+/// recipient's key in `sealed_box`.
+///
+/// The other arguments, and the result, are the same as for
+/// `agree_ephemeral`.
+pub fn agree_static<F, R, E>(my_private_key: &StaticPrivateKey,
+                             peer_public_key_alg: &Algorithm,
+                             peer_public_key: untrusted::Input,
+                             error_value: E, kdf: F) -> Result<R, E>
+                             where F: FnOnce(&[u8]) -> Result<R, E> {
+    agree_(&my_private_key.key_pair.private_key, my_private_key.alg,
+           peer_public_key_alg, peer_public_key, error_value, kdf)
+}
+
+fn agree_<F, R, E>(my_private_key: &ec::PrivateKey,
+                   my_alg: &Algorithm,
+                   peer_public_key_alg: &Algorithm,
+                   peer_public_key: untrusted::Input,
+                   error_value: E, kdf: F) -> Result<R, E>
+                   where F: FnOnce(&[u8]) -> Result<R, E> {
     // NSA Guide Prerequisite 1.
     //
     // The domain parameters are hard-coded. This check verifies that the
     // peer's public key's domain parameters match the domain parameters of
     // this private key.
-    if peer_public_key_alg.i.curve.id != my_private_key.alg.i.curve.id {
+    if peer_public_key_alg.i.curve.id != my_alg.i.curve.id {
         return Err(error_value);
     }
 
-    let alg = &my_private_key.alg.i;
+    let alg = &my_alg.i;
 
     // NSA Guide Prerequisite 2, regarding which KDFs are allowed, is delegated
     // to the caller.
@@ -205,7 +335,7 @@ pub fn agree_ephemeral<F, R, E>(my_private_key: EphemeralPrivateKey,
     //
     // We have a pretty liberal interpretation of the NIST's spec's "Destroy"
     // that doesn't meet the NSA requirement to "zeroize."
-    (alg.ecdh)(shared_key, &my_private_key.private_key, peer_public_key)
+    (alg.ecdh)(shared_key, my_private_key, peer_public_key)
         .map_err(|_| error_value)?;
 
     // NSA Guide Steps 5 and 6.
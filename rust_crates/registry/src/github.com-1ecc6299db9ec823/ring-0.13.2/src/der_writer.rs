@@ -0,0 +1,87 @@
+// Copyright 2018 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Building blocks for writing DER-encoded ASN.1 structures.
+//!
+//! This is the counterpart of `der`. It only supports what *ring* needs in
+//! order to serialize keys.
+
+use der;
+use std;
+
+/// Writes `value` with the given tag.
+pub(crate) fn write_tlv(output: &mut std::vec::Vec<u8>, tag: der::Tag,
+                        value: &[u8]) {
+    output.push(tag as u8);
+    let len = value.len();
+    if len < 0x80 {
+        output.push(len as u8);
+    } else if len <= 0xff {
+        output.push(0x81);
+        output.push(len as u8);
+    } else {
+        // `der` doesn't accept anything longer than this either.
+        assert!(len <= 0xffff);
+        output.push(0x82);
+        output.push((len >> 8) as u8);
+        output.push(len as u8);
+    }
+    output.extend_from_slice(value);
+}
+
+/// Writes the value written by `write_value` with the given tag.
+pub(crate) fn write_nested<F>(output: &mut std::vec::Vec<u8>, tag: der::Tag,
+                              write_value: F)
+                              where F: FnOnce(&mut std::vec::Vec<u8>) {
+    let mut value = std::vec::Vec::new();
+    write_value(&mut value);
+    write_tlv(output, tag, &value);
+}
+
+/// Writes the big-endian-encoded nonnegative integer `value` as an
+/// `INTEGER`. `value` may have leading zeros.
+pub(crate) fn write_positive_integer(output: &mut std::vec::Vec<u8>,
+                                     value: &[u8]) {
+    let first_nonzero =
+        value.iter().position(|&b| b != 0).unwrap_or(value.len());
+    let value = &value[first_nonzero..];
+    let mut encoded = std::vec::Vec::with_capacity(value.len() + 1);
+    // Zero is encoded as a single zero byte, and a leading zero byte is
+    // needed when the high bit is set so that the value isn't negative.
+    if value.first().map_or(true, |&b| b & 0x80 != 0) {
+        encoded.push(0);
+    }
+    encoded.extend_from_slice(value);
+    write_tlv(output, der::Tag::Integer, &encoded);
+}
+
+/// Returns a DER-encoded `SubjectPublicKeyInfo` ([RFC 5280 Section 4.1]).
+///
+/// `alg_id` is the encoded value (not including the outermost `SEQUENCE` tag
+/// and length) of the `AlgorithmIdentifier` of the key, and `public_key` is
+/// the contents of the `subjectPublicKey` bit string.
+///
+/// [RFC 5280 Section 4.1]: https://tools.ietf.org/html/rfc5280#section-4.1
+pub(crate) fn subject_public_key_info(alg_id: &[u8], public_key: &[u8])
+                                      -> std::vec::Vec<u8> {
+    let mut spki = std::vec::Vec::new();
+    write_nested(&mut spki, der::Tag::Sequence, |spki| {
+        write_tlv(spki, der::Tag::Sequence, alg_id);
+        write_nested(spki, der::Tag::BitString, |bit_string| {
+            bit_string.push(0); // No unused bits.
+            bit_string.extend_from_slice(public_key);
+        });
+    });
+    spki
+}
@@ -12,11 +12,16 @@
 // OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
 // CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
-//! SHA-2 and the legacy SHA-1 digest algorithm.
+//! SHA-2, SHA-3, and the legacy SHA-1 digest algorithm, and the SHAKE
+//! extendable-output functions.
 //!
 //! If all the data is available in a single contiguous slice then the `digest`
 //! function should be used. Otherwise, the digest can be calculated in
 //! multiple steps using `Context`.
+//!
+//! SHAKE128 and SHAKE256 can be used like the other algorithms, in which case
+//! their output has a fixed length, `Algorithm::output_len`. Use
+//! `Context::finish_xof` to get output of any length from them instead.
 
 // Note on why are we doing things the hard way: It would be easy to implement
 // this using the C `EVP_MD`/`EVP_MD_CTX` interface. However, if we were to do
@@ -24,7 +29,7 @@
 // The goal for this implementation is to drive the overhead as close to zero
 // as possible.
 
-use {c, init, polyfill};
+use {c, error, init, polyfill};
 use core;
 
 // XXX: Replace with `const fn` when `const fn` is stable:
@@ -36,6 +41,7 @@ macro_rules! u32x2 {
 }
 
 mod sha1;
+mod sha3;
 
 /// A context for multi-step (Init-Update-Finish) digest calculations.
 ///
@@ -80,9 +86,13 @@ impl Context {
     pub fn new(algorithm: &'static Algorithm) -> Context {
         init::init_once();
 
+        let mut state = [0u64; STATE_LEN / 8];
+        state[..algorithm.initial_state.len()]
+            .copy_from_slice(&algorithm.initial_state);
+
         Context {
             algorithm: algorithm,
-            state: algorithm.initial_state,
+            state: state,
             completed_data_blocks: 0,
             pending: [0u8; MAX_BLOCK_LEN],
             num_pending: 0,
@@ -146,6 +156,69 @@ impl Context {
     ///
     /// C analogs: `EVP_DigestFinal`, `EVP_DigestFinal_ex`
     pub fn finish(mut self) -> Digest {
+        self.process_final_block();
+        Digest {
+            algorithm: self.algorithm,
+            value: (self.algorithm.format_output)(&self.state),
+        }
+    }
+
+    /// Finalizes the calculation of an extendable-output function (XOF) and
+    /// returns a reader for its output. Like `finish`, `finish_xof` consumes
+    /// the context.
+    ///
+    /// The first `self.algorithm().output_len` bytes of the output are the
+    /// same as the value that `finish` would have returned.
+    ///
+    /// Fails if the context's algorithm isn't an XOF, i.e. unless it is
+    /// `SHAKE128` or `SHAKE256`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ring::digest;
+    ///
+    /// let mut ctx = digest::Context::new(&digest::SHAKE128);
+    /// ctx.update(b"hello, world");
+    /// let mut xof = ctx.finish_xof().unwrap();
+    ///
+    /// let mut output = [0u8; 100];
+    /// xof.squeeze(&mut output[..10]);
+    /// xof.squeeze(&mut output[10..]);
+    ///
+    /// let fixed_len = digest::digest(&digest::SHAKE128, b"hello, world");
+    /// assert_eq!(fixed_len.as_ref(), &output[..digest::SHAKE128_OUTPUT_LEN]);
+    /// ```
+    pub fn finish_xof(mut self) -> Result<XofReader, error::Unspecified> {
+        if !self.algorithm.is_xof() {
+            return Err(error::Unspecified);
+        }
+        self.process_final_block();
+        Ok(XofReader {
+            state: self.state,
+            pos: 0,
+            algorithm: self.algorithm,
+        })
+    }
+
+    /// The algorithm that this context is using.
+    #[inline(always)]
+    pub fn algorithm(&self) -> &'static Algorithm { self.algorithm }
+
+    // Pads the pending data and processes it.
+    fn process_final_block(&mut self) {
+        let len_len = match self.algorithm.padding {
+            Padding::MerkleDamgard { len_len } => len_len,
+            Padding::SHA3 => {
+                self.process_final_keccak_block(sha3::SHA3_SUFFIX);
+                return;
+            },
+            Padding::SHAKE => {
+                self.process_final_keccak_block(sha3::SHAKE_SUFFIX);
+                return;
+            },
+        };
+
         // We know |num_pending < self.algorithm.block_len|, because we would
         // have processed the block otherwise.
 
@@ -153,7 +226,7 @@ impl Context {
         self.pending[padding_pos] = 0x80;
         padding_pos += 1;
 
-        if padding_pos > self.algorithm.block_len - self.algorithm.len_len {
+        if padding_pos > self.algorithm.block_len - len_len {
             polyfill::slice::fill(
                 &mut self.pending[padding_pos..self.algorithm.block_len], 0);
             unsafe {
@@ -184,14 +257,55 @@ impl Context {
             (self.algorithm.block_data_order)(&mut self.state,
                                               self.pending.as_ptr(), 1);
         }
+    }
 
-        Digest {
-            algorithm: self.algorithm,
-            value: (self.algorithm.format_output)(&self.state),
+    // The pad10*1 padding of FIPS 202, preceded by the domain separation
+    // bits, which are combined with the first bit of the padding in `suffix`.
+    fn process_final_keccak_block(&mut self, suffix: u8) {
+        let block_len = self.algorithm.block_len;
+        self.pending[self.num_pending] = suffix;
+        polyfill::slice::fill(
+            &mut self.pending[(self.num_pending + 1)..block_len], 0);
+        self.pending[block_len - 1] |= 0x80;
+        unsafe {
+            (self.algorithm.block_data_order)(&mut self.state,
+                                              self.pending.as_ptr(), 1);
         }
     }
+}
 
-    /// The algorithm that this context is using.
+/// A reader of the output of an extendable-output function (XOF), returned
+/// by `Context::finish_xof`.
+#[derive(Clone)]
+pub struct XofReader {
+    state: State,
+
+    // The number of bytes of the current output block that have already been
+    // squeezed out.
+    pos: usize,
+
+    algorithm: &'static Algorithm,
+}
+
+impl XofReader {
+    /// Fills `out` with the next `out.len()` bytes of output. The output
+    /// doesn't depend on how it is split up into calls to `squeeze`.
+    pub fn squeeze(&mut self, out: &mut [u8]) {
+        let block_len = self.algorithm.block_len;
+        let mut out = out;
+        while !out.is_empty() {
+            if self.pos == block_len {
+                sha3::keccak_f(&mut self.state);
+                self.pos = 0;
+            }
+            let n = core::cmp::min(out.len(), block_len - self.pos);
+            sha3::squeeze(&self.state, self.pos, &mut out[..n]);
+            self.pos += n;
+            out = &mut {out}[n..];
+        }
+    }
+
+    /// The algorithm of the XOF.
     #[inline(always)]
     pub fn algorithm(&self) -> &'static Algorithm { self.algorithm }
 }
@@ -268,24 +382,35 @@ pub struct Algorithm {
     /// `output_len`. For truncated algorithms (e.g. SHA-384, SHA-512/256),
     /// this is equal to the length before truncation. This is mostly helpful
     /// for determining the size of an HMAC key that is appropriate for the
-    /// digest algorithm.
+    /// digest algorithm. For SHA-3 and SHAKE, whose internal state is much
+    /// larger than their output, this is equal to `output_len`.
     pub chaining_len: usize,
 
     /// C analog: `EVP_MD_block_size`
+    ///
+    /// For SHA-3 and SHAKE, this is the rate of the sponge.
     pub block_len: usize,
 
-    /// The length of the length in the padding.
-    len_len: usize,
+    padding: Padding,
 
     block_data_order: unsafe extern fn(state: &mut State, data: *const u8,
                                        num: c::size_t),
     format_output: fn(input: &State) -> Output,
 
-    initial_state: State,
+    initial_state: [u64; MAX_CHAINING_LEN / 8],
 
     id: AlgorithmID,
 }
 
+impl Algorithm {
+    fn is_xof(&self) -> bool {
+        match self.padding {
+            Padding::SHAKE => true,
+            _ => false,
+        }
+    }
+}
+
 #[derive(Debug, Eq, PartialEq)]
 enum AlgorithmID {
     SHA1,
@@ -293,6 +418,21 @@ enum AlgorithmID {
     SHA384,
     SHA512,
     SHA512_256,
+    SHA3_256,
+    SHA3_512,
+    SHAKE128,
+    SHAKE256,
+}
+
+enum Padding {
+    // The padding of SHA-1 and SHA-2: a 1 bit, zeros, and the length of the
+    // input in bits, in a `len_len`-byte field.
+    MerkleDamgard { len_len: usize },
+
+    // The padding of SHA-3 and of SHAKE, which differ in their domain
+    // separation bits. The state is initially all zeros.
+    SHA3,
+    SHAKE,
 }
 
 impl PartialEq for Algorithm {
@@ -310,7 +450,7 @@ pub static SHA1: Algorithm = Algorithm {
     output_len: sha1::OUTPUT_LEN,
     chaining_len: sha1::CHAINING_LEN,
     block_len: sha1::BLOCK_LEN,
-    len_len: 64 / 8,
+    padding: Padding::MerkleDamgard { len_len: 64 / 8 },
     block_data_order: sha1::block_data_order,
     format_output: sha256_format_output,
     initial_state: [
@@ -329,7 +469,7 @@ pub static SHA256: Algorithm = Algorithm {
     output_len: SHA256_OUTPUT_LEN,
     chaining_len: SHA256_OUTPUT_LEN,
     block_len: 512 / 8,
-    len_len: 64 / 8,
+    padding: Padding::MerkleDamgard { len_len: 64 / 8 },
     block_data_order: GFp_sha256_block_data_order,
     format_output: sha256_format_output,
     initial_state: [
@@ -349,7 +489,7 @@ pub static SHA384: Algorithm = Algorithm {
     output_len: SHA384_OUTPUT_LEN,
     chaining_len: SHA512_OUTPUT_LEN,
     block_len: SHA512_BLOCK_LEN,
-    len_len: SHA512_LEN_LEN,
+    padding: Padding::MerkleDamgard { len_len: SHA512_LEN_LEN },
     block_data_order: GFp_sha512_block_data_order,
     format_output: sha512_format_output,
     initial_state: [
@@ -372,7 +512,7 @@ pub static SHA512: Algorithm = Algorithm {
     output_len: SHA512_OUTPUT_LEN,
     chaining_len: SHA512_OUTPUT_LEN,
     block_len: SHA512_BLOCK_LEN,
-    len_len: SHA512_LEN_LEN,
+    padding: Padding::MerkleDamgard { len_len: SHA512_LEN_LEN },
     block_data_order: GFp_sha512_block_data_order,
     format_output: sha512_format_output,
     initial_state: [
@@ -399,7 +539,7 @@ pub static SHA512_256: Algorithm = Algorithm {
     output_len: SHA512_256_OUTPUT_LEN,
     chaining_len: SHA512_OUTPUT_LEN,
     block_len: SHA512_BLOCK_LEN,
-    len_len: SHA512_LEN_LEN,
+    padding: Padding::MerkleDamgard { len_len: SHA512_LEN_LEN },
     block_data_order: GFp_sha512_block_data_order,
     format_output: sha512_format_output,
     initial_state: [
@@ -415,14 +555,82 @@ pub static SHA512_256: Algorithm = Algorithm {
     id: AlgorithmID::SHA512_256,
 };
 
+/// SHA3-256 as specified in [FIPS 202].
+///
+/// [FIPS 202]: http://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf
+pub static SHA3_256: Algorithm = Algorithm {
+    output_len: SHA3_256_OUTPUT_LEN,
+    chaining_len: SHA3_256_OUTPUT_LEN,
+    block_len: sha3::SHA3_256_BLOCK_LEN,
+    padding: Padding::SHA3,
+    block_data_order: sha3::block_data_order_136,
+    format_output: sha3_format_output,
+    initial_state: [0; MAX_CHAINING_LEN / 8],
+    id: AlgorithmID::SHA3_256,
+};
+
+/// SHA3-512 as specified in [FIPS 202].
+///
+/// [FIPS 202]: http://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf
+pub static SHA3_512: Algorithm = Algorithm {
+    output_len: SHA3_512_OUTPUT_LEN,
+    chaining_len: SHA3_512_OUTPUT_LEN,
+    block_len: sha3::SHA3_512_BLOCK_LEN,
+    padding: Padding::SHA3,
+    block_data_order: sha3::block_data_order_72,
+    format_output: sha3_format_output,
+    initial_state: [0; MAX_CHAINING_LEN / 8],
+    id: AlgorithmID::SHA3_512,
+};
+
+/// SHAKE128 as specified in [FIPS 202].
+///
+/// Used as a digest algorithm, its output is `SHAKE128_OUTPUT_LEN` (32)
+/// bytes long, which gives it the full 128-bit security level. Use
+/// `Context::finish_xof` for output of other lengths.
+///
+/// [FIPS 202]: http://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf
+pub static SHAKE128: Algorithm = Algorithm {
+    output_len: SHAKE128_OUTPUT_LEN,
+    chaining_len: SHAKE128_OUTPUT_LEN,
+    block_len: sha3::SHAKE128_BLOCK_LEN,
+    padding: Padding::SHAKE,
+    block_data_order: sha3::block_data_order_168,
+    format_output: sha3_format_output,
+    initial_state: [0; MAX_CHAINING_LEN / 8],
+    id: AlgorithmID::SHAKE128,
+};
+
+/// SHAKE256 as specified in [FIPS 202].
+///
+/// Used as a digest algorithm, its output is `SHAKE256_OUTPUT_LEN` (64)
+/// bytes long, which gives it the full 256-bit security level. Use
+/// `Context::finish_xof` for output of other lengths.
+///
+/// [FIPS 202]: http://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf
+pub static SHAKE256: Algorithm = Algorithm {
+    output_len: SHAKE256_OUTPUT_LEN,
+    chaining_len: SHAKE256_OUTPUT_LEN,
+    block_len: sha3::SHAKE256_BLOCK_LEN,
+    padding: Padding::SHAKE,
+    block_data_order: sha3::block_data_order_136,
+    format_output: sha3_format_output,
+    initial_state: [0; MAX_CHAINING_LEN / 8],
+    id: AlgorithmID::SHAKE256,
+};
+
 // We use u64 to try to ensure 64-bit alignment/padding.
-type State = [u64; MAX_CHAINING_LEN / 8];
+type State = [u64; STATE_LEN / 8];
+
+// The length of the state of Keccak-f[1600], the largest state of all the
+// algorithms in this module.
+const STATE_LEN: usize = 1600 / 8;
 
 type Output = [u64; MAX_OUTPUT_LEN / 8];
 
 /// The maximum block length (`Algorithm::block_len`) of all the algorithms in
 /// this module.
-pub const MAX_BLOCK_LEN: usize = 1024 / 8;
+pub const MAX_BLOCK_LEN: usize = sha3::SHAKE128_BLOCK_LEN;
 
 /// The maximum output length (`Algorithm::output_len`) of all the algorithms
 /// in this module.
@@ -455,6 +663,17 @@ fn sha512_format_output(input: &State) -> Output {
      input[7].to_be()]
 }
 
+fn sha3_format_output(input: &State) -> Output {
+    [input[0].to_le(),
+     input[1].to_le(),
+     input[2].to_le(),
+     input[3].to_le(),
+     input[4].to_le(),
+     input[5].to_le(),
+     input[6].to_le(),
+     input[7].to_le()]
+}
+
 /// The length of the output of SHA-1, in bytes.
 pub const SHA1_OUTPUT_LEN: usize = sha1::OUTPUT_LEN;
 
@@ -470,6 +689,20 @@ pub const SHA512_OUTPUT_LEN: usize = 512 / 8;
 /// The length of the output of SHA-512/256, in bytes.
 pub const SHA512_256_OUTPUT_LEN: usize = 256 / 8;
 
+/// The length of the output of SHA3-256, in bytes.
+pub const SHA3_256_OUTPUT_LEN: usize = 256 / 8;
+
+/// The length of the output of SHA3-512, in bytes.
+pub const SHA3_512_OUTPUT_LEN: usize = 512 / 8;
+
+/// The length of the output of SHAKE128 when it is used as a digest
+/// algorithm, in bytes.
+pub const SHAKE128_OUTPUT_LEN: usize = 256 / 8;
+
+/// The length of the output of SHAKE256 when it is used as a digest
+/// algorithm, in bytes.
+pub const SHAKE256_OUTPUT_LEN: usize = 512 / 8;
+
 /// The length of a block for SHA-512-based algorithms, in bytes.
 const SHA512_BLOCK_LEN: usize = 1024 / 8;
 
@@ -488,12 +721,16 @@ extern {
 pub mod test_util {
     use super::super::digest;
 
-    pub static ALL_ALGORITHMS: [&'static digest::Algorithm; 5] = [
+    pub static ALL_ALGORITHMS: [&'static digest::Algorithm; 9] = [
         &digest::SHA1,
         &digest::SHA256,
         &digest::SHA384,
         &digest::SHA512,
         &digest::SHA512_256,
+        &digest::SHA3_256,
+        &digest::SHA3_512,
+        &digest::SHAKE128,
+        &digest::SHAKE256,
     ];
 }
 
@@ -560,11 +797,8 @@ mod tests {
             let max_bytes = 1u64 << (64 - 3);
             let max_blocks = max_bytes / (alg.block_len as u64);
             digest::Context {
-                algorithm: alg,
-                state: alg.initial_state,
                 completed_data_blocks: max_blocks - 1,
-                pending: [0u8; digest::MAX_BLOCK_LEN],
-                num_pending: 0,
+                ..digest::Context::new(alg)
             }
         }
 
@@ -20,7 +20,7 @@ use core::num::Wrapping;
 // XXX: This duplicates super::State and shouldn't need to be public.
 // TODO: Remove the duplication, but be wary of
 // https://github.com/rust-lang/rust/issues/30905.
-pub type State = [u64; super::MAX_CHAINING_LEN / 8];
+pub type State = [u64; super::STATE_LEN / 8];
 
 pub const BLOCK_LEN: usize = 512 / 8;
 pub const CHAINING_LEN: usize = 160 / 8;
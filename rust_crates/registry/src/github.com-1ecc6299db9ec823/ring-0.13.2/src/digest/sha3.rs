@@ -0,0 +1,129 @@
+// Copyright 2015-2016 Brian Smith.
+// Copyright 2016 Simon Sapin.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+
+//! SHA-3 and SHAKE, as specified in [FIPS 202].
+//!
+//! [FIPS 202]: http://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf
+
+use c;
+use core;
+
+// The rates (block lengths) of the sponge, in bytes. The rate is the width of
+// Keccak-f[1600] (1600 bits) minus twice the security level.
+pub const SHA3_256_BLOCK_LEN: usize = (1600 - 2 * 256) / 8;
+pub const SHA3_512_BLOCK_LEN: usize = (1600 - 2 * 512) / 8;
+pub const SHAKE128_BLOCK_LEN: usize = (1600 - 2 * 128) / 8;
+pub const SHAKE256_BLOCK_LEN: usize = (1600 - 2 * 256) / 8;
+
+// The domain separation bits that are appended to the message, together with
+// the first bit of the pad10*1 padding.
+pub const SHA3_SUFFIX: u8 = 0x06;
+pub const SHAKE_SUFFIX: u8 = 0x1f;
+
+pub unsafe extern fn block_data_order_72(state: &mut super::State,
+                                         data: *const u8, num: c::size_t) {
+    block_data_order(state, data, num, SHA3_512_BLOCK_LEN)
+}
+
+pub unsafe extern fn block_data_order_136(state: &mut super::State,
+                                          data: *const u8, num: c::size_t) {
+    block_data_order(state, data, num, SHA3_256_BLOCK_LEN)
+}
+
+pub unsafe extern fn block_data_order_168(state: &mut super::State,
+                                          data: *const u8, num: c::size_t) {
+    block_data_order(state, data, num, SHAKE128_BLOCK_LEN)
+}
+
+unsafe fn block_data_order(state: &mut super::State, data: *const u8,
+                           num: c::size_t, block_len: usize) {
+    let data = core::slice::from_raw_parts(data, num * block_len);
+    for block in data.chunks(block_len) {
+        for (lane, bytes) in state.iter_mut().zip(block.chunks(8)) {
+            *lane ^= bytes.iter().rev()
+                .fold(0, |acc, &b| (acc << 8) | u64::from(b));
+        }
+        keccak_f(state);
+    }
+}
+
+/// Writes the bytes of the rate part of `state` starting at byte `offset` to
+/// `out`. `offset + out.len()` must not be more than the rate.
+pub fn squeeze(state: &super::State, offset: usize, out: &mut [u8]) {
+    for (i, out) in (offset..).zip(out.iter_mut()) {
+        *out = (state[i / 8] >> (8 * (i % 8))) as u8;
+    }
+}
+
+/// The Keccak-f[1600] permutation.
+pub fn keccak_f(state: &mut super::State) {
+    let a = state;
+
+    for round_constant in ROUND_CONSTANTS.iter() {
+        // Theta.
+        let mut c = [0u64; 5];
+        for x in 0..5 {
+            c[x] = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                a[5 * y + x] ^= d;
+            }
+        }
+
+        // Rho and pi.
+        let mut last = a[1];
+        for (&rotation, &lane) in RHO.iter().zip(PI.iter()) {
+            let next = a[lane];
+            a[lane] = last.rotate_left(rotation);
+            last = next;
+        }
+
+        // Chi.
+        for y in 0..5 {
+            let row = [a[5 * y], a[5 * y + 1], a[5 * y + 2], a[5 * y + 3],
+                       a[5 * y + 4]];
+            for x in 0..5 {
+                a[5 * y + x] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+
+        // Iota.
+        a[0] ^= *round_constant;
+    }
+}
+
+// The rotation offsets of rho, in the order in which pi visits the lanes.
+const RHO: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14,
+    27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+
+// The order in which pi visits the lanes, starting from lane 1.
+const PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4,
+    15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808a,
+    0x8000000080008000, 0x000000000000808b, 0x0000000080000001,
+    0x8000000080008081, 0x8000000000008009, 0x000000000000008a,
+    0x0000000000000088, 0x0000000080008009, 0x000000008000000a,
+    0x000000008000808b, 0x800000000000008b, 0x8000000000008089,
+    0x8000000000008003, 0x8000000000008002, 0x8000000000000080,
+    0x000000000000800a, 0x800000008000000a, 0x8000000080008081,
+    0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+];
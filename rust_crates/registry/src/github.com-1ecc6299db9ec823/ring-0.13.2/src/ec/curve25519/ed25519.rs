@@ -16,6 +16,8 @@
 
 use core;
 use {der, digest, error, pkcs8, private, rand, signature, signature_impl};
+#[cfg(feature = "use_heap")]
+use {der_writer, std};
 use super::ops::*;
 use untrusted;
 
@@ -167,6 +169,18 @@ impl<'a> KeyPair {
         &self.public_key
     }
 
+    /// Returns the public key encoded as a DER `SubjectPublicKeyInfo`
+    /// ([RFC 5280 Section 4.1]) with the id-Ed25519 algorithm identifier, as
+    /// used in X.509 certificates ([RFC 8410]).
+    ///
+    /// [RFC 5280 Section 4.1]: https://tools.ietf.org/html/rfc5280#section-4.1
+    /// [RFC 8410]: https://tools.ietf.org/html/rfc8410
+    #[cfg(feature = "use_heap")]
+    pub fn public_key_spki(&self) -> std::vec::Vec<u8> {
+        der_writer::subject_public_key_info(PKCS8_TEMPLATE.alg_id_value(),
+                                            &self.public_key)
+    }
+
     /// Returns the signature of the message `msg`.
     pub fn sign(&self, msg: &[u8]) -> signature::Signature {
         let mut signature_bytes = [0u8; SIGNATURE_LEN];
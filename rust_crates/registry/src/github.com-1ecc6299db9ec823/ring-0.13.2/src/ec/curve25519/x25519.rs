@@ -14,7 +14,8 @@
 
 //! X25519 Key agreement.
 
-use {agreement, constant_time, ec, error, rand};
+use core;
+use {agreement, constant_time, der, ec, error, pkcs8, rand};
 use super::ops;
 use untrusted;
 
@@ -40,6 +41,8 @@ pub static X25519: agreement::Algorithm = agreement::Algorithm {
     i: ec::AgreementAlgorithmImpl {
         curve: &CURVE25519,
         ecdh: x25519_ecdh,
+        pkcs8_template: &PKCS8_TEMPLATE,
+        key_pair_from_pkcs8: x25519_key_pair_from_pkcs8,
     },
 };
 
@@ -99,6 +102,46 @@ fn x25519_ecdh(out: &mut [u8], my_private_key: &ec::PrivateKey,
     Ok(())
 }
 
+// Parses an X25519 private key as described in [RFC 8410]. PKCS#8 v1
+// documents, which don't contain the public key, are accepted, since that is
+// what OpenSSL generates. When the public key is present it must match the
+// private key.
+//
+// [RFC 8410]: https://tools.ietf.org/html/rfc8410
+fn x25519_key_pair_from_pkcs8(curve: &ec::Curve, template: &pkcs8::Template,
+                              input: untrusted::Input)
+                              -> Result<ec::KeyPair, error::Unspecified> {
+    let (private_key, public_key) =
+        pkcs8::unwrap_key(template, pkcs8::Version::V1OrV2, input)?;
+    let private_key = private_key.read_all(error::Unspecified, |input| {
+        der::expect_tag_and_get_value(input, der::Tag::OctetString)
+    })?;
+    let private_key = ec::PrivateKey::from_bytes(curve, private_key)?;
+
+    let mut public_key_bytes = [0; ec::PUBLIC_KEY_MAX_LEN];
+    { // Borrow `public_key_bytes`.
+        let computed = &mut public_key_bytes[..curve.public_key_len];
+        private_key.compute_public_key(curve, computed)?;
+        if let Some(public_key) = public_key {
+            if public_key != &*computed {
+                return Err(error::Unspecified);
+            }
+        }
+    }
+
+    Ok(ec::KeyPair {
+        private_key: private_key,
+        public_key: public_key_bytes,
+    })
+}
+
+static PKCS8_TEMPLATE: pkcs8::Template = pkcs8::Template {
+    bytes: include_bytes!("x25519_pkcs8_v2_template.der"),
+    alg_id_range: core::ops::Range { start: 7, end: 12 },
+    curve_id_index: 0,
+    private_key_index: 0x10,
+};
+
 const ELEM_AND_SCALAR_LEN: usize = ops::ELEM_LEN;
 
 // An X25519 private key as an unmasked scalar.
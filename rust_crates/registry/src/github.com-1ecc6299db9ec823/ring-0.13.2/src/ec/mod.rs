@@ -12,7 +12,7 @@
 // OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
 // CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
-use {error, init, rand};
+use {error, init, pkcs8, rand};
 use untrusted;
 
 /// A key agreement algorithm.
@@ -22,6 +22,13 @@ pub struct AgreementAlgorithmImpl {
     pub ecdh: fn(out: &mut [u8], private_key: &PrivateKey,
                  peer_public_key: untrusted::Input)
                  -> Result<(), error::Unspecified>,
+
+    // The template for PKCS#8 documents generated for static private keys,
+    // and the function that parses them.
+    pub pkcs8_template: &'static pkcs8::Template,
+    pub key_pair_from_pkcs8: fn(curve: &Curve, template: &pkcs8::Template,
+                                input: untrusted::Input)
+                                -> Result<KeyPair, error::Unspecified>,
 }
 
 derive_debug_from_field!(AgreementAlgorithmImpl, curve);
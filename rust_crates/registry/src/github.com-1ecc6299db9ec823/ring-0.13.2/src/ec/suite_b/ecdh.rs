@@ -23,7 +23,7 @@ use untrusted;
 /// A key agreement algorithm.
 macro_rules! ecdh {
     ( $NAME:ident, $curve:expr, $name_str:expr, $private_key_ops:expr,
-      $public_key_ops:expr, $ecdh:ident ) =>
+      $public_key_ops:expr, $pkcs8_template:expr, $ecdh:ident ) =>
     {
         #[doc="ECDH using the NSA Suite B"]
         #[doc=$name_str]
@@ -46,6 +46,8 @@ macro_rules! ecdh {
             i: ec::AgreementAlgorithmImpl {
                 curve: $curve,
                 ecdh: $ecdh,
+                pkcs8_template: $pkcs8_template,
+                key_pair_from_pkcs8: ec::suite_b::key_pair_from_pkcs8,
             },
         };
 
@@ -59,10 +61,14 @@ macro_rules! ecdh {
 }
 
 ecdh!(ECDH_P256, &ec::suite_b::curve::P256, "P-256 (secp256r1)",
-      &p256::PRIVATE_KEY_OPS, &p256::PUBLIC_KEY_OPS, p256_ecdh);
+      &p256::PRIVATE_KEY_OPS, &p256::PUBLIC_KEY_OPS,
+      &ec::suite_b::ecdsa::signing::EC_PUBLIC_KEY_P256_PKCS8_V1_TEMPLATE,
+      p256_ecdh);
 
 ecdh!(ECDH_P384, &ec::suite_b::curve::P384, "P-384 (secp384r1)",
-      &p384::PRIVATE_KEY_OPS, &p384::PUBLIC_KEY_OPS, p384_ecdh);
+      &p384::PRIVATE_KEY_OPS, &p384::PUBLIC_KEY_OPS,
+      &ec::suite_b::ecdsa::signing::EC_PUBLIC_KEY_P384_PKCS8_V1_TEMPLATE,
+      p384_ecdh);
 
 
 fn ecdh(private_key_ops: &PrivateKeyOps, public_key_ops: &PublicKeyOps,
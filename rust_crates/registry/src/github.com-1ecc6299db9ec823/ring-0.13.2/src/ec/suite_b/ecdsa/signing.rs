@@ -17,6 +17,8 @@
 use arithmetic::montgomery::*;
 use core;
 use {der, digest, ec, error, pkcs8, rand, signature, signature_impl};
+#[cfg(feature = "use_heap")]
+use {der_writer, std};
 use super::digest_scalar::digest_scalar;
 use ec::suite_b::{ops::*, private_key};
 use untrusted;
@@ -64,6 +66,7 @@ impl signature::SigningAlgorithm for Algorithm {
 /// An ECDSA key pair, used for signing.
 pub struct Key {
     d: Scalar<R>,
+    public_key: [u8; ec::PUBLIC_KEY_MAX_LEN],
     alg: &'static Algorithm,
 }
 
@@ -129,7 +132,20 @@ impl<'a> Key {
         let d = alg.private_scalar_ops.scalar_ops.scalar_product(
             &d, &alg.private_scalar_ops.oneRR_mod_n);
 
-        Self { d, alg }
+        Self { d, public_key: key_pair.public_key, alg }
+    }
+
+    /// Returns the public key encoded as a DER `SubjectPublicKeyInfo`
+    /// ([RFC 5280 Section 4.1]) with the id-ecPublicKey algorithm identifier
+    /// and a named curve, as used in X.509 certificates ([RFC 5480]).
+    ///
+    /// [RFC 5280 Section 4.1]: https://tools.ietf.org/html/rfc5280#section-4.1
+    /// [RFC 5480]: https://tools.ietf.org/html/rfc5480
+    #[cfg(feature = "use_heap")]
+    pub fn public_key_spki(&self) -> std::vec::Vec<u8> {
+        der_writer::subject_public_key_info(
+            self.alg.pkcs8_template.alg_id_value(),
+            &self.public_key[..self.alg.curve.public_key_len])
     }
 
     /// Deprecated.
@@ -346,14 +362,14 @@ pub static ECDSA_P384_SHA384_ASN1_SIGNING: Algorithm = Algorithm {
     id: AlgorithmID::ECDSA_P384_SHA384_ASN1_SIGNING,
 };
 
-static EC_PUBLIC_KEY_P256_PKCS8_V1_TEMPLATE: pkcs8::Template = pkcs8::Template {
+pub(crate) static EC_PUBLIC_KEY_P256_PKCS8_V1_TEMPLATE: pkcs8::Template = pkcs8::Template {
     bytes: include_bytes ! ("ecPublicKey_p256_pkcs8_v1_template.der"),
     alg_id_range: core::ops::Range { start: 8, end: 27 },
     curve_id_index: 9,
     private_key_index: 0x24,
 };
 
-static EC_PUBLIC_KEY_P384_PKCS8_V1_TEMPLATE: pkcs8::Template = pkcs8::Template {
+pub(crate) static EC_PUBLIC_KEY_P384_PKCS8_V1_TEMPLATE: pkcs8::Template = pkcs8::Template {
     bytes: include_bytes!("ecPublicKey_p384_pkcs8_v1_template.der"),
     alg_id_range: core::ops::Range { start: 8, end: 24 },
     curve_id_index: 9,
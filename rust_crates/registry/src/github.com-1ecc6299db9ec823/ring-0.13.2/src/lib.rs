@@ -112,6 +112,9 @@ pub mod constant_time;
 #[doc(hidden)]
 pub mod der;
 
+#[cfg(feature = "use_heap")]
+mod der_writer;
+
 pub mod digest;
 mod ec;
 pub mod error;
@@ -127,6 +130,10 @@ pub mod rand;
 #[cfg(feature = "use_heap")]
 mod rsa;
 
+#[cfg(feature = "use_heap")]
+pub mod scrypt;
+
+pub mod sealed_box;
 pub mod signature;
 mod signature_impl;
 
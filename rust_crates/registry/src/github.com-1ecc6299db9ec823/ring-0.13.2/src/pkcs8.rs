@@ -49,7 +49,7 @@ pub(crate) struct Template {
 
 impl Template {
     #[inline]
-    fn alg_id_value(&self) -> &[u8] {
+    pub fn alg_id_value(&self) -> &[u8] {
         &self.bytes[self.alg_id_range.start..self.alg_id_range.end]
     }
 
@@ -276,6 +276,15 @@ impl<M> Modulus<M> {
         })
     }
 
+    /// Returns the big-endian encoding of the modulus, possibly with leading
+    /// zeros.
+    #[cfg(feature = "rsa_signing")]
+    pub fn to_be_bytes(&self) -> std::vec::Vec<u8> {
+        let mut bytes = vec![0; self.limbs.len() * limb::LIMB_BYTES];
+        limb::big_endian_from_limbs(&self.limbs, &mut bytes);
+        bytes
+    }
+
     #[inline]
     fn width(&self) -> Width<M> { self.limbs.width() }
 
@@ -632,6 +641,19 @@ impl PublicExponent {
     }
 }
 
+#[cfg(feature = "rsa_signing")]
+impl PublicExponent {
+    /// Returns the big-endian encoding of the exponent, possibly with leading
+    /// zeros.
+    pub fn to_be_bytes(&self) -> [u8; 8] {
+        let mut bytes = [0u8; 8];
+        for (i, b) in bytes.iter_mut().enumerate() {
+            *b = (self.0 >> (8 * (7 - i))) as u8;
+        }
+        bytes
+    }
+}
+
 // This limit was chosen to bound the performance of the simple
 // exponentiation-by-squaring implementation in `elem_exp_vartime`. In
 // particular, it helps mitigate theoretical resource exhaustion attacks. 33
@@ -0,0 +1,356 @@
+// Copyright 2018 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! RSA key generation.
+//!
+//! The primes are generated roughly as described in [FIPS 186-4] Appendix
+//! B.3.3, using probable primes. Random odd candidates with their two most
+//! significant bits set are tested with trial division by small primes and
+//! then with the Miller-Rabin test, using the number of rounds from
+//! [FIPS 186-4] Table C.3.
+//!
+//! The Miller-Rabin tests use the modular arithmetic of `super::bigint`. The
+//! rest of the arithmetic, which is only done once per key, is done with the
+//! simple big-endian byte string arithmetic at the end of this module. None of
+//! this is constant-time, but the only values that are treated differently
+//! depending on their value are the candidates that are rejected.
+//!
+//! [FIPS 186-4]: http://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.186-4.pdf
+
+use {bits, der, der_writer, error, limb, rand};
+use std;
+use super::bigint::{self, Prime};
+use arithmetic::montgomery::R;
+use untrusted;
+
+/// The public exponent of generated keys.
+const PUBLIC_EXPONENT: u32 = 65537;
+
+/// Generates an RSA private key with a public modulus of exactly `n_bits`
+/// bits and returns it as a DER-encoded `RSAPrivateKey`
+/// ([RFC 3447 Appendix A.1.2]).
+///
+/// [RFC 3447 Appendix A.1.2]:
+///     https://tools.ietf.org/html/rfc3447#appendix-A.1.2
+pub fn generate_rsa_private_key(n_bits: bits::BitLength,
+                                rng: &rand::SecureRandom)
+                                -> Result<std::vec::Vec<u8>,
+                                          error::Unspecified> {
+    let n_bits = n_bits.as_usize_bits();
+    if n_bits % 2 != 0 {
+        return Err(error::Unspecified);
+    }
+    let prime_bits = n_bits / 2;
+    let small_primes = small_primes();
+
+    // FIPS 186-4 Appendix B.3.3 Step 5.4: |p - q| must be larger than
+    // 2**(nlen/2 - 100). This essentially never fails but it is cheap to
+    // check.
+    let (p, q) = loop {
+        let p = generate_prime(prime_bits, &small_primes, rng)?;
+        let q = generate_prime(prime_bits, &small_primes, rng)?;
+        // `super::signing::KeyPair` requires `p > q`.
+        let (p, q) = if p > q { (p, q) } else { (q, p) };
+        if bit_length(&sub(&p, &q)) > prime_bits - 100 {
+            break (p, q);
+        }
+    };
+
+    let n = mul(&p, &q);
+    let p_minus_1 = minus_one(&p);
+    let q_minus_1 = minus_one(&q);
+
+    // Like many other implementations, `d` is e**-1 (mod φ(n)) instead of
+    // e**-1 (mod λ(n)). *ring* only uses `dP` and `dQ` anyway.
+    let d = inverse_of_public_exponent(&mul(&p_minus_1, &q_minus_1));
+    let dP = inverse_of_public_exponent(&p_minus_1);
+    let dQ = inverse_of_public_exponent(&q_minus_1);
+    let qInv = inverse_mod_prime(&q, &p)?;
+
+    let e = be_bytes_from_u32(PUBLIC_EXPONENT);
+
+    let mut der = std::vec::Vec::new();
+    der_writer::write_nested(&mut der, der::Tag::Sequence, |der| {
+        der_writer::write_positive_integer(der, &[0]); // Version two-prime.
+        for value in [&n[..], &e, &d, &p, &q, &dP, &dQ, &qInv].iter() {
+            der_writer::write_positive_integer(der, value);
+        }
+    });
+    Ok(der)
+}
+
+// Returns a random probable prime of exactly `bits` bits that is at least
+// 2**(bits - 1) + 2**(bits - 2), so that the product of two of them has
+// exactly `2 * bits` bits, and such that `p - 1` is coprime to the public
+// exponent.
+fn generate_prime(bits: usize, small_primes: &[u32], rng: &rand::SecureRandom)
+                  -> Result<std::vec::Vec<u8>, error::Unspecified> {
+    let len = (bits + 7) / 8;
+    let mut candidate = vec![0u8; len];
+
+    // FIPS 186-4 Appendix B.3.3 Step 4.7 gives up after 5 * bits candidates.
+    for _ in 0..(5 * bits) {
+        rng.fill(&mut candidate)?;
+        candidate[0] &= 0xff >> (8 * len - bits);
+        set_bit(&mut candidate, bits - 1);
+        set_bit(&mut candidate, bits - 2);
+        set_bit(&mut candidate, 0);
+
+        if small_primes.iter()
+            .any(|&prime| mod_small(&candidate, prime) == 0) {
+            continue;
+        }
+        if mod_small(&candidate, PUBLIC_EXPONENT) == 1 {
+            continue;
+        }
+        if is_probably_prime(&candidate, miller_rabin_rounds(bits), rng)? {
+            return Ok(candidate);
+        }
+    }
+
+    Err(error::Unspecified)
+}
+
+// FIPS 186-4 Table C.3, for an error probability of at most 2**-100.
+fn miller_rabin_rounds(bits: usize) -> usize {
+    if bits >= 1536 { 4 } else { 5 }
+}
+
+// The odd primes less than 2**11, for trial division.
+fn small_primes() -> std::vec::Vec<u32> {
+    const LIMIT: u32 = 1 << 11;
+    let mut primes: std::vec::Vec<u32> = std::vec::Vec::new();
+    let mut i = 3;
+    while i < LIMIT {
+        if primes.iter().all(|&prime| i % prime != 0) {
+            primes.push(i);
+        }
+        i += 2;
+    }
+    primes
+}
+
+// Type-level representation of a prime candidate that hasn't been tested yet.
+enum Candidate {}
+
+// Type-level representation of `p` when calculating `qInv`.
+enum P {}
+unsafe impl Prime for P {}
+
+// The Miller-Rabin test of the odd number `n`, which must be larger than 3.
+fn is_probably_prime(n: &[u8], rounds: usize, rng: &rand::SecureRandom)
+                     -> Result<bool, error::Unspecified> {
+    let n_minus_1 = minus_one(n);
+
+    // n - 1 == 2**s * d, where `d` is odd.
+    let mut d = n_minus_1.clone();
+    let mut s = 0;
+    while d.last().map_or(false, |&b| b & 1 == 0) {
+        d = div_small(&d, 2).0;
+        s += 1;
+    }
+
+    let (m, _) = bigint::Nonnegative::from_be_bytes_with_bit_length(
+        untrusted::Input::from(n))?;
+    let m: bigint::Modulus<Candidate> = bigint::Modulus::from(m)?;
+    let d = bigint::PrivateExponent::from_be_bytes_padded(
+        untrusted::Input::from(&d), &m)?;
+    let oneRR = bigint::One::newRR(&m);
+    let oneR = bigint::One::newR(&oneRR, &m);
+    let minus_oneR = bigint::elem_sub(m.zero(), oneR.as_ref(), &m);
+
+    let mut a = vec![0u8; n.len()];
+    for _ in 0..rounds {
+        // Choose `a` uniformly from [2, n - 2].
+        loop {
+            rng.fill(&mut a)?;
+            a[0] &= 0xff >> n[0].leading_zeros();
+            if a[..] >= n_minus_1[..] {
+                continue;
+            }
+            if bit_length(&a) < 2 {
+                continue;
+            }
+            break;
+        }
+        let a = bigint::Elem::from_be_bytes_padded(untrusted::Input::from(&a),
+                                                   &m)?;
+        let a = bigint::elem_mul(oneRR.as_ref(), a, &m);
+
+        let x = bigint::elem_exp_consttime(a, &d, &oneR, &m)?;
+        let mut x: bigint::Elem<Candidate, R> =
+            bigint::elem_mul(oneRR.as_ref(), x, &m);
+        if is_equal(&x, oneR.as_ref()) || is_equal(&x, &minus_oneR) {
+            continue;
+        }
+        let mut maybe_prime = false;
+        for _ in 1..s {
+            x = bigint::elem_squared(x, &m);
+            if is_equal(&x, &minus_oneR) {
+                maybe_prime = true;
+                break;
+            }
+            if is_equal(&x, oneR.as_ref()) {
+                break;
+            }
+        }
+        if !maybe_prime {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+fn is_equal<M, E>(a: &bigint::Elem<M, E>, b: &bigint::Elem<M, E>) -> bool {
+    bigint::elem_verify_equal_consttime(a, b).is_ok()
+}
+
+// Returns q**-1 (mod p).
+fn inverse_mod_prime(q: &[u8], p: &[u8])
+                     -> Result<std::vec::Vec<u8>, error::Unspecified> {
+    let (q, _) = bigint::Nonnegative::from_be_bytes_with_bit_length(
+        untrusted::Input::from(q))?;
+    let (p_value, _) = bigint::Nonnegative::from_be_bytes_with_bit_length(
+        untrusted::Input::from(p))?;
+    let p_modulus: bigint::Modulus<P> = bigint::Modulus::from(p_value)?;
+    let oneRR = bigint::One::newRR(&p_modulus);
+    let oneR = bigint::One::newR(&oneRR, &p_modulus);
+    let q = q.to_elem(&p_modulus)?;
+    let q = bigint::elem_mul(oneRR.as_ref(), q, &p_modulus);
+    let qInv = bigint::elem_inverse_consttime(q, &p_modulus, &oneR)?;
+    let num_limbs = (p.len() + limb::LIMB_BYTES - 1) / limb::LIMB_BYTES;
+    let mut qInv_bytes = vec![0u8; num_limbs * limb::LIMB_BYTES];
+    qInv.fill_be_bytes(&mut qInv_bytes);
+    Ok(qInv_bytes)
+}
+
+// Returns e**-1 (mod m), where `PUBLIC_EXPONENT` doesn't divide `m`.
+//
+// Since `PUBLIC_EXPONENT` is prime, `m**-1 (mod e)` can be calculated with
+// Fermat's Little Theorem. Then `(1 + k*m) / e` is the inverse when
+// `k == -(m**-1) (mod e)`, and it is less than `m` since `k < e`.
+fn inverse_of_public_exponent(m: &[u8]) -> std::vec::Vec<u8> {
+    let e = u64::from(PUBLIC_EXPONENT);
+    let m_mod_e = u64::from(mod_small(m, PUBLIC_EXPONENT));
+    debug_assert_ne!(m_mod_e, 0);
+    let mut m_inv_mod_e = 1;
+    let mut base = m_mod_e;
+    let mut exponent = e - 2;
+    while exponent != 0 {
+        if exponent & 1 == 1 {
+            m_inv_mod_e = (m_inv_mod_e * base) % e;
+        }
+        base = (base * base) % e;
+        exponent >>= 1;
+    }
+    let k = (e - m_inv_mod_e) as u32;
+    let (d, remainder) =
+        div_small(&add_one(&mul(m, &be_bytes_from_u32(k))), PUBLIC_EXPONENT);
+    debug_assert_eq!(remainder, 0);
+    d
+}
+
+// Arithmetic on nonnegative integers encoded as big-endian byte strings,
+// possibly with leading zeros.
+
+fn be_bytes_from_u32(value: u32) -> [u8; 4] {
+    [(value >> 24) as u8, (value >> 16) as u8, (value >> 8) as u8,
+     value as u8]
+}
+
+fn set_bit(a: &mut [u8], bit: usize) {
+    let len = a.len();
+    a[len - 1 - (bit / 8)] |= 1 << (bit % 8);
+}
+
+fn bit_length(a: &[u8]) -> usize {
+    match a.iter().position(|&b| b != 0) {
+        Some(i) => (a.len() - i) * 8 - (a[i].leading_zeros() as usize),
+        None => 0,
+    }
+}
+
+// Returns `a - 1` for odd `a`.
+fn minus_one(a: &[u8]) -> std::vec::Vec<u8> {
+    let mut r = a.to_vec();
+    let last = r.len() - 1;
+    debug_assert_eq!(r[last] & 1, 1);
+    r[last] &= !1;
+    r
+}
+
+// Returns `a + 1`, one byte longer than `a`.
+fn add_one(a: &[u8]) -> std::vec::Vec<u8> {
+    let mut r = vec![0u8; a.len() + 1];
+    r[1..].copy_from_slice(a);
+    for b in r.iter_mut().rev() {
+        let (sum, carry) = b.overflowing_add(1);
+        *b = sum;
+        if !carry {
+            break;
+        }
+    }
+    r
+}
+
+// Returns `a - b` for `a >= b` of the same length.
+fn sub(a: &[u8], b: &[u8]) -> std::vec::Vec<u8> {
+    debug_assert_eq!(a.len(), b.len());
+    let mut r = vec![0u8; a.len()];
+    let mut borrow = 0i32;
+    for i in (0..a.len()).rev() {
+        let difference = i32::from(a[i]) - i32::from(b[i]) - borrow;
+        r[i] = difference as u8;
+        borrow = if difference < 0 { 1 } else { 0 };
+    }
+    debug_assert_eq!(borrow, 0);
+    r
+}
+
+// Returns `a * b`, with a length of `a.len() + b.len()`.
+fn mul(a: &[u8], b: &[u8]) -> std::vec::Vec<u8> {
+    let mut columns = vec![0u64; a.len() + b.len()];
+    for (i, &a_i) in a.iter().rev().enumerate() {
+        for (j, &b_j) in b.iter().rev().enumerate() {
+            columns[i + j] += u64::from(a_i) * u64::from(b_j);
+        }
+    }
+    let mut r = vec![0u8; columns.len()];
+    let mut carry = 0u64;
+    for (r_i, column) in r.iter_mut().rev().zip(columns.iter()) {
+        let sum = column + carry;
+        *r_i = sum as u8;
+        carry = sum >> 8;
+    }
+    debug_assert_eq!(carry, 0);
+    r
+}
+
+// Returns `(a / m, a % m)`.
+fn div_small(a: &[u8], m: u32) -> (std::vec::Vec<u8>, u32) {
+    let m = u64::from(m);
+    let mut remainder = 0u64;
+    let quotient = a.iter().map(|&b| {
+        let value = (remainder << 8) | u64::from(b);
+        remainder = value % m;
+        (value / m) as u8
+    }).collect();
+    (quotient, remainder as u32)
+}
+
+fn mod_small(a: &[u8], m: u32) -> u32 {
+    let m = u64::from(m);
+    a.iter().fold(0u64, |r, &b| ((r << 8) | u64::from(b)) % m) as u32
+}
@@ -88,4 +88,7 @@ pub mod verification;
 #[cfg(feature = "rsa_signing")]
 pub mod signing;
 
+#[cfg(feature = "rsa_signing")]
+mod keygen;
+
 mod bigint;
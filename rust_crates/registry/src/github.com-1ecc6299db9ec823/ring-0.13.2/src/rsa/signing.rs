@@ -14,10 +14,10 @@
 
 /// RSA PKCS#1 1.5 signatures.
 
-use {bits, der, digest, error, pkcs8};
+use {bits, der, der_writer, digest, error, pkcs8};
 use rand;
 use std;
-use super::{bigint, bigint::Prime, N, verification};
+use super::{bigint, bigint::Prime, keygen, N, verification};
 use arithmetic::montgomery::{R, RR, RRR};
 use untrusted;
 
@@ -40,6 +40,55 @@ pub struct KeyPair {
 derive_debug_from_field!(KeyPair, public_key);
 
 impl KeyPair {
+    /// Generates a new key pair and returns the key pair serialized as a
+    /// PKCS#8 document.
+    ///
+    /// The public modulus will be exactly `public_modulus_bits` bits long,
+    /// which must be 2048, 3072, or 4096. It is recommended that the public
+    /// modulus be 2048 or 3072 bits. (Other sizes aren't supported because
+    /// the private key operations on some platforms don't support primes of
+    /// every size.) The public exponent is always 65537.
+    ///
+    /// The PKCS#8 document will be a v1 `OneAsymmetricKey` holding a
+    /// two-prime `RSAPrivateKey` with the rsaEncryption algorithm identifier,
+    /// as described in [RFC 5958 Section 2] and [RFC 3447 Appendix A.1.2],
+    /// which is the form that `from_pkcs8()` and most other software expect.
+    ///
+    /// The primes are generated as probable primes, similarly to
+    /// [FIPS 186-4] Appendix B.3.3. Key generation takes much longer than
+    /// any other operation on RSA keys; the time it takes varies a lot from
+    /// key to key.
+    ///
+    /// [RFC 3447 Appendix A.1.2]:
+    ///     https://tools.ietf.org/html/rfc3447#appendix-A.1.2
+    /// [RFC 5958 Section 2]: https://tools.ietf.org/html/rfc5958#section-2
+    /// [FIPS 186-4]:
+    ///     http://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.186-4.pdf
+    pub fn generate_pkcs8(public_modulus_bits: usize,
+                          rng: &rand::SecureRandom)
+                          -> Result<std::vec::Vec<u8>, error::Unspecified> {
+        match public_modulus_bits {
+            2048 | 3072 | 4096 => {},
+            _ => { return Err(error::Unspecified); }
+        }
+        let n_bits = bits::BitLength::from_usize_bits(public_modulus_bits);
+        debug_assert!(n_bits <= super::PRIVATE_KEY_PUBLIC_MODULUS_MAX_BITS);
+        let rsa_private_key = keygen::generate_rsa_private_key(n_bits, rng)?;
+
+        let mut pkcs8 = std::vec::Vec::new();
+        der_writer::write_nested(&mut pkcs8, der::Tag::Sequence, |pkcs8| {
+            der_writer::write_positive_integer(pkcs8, &[0]); // Version v1.
+            der_writer::write_tlv(pkcs8, der::Tag::Sequence, RSA_ENCRYPTION);
+            der_writer::write_tlv(pkcs8, der::Tag::OctetString,
+                                  &rsa_private_key);
+        });
+
+        // Never return a key that `from_pkcs8()` would reject.
+        let _ = Self::from_pkcs8(untrusted::Input::from(&pkcs8))?;
+
+        Ok(pkcs8)
+    }
+
     /// Parses an unencrypted PKCS#8-encoded RSA private key.
     ///
     /// Only two-prime (not multi-prime) keys are supported. The public modulus
@@ -138,9 +187,7 @@ impl KeyPair {
     ///
     pub fn from_pkcs8(input: untrusted::Input)
                       -> Result<Self, error::Unspecified> {
-        const RSA_ENCRYPTION: &'static [u8] =
-            include_bytes!("../data/alg-rsa-encryption.der");
-        let (der, _) = pkcs8::unwrap_key_(&RSA_ENCRYPTION,
+        let (der, _) = pkcs8::unwrap_key_(RSA_ENCRYPTION,
                                           pkcs8::Version::V1Only, input)?;
         Self::from_der(der)
     }
@@ -360,8 +407,28 @@ impl KeyPair {
     pub fn public_modulus_len(&self) -> usize {
         self.public_key.modulus_len()
     }
+
+    /// Returns the public key encoded as a DER `SubjectPublicKeyInfo`
+    /// ([RFC 5280 Section 4.1]) with the rsaEncryption algorithm identifier,
+    /// as used in X.509 certificates.
+    ///
+    /// [RFC 5280 Section 4.1]: https://tools.ietf.org/html/rfc5280#section-4.1
+    pub fn public_key_spki(&self) -> std::vec::Vec<u8> {
+        // RSAPublicKey, as described in RFC 3447 Appendix A.1.1.
+        let mut public_key = std::vec::Vec::new();
+        der_writer::write_nested(&mut public_key, der::Tag::Sequence, |key| {
+            der_writer::write_positive_integer(
+                key, &self.public_key.n.to_be_bytes());
+            der_writer::write_positive_integer(
+                key, &self.public_key.e.to_be_bytes());
+        });
+        der_writer::subject_public_key_info(RSA_ENCRYPTION, &public_key)
+    }
 }
 
+const RSA_ENCRYPTION: &'static [u8] =
+    include_bytes!("../data/alg-rsa-encryption.der");
+
 struct PrivatePrime<M: Prime> {
     modulus: bigint::Modulus<M>,
     exponent: bigint::PrivateExponent<M>,
@@ -0,0 +1,530 @@
+// Copyright 2018 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! scrypt derivation and verification.
+//!
+//! scrypt is a memory-hard password-based key derivation function: deriving a
+//! key requires `128 * r * 2**log_n` bytes of memory, which makes large-scale
+//! guessing attacks with custom hardware much more expensive than they are
+//! for PBKDF2. Use `derive` to derive scrypt outputs. Use `verify` to verify
+//! secrets against previously-derived outputs. `derive` and `verify` work like
+//! their counterparts in `ring::pbkdf2`.
+//!
+//! Password databases usually store the parameters and the salt along with
+//! the derived key. `derive_phc_string` does this using the [PHC string
+//! format], in the same form as other libraries use for scrypt, e.g.
+//! `$scrypt$ln=15,r=8,p=1$<salt>$<derived key>`, and `verify_phc_string`
+//! verifies secrets against such strings.
+//!
+//! scrypt is specified in [RFC 7914], which also gives the test vectors.
+//!
+//! Only available in `use_heap` mode.
+//!
+//! [RFC 7914]: https://tools.ietf.org/html/rfc7914
+//! [PHC string format]:
+//!     https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md
+//!
+//! # Example
+//!
+//! ```
+//! use ring::{rand, scrypt};
+//! use ring::rand::SecureRandom;
+//!
+//! # fn scrypt_example() -> Result<(), ring::error::Unspecified> {
+//! // These parameters use 16 MiB of memory per derivation.
+//! let params = scrypt::Params::new(14, 8, 1)?;
+//!
+//! let rng = rand::SystemRandom::new();
+//! let mut salt = [0u8; 16];
+//! rng.fill(&mut salt)?;
+//!
+//! let stored = scrypt::derive_phc_string(&params, &salt, b"hunter2");
+//!
+//! // An attempt to log in with the wrong password fails.
+//! assert!(scrypt::verify_phc_string(&stored, b"wrong password").is_err());
+//!
+//! // An attempt to log in with the right password succeeds.
+//! assert!(scrypt::verify_phc_string(&stored, b"hunter2").is_ok());
+//! # Ok(())
+//! # }
+//! # fn main() { scrypt_example().unwrap() }
+//! ```
+
+use {constant_time, digest, error, pbkdf2};
+use core;
+use std;
+
+/// scrypt cost parameters.
+///
+/// The recommended parameters for interactive logins are `log_n = 15`,
+/// `r = 8`, and `p = 1`, which use 32 MiB of memory.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Params {
+    log_n: u8,
+    r: u32,
+    p: u32,
+}
+
+impl Params {
+    /// Constructs scrypt parameters.
+    ///
+    /// | Parameter | RFC 7914 Term
+    /// |-----------|----------------------------------
+    /// | log_n     | log2(N) (CPU/Memory cost parameter)
+    /// | r         | r (block size parameter)
+    /// | p         | p (parallelization parameter)
+    ///
+    /// Fails unless `N > 1`, `N < 2**(16 * r)`, `r > 0`, `p > 0`, and
+    /// `r * p < 2**30`, as required by [RFC 7914], and the amount of memory
+    /// needed is addressable.
+    ///
+    /// [RFC 7914]: https://tools.ietf.org/html/rfc7914
+    pub fn new(log_n: u8, r: u32, p: u32)
+               -> Result<Params, error::Unspecified> {
+        if log_n < 1 || r < 1 || p < 1 {
+            return Err(error::Unspecified);
+        }
+        if u64::from(log_n) >= 16 * u64::from(r) {
+            return Err(error::Unspecified);
+        }
+        if u64::from(r) * u64::from(p) >= 1 << 30 {
+            return Err(error::Unspecified);
+        }
+        let params = Params { log_n, r, p };
+        let _ = params.memory_words()?;
+        let _ = params.block_len()?
+            .checked_mul(p as usize)
+            .ok_or(error::Unspecified)?;
+        Ok(params)
+    }
+
+    /// log2(N), the CPU/memory cost parameter.
+    #[inline]
+    pub fn log_n(&self) -> u8 { self.log_n }
+
+    /// r, the block size parameter.
+    #[inline]
+    pub fn r(&self) -> u32 { self.r }
+
+    /// p, the parallelization parameter.
+    #[inline]
+    pub fn p(&self) -> u32 { self.p }
+
+    #[inline]
+    fn n(&self) -> u64 { 1 << self.log_n }
+
+    // The length of a block, 128 * r bytes.
+    fn block_len(&self) -> Result<usize, error::Unspecified> {
+        (self.r as usize).checked_mul(128).ok_or(error::Unspecified)
+    }
+
+    // The memory needed to run all `p` lanes of `ROMix` at once,
+    // 128 * r * N * p bytes, saturating at `u64::MAX`. The work done by
+    // `derive` is proportional to it too.
+    fn parallel_memory_len(&self) -> u64 {
+        let lanes_len = 128 * u64::from(self.r) * u64::from(self.p);
+        if u32::from(self.log_n) >= lanes_len.leading_zeros() {
+            return core::u64::MAX;
+        }
+        lanes_len << self.log_n
+    }
+
+    // The number of 32-bit words of the `V` array of `ROMix`.
+    fn memory_words(&self) -> Result<usize, error::Unspecified> {
+        1usize.checked_shl(u32::from(self.log_n))
+            .ok_or(error::Unspecified)?
+            .checked_mul(self.block_len()? / 4)
+            .ok_or(error::Unspecified)
+    }
+}
+
+/// Fills `out` with the key derived using scrypt with the given inputs.
+///
+/// Do not use `derive` as part of verifying a secret; use `verify` instead, to
+/// minimize the effectiveness of timing attacks.
+///
+/// | Parameter   | RFC 7914 Term
+/// |-------------|-----------------------------------
+/// | params      | N, r, p
+/// | salt        | S (salt)
+/// | secret      | P (passphrase)
+/// | out         | DK (derived key)
+/// | out.len()   | dkLen (intended output length)
+///
+/// C analog: `EVP_PBE_scrypt`
+///
+/// # Panics
+///
+/// `derive` panics if `out.len()` is larger than (2**32 - 1) * 32, per the
+/// scrypt specification, or if the memory needed can't be allocated.
+pub fn derive(params: &Params, salt: &[u8], secret: &[u8], out: &mut [u8]) {
+    let block_len = params.block_len().unwrap();
+    let mut b = vec![0u8; block_len * (params.p as usize)];
+    pbkdf2::derive(PRF, 1, salt, secret, &mut b);
+
+    let mut v = vec![0u32; params.memory_words().unwrap()];
+    let mut x = vec![0u32; block_len / 4];
+    let mut y = vec![0u32; block_len / 4];
+    for block in b.chunks_mut(block_len) {
+        ro_mix(params, block, &mut v, &mut x, &mut y);
+    }
+
+    pbkdf2::derive(PRF, 1, &b, secret, out);
+}
+
+/// Verifies that a previously-derived (e.g., using `derive`) scrypt value
+/// matches the scrypt value derived from the other inputs.
+///
+/// The comparison is done in constant time to prevent timing attacks. The
+/// comparison will fail if `previously_derived` is empty (has a length of
+/// zero).
+///
+/// | Parameter                  | RFC 7914 Term
+/// |----------------------------|-----------------------------------
+/// | params                     | N, r, p
+/// | salt                       | S (salt)
+/// | secret                     | P (passphrase)
+/// | `previously_derived`       | DK (derived key)
+/// | `previously_derived.len()` | dkLen (intended output length)
+///
+/// # Panics
+///
+/// `verify` panics if `previously_derived.len()` is larger than
+/// (2**32 - 1) * 32, per the scrypt specification, or if the memory needed
+/// can't be allocated.
+pub fn verify(params: &Params, salt: &[u8], secret: &[u8],
+              previously_derived: &[u8]) -> Result<(), error::Unspecified> {
+    if previously_derived.is_empty() {
+        return Err(error::Unspecified);
+    }
+    let mut derived = vec![0u8; previously_derived.len()];
+    derive(params, salt, secret, &mut derived);
+    constant_time::verify_slices_are_equal(&derived, previously_derived)
+}
+
+/// Derives a key from `secret` and returns it, along with `params` and
+/// `salt`, as a PHC string.
+///
+/// The derived key is `PHC_DERIVED_KEY_LEN` bytes long. The salt should be
+/// at least 16 random bytes.
+///
+/// # Panics
+///
+/// `derive_phc_string` panics if the memory needed can't be allocated.
+pub fn derive_phc_string(params: &Params, salt: &[u8], secret: &[u8])
+                         -> std::string::String {
+    let mut derived = [0u8; PHC_DERIVED_KEY_LEN];
+    derive(params, salt, secret, &mut derived);
+
+    let mut phc_string = std::string::String::new();
+    phc_string.push_str(PHC_ID);
+    phc_string.push_str(&format!("ln={},r={},p={}$", params.log_n, params.r,
+                                 params.p));
+    base64_encode(salt, &mut phc_string);
+    phc_string.push('$');
+    base64_encode(&derived, &mut phc_string);
+    phc_string
+}
+
+/// Verifies that `secret` matches the PHC string `phc_string`, e.g. one
+/// returned by `derive_phc_string`.
+///
+/// The comparison of the derived keys is done in constant time. Malformed
+/// PHC strings, and PHC strings for algorithms other than scrypt, are
+/// rejected.
+///
+/// The parameters are taken from `phc_string`, so PHC strings whose
+/// parameters need more than `PHC_DEFAULT_MAX_MEMORY` bytes, counted as
+/// `128 * r * 2**log_n * p`, are rejected too. Use
+/// `verify_phc_string_with_max_memory` to choose a different limit.
+///
+/// # Panics
+///
+/// `verify_phc_string` panics if the memory needed can't be allocated.
+pub fn verify_phc_string(phc_string: &str, secret: &[u8])
+                         -> Result<(), error::Unspecified> {
+    verify_phc_string_with_max_memory(phc_string, secret,
+                                      PHC_DEFAULT_MAX_MEMORY)
+}
+
+/// Like `verify_phc_string`, but rejects PHC strings whose parameters need
+/// more than `max_memory` bytes, counted as `128 * r * 2**log_n * p`, instead
+/// of `PHC_DEFAULT_MAX_MEMORY`. The time taken is proportional to the same
+/// amount, so this bounds both.
+///
+/// # Panics
+///
+/// `verify_phc_string_with_max_memory` panics if the memory needed can't be
+/// allocated.
+pub fn verify_phc_string_with_max_memory(phc_string: &str, secret: &[u8],
+                                         max_memory: usize)
+                                         -> Result<(), error::Unspecified> {
+    let (params, salt, derived) = parse_phc_string(phc_string, max_memory)?;
+    verify(&params, &salt, secret, &derived)
+}
+
+/// The length of the derived keys in PHC strings generated by
+/// `derive_phc_string`.
+pub const PHC_DERIVED_KEY_LEN: usize = 32;
+
+/// The most memory that `verify_phc_string` will use, 128 MiB. This allows
+/// four times the recommended parameters.
+pub const PHC_DEFAULT_MAX_MEMORY: usize = 128 * 1024 * 1024;
+
+const PHC_ID: &str = "$scrypt$";
+
+// Parses "$scrypt$ln=<log_n>,r=<r>,p=<p>$<salt>$<derived key>".
+// Parameters that need more than `max_memory` bytes are rejected.
+fn parse_phc_string(phc_string: &str, max_memory: usize)
+                    -> Result<(Params, std::vec::Vec<u8>, std::vec::Vec<u8>),
+                              error::Unspecified> {
+    if !phc_string.starts_with(PHC_ID) {
+        return Err(error::Unspecified);
+    }
+    let mut fields = phc_string[PHC_ID.len()..].split('$');
+    let (params, salt, derived) =
+        match (fields.next(), fields.next(), fields.next(), fields.next()) {
+            (Some(params), Some(salt), Some(derived), None) =>
+                (params, salt, derived),
+            _ => { return Err(error::Unspecified); }
+        };
+
+    let mut params = params.split(',');
+    let log_n = parse_phc_param(params.next(), "ln")?;
+    let r = parse_phc_param(params.next(), "r")?;
+    let p = parse_phc_param(params.next(), "p")?;
+    if params.next().is_some() || log_n > u64::from(core::u8::MAX) ||
+       r > u64::from(core::u32::MAX) || p > u64::from(core::u32::MAX) {
+        return Err(error::Unspecified);
+    }
+    let params = Params::new(log_n as u8, r as u32, p as u32)?;
+    if params.parallel_memory_len() > max_memory as u64 {
+        return Err(error::Unspecified);
+    }
+
+    Ok((params, base64_decode(salt)?, base64_decode(derived)?))
+}
+
+// Parses "<name>=<decimal value>", without leading zeros.
+fn parse_phc_param(param: Option<&str>, name: &str)
+                   -> Result<u64, error::Unspecified> {
+    let param = param.ok_or(error::Unspecified)?;
+    if !param.starts_with(name) || !param[name.len()..].starts_with('=') {
+        return Err(error::Unspecified);
+    }
+    let value = &param[(name.len() + 1)..];
+    if value.is_empty() || value.len() > 10 ||
+       (value.starts_with('0') && value != "0") ||
+       !value.bytes().all(|b| b >= b'0' && b <= b'9') {
+        return Err(error::Unspecified);
+    }
+    value.parse().map_err(|_| error::Unspecified)
+}
+
+// The PRF of PBKDF2 within scrypt is HMAC-SHA256.
+static PRF: &'static digest::Algorithm = &digest::SHA256;
+
+// `scryptROMix` from RFC 7914 Section 5, on `block` in place. `v`, `x` and `y`
+// are scratch space of `params.memory_words()`, `32 * r`, and `32 * r` words.
+fn ro_mix(params: &Params, block: &mut [u8], v: &mut [u32], x: &mut [u32],
+          y: &mut [u32]) {
+    let block_words = x.len();
+
+    for (x, bytes) in x.iter_mut().zip(block.chunks(4)) {
+        *x = u32::from(bytes[0]) | u32::from(bytes[1]) << 8 |
+             u32::from(bytes[2]) << 16 | u32::from(bytes[3]) << 24;
+    }
+
+    for v_i in v.chunks_mut(block_words) {
+        v_i.copy_from_slice(x);
+        block_mix(x, y);
+    }
+
+    for _ in 0..params.n() {
+        // Integerify(X) mod N.
+        let last = &x[(block_words - 16)..];
+        let j = (u64::from(last[0]) | u64::from(last[1]) << 32) &
+                (params.n() - 1);
+        let j = j as usize;
+        for (x, v_j) in x.iter_mut()
+                .zip(&v[(j * block_words)..((j + 1) * block_words)]) {
+            *x ^= *v_j;
+        }
+        block_mix(x, y);
+    }
+
+    for (bytes, x) in block.chunks_mut(4).zip(x.iter()) {
+        bytes[0] = *x as u8;
+        bytes[1] = (*x >> 8) as u8;
+        bytes[2] = (*x >> 16) as u8;
+        bytes[3] = (*x >> 24) as u8;
+    }
+}
+
+// `scryptBlockMix` from RFC 7914 Section 4, on `b` in place, using `y` as
+// scratch space.
+fn block_mix(b: &mut [u32], y: &mut [u32]) {
+    let two_r = b.len() / 16;
+    let mut x = [0u32; 16];
+    x.copy_from_slice(&b[(b.len() - 16)..]);
+    for i in 0..two_r {
+        for (x, b_i) in x.iter_mut().zip(&b[(i * 16)..((i + 1) * 16)]) {
+            *x ^= *b_i;
+        }
+        salsa20_8(&mut x);
+        // The even-numbered blocks go into the first half of the output and
+        // the odd-numbered blocks go into the second half.
+        let out_index = (i / 2) + (i % 2) * (two_r / 2);
+        y[(out_index * 16)..((out_index + 1) * 16)].copy_from_slice(&x);
+    }
+    b.copy_from_slice(y);
+}
+
+// The Salsa20/8 Core from RFC 7914 Section 3.
+fn salsa20_8(b: &mut [u32; 16]) {
+    fn quarter_round(x: &mut [u32; 16], a: usize, b: usize, c: usize,
+                     d: usize) {
+        x[b] ^= x[a].wrapping_add(x[d]).rotate_left(7);
+        x[c] ^= x[b].wrapping_add(x[a]).rotate_left(9);
+        x[d] ^= x[c].wrapping_add(x[b]).rotate_left(13);
+        x[a] ^= x[d].wrapping_add(x[c]).rotate_left(18);
+    }
+
+    let mut x = *b;
+    for _ in 0..4 {
+        // Columns.
+        quarter_round(&mut x, 0, 4, 8, 12);
+        quarter_round(&mut x, 5, 9, 13, 1);
+        quarter_round(&mut x, 10, 14, 2, 6);
+        quarter_round(&mut x, 15, 3, 7, 11);
+        // Rows.
+        quarter_round(&mut x, 0, 1, 2, 3);
+        quarter_round(&mut x, 5, 6, 7, 4);
+        quarter_round(&mut x, 10, 11, 8, 9);
+        quarter_round(&mut x, 15, 12, 13, 14);
+    }
+    for (b, x) in b.iter_mut().zip(x.iter()) {
+        *b = b.wrapping_add(*x);
+    }
+}
+
+// The PHC string format uses the standard Base64 alphabet without padding.
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(input: &[u8], out: &mut std::string::String) {
+    for chunk in input.chunks(3) {
+        let mut bytes = [0u8; 3];
+        bytes[..chunk.len()].copy_from_slice(chunk);
+        let value = u32::from(bytes[0]) << 16 | u32::from(bytes[1]) << 8 |
+                    u32::from(bytes[2]);
+        for i in 0..(chunk.len() + 1) {
+            let index = (value >> (18 - 6 * i)) & 0x3f;
+            out.push(BASE64_ALPHABET[index as usize] as char);
+        }
+    }
+}
+
+fn base64_decode(input: &str)
+                 -> Result<std::vec::Vec<u8>, error::Unspecified> {
+    let input = input.as_bytes();
+    if input.len() % 4 == 1 {
+        return Err(error::Unspecified);
+    }
+    let mut out = std::vec::Vec::with_capacity(input.len() * 3 / 4);
+    for chunk in input.chunks(4) {
+        let mut value = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            let index = BASE64_ALPHABET.iter().position(|b| b == c)
+                .ok_or(error::Unspecified)?;
+            value |= (index as u32) << (18 - 6 * i);
+        }
+        let len = chunk.len() - 1;
+        // Reject encodings with non-zero unused bits so that every value has
+        // exactly one encoding.
+        if value & (0xffffff >> (8 * len)) != 0 {
+            return Err(error::Unspecified);
+        }
+        let bytes = [(value >> 16) as u8, (value >> 8) as u8, value as u8];
+        out.extend_from_slice(&bytes[..len]);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_salsa20_8() {
+        // RFC 7914 Section 8.
+        const INPUT: [u8; 64] = [
+            0x7e, 0x87, 0x9a, 0x21, 0x4f, 0x3e, 0xc9, 0x86,
+            0x7c, 0xa9, 0x40, 0xe6, 0x41, 0x71, 0x8f, 0x26,
+            0xba, 0xee, 0x55, 0x5b, 0x8c, 0x61, 0xc1, 0xb5,
+            0x0d, 0xf8, 0x46, 0x11, 0x6d, 0xcd, 0x3b, 0x1d,
+            0xee, 0x24, 0xf3, 0x19, 0xdf, 0x9b, 0x3d, 0x85,
+            0x14, 0x12, 0x1e, 0x4b, 0x5a, 0xc5, 0xaa, 0x32,
+            0x76, 0x02, 0x1d, 0x29, 0x09, 0xc7, 0x48, 0x29,
+            0xed, 0xeb, 0xc6, 0x8d, 0xb8, 0xb8, 0xc2, 0x5e,
+        ];
+        const OUTPUT: [u8; 64] = [
+            0xa4, 0x1f, 0x85, 0x9c, 0x66, 0x08, 0xcc, 0x99,
+            0x3b, 0x81, 0xca, 0xcb, 0x02, 0x0c, 0xef, 0x05,
+            0x04, 0x4b, 0x21, 0x81, 0xa2, 0xfd, 0x33, 0x7d,
+            0xfd, 0x7b, 0x1c, 0x63, 0x96, 0x68, 0x2f, 0x29,
+            0xb4, 0x39, 0x31, 0x68, 0xe3, 0xc9, 0xe6, 0xbc,
+            0xfe, 0x6b, 0xc5, 0xb7, 0xa0, 0x6d, 0x96, 0xba,
+            0xe4, 0x24, 0xcc, 0x10, 0x2c, 0x91, 0x74, 0x5c,
+            0x24, 0xad, 0x67, 0x3d, 0xc7, 0x61, 0x8f, 0x81,
+        ];
+
+        let mut b = words_from_le_bytes(&INPUT);
+        salsa20_8(&mut b);
+        assert_eq!(b, words_from_le_bytes(&OUTPUT));
+    }
+
+    fn words_from_le_bytes(bytes: &[u8; 64]) -> [u32; 16] {
+        let mut words = [0u32; 16];
+        for (word, bytes) in words.iter_mut().zip(bytes.chunks(4)) {
+            *word = u32::from(bytes[0]) | u32::from(bytes[1]) << 8 |
+                    u32::from(bytes[2]) << 16 | u32::from(bytes[3]) << 24;
+        }
+        words
+    }
+
+    #[test]
+    fn test_base64() {
+        for &(bytes, encoded) in &[
+            (&b""[..], ""),
+            (&b"f"[..], "Zg"),
+            (&b"fo"[..], "Zm8"),
+            (&b"foo"[..], "Zm9v"),
+            (&b"foob"[..], "Zm9vYg"),
+            (&b"fooba"[..], "Zm9vYmE"),
+            (&b"foobar"[..], "Zm9vYmFy"),
+        ] {
+            let mut actual = std::string::String::new();
+            base64_encode(bytes, &mut actual);
+            assert_eq!(actual, encoded);
+            assert_eq!(base64_decode(encoded).unwrap(), bytes);
+        }
+
+        // Wrong length, non-zero unused bits, padding, invalid characters.
+        for encoded in &["Z", "Zh", "Zm9", "Zg==", "Zm9v!", "Zm 9v"] {
+            assert!(base64_decode(encoded).is_err());
+        }
+    }
+}
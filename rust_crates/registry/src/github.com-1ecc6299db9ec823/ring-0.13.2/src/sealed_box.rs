@@ -0,0 +1,247 @@
+// Copyright 2018 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Public-key encryption ("sealed boxes").
+//!
+//! A sealed box is a message encrypted to a recipient's static public key.
+//! Only the holder of the matching `agreement::StaticPrivateKey` can open it.
+//! Sealed boxes are anonymous: the sender doesn't need a key pair of its own,
+//! and the recipient learns nothing about who sealed the box.
+//!
+//! The construction is similar to the base mode of [HPKE]:
+//!
+//! 1. The sender generates an ephemeral private key and does key agreement
+//!    with it and the recipient's public key. The ephemeral public key is the
+//!    *encapsulated key*.
+//! 2. The AEAD key is derived with HKDF. The input keying material is the
+//!    shared secret, the encapsulated key, and the recipient's public key,
+//!    concatenated in that order; the salt is the ASCII string
+//!    `"ring sealed box"`; and the info is the caller's `info`.
+//! 3. The message is sealed with the AEAD, using an all-zero nonce and the
+//!    caller's additional authenticated data. Each AEAD key is used only
+//!    once, since each sealed box has its own ephemeral key.
+//!
+//! The sealed box is the encapsulated key followed by the AEAD ciphertext and
+//! tag.
+//!
+//! `info` should identify the application and the purpose of the message,
+//! so that a sealed box made for one purpose can't be opened for another.
+//!
+//! # Example
+//!
+//! ```
+//! # extern crate untrusted;
+//! # extern crate ring;
+//! #
+//! # fn sealed_box_example() -> Result<(), ring::error::Unspecified> {
+//! use ring::{agreement, rand, sealed_box};
+//! use untrusted;
+//!
+//! let rng = rand::SystemRandom::new();
+//! let alg = &sealed_box::X25519_HKDF_SHA256_CHACHA20_POLY1305;
+//!
+//! // The recipient generates a key pair once and stores the PKCS#8 document.
+//! let pkcs8_bytes = agreement::StaticPrivateKey::generate_pkcs8(
+//!     alg.agreement_algorithm(), &rng)?;
+//! let recipient = agreement::StaticPrivateKey::from_pkcs8(
+//!     alg.agreement_algorithm(), untrusted::Input::from(pkcs8_bytes.as_ref()))?;
+//!
+//! // The sender only needs the recipient's public key.
+//! let secret = b"database password";
+//! let mut sealed =
+//!     vec![0u8; alg.encapsulated_key_len() + secret.len() + alg.tag_len()];
+//! sealed[alg.encapsulated_key_len()..][..secret.len()]
+//!     .copy_from_slice(secret);
+//! let sealed_len = sealed_box::seal_in_place(
+//!     alg, untrusted::Input::from(recipient.public_key()), b"config secrets",
+//!     &[], &mut sealed, alg.tag_len(), &rng)?;
+//!
+//! let opened = sealed_box::open_in_place(alg, &recipient, b"config secrets",
+//!                                        &[], &mut sealed[..sealed_len])?;
+//! assert_eq!(opened, &secret[..]);
+//! # Ok(())
+//! # }
+//! # fn main() { sealed_box_example().unwrap() }
+//! ```
+//!
+//! [HPKE]: https://tools.ietf.org/html/draft-irtf-cfrg-hpke
+
+use {aead, agreement, digest, error, hkdf, hmac, rand};
+use untrusted;
+
+/// A sealed box algorithm: a key agreement algorithm, a KDF, and an AEAD.
+pub struct Algorithm {
+    kem: &'static agreement::Algorithm,
+    kdf: &'static digest::Algorithm,
+    aead: &'static aead::Algorithm,
+    id: AlgorithmID,
+}
+
+impl Algorithm {
+    /// The key agreement algorithm. Recipient keys must be generated for
+    /// this algorithm.
+    #[inline(always)]
+    pub fn agreement_algorithm(&self) -> &'static agreement::Algorithm {
+        self.kem
+    }
+
+    /// The AEAD algorithm.
+    #[inline(always)]
+    pub fn aead_algorithm(&self) -> &'static aead::Algorithm { self.aead }
+
+    /// The length of the encapsulated key at the start of a sealed box.
+    #[inline(always)]
+    pub fn encapsulated_key_len(&self) -> usize {
+        self.kem.i.curve.public_key_len
+    }
+
+    /// The length of the tag at the end of a sealed box.
+    #[inline(always)]
+    pub fn tag_len(&self) -> usize { self.aead.tag_len() }
+}
+
+derive_debug_from_field!(Algorithm, id);
+
+#[derive(Debug, Eq, PartialEq)]
+enum AlgorithmID {
+    X25519_HKDF_SHA256_CHACHA20_POLY1305,
+    ECDH_P256_HKDF_SHA256_AES_128_GCM,
+}
+
+impl PartialEq for Algorithm {
+    fn eq(&self, other: &Self) -> bool { self.id == other.id }
+}
+
+impl Eq for Algorithm {}
+
+/// Sealed boxes using X25519, HKDF-SHA256, and ChaCha20-Poly1305.
+pub static X25519_HKDF_SHA256_CHACHA20_POLY1305: Algorithm = Algorithm {
+    kem: &agreement::X25519,
+    kdf: &digest::SHA256,
+    aead: &aead::CHACHA20_POLY1305,
+    id: AlgorithmID::X25519_HKDF_SHA256_CHACHA20_POLY1305,
+};
+
+/// Sealed boxes using ECDH with P-256, HKDF-SHA256, and AES-128-GCM.
+pub static ECDH_P256_HKDF_SHA256_AES_128_GCM: Algorithm = Algorithm {
+    kem: &agreement::ECDH_P256,
+    kdf: &digest::SHA256,
+    aead: &aead::AES_128_GCM,
+    id: AlgorithmID::ECDH_P256_HKDF_SHA256_AES_128_GCM,
+};
+
+/// Encrypts data to `recipient_public_key` (“seals a box”) in place.
+///
+/// `in_out` is laid out as the sealed box will be: the first
+/// `alg.encapsulated_key_len()` bytes are ignored on input and overwritten
+/// with the encapsulated key, the input is the part of `in_out` between that
+/// prefix and the last `out_suffix_capacity` bytes. When `seal_in_place()`
+/// returns `Ok(out_len)`, the sealed box is `in_out[..out_len]`.
+///
+/// `recipient_public_key` must be a public key for
+/// `alg.agreement_algorithm()`, encoded in the standard form for that
+/// algorithm. `out_suffix_capacity` must be at least `alg.tag_len()`. `info`
+/// and `ad` must be the same when the box is opened.
+pub fn seal_in_place(alg: &'static Algorithm,
+                     recipient_public_key: untrusted::Input, info: &[u8],
+                     ad: &[u8], in_out: &mut [u8], out_suffix_capacity: usize,
+                     rng: &rand::SecureRandom)
+                     -> Result<usize, error::Unspecified> {
+    let encapsulated_key_len = alg.encapsulated_key_len();
+    if in_out.len() < encapsulated_key_len {
+        return Err(error::Unspecified);
+    }
+    let (encapsulated_key, in_out) = in_out.split_at_mut(encapsulated_key_len);
+
+    let my_private_key =
+        agreement::EphemeralPrivateKey::generate(alg.kem, rng)?;
+    my_private_key.compute_public_key(encapsulated_key)?;
+    let encapsulated_key = &*encapsulated_key;
+
+    let key = agreement::agree_ephemeral(
+            my_private_key, alg.kem, recipient_public_key, error::Unspecified,
+            |shared_secret| {
+        let mut key_bytes = [0u8; MAX_KEY_LEN];
+        let key_bytes = &mut key_bytes[..alg.aead.key_len()];
+        derive_key(alg, shared_secret, encapsulated_key,
+                   recipient_public_key.as_slice_less_safe(), info,
+                   key_bytes);
+        aead::SealingKey::new(alg.aead, key_bytes)
+    })?;
+
+    let out_len =
+        aead::seal_in_place(&key, &NONCE, ad, in_out, out_suffix_capacity)?;
+    Ok(encapsulated_key_len + out_len)
+}
+
+/// Decrypts and authenticates (“opens”) a sealed box in place.
+///
+/// `sealed_box_modified_in_place` is the whole sealed box. When
+/// `open_in_place()` returns `Ok(plaintext)`, the plaintext has been written
+/// over the start of the sealed box, like `aead::open_in_place()` does with
+/// a prefix. When it returns `Err(..)`, `sealed_box_modified_in_place` may
+/// have been overwritten in an unspecified way.
+///
+/// `recipient` must be a key for `alg.agreement_algorithm()`.
+pub fn open_in_place<'a>(alg: &'static Algorithm,
+                         recipient: &agreement::StaticPrivateKey, info: &[u8],
+                         ad: &[u8], sealed_box_modified_in_place: &'a mut [u8])
+                         -> Result<&'a mut [u8], error::Unspecified> {
+    let encapsulated_key_len = alg.encapsulated_key_len();
+    if sealed_box_modified_in_place.len() < encapsulated_key_len {
+        return Err(error::Unspecified);
+    }
+
+    // Copy the encapsulated key out, since the plaintext will overwrite it.
+    let mut encapsulated_key = [0u8; agreement::PUBLIC_KEY_MAX_LEN];
+    let encapsulated_key = &mut encapsulated_key[..encapsulated_key_len];
+    encapsulated_key.copy_from_slice(
+        &sealed_box_modified_in_place[..encapsulated_key_len]);
+    let encapsulated_key = &*encapsulated_key;
+
+    let key = agreement::agree_static(
+            recipient, alg.kem, untrusted::Input::from(encapsulated_key),
+            error::Unspecified, |shared_secret| {
+        let mut key_bytes = [0u8; MAX_KEY_LEN];
+        let key_bytes = &mut key_bytes[..alg.aead.key_len()];
+        derive_key(alg, shared_secret, encapsulated_key,
+                   recipient.public_key(), info, key_bytes);
+        aead::OpeningKey::new(alg.aead, key_bytes)
+    })?;
+
+    aead::open_in_place(&key, &NONCE, ad, encapsulated_key_len,
+                        sealed_box_modified_in_place)
+}
+
+// HKDF-Extract of shared_secret || encapsulated_key || recipient_public_key,
+// followed by HKDF-Expand with `info`. The extract step is done with an
+// `hmac::SigningContext` to avoid concatenating the inputs into a buffer.
+fn derive_key(alg: &Algorithm, shared_secret: &[u8], encapsulated_key: &[u8],
+              recipient_public_key: &[u8], info: &[u8], out: &mut [u8]) {
+    let salt = hmac::SigningKey::new(alg.kdf, SALT);
+    let mut ctx = hmac::SigningContext::with_key(&salt);
+    ctx.update(shared_secret);
+    ctx.update(encapsulated_key);
+    ctx.update(recipient_public_key);
+    let prk = hmac::SigningKey::new(alg.kdf, ctx.sign().as_ref());
+    hkdf::expand(&prk, info, out);
+}
+
+const SALT: &[u8] = b"ring sealed box";
+
+// Every key is used for exactly one message, so a fixed nonce is safe. All the
+// AEADs we support use 96-bit nonces.
+const NONCE: [u8; 96 / 8] = [0u8; 96 / 8];
+
+const MAX_KEY_LEN: usize = 256 / 8;
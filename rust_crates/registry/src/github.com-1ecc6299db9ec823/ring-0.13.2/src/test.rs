@@ -181,6 +181,10 @@ impl TestCase {
             "SHA384" => Some(&digest::SHA384),
             "SHA512" => Some(&digest::SHA512),
             "SHA512_256" => Some(&digest::SHA512_256),
+            "SHA3_256" => Some(&digest::SHA3_256),
+            "SHA3_512" => Some(&digest::SHA3_512),
+            "SHAKE128" => Some(&digest::SHAKE128),
+            "SHAKE256" => Some(&digest::SHAKE256),
             _ => panic!("Unsupported digest algorithm: {}", name),
         }
     }
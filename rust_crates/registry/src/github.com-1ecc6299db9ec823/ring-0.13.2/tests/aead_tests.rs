@@ -34,6 +34,7 @@
 extern crate ring;
 
 use ring::{aead, error, test};
+use std::io::{Read, Write};
 use std::vec::Vec;
 
 #[test]
@@ -52,6 +53,168 @@ fn aead_chacha20_poly1305() {
               "tests/aead_chacha20_poly1305_tests.txt");
 }
 
+#[test]
+fn aead_stream() {
+    test::from_file("tests/aead_stream_tests.txt", |section, test_case| {
+        assert_eq!(section, "");
+
+        let aead_alg = match test_case.consume_string("Algorithm").as_str() {
+            "AES_128_GCM" => &aead::AES_128_GCM,
+            "AES_256_GCM" => &aead::AES_256_GCM,
+            "CHACHA20_POLY1305" => &aead::CHACHA20_POLY1305,
+            name => panic!("Unsupported algorithm: {}", name),
+        };
+        let key_bytes = test_case.consume_bytes("KEY");
+        let nonce_prefix = test_case.consume_bytes("NONCE_PREFIX");
+        let ad = test_case.consume_bytes("AD");
+        let segment_len = test_case.consume_usize("SEGMENT_LEN");
+        let plaintext = test_case.consume_bytes("IN");
+        let sealed = test_case.consume_bytes("OUT");
+
+        // Write the plaintext a byte at a time, to exercise the buffering.
+        let s_key = aead::SealingKey::new(aead_alg, &key_bytes)?;
+        let mut writer = aead::stream::SealingWriter::new(
+            s_key, &nonce_prefix, &ad, segment_len, Vec::new())?;
+        for b in &plaintext {
+            writer.write_all(&[*b]).unwrap();
+        }
+        assert_eq!(writer.finish().unwrap(), sealed);
+
+        assert_eq!(open_stream(aead_alg, &key_bytes, &nonce_prefix, &ad,
+                               segment_len, &sealed).unwrap(), plaintext);
+
+        // Any change to the sealed stream must be detected.
+        for i in 0..sealed.len() {
+            let mut modified = sealed.clone();
+            modified[i] ^= 1;
+            assert!(open_stream(aead_alg, &key_bytes, &nonce_prefix, &ad,
+                                segment_len, &modified).is_err());
+        }
+
+        let sealed_segment_len = segment_len + aead_alg.tag_len();
+        let segments: Vec<&[u8]> =
+            sealed.chunks(sealed_segment_len).collect();
+
+        // Truncating the stream at a segment boundary must be detected.
+        for n in 0..segments.len() {
+            let truncated = segments[..n].concat();
+            assert!(open_stream(aead_alg, &key_bytes, &nonce_prefix, &ad,
+                                segment_len, &truncated).is_err());
+        }
+
+        // Reordering the segments must be detected.
+        if segments.len() > 1 {
+            let mut reordered = segments.clone();
+            reordered.swap(0, 1);
+            assert!(open_stream(aead_alg, &key_bytes, &nonce_prefix, &ad,
+                                segment_len, &reordered.concat()).is_err());
+        }
+
+        // Appending a segment must be detected.
+        let mut extended = sealed.clone();
+        extended.extend_from_slice(segments[0]);
+        assert!(open_stream(aead_alg, &key_bytes, &nonce_prefix, &ad,
+                            segment_len, &extended).is_err());
+
+        // The wrong associated data must be detected.
+        let mut other_ad = ad.clone();
+        other_ad.push(0);
+        assert!(open_stream(aead_alg, &key_bytes, &nonce_prefix, &other_ad,
+                            segment_len, &sealed).is_err());
+
+        Ok(())
+    });
+}
+
+#[test]
+fn aead_stream_round_trip() {
+    let key_bytes = [0x42; 32];
+    let nonce_prefix = [7u8; aead::stream::NONCE_PREFIX_LEN];
+    let segment_len = 64;
+
+    for len in &[0, 1, 63, 64, 65, 128, 1000] {
+        let plaintext: Vec<u8> = (0..*len).map(|i| i as u8).collect();
+
+        let s_key =
+            aead::SealingKey::new(&aead::CHACHA20_POLY1305, &key_bytes).unwrap();
+        let mut writer = aead::stream::SealingWriter::new(
+            s_key, &nonce_prefix, b"", segment_len, Vec::new()).unwrap();
+        writer.write_all(&plaintext).unwrap();
+        let sealed = writer.finish().unwrap();
+
+        let num_segments =
+            std::cmp::max(1, (len + segment_len - 1) / segment_len);
+        assert_eq!(sealed.len(), len + num_segments * aead::MAX_TAG_LEN);
+
+        assert_eq!(open_stream(&aead::CHACHA20_POLY1305, &key_bytes,
+                               &nonce_prefix, b"", segment_len,
+                               &sealed).unwrap(), plaintext);
+
+        // Opening with a different segment length fails.
+        if *len > segment_len {
+            assert!(open_stream(&aead::CHACHA20_POLY1305, &key_bytes,
+                                &nonce_prefix, b"", segment_len * 2,
+                                &sealed).is_err());
+        }
+    }
+}
+
+#[test]
+fn aead_stream_fails_after_tampered_segment() {
+    let key_bytes = [0x42; 32];
+    let nonce_prefix = [7u8; aead::stream::NONCE_PREFIX_LEN];
+    let segment_len = 64;
+
+    let s_key =
+        aead::SealingKey::new(&aead::CHACHA20_POLY1305, &key_bytes).unwrap();
+    let mut writer = aead::stream::SealingWriter::new(
+        s_key, &nonce_prefix, b"", segment_len, Vec::new()).unwrap();
+    writer.write_all(&[0u8; 200]).unwrap();
+    let mut sealed = writer.finish().unwrap();
+    sealed[0] ^= 1;
+
+    let o_key =
+        aead::OpeningKey::new(&aead::CHACHA20_POLY1305, &key_bytes).unwrap();
+    let mut reader = aead::stream::OpeningReader::new(
+        o_key, &nonce_prefix, b"", segment_len, &sealed[..]).unwrap();
+
+    // Later reads must not skip over the bad segment and return the
+    // plaintext of the segments after it.
+    let mut buf = [0u8; 16];
+    for _ in 0..3 {
+        let err = reader.read(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
+
+#[test]
+fn aead_stream_bad_parameters() {
+    let key_bytes = [0u8; 16];
+    let new_writer = |nonce_prefix: &[u8], segment_len| {
+        let s_key =
+            aead::SealingKey::new(&aead::AES_128_GCM, &key_bytes).unwrap();
+        aead::stream::SealingWriter::new(s_key, nonce_prefix, b"", segment_len,
+                                         Vec::new())
+    };
+
+    let nonce_prefix = [0u8; aead::stream::NONCE_PREFIX_LEN + 1];
+    assert!(new_writer(&nonce_prefix[..(nonce_prefix.len() - 2)], 16)
+                .is_err());
+    assert!(new_writer(&nonce_prefix[..(nonce_prefix.len() - 1)], 16).is_ok());
+    assert!(new_writer(&nonce_prefix[..], 16).is_err());
+    assert!(new_writer(&nonce_prefix[..(nonce_prefix.len() - 1)], 0).is_err());
+}
+
+fn open_stream(aead_alg: &'static aead::Algorithm, key_bytes: &[u8],
+               nonce_prefix: &[u8], ad: &[u8], segment_len: usize,
+               sealed: &[u8]) -> std::io::Result<Vec<u8>> {
+    let o_key = aead::OpeningKey::new(aead_alg, key_bytes).unwrap();
+    let mut reader = aead::stream::OpeningReader::new(
+        o_key, nonce_prefix, ad, segment_len, sealed).unwrap();
+    let mut plaintext = Vec::new();
+    let _ = reader.read_to_end(&mut plaintext)?;
+    Ok(plaintext)
+}
 
 fn test_aead(aead_alg: &'static aead::Algorithm, file_path: &str) {
     test_aead_key_sizes(aead_alg);
@@ -90,6 +90,111 @@ fn agreement_agree_ephemeral() {
     });
 }
 
+#[test]
+fn agreement_agree_static() {
+    let rng = rand::SystemRandom::new();
+
+    test::from_file("tests/agreement_tests.txt", |section, test_case| {
+        assert_eq!(section, "");
+
+        let curve_name = test_case.consume_string("Curve");
+        let alg = alg_from_curve_name(&curve_name);
+        let peer_public = test_case.consume_bytes("PeerQ");
+        let peer_public = untrusted::Input::from(&peer_public);
+
+        match test_case.consume_optional_string("Error") {
+            None => {
+                let my_private = test_case.consume_bytes("D");
+                let rng = test::rand::FixedSliceRandom { bytes: &my_private };
+                let my_private =
+                    agreement::StaticPrivateKey::generate(alg, &rng)?;
+
+                let my_public = test_case.consume_bytes("MyQ");
+                let output = test_case.consume_bytes("Output");
+
+                assert_eq!(my_private.public_key(), &my_public[..]);
+
+                // The same key can be used for more than one key agreement.
+                for _ in 0..2 {
+                    assert!(agreement::agree_static(&my_private, alg,
+                                                    peer_public, (),
+                                                    |key_material| {
+                        assert_eq!(key_material, &output[..]);
+                        Ok(())
+                    }).is_ok());
+                }
+            },
+
+            Some(_) => {
+                let dummy_private_key =
+                    agreement::StaticPrivateKey::generate(alg, &rng)?;
+                fn kdf_not_called(_: &[u8]) -> Result<(), ()> {
+                    panic!("The KDF was called during ECDH when the peer's \
+                            public key is invalid.");
+                }
+                assert!(agreement::agree_static(&dummy_private_key, alg,
+                                                peer_public, (),
+                                                kdf_not_called).is_err());
+            }
+        }
+
+        return Ok(());
+    });
+}
+
+#[test]
+fn agreement_from_pkcs8_test() {
+    test::from_file("tests/agreement_from_pkcs8_tests.txt",
+                    |section, test_case| {
+        assert_eq!(section, "");
+
+        let curve_name = test_case.consume_string("Curve");
+        let alg = alg_from_curve_name(&curve_name);
+        let input = test_case.consume_bytes("Input");
+        let input = untrusted::Input::from(&input);
+
+        match test_case.consume_optional_string("Error") {
+            None => {
+                let public_key = test_case.consume_bytes("PublicKey");
+                let spki = test_case.consume_bytes("SPKI");
+                let key = agreement::StaticPrivateKey::from_pkcs8(alg, input)?;
+                assert_eq!(key.public_key(), &public_key[..]);
+                assert_eq!(key.public_key_spki(), spki);
+            },
+
+            Some(_) => {
+                assert!(agreement::StaticPrivateKey::from_pkcs8(alg, input)
+                            .is_err());
+            }
+        }
+
+        return Ok(());
+    });
+}
+
+// Verify that, at least, we generate PKCS#8 documents that we can read.
+#[test]
+fn agreement_generate_pkcs8_test() {
+    let rng = rand::SystemRandom::new();
+
+    for alg in &[&agreement::ECDH_P256, &agreement::ECDH_P384,
+                 &agreement::X25519] {
+        let pkcs8 =
+            agreement::StaticPrivateKey::generate_pkcs8(alg, &rng).unwrap();
+        let pkcs8 = untrusted::Input::from(pkcs8.as_ref());
+        let key = agreement::StaticPrivateKey::from_pkcs8(alg, pkcs8).unwrap();
+        assert_eq!(key.algorithm(), *alg);
+
+        for other in &[&agreement::ECDH_P256, &agreement::ECDH_P384,
+                       &agreement::X25519] {
+            if other != alg {
+                assert!(agreement::StaticPrivateKey::from_pkcs8(other, pkcs8)
+                            .is_err());
+            }
+        }
+    }
+}
+
 #[test]
 fn test_agreement_ecdh_x25519_rfc_iterated() {
     let mut k =
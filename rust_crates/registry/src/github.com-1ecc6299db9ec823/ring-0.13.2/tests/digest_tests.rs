@@ -62,6 +62,49 @@ fn digest_misc() {
     });
 }
 
+#[test]
+fn digest_xof() {
+    test::from_file("tests/digest_xof_tests.txt", |section, test_case| {
+        assert_eq!(section, "");
+        let digest_alg = test_case.consume_digest_alg("Hash").unwrap();
+        let input = test_case.consume_bytes("Input");
+        let repeat = test_case.consume_usize("Repeat");
+        let expected = test_case.consume_bytes("Output");
+
+        let mut ctx = digest::Context::new(digest_alg);
+        for _ in 0..repeat {
+            ctx.update(&input);
+        }
+
+        // The output must not depend on how it is squeezed out.
+        for &chunk_len in &[1, 7, 64, digest_alg.block_len,
+                            digest_alg.block_len + 1, expected.len()] {
+            let mut xof = ctx.clone().finish_xof().unwrap();
+            let mut actual = vec![0u8; expected.len()];
+            for chunk in actual.chunks_mut(std::cmp::max(chunk_len, 1)) {
+                xof.squeeze(chunk);
+            }
+            assert_eq!(&expected, &actual);
+        }
+
+        // The output of the fixed-length digest is a prefix of the output.
+        let fixed_len = ctx.finish();
+        let len = std::cmp::min(expected.len(), digest_alg.output_len);
+        assert_eq!(&expected[..len], &fixed_len.as_ref()[..len]);
+
+        Ok(())
+    });
+}
+
+#[test]
+fn digest_xof_not_xof() {
+    for digest_alg in &[&digest::SHA1, &digest::SHA256, &digest::SHA384,
+                        &digest::SHA512, &digest::SHA512_256,
+                        &digest::SHA3_256, &digest::SHA3_512] {
+        assert!(digest::Context::new(digest_alg).finish_xof().is_err());
+    }
+}
+
 mod digest_shavs {
     use std::vec::Vec;
     use ring::{digest, test};
@@ -219,6 +262,10 @@ test_i_u_f!(digest_test_i_u_f_sha1, digest::SHA1);
 test_i_u_f!(digest_test_i_u_f_sha256, digest::SHA256);
 test_i_u_f!(digest_test_i_u_f_sha384, digest::SHA384);
 test_i_u_f!(digest_test_i_u_f_sha512, digest::SHA512);
+test_i_u_f!(digest_test_i_u_f_sha3_256, digest::SHA3_256);
+test_i_u_f!(digest_test_i_u_f_sha3_512, digest::SHA3_512);
+test_i_u_f!(digest_test_i_u_f_shake128, digest::SHAKE128);
+test_i_u_f!(digest_test_i_u_f_shake256, digest::SHAKE256);
 
 /// See https://bugzilla.mozilla.org/show_bug.cgi?id=610162. This tests the
 /// calculation of 8GB of the byte 123.
@@ -308,6 +355,10 @@ fn test_fmt_algorithm() {
     assert_eq!("SHA384", &format!("{:?}", digest::SHA384));
     assert_eq!("SHA512", &format!("{:?}", digest::SHA512));
     assert_eq!("SHA512_256", &format!("{:?}", digest::SHA512_256));
+    assert_eq!("SHA3_256", &format!("{:?}", digest::SHA3_256));
+    assert_eq!("SHA3_512", &format!("{:?}", digest::SHA3_512));
+    assert_eq!("SHAKE128", &format!("{:?}", digest::SHAKE128));
+    assert_eq!("SHAKE256", &format!("{:?}", digest::SHAKE256));
 }
 
 #[test]
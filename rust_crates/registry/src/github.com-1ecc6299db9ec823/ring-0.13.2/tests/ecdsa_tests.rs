@@ -77,6 +77,29 @@ fn ecdsa_from_pkcs8_test() {
     });
 }
 
+#[test]
+fn ecdsa_public_key_spki_test() {
+    test::from_file("tests/ecdsa_spki_tests.txt", |section, test_case| {
+        assert_eq!(section, "");
+
+        let curve_name = test_case.consume_string("Curve");
+        let alg = match curve_name.as_str() {
+            "P-256" => &signature::ECDSA_P256_SHA256_ASN1_SIGNING,
+            "P-384" => &signature::ECDSA_P384_SHA384_ASN1_SIGNING,
+            _ => unreachable!(),
+        };
+
+        let input = test_case.consume_bytes("Input");
+        let expected = test_case.consume_bytes("SPKI");
+
+        let key_pair = signature::ECDSAKeyPair::from_pkcs8(
+            alg, untrusted::Input::from(&input)).unwrap();
+        assert_eq!(key_pair.public_key_spki(), expected);
+
+        Ok(())
+    });
+}
+
 // Verify that, at least, we generate PKCS#8 documents that we can read.
 #[test]
 fn ecdsa_generate_pkcs8_test() {
@@ -107,6 +107,23 @@ fn test_ed25519_from_seed_and_public_key_misuse() {
         untrusted::Input::from(PRIVATE_KEY)).is_err());
 }
 
+#[test]
+fn test_ed25519_public_key_spki() {
+    // The example key from RFC 8410 Section 10.3 and its public key from
+    // RFC 8410 Section 10.1.
+    const PKCS8_V1: &str =
+        "302e020100300506032b657004220420d4ee72dbf913584ad5b6d8f1f769f8ad3a\
+         fe7c28cbf1d4fbe097a88f44755842";
+    const SPKI: &str =
+        "302a300506032b657003210019bf44096984cdfe8541bac167dc3b96c85086aa30\
+         b6b6cb0c5c38ad703166e1";
+
+    let pkcs8 = test::from_hex(PKCS8_V1).unwrap();
+    let key_pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(
+        untrusted::Input::from(&pkcs8)).unwrap();
+    assert_eq!(key_pair.public_key_spki(), test::from_hex(SPKI).unwrap());
+}
+
 #[test]
 fn test_ed25519_from_pkcs8_unchecked() {
     // Just test that we can parse the input.
@@ -58,6 +58,76 @@ fn rsa_from_pkcs8_test() {
     });
 }
 
+#[cfg(feature = "rsa_signing")]
+#[test]
+fn rsa_public_key_spki_test() {
+    test::from_file("tests/rsa_spki_tests.txt", |section, test_case| {
+        assert_eq!(section, "");
+
+        let input = test_case.consume_bytes("Input");
+        let expected = test_case.consume_bytes("SPKI");
+
+        let key_pair =
+            signature::RSAKeyPair::from_pkcs8(untrusted::Input::from(&input))
+                .unwrap();
+        assert_eq!(key_pair.public_key_spki(), expected);
+
+        Ok(())
+    });
+}
+
+#[cfg(feature = "rsa_signing")]
+#[test]
+fn rsa_generate_pkcs8_test() {
+    let rng = rand::SystemRandom::new();
+
+    for &bits in [2048, 3072].iter() {
+        let pkcs8 = signature::RSAKeyPair::generate_pkcs8(bits, &rng).unwrap();
+        let key_pair =
+            signature::RSAKeyPair::from_pkcs8(untrusted::Input::from(&pkcs8))
+                .unwrap();
+        assert_eq!(key_pair.public_modulus_len() * 8, bits);
+
+        // Sign a message with the generated key and verify the signature
+        // with the `RSAPublicKey` in the exported `SubjectPublicKeyInfo`.
+        let spki = key_pair.public_key_spki();
+        let public_key = spki_public_key(&spki);
+
+        const MESSAGE: &[u8] = b"hello, world";
+        let key_pair = std::sync::Arc::new(key_pair);
+        let mut signing_state =
+            signature::RSASigningState::new(key_pair).unwrap();
+        let mut sig = vec![0u8; signing_state.key_pair().public_modulus_len()];
+        signing_state.sign(&signature::RSA_PKCS1_SHA256, &rng, MESSAGE,
+                           &mut sig).unwrap();
+        assert!(signature::verify(&signature::RSA_PKCS1_2048_8192_SHA256,
+                                  public_key, untrusted::Input::from(MESSAGE),
+                                  untrusted::Input::from(&sig)).is_ok());
+    }
+
+    // Sizes that aren't supported.
+    for &bits in [0, 1024, 2047, 2049, 2056, 4000, 4097, 8192].iter() {
+        assert!(signature::RSAKeyPair::generate_pkcs8(bits, &rng).is_err());
+    }
+}
+
+// Returns the public key in a `SubjectPublicKeyInfo`, checking that the
+// algorithm is rsaEncryption.
+#[cfg(feature = "rsa_signing")]
+fn spki_public_key(spki: &[u8]) -> untrusted::Input {
+    const RSA_ENCRYPTION: &[u8] =
+        &[0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01,
+          0x05, 0x00];
+    untrusted::Input::from(spki).read_all(error::Unspecified, |input| {
+        der::nested(input, der::Tag::Sequence, error::Unspecified, |input| {
+            let alg_id = der::expect_tag_and_get_value(input,
+                                                       der::Tag::Sequence)?;
+            assert_eq!(alg_id, untrusted::Input::from(RSA_ENCRYPTION));
+            der::bit_string_with_no_unused_bits(input)
+        })
+    }).unwrap()
+}
+
 #[cfg(feature = "rsa_signing")]
 #[test]
 fn test_signature_rsa_pkcs1_sign() {
@@ -0,0 +1,143 @@
+// Copyright 2015-2017 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings,
+)]
+
+extern crate ring;
+
+use ring::{error, scrypt, test};
+
+#[test]
+pub fn scrypt_tests() {
+    test::from_file("tests/scrypt_tests.txt", |section, test_case| {
+        assert_eq!(section, "");
+        let secret = test_case.consume_bytes("P");
+        let salt = test_case.consume_bytes("S");
+        let params = consume_params(test_case);
+        let dk = test_case.consume_bytes("DK");
+        let verify_expected_result = consume_verify(test_case);
+
+        {
+            let mut out = vec![0u8; dk.len()];
+            scrypt::derive(&params, &salt, &secret, &mut out);
+            assert_eq!(dk == out,
+                       verify_expected_result.is_ok() || dk.is_empty());
+        }
+
+        assert_eq!(scrypt::verify(&params, &salt, &secret, &dk),
+                   verify_expected_result);
+
+        Ok(())
+    });
+}
+
+#[test]
+pub fn scrypt_phc_tests() {
+    test::from_file("tests/scrypt_phc_tests.txt", |section, test_case| {
+        let secret = test_case.consume_bytes("P");
+        match section {
+            "Derive" => {
+                let salt = test_case.consume_bytes("S");
+                let params = consume_params(test_case);
+                let expected = test_case.consume_string("PHC");
+                let phc_string =
+                    scrypt::derive_phc_string(&params, &salt, &secret);
+                assert_eq!(phc_string, expected);
+                assert_eq!(scrypt::verify_phc_string(&phc_string, &secret),
+                           Ok(()));
+            },
+            "Verify" => {
+                let phc_string = test_case.consume_string("PHC");
+                let verify_expected_result = consume_verify(test_case);
+                assert_eq!(scrypt::verify_phc_string(&phc_string, &secret),
+                           verify_expected_result);
+            },
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+pub fn scrypt_phc_max_memory_test() {
+    // These parameters need 128 * 1 * 2**4 * 1 = 2048 bytes.
+    const PHC: &str = "$scrypt$ln=4,r=1,p=1$c2FsdHNhbHRzYWx0c2FsdA$\
+                       OW+YCz5hkohNQCW7WleBqzj2qIyQr3XOr8uAq3oite0";
+    assert_eq!(scrypt::verify_phc_string_with_max_memory(PHC, b"password",
+                                                         2048),
+               Ok(()));
+    assert_eq!(scrypt::verify_phc_string_with_max_memory(PHC, b"password",
+                                                         2047),
+               Err(error::Unspecified));
+}
+
+#[test]
+pub fn scrypt_params_test() {
+    let params = scrypt::Params::new(14, 8, 1).unwrap();
+    assert_eq!(params.log_n(), 14);
+    assert_eq!(params.r(), 8);
+    assert_eq!(params.p(), 1);
+
+    // N must be larger than 1.
+    assert!(scrypt::Params::new(0, 8, 1).is_err());
+    assert!(scrypt::Params::new(1, 8, 1).is_ok());
+
+    // r and p must be positive.
+    assert!(scrypt::Params::new(14, 0, 1).is_err());
+    assert!(scrypt::Params::new(14, 8, 0).is_err());
+
+    // N must be less than 2**(16 * r).
+    assert!(scrypt::Params::new(15, 1, 1).is_ok());
+    assert!(scrypt::Params::new(16, 1, 1).is_err());
+    assert!(scrypt::Params::new(31, 2, 1).is_ok());
+    assert!(scrypt::Params::new(32, 2, 1).is_err());
+
+    // r * p must be less than 2**30.
+    assert!(scrypt::Params::new(1, 1 << 15, (1 << 15) - 1).is_ok());
+    assert!(scrypt::Params::new(1, 1 << 15, 1 << 15).is_err());
+}
+
+fn consume_params(test_case: &mut test::TestCase) -> scrypt::Params {
+    let log_n = test_case.consume_usize("log_N");
+    let r = test_case.consume_usize("r");
+    let p = test_case.consume_usize("p");
+    scrypt::Params::new(log_n as u8, r as u32, p as u32).unwrap()
+}
+
+fn consume_verify(test_case: &mut test::TestCase)
+                  -> Result<(), error::Unspecified> {
+    match test_case.consume_string("Verify").as_str() {
+        "OK" => Ok(()),
+        "Err" => Err(error::Unspecified),
+        _ => panic!("Unsupported value of \"Verify\""),
+    }
+}
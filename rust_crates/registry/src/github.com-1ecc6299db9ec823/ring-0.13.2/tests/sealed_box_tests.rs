@@ -0,0 +1,152 @@
+// Copyright 2018 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+#![forbid(
+    anonymous_parameters,
+    box_pointers,
+    legacy_directory_ownership,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unstable_features,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences,
+    warnings,
+)]
+
+extern crate ring;
+extern crate untrusted;
+
+use ring::{agreement, rand, sealed_box, test};
+
+#[test]
+fn sealed_box_test() {
+    test::from_file("tests/sealed_box_tests.txt", |section, test_case| {
+        assert_eq!(section, "");
+
+        let alg = alg_from_name(&test_case.consume_string("Algorithm"));
+        let d = test_case.consume_bytes("D");
+        let e = test_case.consume_bytes("E");
+        let info = test_case.consume_bytes("Info");
+        let ad = test_case.consume_bytes("AD");
+        let plaintext = test_case.consume_bytes("Plaintext");
+        let sealed = test_case.consume_bytes("Sealed");
+
+        let rng = test::rand::FixedSliceRandom { bytes: &d };
+        let recipient = agreement::StaticPrivateKey::generate(
+            alg.agreement_algorithm(), &rng)?;
+
+        let mut in_out = vec![0u8; alg.encapsulated_key_len()];
+        in_out.extend_from_slice(&plaintext);
+        in_out.extend(vec![0u8; alg.tag_len()]);
+        let rng = test::rand::FixedSliceRandom { bytes: &e };
+        let sealed_len = sealed_box::seal_in_place(
+            alg, untrusted::Input::from(recipient.public_key()), &info, &ad,
+            &mut in_out, alg.tag_len(), &rng)?;
+        assert_eq!(&in_out[..sealed_len], &sealed[..]);
+
+        let mut in_out = sealed.clone();
+        {
+            let opened = sealed_box::open_in_place(alg, &recipient, &info,
+                                                   &ad, &mut in_out)?;
+            assert_eq!(opened, &plaintext[..]);
+        }
+
+        // Any change to the sealed box, `info`, or `ad` must be detected.
+        for i in 0..sealed.len() {
+            let mut in_out = sealed.clone();
+            in_out[i] ^= 1;
+            assert!(sealed_box::open_in_place(alg, &recipient, &info, &ad,
+                                              &mut in_out).is_err());
+        }
+        let mut in_out = sealed.clone();
+        assert!(sealed_box::open_in_place(alg, &recipient, b"other", &ad,
+                                          &mut in_out).is_err());
+        let mut in_out = sealed.clone();
+        assert!(sealed_box::open_in_place(alg, &recipient, &info, b"other",
+                                          &mut in_out).is_err());
+
+        Ok(())
+    });
+}
+
+#[test]
+fn sealed_box_round_trip() {
+    let rng = rand::SystemRandom::new();
+
+    for alg in &[&sealed_box::X25519_HKDF_SHA256_CHACHA20_POLY1305,
+                 &sealed_box::ECDH_P256_HKDF_SHA256_AES_128_GCM] {
+        let recipient = agreement::StaticPrivateKey::generate(
+            alg.agreement_algorithm(), &rng).unwrap();
+        let someone_else = agreement::StaticPrivateKey::generate(
+            alg.agreement_algorithm(), &rng).unwrap();
+
+        let plaintext = b"database password";
+        let mut sealed = vec![0u8; alg.encapsulated_key_len()];
+        sealed.extend_from_slice(plaintext);
+        sealed.extend(vec![0u8; alg.tag_len()]);
+        let sealed_len = sealed_box::seal_in_place(
+            alg, untrusted::Input::from(recipient.public_key()), b"info", b"",
+            &mut sealed, alg.tag_len(), &rng).unwrap();
+        assert_eq!(sealed_len, sealed.len());
+
+        let mut in_out = sealed.clone();
+        assert!(sealed_box::open_in_place(alg, &someone_else, b"info", b"",
+                                          &mut in_out).is_err());
+
+        let mut in_out = sealed.clone();
+        let opened = sealed_box::open_in_place(alg, &recipient, b"info", b"",
+                                               &mut in_out).unwrap();
+        assert_eq!(opened, &plaintext[..]);
+    }
+}
+
+#[test]
+fn sealed_box_wrong_key_type() {
+    let rng = rand::SystemRandom::new();
+
+    let alg = &sealed_box::X25519_HKDF_SHA256_CHACHA20_POLY1305;
+    let p256_recipient =
+        agreement::StaticPrivateKey::generate(&agreement::ECDH_P256, &rng)
+            .unwrap();
+
+    let mut in_out = vec![0u8; 100];
+    assert!(sealed_box::seal_in_place(
+        alg, untrusted::Input::from(p256_recipient.public_key()), b"", b"",
+        &mut in_out, alg.tag_len(), &rng).is_err());
+    assert!(sealed_box::open_in_place(alg, &p256_recipient, b"", b"",
+                                      &mut in_out).is_err());
+
+    // Too short to hold the encapsulated key.
+    let mut in_out = vec![0u8; alg.encapsulated_key_len() - 1];
+    assert!(sealed_box::seal_in_place(
+        alg, untrusted::Input::from(p256_recipient.public_key()), b"", b"",
+        &mut in_out, 0, &rng).is_err());
+}
+
+fn alg_from_name(name: &str) -> &'static sealed_box::Algorithm {
+    match name {
+        "X25519_HKDF_SHA256_CHACHA20_POLY1305" =>
+            &sealed_box::X25519_HKDF_SHA256_CHACHA20_POLY1305,
+        "ECDH_P256_HKDF_SHA256_AES_128_GCM" =>
+            &sealed_box::ECDH_P256_HKDF_SHA256_AES_128_GCM,
+        _ => panic!("Unsupported algorithm: {}", name),
+    }
+}
@@ -172,7 +172,7 @@ impl rustls::ServerCertVerifier for DummyServerAuth {
     fn verify_server_cert(&self,
                           _roots: &rustls::RootCertStore,
                           _certs: &[rustls::Certificate],
-                          _hostname: webpki::DNSNameRef,
+                          _server_name: webpki::SubjectNameRef,
                           _ocsp: &[u8]) -> Result<rustls::ServerCertVerified, rustls::TLSError> {
         Ok(rustls::ServerCertVerified::assertion())
     }
@@ -81,7 +81,7 @@ impl io::Read for TlsClient {
 }
 
 impl TlsClient {
-    fn new(sock: TcpStream, hostname: webpki::DNSNameRef, cfg: Arc<rustls::ClientConfig>) -> TlsClient {
+    fn new(sock: TcpStream, hostname: webpki::SubjectNameRef, cfg: Arc<rustls::ClientConfig>) -> TlsClient {
         TlsClient {
             socket: sock,
             closing: false,
@@ -425,7 +425,7 @@ mod danger {
         fn verify_server_cert(&self,
                               _roots: &rustls::RootCertStore,
                               _presented_certs: &[rustls::Certificate],
-                              _dns_name: webpki::DNSNameRef,
+                              _server_name: webpki::SubjectNameRef,
                               _ocsp: &[u8]) -> Result<rustls::ServerCertVerified, rustls::TLSError> {
             Ok(rustls::ServerCertVerified::assertion())
         }
@@ -472,6 +472,7 @@ fn make_config(args: &Args) -> Arc<rustls::ClientConfig> {
     } else {
         config.root_store.add_server_trust_anchors(&webpki_roots::TLS_SERVER_ROOTS);
         config.ct_logs = Some(&ct_logs::LOGS);
+        config.ct_policy.disqualified_logs = ct_logs::DISQUALIFIED_LOGS.to_vec();
     }
 
     if args.flag_no_tickets {
@@ -526,8 +527,8 @@ fn main() {
     let config = make_config(&args);
 
     let sock = TcpStream::connect(&addr).unwrap();
-    let dns_name = webpki::DNSNameRef::try_from_ascii_str(&args.arg_hostname).unwrap();
-    let mut tlsclient = TlsClient::new(sock, dns_name, config);
+    let server_name = webpki::SubjectNameRef::try_from_ascii_str(&args.arg_hostname).unwrap();
+    let mut tlsclient = TlsClient::new(sock, server_name, config);
 
     if args.flag_http {
         let httpreq = format!("GET / HTTP/1.1\r\nHost: {}\r\nConnection: \
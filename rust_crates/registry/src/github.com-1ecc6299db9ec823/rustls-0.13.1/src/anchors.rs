@@ -3,14 +3,31 @@ use untrusted;
 
 pub use msgs::handshake::{DistinguishedName, DistinguishedNames};
 use pemfile;
+use pemfile::PemError;
 use x509;
 use key;
 use std::io;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Certificate bundles used by common Linux distributions, in order
+/// of preference.  Only the first one found is read.
+static SYSTEM_CERT_FILES: &[&str] = &[
+    "/etc/ssl/certs/ca-certificates.crt", // Debian, Ubuntu, Arch, Gentoo
+    "/etc/pki/tls/certs/ca-bundle.crt", // Fedora, RHEL, CentOS
+    "/etc/ssl/ca-bundle.pem", // openSUSE
+    "/etc/ssl/cert.pem", // Alpine
+];
+
+/// The directory of individual certificates, as used by OpenSSL's
+/// `c_rehash`.
+static SYSTEM_CERT_DIR: &str = "/etc/ssl/certs";
 
 /// This is like a `webpki::TrustAnchor`, except it owns
 /// rather than borrows its memory.  That prevents lifetimes
 /// leaking up the object tree.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct OwnedTrustAnchor {
     subject: Vec<u8>,
     spki: Vec<u8>,
@@ -35,6 +52,66 @@ impl OwnedTrustAnchor {
     }
 }
 
+/// Why a certificate was not added by `RootCertStore::add_pem_path`
+/// or `RootCertStore::add_system_roots`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SkipReason {
+    /// The file or directory couldn't be read.
+    Io(io::ErrorKind),
+
+    /// The file isn't valid PEM, or contains a section which
+    /// isn't a certificate we understand.  Other sections in
+    /// the file may still have been added.
+    Pem(PemError),
+
+    /// The `index`th certificate in the file (counting from 0)
+    /// was rejected by webpki.
+    BadCertificate {
+        /// Which certificate in the file.
+        index: usize,
+        /// Why it was rejected.
+        error: webpki::Error,
+    },
+}
+
+/// A certificate, file or directory that was skipped while loading
+/// trust anchors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedCert {
+    /// The file or directory concerned.
+    pub path: PathBuf,
+    /// What went wrong.
+    pub reason: SkipReason,
+}
+
+/// The outcome of loading trust anchors from the filesystem.
+///
+/// Loading continues past bad certificates and unreadable files,
+/// which are listed in `skipped`: root bundles often contain
+/// certificates webpki can't use, and these shouldn't stop the
+/// others from being trusted.
+#[derive(Debug, Clone, Default)]
+pub struct RootsLoadReport {
+    /// Files which were read.
+    pub sources: Vec<PathBuf>,
+    /// The number of trust anchors added.
+    pub added: usize,
+    /// The number of certificates which were already in the store.
+    pub duplicates: usize,
+    /// Everything that couldn't be loaded.
+    pub skipped: Vec<SkippedCert>,
+}
+
+impl RootsLoadReport {
+    fn skip(&mut self, path: &Path, reason: SkipReason) {
+        debug!("skipping trust anchors in {:?}: {:?}", path, reason);
+        self.skipped.push(SkippedCert {
+            path: path.to_path_buf(),
+            reason,
+        });
+    }
+}
+
 /// A container for root certificates able to provide a root-of-trust
 /// for connection authentication.
 #[derive(Debug, Clone)]
@@ -127,4 +204,156 @@ impl RootCertStore {
 
         Ok((valid_count, invalid_count))
     }
+
+    /// Load trust anchors from the operating system's certificate
+    /// store, into a new `RootCertStore`.
+    ///
+    /// See `add_system_roots` for details.
+    pub fn load_system_roots() -> (RootCertStore, RootsLoadReport) {
+        let mut store = RootCertStore::empty();
+        let report = store.add_system_roots();
+        (store, report)
+    }
+
+    /// Add trust anchors from the operating system's certificate
+    /// store.
+    ///
+    /// Like OpenSSL, this reads a bundle file and a directory of
+    /// certificates.  The bundle is `SSL_CERT_FILE` if that is set,
+    /// otherwise the first that exists of the usual Linux
+    /// locations (such as `/etc/ssl/certs/ca-certificates.crt`).
+    /// The directories are those listed in `SSL_CERT_DIR`, separated
+    /// by colons, if that is set, otherwise `/etc/ssl/certs`.
+    ///
+    /// Default locations which don't exist are ignored.  Paths given
+    /// in the environment which can't be read are reported.
+    pub fn add_system_roots(&mut self) -> RootsLoadReport {
+        let mut report = RootsLoadReport::default();
+
+        match env::var_os("SSL_CERT_FILE") {
+            Some(file) => self.add_pem_path_to_report(Path::new(&file), &mut report),
+            None => {
+                let found = SYSTEM_CERT_FILES.iter()
+                    .map(Path::new)
+                    .find(|path| path.is_file());
+                if let Some(path) = found {
+                    self.add_pem_path_to_report(path, &mut report);
+                }
+            }
+        }
+
+        match env::var_os("SSL_CERT_DIR") {
+            Some(dirs) => {
+                for dir in env::split_paths(&dirs) {
+                    if !dir.as_os_str().is_empty() {
+                        self.add_pem_path_to_report(&dir, &mut report);
+                    }
+                }
+            }
+            None => {
+                let dir = Path::new(SYSTEM_CERT_DIR);
+                if dir.is_dir() {
+                    self.add_pem_path_to_report(dir, &mut report);
+                }
+            }
+        }
+
+        debug!("add_system_roots added {} trust anchors from {} files, skipping {}",
+               report.added,
+               report.sources.len(),
+               report.skipped.len());
+        report
+    }
+
+    /// Add all the certificates found in the PEM file at `path`.
+    /// If `path` is a directory, do that for every file in it
+    /// (but not in its subdirectories).
+    ///
+    /// Certificates already in the store aren't added again, so
+    /// it's fine for a directory to contain the same certificate
+    /// under several names.
+    pub fn add_pem_path<P: AsRef<Path>>(&mut self, path: P) -> RootsLoadReport {
+        let mut report = RootsLoadReport::default();
+        self.add_pem_path_to_report(path.as_ref(), &mut report);
+        report
+    }
+
+    fn add_pem_path_to_report(&mut self, path: &Path, report: &mut RootsLoadReport) {
+        let metadata = match fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(err) => return report.skip(path, SkipReason::Io(err.kind())),
+        };
+
+        if !metadata.is_dir() {
+            return self.add_pem_file_to_report(path, report);
+        }
+
+        let entries = match fs::read_dir(path) {
+            Ok(entries) => entries,
+            Err(err) => return report.skip(path, SkipReason::Io(err.kind())),
+        };
+
+        let mut files = Vec::new();
+        for entry in entries {
+            match entry {
+                Ok(entry) => files.push(entry.path()),
+                Err(err) => report.skip(path, SkipReason::Io(err.kind())),
+            }
+        }
+
+        // Sorted so the results don't depend on directory order.
+        files.sort();
+        for file in files {
+            if file.is_file() {
+                self.add_pem_file_to_report(&file, report);
+            }
+        }
+    }
+
+    fn add_pem_file_to_report(&mut self, path: &Path, report: &mut RootsLoadReport) {
+        let file = match fs::File::open(path) {
+            Ok(file) => file,
+            Err(err) => return report.skip(path, SkipReason::Io(err.kind())),
+        };
+
+        report.sources.push(path.to_path_buf());
+        let mut rd = io::BufReader::new(file);
+        let mut index = 0;
+
+        for item in pemfile::items(&mut rd) {
+            let der = match item {
+                Ok(pemfile::Item::X509Certificate(der)) => der,
+                Ok(_) |
+                Err(PemError::EncryptedKey { .. }) => continue,
+                Err(err) => {
+                    report.skip(path, SkipReason::Pem(err));
+                    continue;
+                }
+            };
+
+            match self.add_unique(&der) {
+                Ok(true) => report.added += 1,
+                Ok(false) => report.duplicates += 1,
+                Err(error) => report.skip(path, SkipReason::BadCertificate { index, error }),
+            }
+            index += 1;
+        }
+    }
+
+    /// Like `add`, but returns false rather than adding a trust
+    /// anchor which is already present.
+    fn add_unique(&mut self, der: &key::Certificate) -> Result<bool, webpki::Error> {
+        let ota = {
+            let inp = untrusted::Input::from(&der.0);
+            let ta = webpki::trust_anchor_util::cert_der_as_trust_anchor(inp)?;
+            OwnedTrustAnchor::from_trust_anchor(&ta)
+        };
+
+        if self.roots.contains(&ota) {
+            return Ok(false);
+        }
+
+        self.roots.push(ota);
+        Ok(true)
+    }
 }
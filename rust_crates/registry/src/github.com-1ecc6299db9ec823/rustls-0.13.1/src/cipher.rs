@@ -1,4 +1,3 @@
-use ring;
 use std::io::Write;
 use msgs::codec;
 use msgs::codec::Codec;
@@ -8,6 +7,7 @@ use msgs::fragmenter::MAX_FRAGMENT_LEN;
 use error::TLSError;
 use session::SessionSecrets;
 use suites::{SupportedCipherSuite, BulkAlgorithm};
+use crypto::{CryptoProvider, AeadKey};
 use key_schedule::{derive_traffic_key, derive_traffic_iv};
 
 // accum[i] ^= offset[i] for all i in 0..len(accum)
@@ -54,8 +54,9 @@ fn make_tls12_aad(seq: u64,
 }
 
 /// Make a `MessageCipherPair` based on the given supported ciphersuite `scs`,
-/// and the session's `secrets`.
-pub fn new_tls12(scs: &'static SupportedCipherSuite,
+/// and the session's `secrets`, using keys made by `provider`.
+pub fn new_tls12(provider: &CryptoProvider,
+                 scs: &'static SupportedCipherSuite,
                  secrets: &SessionSecrets)
                  -> MessageCipherPair {
     // Make a key block, and chop it up.
@@ -85,62 +86,60 @@ pub fn new_tls12(scs: &'static SupportedCipherSuite,
         (client_write_key, client_write_iv)
     };
 
-    let aead_alg = scs.get_aead_alg();
+    let read_key = provider.aead_key(&scs.bulk, read_key).unwrap();
+    let write_key = provider.aead_key(&scs.bulk, write_key).unwrap();
 
     match scs.bulk {
         BulkAlgorithm::AES_128_GCM |
         BulkAlgorithm::AES_256_GCM => {
-            (Box::new(GCMMessageDecrypter::new(aead_alg,
-                                               read_key,
+            (Box::new(GCMMessageDecrypter::new(read_key,
                                                read_iv)),
-             Box::new(GCMMessageEncrypter::new(aead_alg,
-                                               write_key,
+             Box::new(GCMMessageEncrypter::new(write_key,
                                                write_iv,
                                                explicit_nonce_offs)))
         }
 
         BulkAlgorithm::CHACHA20_POLY1305 => {
-            (Box::new(ChaCha20Poly1305MessageDecrypter::new(aead_alg,
-                                                            read_key,
+            (Box::new(ChaCha20Poly1305MessageDecrypter::new(read_key,
                                                             read_iv)),
-             Box::new(ChaCha20Poly1305MessageEncrypter::new(aead_alg,
-                                                            write_key,
+             Box::new(ChaCha20Poly1305MessageEncrypter::new(write_key,
                                                             write_iv)))
         }
     }
 }
 
-pub fn new_tls13_read(scs: &'static SupportedCipherSuite,
+pub fn new_tls13_read(provider: &CryptoProvider,
+                      scs: &'static SupportedCipherSuite,
                       secret: &[u8]) -> Box<MessageDecrypter> {
-    let hash = scs.get_hash();
+    let hash = scs.get_hash(provider);
     let key = derive_traffic_key(hash, secret, scs.enc_key_len);
     let iv = derive_traffic_iv(hash, secret, scs.fixed_iv_len);
-    let aead_alg = scs.get_aead_alg();
+    let key = provider.aead_key(&scs.bulk, &key).unwrap();
 
-    Box::new(TLS13MessageDecrypter::new(aead_alg, &key, &iv))
+    Box::new(TLS13MessageDecrypter::new(key, &iv))
 }
 
-pub fn new_tls13_write(scs: &'static SupportedCipherSuite,
+pub fn new_tls13_write(provider: &CryptoProvider,
+                       scs: &'static SupportedCipherSuite,
                        secret: &[u8]) -> Box<MessageEncrypter> {
-    let hash = scs.get_hash();
+    let hash = scs.get_hash(provider);
     let key = derive_traffic_key(hash, secret, scs.enc_key_len);
     let iv = derive_traffic_iv(hash, secret, scs.fixed_iv_len);
-    let aead_alg = scs.get_aead_alg();
+    let key = provider.aead_key(&scs.bulk, &key).unwrap();
 
-    Box::new(TLS13MessageEncrypter::new(aead_alg, &key, &iv))
+    Box::new(TLS13MessageEncrypter::new(key, &iv))
 }
 
 /// A `MessageEncrypter` for AES-GCM AEAD ciphersuites. TLS 1.2 only.
 pub struct GCMMessageEncrypter {
-    alg: &'static ring::aead::Algorithm,
-    enc_key: ring::aead::SealingKey,
+    enc_key: Box<AeadKey>,
     enc_salt: [u8; 4],
     nonce_offset: [u8; 8],
 }
 
 /// A `MessageDecrypter` for AES-GCM AEAD ciphersuites.  TLS1.2 only.
 pub struct GCMMessageDecrypter {
-    dec_key: ring::aead::OpeningKey,
+    dec_key: Box<AeadKey>,
     dec_salt: [u8; 4],
 }
 
@@ -164,13 +163,11 @@ impl MessageDecrypter for GCMMessageDecrypter {
         let mut aad = [0u8; TLS12_AAD_SIZE];
         make_tls12_aad(seq, msg.typ, msg.version, buf.len() - GCM_OVERHEAD, &mut aad);
 
-        let plain_len = ring::aead::open_in_place(&self.dec_key,
-                                                  &nonce,
-                                                  &aad,
-                                                  GCM_EXPLICIT_NONCE_LEN,
-                                                  &mut buf)
-            .map_err(|_| TLSError::DecryptError)?
-            .len();
+        let plain_len = self.dec_key.open_in_place(&nonce,
+                                                   &aad,
+                                                   GCM_EXPLICIT_NONCE_LEN,
+                                                   &mut buf)
+            .map_err(|_| TLSError::DecryptError)?;
 
         if plain_len > MAX_FRAGMENT_LEN {
             return Err(TLSError::PeerSentOversizedRecord);
@@ -202,7 +199,7 @@ impl MessageEncrypter for GCMMessageEncrypter {
         xor(&mut nonce[4..], &self.nonce_offset);
 
         // make output buffer with room for nonce/tag
-        let tag_len = self.alg.tag_len();
+        let tag_len = self.enc_key.tag_len();
         let total_len = 8 + msg.payload.len() + tag_len;
         let mut buf = Vec::with_capacity(total_len);
         buf.extend_from_slice(&nonce[4..]);
@@ -212,7 +209,7 @@ impl MessageEncrypter for GCMMessageEncrypter {
         let mut aad = [0u8; TLS12_AAD_SIZE];
         make_tls12_aad(seq, msg.typ, msg.version, msg.payload.len(), &mut aad);
 
-        ring::aead::seal_in_place(&self.enc_key, &nonce, &aad, &mut buf[8..], tag_len)
+        self.enc_key.seal_in_place(&nonce, &aad, &mut buf[8..])
             .map_err(|_| TLSError::General("encrypt failed".to_string()))?;
 
         Ok(Message {
@@ -224,14 +221,12 @@ impl MessageEncrypter for GCMMessageEncrypter {
 }
 
 impl GCMMessageEncrypter {
-    fn new(alg: &'static ring::aead::Algorithm,
-           enc_key: &[u8],
+    fn new(enc_key: Box<AeadKey>,
            enc_iv: &[u8],
            nonce_offset: &[u8])
            -> GCMMessageEncrypter {
         let mut ret = GCMMessageEncrypter {
-            alg,
-            enc_key: ring::aead::SealingKey::new(alg, enc_key).unwrap(),
+            enc_key,
             enc_salt: [0u8; 4],
             nonce_offset: [0u8; 8],
         };
@@ -246,11 +241,10 @@ impl GCMMessageEncrypter {
 }
 
 impl GCMMessageDecrypter {
-    fn new(alg: &'static ring::aead::Algorithm,
-           dec_key: &[u8],
+    fn new(dec_key: Box<AeadKey>,
            dec_iv: &[u8]) -> GCMMessageDecrypter {
         let mut ret = GCMMessageDecrypter {
-            dec_key: ring::aead::OpeningKey::new(alg, dec_key).unwrap(),
+            dec_key,
             dec_salt: [0u8; 4],
         };
 
@@ -261,14 +255,12 @@ impl GCMMessageDecrypter {
 }
 
 struct TLS13MessageEncrypter {
-    alg: &'static ring::aead::Algorithm,
-    enc_key: ring::aead::SealingKey,
+    enc_key: Box<AeadKey>,
     enc_offset: [u8; 12],
 }
 
 struct TLS13MessageDecrypter {
-    alg: &'static ring::aead::Algorithm,
-    dec_key: ring::aead::OpeningKey,
+    dec_key: Box<AeadKey>,
     dec_offset: [u8; 12],
 }
 
@@ -291,14 +283,14 @@ impl MessageEncrypter for TLS13MessageEncrypter {
         xor(&mut nonce, &self.enc_offset);
 
         // make output buffer with room for content type and tag
-        let tag_len = self.alg.tag_len();
+        let tag_len = self.enc_key.tag_len();
         let total_len = msg.payload.len() + 1 + tag_len;
         let mut buf = Vec::with_capacity(total_len);
         buf.extend_from_slice(msg.payload);
         msg.typ.encode(&mut buf);
         buf.resize(total_len, 0u8);
 
-        ring::aead::seal_in_place(&self.enc_key, &nonce, &[], &mut buf, tag_len)
+        self.enc_key.seal_in_place(&nonce, &[], &mut buf)
             .map_err(|_| TLSError::General("encrypt failed".to_string()))?;
 
         Ok(Message {
@@ -319,13 +311,12 @@ impl MessageDecrypter for TLS13MessageDecrypter {
             .ok_or(TLSError::DecryptError)?;
         let mut buf = payload.0;
 
-        if buf.len() < self.alg.tag_len() {
+        if buf.len() < self.dec_key.tag_len() {
             return Err(TLSError::DecryptError);
         }
 
-        let plain_len = ring::aead::open_in_place(&self.dec_key, &nonce, &[], 0, &mut buf)
-            .map_err(|_| TLSError::DecryptError)?
-            .len();
+        let plain_len = self.dec_key.open_in_place(&nonce, &[], 0, &mut buf)
+            .map_err(|_| TLSError::DecryptError)?;
 
         buf.truncate(plain_len);
 
@@ -352,12 +343,10 @@ impl MessageDecrypter for TLS13MessageDecrypter {
 }
 
 impl TLS13MessageEncrypter {
-    fn new(alg: &'static ring::aead::Algorithm,
-           enc_key: &[u8],
+    fn new(enc_key: Box<AeadKey>,
            enc_iv: &[u8]) -> TLS13MessageEncrypter {
         let mut ret = TLS13MessageEncrypter {
-            alg,
-            enc_key: ring::aead::SealingKey::new(alg, enc_key).unwrap(),
+            enc_key,
             enc_offset: [0u8; 12],
         };
 
@@ -367,12 +356,10 @@ impl TLS13MessageEncrypter {
 }
 
 impl TLS13MessageDecrypter {
-    fn new(alg: &'static ring::aead::Algorithm,
-           dec_key: &[u8],
+    fn new(dec_key: Box<AeadKey>,
            dec_iv: &[u8]) -> TLS13MessageDecrypter {
         let mut ret = TLS13MessageDecrypter {
-            alg,
-            dec_key: ring::aead::OpeningKey::new(alg, dec_key).unwrap(),
+            dec_key,
             dec_offset: [0u8; 12],
         };
 
@@ -385,8 +372,7 @@ impl TLS13MessageDecrypter {
 /// This implementation does the AAD construction required in TLS1.2.
 /// TLS1.3 uses `TLS13MessageEncrypter`.
 pub struct ChaCha20Poly1305MessageEncrypter {
-    alg: &'static ring::aead::Algorithm,
-    enc_key: ring::aead::SealingKey,
+    enc_key: Box<AeadKey>,
     enc_offset: [u8; 12],
 }
 
@@ -394,17 +380,15 @@ pub struct ChaCha20Poly1305MessageEncrypter {
 /// This implementation does the AAD construction required in TLS1.2.
 /// TLS1.3 uses `TLS13MessageDecrypter`.
 pub struct ChaCha20Poly1305MessageDecrypter {
-    dec_key: ring::aead::OpeningKey,
+    dec_key: Box<AeadKey>,
     dec_offset: [u8; 12],
 }
 
 impl ChaCha20Poly1305MessageEncrypter {
-    fn new(alg: &'static ring::aead::Algorithm,
-           enc_key: &[u8],
+    fn new(enc_key: Box<AeadKey>,
            enc_iv: &[u8]) -> ChaCha20Poly1305MessageEncrypter {
         let mut ret = ChaCha20Poly1305MessageEncrypter {
-            alg,
-            enc_key: ring::aead::SealingKey::new(alg, enc_key).unwrap(),
+            enc_key,
             enc_offset: [0u8; 12],
         };
 
@@ -414,11 +398,10 @@ impl ChaCha20Poly1305MessageEncrypter {
 }
 
 impl ChaCha20Poly1305MessageDecrypter {
-    fn new(alg: &'static ring::aead::Algorithm,
-           dec_key: &[u8],
+    fn new(dec_key: Box<AeadKey>,
            dec_iv: &[u8]) -> ChaCha20Poly1305MessageDecrypter {
         let mut ret = ChaCha20Poly1305MessageDecrypter {
-            dec_key: ring::aead::OpeningKey::new(alg, dec_key).unwrap(),
+            dec_key,
             dec_offset: [0u8; 12],
         };
 
@@ -447,9 +430,8 @@ impl MessageDecrypter for ChaCha20Poly1305MessageDecrypter {
         let mut aad = [0u8; TLS12_AAD_SIZE];
         make_tls12_aad(seq, msg.typ, msg.version, buf.len() - CHACHAPOLY1305_OVERHEAD, &mut aad);
 
-        let plain_len = ring::aead::open_in_place(&self.dec_key, &nonce, &aad, 0, &mut buf)
-            .map_err(|_| TLSError::DecryptError)?
-            .len();
+        let plain_len = self.dec_key.open_in_place(&nonce, &aad, 0, &mut buf)
+            .map_err(|_| TLSError::DecryptError)?;
 
         if plain_len > MAX_FRAGMENT_LEN {
             return Err(TLSError::PeerSentOversizedRecord);
@@ -475,13 +457,13 @@ impl MessageEncrypter for ChaCha20Poly1305MessageEncrypter {
         make_tls12_aad(seq, msg.typ, msg.version, msg.payload.len(), &mut aad);
 
         // make result buffer with room for tag, etc.
-        let tag_len = self.alg.tag_len();
+        let tag_len = self.enc_key.tag_len();
         let total_len = msg.payload.len() + tag_len;
         let mut buf = Vec::with_capacity(total_len);
         buf.extend_from_slice(msg.payload);
         buf.resize(total_len, 0u8);
 
-        ring::aead::seal_in_place(&self.enc_key, &nonce, &aad, &mut buf, tag_len)
+        self.enc_key.seal_in_place(&nonce, &aad, &mut buf)
             .map_err(|_| TLSError::General("encrypt failed".to_string()))?;
 
         Ok(Message {
@@ -8,6 +8,7 @@ use msgs::persist;
 use msgs::enums::ExtensionType;
 use msgs::enums::NamedGroup;
 use session::SessionRandoms;
+use crypto::CryptoProvider;
 use hash_hs;
 use sign;
 use suites;
@@ -56,20 +57,22 @@ pub struct HandshakeDetails {
     pub using_ems: bool,
     pub session_id: SessionID,
     pub sent_tls13_fake_ccs: bool,
-    pub dns_name: webpki::DNSName,
+    pub server_name: webpki::SubjectName,
     pub extra_exts: Vec<ClientExtension>,
 }
 
 impl HandshakeDetails {
-    pub fn new(host_name: webpki::DNSName, extra_exts: Vec<ClientExtension>) -> HandshakeDetails {
+    pub fn new(provider: &CryptoProvider,
+               server_name: webpki::SubjectName,
+               extra_exts: Vec<ClientExtension>) -> HandshakeDetails {
         HandshakeDetails {
             transcript: hash_hs::HandshakeHash::new(),
             resuming_session: None,
-            randoms: SessionRandoms::for_client(),
+            randoms: SessionRandoms::for_client(provider),
             using_ems: false,
             session_id: SessionID::empty(),
             sent_tls13_fake_ccs: false,
-            dns_name: host_name,
+            server_name,
             extra_exts,
         }
     }
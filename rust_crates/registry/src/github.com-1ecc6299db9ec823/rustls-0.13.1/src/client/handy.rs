@@ -1,10 +1,20 @@
 use msgs::enums::SignatureScheme;
+use msgs::codec::{Codec, Reader};
+use msgs::persist;
 use sign;
 use key;
 use client;
+use ticketer;
+use util;
 
 use std::collections;
+use std::fs;
+use std::io::{self, Read};
+use std::mem;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time;
 
 /// An implementor of `StoresClientSessions` which does nothing.
 pub struct NoClientSessionStorage {}
@@ -63,6 +73,282 @@ impl client::StoresClientSessions for ClientSessionMemoryCache {
     }
 }
 
+const FILE_CACHE_VERSION: u8 = 1;
+const FILE_CACHE_NAME: &str = "rustls-client-sessions";
+
+/// How long we wait for another process to finish with the cache.
+const FILE_CACHE_LOCK_TIMEOUT_MS: u64 = 1000;
+
+/// A lock file older than this was left by a process which died
+/// while holding it.
+const FILE_CACHE_STALE_LOCK_SECS: u64 = 10;
+
+struct FileCacheEntry {
+    key: Vec<u8>,
+    value: Vec<u8>,
+    last_used: u64,
+    expires: Option<u64>,
+}
+
+impl FileCacheEntry {
+    fn new(key: Vec<u8>, value: Vec<u8>, last_used: u64) -> FileCacheEntry {
+        // Sessions expire with their ticket lifetime.  Other values
+        // (like key exchange hints) are kept until evicted.
+        let expires = persist::ClientSessionValue::read_bytes(&value)
+            .and_then(|sess| if sess.lifetime != 0 {
+                Some(sess.epoch + u64::from(sess.lifetime))
+            } else {
+                None
+            });
+
+        FileCacheEntry { key, value, last_used, expires }
+    }
+
+    fn has_expired(&self, time_now: u64) -> bool {
+        self.expires.map_or(false, |expires| expires < time_now)
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        self.last_used.encode(bytes);
+        self.expires.unwrap_or(0).encode(bytes);
+        (self.key.len() as u32).encode(bytes);
+        bytes.extend_from_slice(&self.key);
+        (self.value.len() as u32).encode(bytes);
+        bytes.extend_from_slice(&self.value);
+    }
+
+    fn read(r: &mut Reader) -> Option<FileCacheEntry> {
+        let last_used = u64::read(r)?;
+        let expires = u64::read(r)?;
+        let key_len = u32::read(r)? as usize;
+        let key = r.take(key_len)?.to_vec();
+        let value_len = u32::read(r)? as usize;
+        let value = r.take(value_len)?.to_vec();
+
+        Some(FileCacheEntry {
+            key,
+            value,
+            last_used,
+            expires: if expires == 0 { None } else { Some(expires) },
+        })
+    }
+}
+
+/// Excludes other processes from the cache file while it exists.
+struct FileCacheLock {
+    path: PathBuf,
+}
+
+impl FileCacheLock {
+    fn acquire(path: PathBuf) -> io::Result<FileCacheLock> {
+        let start = time::Instant::now();
+
+        loop {
+            match fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(FileCacheLock { path }),
+                Err(ref err) if err.kind() == io::ErrorKind::AlreadyExists => {}
+                Err(err) => return Err(err),
+            }
+
+            let stale = fs::metadata(&path)
+                .and_then(|meta| meta.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .map_or(false, |age| age.as_secs() >= FILE_CACHE_STALE_LOCK_SECS);
+            if stale {
+                let _ = fs::remove_file(&path);
+                continue;
+            }
+
+            if start.elapsed() > time::Duration::from_millis(FILE_CACHE_LOCK_TIMEOUT_MS) {
+                return Err(io::Error::new(io::ErrorKind::TimedOut,
+                                          "timed out locking session cache"));
+            }
+
+            thread::sleep(time::Duration::from_millis(10));
+        }
+    }
+}
+
+impl Drop for FileCacheLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// An implementor of `StoresClientSessions` that stores sessions
+/// in a file, so they survive the process.  This is useful for
+/// programs which run once per connection, like command line tools.
+///
+/// Concurrent processes may share the same cache directory.  Expired
+/// sessions are dropped, and the least recently used are evicted
+/// beyond a limit on the number of entries.
+///
+/// Looking up a session doesn't write to the file.  Which sessions
+/// were used is remembered, and recorded in the file by the next
+/// `put` or when the cache is dropped.
+pub struct ClientSessionFileCache {
+    path: PathBuf,
+    lock_path: PathBuf,
+    max_entries: usize,
+    // Keys found by `get` that haven't been written back yet, least
+    // recently used first.
+    uses: Mutex<Vec<Vec<u8>>>,
+}
+
+impl ClientSessionFileCache {
+    /// Make a new ClientSessionFileCache which keeps its data in
+    /// the directory `dir`, creating it if needed.  `size` is the
+    /// maximum number of stored sessions.
+    pub fn new<P: AsRef<Path>>(dir: P, size: usize) -> Arc<ClientSessionFileCache> {
+        debug_assert!(size > 0);
+        let path = dir.as_ref().join(FILE_CACHE_NAME);
+        let mut lock_path = path.clone().into_os_string();
+        lock_path.push(".lock");
+
+        Arc::new(ClientSessionFileCache {
+            path,
+            lock_path: PathBuf::from(lock_path),
+            max_entries: size,
+            uses: Mutex::new(Vec::new()),
+        })
+    }
+
+    // A missing or corrupt file is treated as empty.  The file is
+    // only ever replaced atomically, so this doesn't need the lock.
+    fn read_entries(&self) -> Vec<FileCacheEntry> {
+        let mut bytes = Vec::new();
+        let read = fs::File::open(&self.path)
+            .and_then(|mut f| f.read_to_end(&mut bytes));
+        if read.is_err() {
+            return Vec::new();
+        }
+
+        let mut rd = Reader::init(&bytes);
+        if u8::read(&mut rd) != Some(FILE_CACHE_VERSION) {
+            return Vec::new();
+        }
+
+        let mut entries = Vec::new();
+        while rd.any_left() {
+            match FileCacheEntry::read(&mut rd) {
+                Some(entry) => entries.push(entry),
+                None => return Vec::new(),
+            }
+        }
+
+        entries
+    }
+
+    fn write_entries(&self, entries: &[FileCacheEntry]) -> io::Result<()> {
+        let mut bytes = Vec::new();
+        FILE_CACHE_VERSION.encode(&mut bytes);
+        for entry in entries {
+            entry.encode(&mut bytes);
+        }
+
+        util::write_private_file(&self.path, &bytes)
+    }
+
+    fn note_use(&self, key: &[u8]) {
+        let mut uses = self.uses.lock().unwrap();
+        uses.retain(|used| used.as_slice() != key);
+        uses.push(key.to_vec());
+    }
+
+    /// Runs `f` over the cache contents while holding the lock,
+    /// and then writes them back if `f` returns true.
+    fn update<F>(&self, f: F) -> io::Result<()>
+        where F: FnOnce(&mut Vec<FileCacheEntry>, u64) -> bool
+    {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let _lock = FileCacheLock::acquire(self.lock_path.clone())?;
+        let mut entries = self.read_entries();
+        let count = entries.len();
+
+        let now = ticketer::timebase();
+        entries.retain(|entry| !entry.has_expired(now));
+
+        let mut next_use = entries.iter()
+            .map(|entry| entry.last_used + 1)
+            .max()
+            .unwrap_or(0);
+
+        let mut changed = entries.len() != count;
+
+        // Record the uses `get` has seen since we last wrote.
+        let uses = mem::replace(&mut *self.uses.lock().unwrap(), Vec::new());
+        for key in uses {
+            if let Some(entry) = entries.iter_mut().find(|entry| entry.key == key) {
+                entry.last_used = next_use;
+                next_use += 1;
+                changed = true;
+            }
+        }
+
+        changed = f(&mut entries, next_use) || changed;
+
+        if entries.len() > self.max_entries {
+            entries.sort_by(|a, b| b.last_used.cmp(&a.last_used));
+            entries.truncate(self.max_entries);
+            changed = true;
+        }
+
+        if changed {
+            self.write_entries(&entries)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl client::StoresClientSessions for ClientSessionFileCache {
+    fn put(&self, key: Vec<u8>, value: Vec<u8>) -> bool {
+        let result = self.update(|entries, next_use| {
+            entries.retain(|entry| entry.key != key);
+            entries.push(FileCacheEntry::new(key, value, next_use));
+            true
+        });
+
+        match result {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("error writing client session cache: {}", e);
+                false
+            }
+        }
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        let now = ticketer::timebase();
+        let found = self.read_entries()
+            .into_iter()
+            .find(|entry| entry.key == key && !entry.has_expired(now))
+            .map(|entry| entry.value);
+
+        if found.is_some() {
+            self.note_use(key);
+        }
+
+        found
+    }
+}
+
+impl Drop for ClientSessionFileCache {
+    fn drop(&mut self) {
+        if self.uses.lock().unwrap().is_empty() {
+            return;
+        }
+
+        if let Err(e) = self.update(|_, _| false) {
+            warn!("error writing client session cache: {}", e);
+        }
+    }
+}
+
 pub struct FailResolveClientCert {}
 
 impl client::ResolvesClientCert for FailResolveClientCert {
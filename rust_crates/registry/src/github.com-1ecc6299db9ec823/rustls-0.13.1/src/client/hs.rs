@@ -27,6 +27,7 @@ use suites;
 use hash_hs;
 use verify;
 use rand;
+use crypto::CryptoProvider;
 use ticketer;
 use error::TLSError;
 use handshake::{check_message, check_handshake_message};
@@ -70,6 +71,8 @@ type NextState = Box<State + Send + Sync>;
 type NextStateOrError = Result<NextState, TLSError>;
 
 pub trait State {
+    /// A name for this state, for `SessionEvent::StateChange`.
+    fn name(&self) -> &'static str;
     fn check_message(&self, m: &Message) -> CheckResult;
     fn handle(self: Box<Self>, sess: &mut ClientSessionImpl, m: Message) -> NextStateOrError;
 }
@@ -87,15 +90,15 @@ fn check_aligned_handshake(sess: &mut ClientSessionImpl) -> Result<(), TLSError>
     }
 }
 
-fn find_session(sess: &mut ClientSessionImpl, dns_name: webpki::DNSNameRef)
+fn find_session(sess: &mut ClientSessionImpl, server_name: webpki::SubjectNameRef)
                 -> Option<persist::ClientSessionValue> {
-    let key = persist::ClientSessionKey::session_for_dns_name(dns_name);
+    let key = persist::ClientSessionKey::session_for_server_name(server_name);
     let key_buf = key.get_encoding();
 
     let maybe_value = sess.config.session_persistence.get(&key_buf);
 
     if maybe_value.is_none() {
-        debug!("No cached session for {:?}", dns_name);
+        debug!("No cached session for {:?}", server_name);
         return None;
     }
 
@@ -103,6 +106,9 @@ fn find_session(sess: &mut ClientSessionImpl, dns_name: webpki::DNSNameRef)
     if let Some(result) = persist::ClientSessionValue::read_bytes(&value) {
         if result.has_expired(ticketer::timebase()) {
             None
+        } else if sess.config.enforces_ct() && !result.ct_enforced {
+            debug!("Not resuming session for {:?}: CT policy wasn't enforced", server_name);
+            None
         } else {
             Some(result)
         }
@@ -111,26 +117,27 @@ fn find_session(sess: &mut ClientSessionImpl, dns_name: webpki::DNSNameRef)
     }
 }
 
-fn find_kx_hint(sess: &mut ClientSessionImpl, dns_name: webpki::DNSNameRef) -> Option<NamedGroup> {
-    let key = persist::ClientSessionKey::hint_for_dns_name(dns_name);
+fn find_kx_hint(sess: &mut ClientSessionImpl, server_name: webpki::SubjectNameRef) -> Option<NamedGroup> {
+    let key = persist::ClientSessionKey::hint_for_server_name(server_name);
     let key_buf = key.get_encoding();
 
     let maybe_value = sess.config.session_persistence.get(&key_buf);
     maybe_value.and_then(|enc| NamedGroup::read_bytes(&enc))
 }
 
-fn save_kx_hint(sess: &mut ClientSessionImpl, dns_name: webpki::DNSNameRef, group: NamedGroup) {
-    let key = persist::ClientSessionKey::hint_for_dns_name(dns_name);
+fn save_kx_hint(sess: &mut ClientSessionImpl, server_name: webpki::SubjectNameRef, group: NamedGroup) {
+    let key = persist::ClientSessionKey::hint_for_server_name(server_name);
 
     sess.config.session_persistence.put(key.get_encoding(), group.get_encoding());
 }
 
 /// If we have a ticket, we use the sessionid as a signal that we're
 /// doing an abbreviated handshake.  See section 3.4 in RFC5077.
-fn randomise_sessionid_for_ticket(csv: &mut persist::ClientSessionValue) {
+fn randomise_sessionid_for_ticket(provider: &CryptoProvider,
+                                  csv: &mut persist::ClientSessionValue) {
     if !csv.ticket.0.is_empty() {
         let mut random_id = [0u8; 32];
-        rand::fill_random(&mut random_id);
+        rand::fill_random(provider, &mut random_id);
         csv.session_id = SessionID::new(&random_id);
     }
 }
@@ -142,7 +149,9 @@ pub fn fill_in_psk_binder(sess: &mut ClientSessionImpl,
                           hmp: &mut HandshakeMessagePayload) {
     // We need to know the hash function of the suite we're trying to resume into.
     let resuming = handshake.resuming_session.as_ref().unwrap();
-    let suite_hash = sess.find_cipher_suite(resuming.cipher_suite).unwrap().get_hash();
+    let suite_hash = sess.find_cipher_suite(resuming.cipher_suite)
+        .unwrap()
+        .get_hash(sess.common.provider());
 
     // The binder is calculated over the clienthello, but doesn't include itself or its
     // length, or the length of its container.
@@ -171,9 +180,11 @@ struct InitialState {
 }
 
 impl InitialState {
-    fn new(host_name: webpki::DNSName, extra_exts: Vec<ClientExtension>) -> InitialState {
+    fn new(provider: &CryptoProvider,
+           server_name: webpki::SubjectName,
+           extra_exts: Vec<ClientExtension>) -> InitialState {
         InitialState {
-            handshake: HandshakeDetails::new(host_name, extra_exts),
+            handshake: HandshakeDetails::new(provider, server_name, extra_exts),
         }
     }
 
@@ -187,9 +198,9 @@ impl InitialState {
 }
 
 
-pub fn start_handshake(sess: &mut ClientSessionImpl, host_name: webpki::DNSName,
+pub fn start_handshake(sess: &mut ClientSessionImpl, server_name: webpki::SubjectName,
                        extra_exts: Vec<ClientExtension>) -> NextState {
-    InitialState::new(host_name, extra_exts)
+    InitialState::new(sess.common.provider(), server_name, extra_exts)
         .emit_initial_client_hello(sess)
 }
 
@@ -236,11 +247,11 @@ fn emit_client_hello_for_retry(sess: &mut ClientSessionImpl,
                                mut hello: ClientHelloDetails,
                                retryreq: Option<&HelloRetryRequest>) -> NextState {
     // Do we have a SessionID or ticket cached for this host?
-    handshake.resuming_session = find_session(sess, handshake.dns_name.as_ref());
+    handshake.resuming_session = find_session(sess, handshake.server_name.as_ref());
     let (session_id, ticket, resume_version) = if handshake.resuming_session.is_some() {
         let resuming = handshake.resuming_session.as_mut().unwrap();
         if resuming.version == ProtocolVersion::TLSv1_2 {
-            randomise_sessionid_for_ticket(resuming);
+            randomise_sessionid_for_ticket(sess.common.provider(), resuming);
         }
         debug!("Resuming session");
         (resuming.session_id, resuming.ticket.0.clone(), resuming.version)
@@ -271,7 +282,7 @@ fn emit_client_hello_for_retry(sess: &mut ClientSessionImpl,
         // - if not, send just X25519.
         //
         let groups = retryreq.and_then(|req| req.get_requested_key_share_group())
-            .or_else(|| find_kx_hint(sess, handshake.dns_name.as_ref()))
+            .or_else(|| find_kx_hint(sess, handshake.server_name.as_ref()))
             .or_else(|| Some(NamedGroup::X25519))
             .map(|grp| vec![ grp ])
             .unwrap();
@@ -285,7 +296,8 @@ fn emit_client_hello_for_retry(sess: &mut ClientSessionImpl,
                 continue;
             }
 
-            if let Some(key_share) = suites::KeyExchange::start_ecdhe(group) {
+            let key_share = suites::KeyExchange::start_ecdhe(sess.common.provider(), group);
+            if let Some(key_share) = key_share {
                 key_shares.push(KeyShareEntry::new(group, &key_share.pubkey));
                 hello.offered_key_shares.push(key_share);
             }
@@ -296,8 +308,12 @@ fn emit_client_hello_for_retry(sess: &mut ClientSessionImpl,
     if !supported_versions.is_empty() {
         exts.push(ClientExtension::SupportedVersions(supported_versions));
     }
-    if sess.config.enable_sni {
-        exts.push(ClientExtension::make_sni(handshake.dns_name.as_ref()));
+    // RFC6066 forbids literal IP addresses in the server_name extension,
+    // so there's nothing to send if that's all we have.
+    if let webpki::SubjectNameRef::DNSName(dns_name) = handshake.server_name.as_ref() {
+        if sess.config.enable_sni {
+            exts.push(ClientExtension::make_sni(dns_name));
+        }
     }
     exts.push(ClientExtension::ECPointFormats(ECPointFormatList::supported()));
     exts.push(ClientExtension::NamedGroups(NamedGroups::supported()));
@@ -343,14 +359,26 @@ fn emit_client_hello_for_retry(sess: &mut ClientSessionImpl,
             //
             // Include an empty binder. It gets filled in below because it depends on
             // the message it's contained in (!!!).
-            let (obfuscated_ticket_age, suite) = {
+            let (obfuscated_ticket_age, suite, max_early_data_size) = {
                 let resuming = handshake.resuming_session
                     .as_ref()
                     .unwrap();
-                (resuming.get_obfuscated_ticket_age(ticketer::timebase()), resuming.cipher_suite)
+                (resuming.get_obfuscated_ticket_age(ticketer::timebase()),
+                 resuming.cipher_suite,
+                 resuming.max_early_data_size)
             };
 
-            let binder_len = sess.find_cipher_suite(suite).unwrap().get_hash().output_len;
+            // We can only send early data in the first ClientHello.
+            if sess.config.enable_early_data &&
+                max_early_data_size > 0 &&
+                retryreq.is_none() {
+                exts.push(ClientExtension::EarlyData);
+            }
+
+            let binder_len = sess.find_cipher_suite(suite)
+                .unwrap()
+                .get_hash(sess.common.provider())
+                .output_len();
             let binder = vec![0u8; binder_len];
 
             let psk_identity = PresharedKeyIdentity::new(ticket, obfuscated_ticket_age);
@@ -408,6 +436,16 @@ fn emit_client_hello_for_retry(sess: &mut ClientSessionImpl,
         payload: MessagePayload::Handshake(chp),
     };
 
+    let early_key_schedule = if fill_in_binder && hello.sent_extensions.contains(&ExtensionType::EarlyData) {
+        let resuming = handshake.resuming_session.as_ref().unwrap();
+        let resuming_suite = sess.find_cipher_suite(resuming.cipher_suite).unwrap();
+        let mut key_schedule = KeySchedule::new(resuming_suite.get_hash(sess.common.provider()));
+        key_schedule.input_secret(&resuming.master_secret.0);
+        Some((resuming_suite, key_schedule, resuming.max_early_data_size))
+    } else {
+        None
+    };
+
     if retryreq.is_some() {
         // send dummy CCS to fool middleboxes prior
         // to second client hello
@@ -419,6 +457,30 @@ fn emit_client_hello_for_retry(sess: &mut ClientSessionImpl,
     handshake.transcript.add_message(&ch);
     sess.common.send_msg(ch, false);
 
+    // Calculate the hash of ClientHello and use it to derive EarlyTrafficSecret
+    if let Some((resuming_suite, key_schedule, max_early_data_size)) = early_key_schedule {
+        let client_hello_hash = handshake.transcript
+            .get_hash_given(resuming_suite.get_hash(sess.common.provider()), &[]);
+        let client_early_traffic_secret = key_schedule
+            .derive(SecretKind::ClientEarlyTrafficSecret, &client_hello_hash);
+        // Set early data encryption key
+        let enc = cipher::new_tls13_write(sess.common.provider(),
+                                          resuming_suite,
+                                          &client_early_traffic_secret);
+        sess.common.set_message_encrypter(enc);
+
+        sess.config.key_log.log("CLIENT_EARLY_TRAFFIC_SECRET",
+                                &handshake.randoms.client,
+                                &client_early_traffic_secret);
+
+        // Now the client can send encrypted early data
+        sess.early_data.enable(max_early_data_size as usize);
+
+        // If middlebox compatibility is wanted, the fake CCS goes before
+        // the first encrypted record.
+        emit_fake_ccs(&mut handshake, sess);
+    }
+
     let next = ExpectServerHello {
         handshake, hello,
         server_cert: ServerCertDetails::new(),
@@ -481,7 +543,7 @@ impl ExpectServerHello {
                                server_hello: &ServerHelloPayload)
                                -> Result<(), TLSError> {
         let suite = sess.common.get_suite_assert();
-        let hash = suite.get_hash();
+        let hash = suite.get_hash(sess.common.provider());
         let mut key_schedule = KeySchedule::new(hash);
 
         if let Some(selected_psk) = server_hello.get_psk_index() {
@@ -504,6 +566,8 @@ impl ExpectServerHello {
             }
         } else {
             debug!("Not resuming");
+            // Discard the early data key schedule.
+            sess.early_data.rejected();
             key_schedule.input_empty();
             self.handshake.resuming_session.take();
         }
@@ -520,7 +584,7 @@ impl ExpectServerHello {
             .ok_or_else(|| TLSError::PeerMisbehavedError("key exchange failed"
                                                          .to_string()))?;
 
-        save_kx_hint(sess, self.handshake.dns_name.as_ref(), their_key_share.group);
+        save_kx_hint(sess, self.handshake.server_name.as_ref(), their_key_share.group);
         key_schedule.input_secret(&shared.premaster_secret);
 
         check_aligned_handshake(sess)?;
@@ -528,8 +592,14 @@ impl ExpectServerHello {
         let handshake_hash = self.handshake.transcript.get_current_hash();
         let write_key = key_schedule.derive(SecretKind::ClientHandshakeTrafficSecret, &handshake_hash);
         let read_key = key_schedule.derive(SecretKind::ServerHandshakeTrafficSecret, &handshake_hash);
-        sess.common.set_message_encrypter(cipher::new_tls13_write(suite, &write_key));
-        sess.common.set_message_decrypter(cipher::new_tls13_read(suite, &read_key));
+        // If we're sending early data, we keep the early traffic key
+        // until the server tells us whether it accepted it.
+        if !sess.early_data.is_enabled() {
+            let enc = cipher::new_tls13_write(sess.common.provider(), suite, &write_key);
+            sess.common.set_message_encrypter(enc);
+        }
+        let dec = cipher::new_tls13_read(sess.common.provider(), suite, &read_key);
+        sess.common.set_message_decrypter(dec);
         sess.config.key_log.log("CLIENT_HANDSHAKE_TRAFFIC_SECRET",
                                 &self.handshake.randoms.client,
                                 &write_key);
@@ -586,6 +656,10 @@ impl ExpectServerHello {
 }
 
 impl State for ExpectServerHello {
+    fn name(&self) -> &'static str {
+        "ExpectServerHello"
+    }
+
     fn check_message(&self, m: &Message) -> CheckResult {
         check_handshake_message(m, &[HandshakeType::ServerHello])
     }
@@ -680,7 +754,7 @@ impl State for ExpectServerHello {
         }
 
         // Start our handshake hash, and input the server-hello.
-        self.handshake.transcript.start_hash(sess.common.get_suite_assert().get_hash());
+        self.handshake.transcript.start_hash(sess.common.get_suite_hash());
         self.handshake.transcript.add_message(&m);
 
         // For TLS1.3, start message encryption using
@@ -749,8 +823,9 @@ impl State for ExpectServerHello {
                     return Err(TLSError::PeerMisbehavedError(error_msg));
                 }
 
+                let hashalg = scs.unwrap().get_hash(sess.common.provider());
                 let secrets = SessionSecrets::new_resume(&self.handshake.randoms,
-                                                         scs.unwrap().get_hash(),
+                                                         hashalg,
                                                          &resuming.master_secret.0);
                 sess.config.key_log.log("CLIENT_RANDOM",
                                         &secrets.randoms.client,
@@ -848,8 +923,14 @@ impl ExpectServerHelloOrHelloRetryRequest {
         // HRR selects the ciphersuite.
         sess.common.set_suite(cs);
 
+        // Any early data we sent is now lost: early data
+        // cannot be sent in a ClientHello that follows a HRR.
+        if sess.early_data.is_enabled() {
+            sess.early_data.rejected();
+        }
+
         // This is the draft19 change where the transcript became a tree
-        self.0.handshake.transcript.start_hash(cs.get_hash());
+        self.0.handshake.transcript.start_hash(cs.get_hash(sess.common.provider()));
         self.0.handshake.transcript.rollup_for_hrr();
         self.0.handshake.transcript.add_message(&m);
 
@@ -861,6 +942,10 @@ impl ExpectServerHelloOrHelloRetryRequest {
 }
 
 impl State for ExpectServerHelloOrHelloRetryRequest {
+    fn name(&self) -> &'static str {
+        "ExpectServerHelloOrHelloRetryRequest"
+    }
+
     fn check_message(&self, m: &Message) -> CheckResult {
         check_handshake_message(m,
                                 &[HandshakeType::ServerHello,
@@ -930,6 +1015,10 @@ impl ExpectTLS13EncryptedExtensions {
 }
 
 impl State for ExpectTLS13EncryptedExtensions {
+    fn name(&self) -> &'static str {
+        "ExpectTLS13EncryptedExtensions"
+    }
+
     fn check_message(&self, m: &Message) -> Result<(), TLSError> {
         check_handshake_message(m, &[HandshakeType::EncryptedExtensions])
     }
@@ -948,6 +1037,35 @@ impl State for ExpectTLS13EncryptedExtensions {
         }
 
         if self.handshake.resuming_session.is_some() {
+            let was_early_traffic = sess.early_data.is_enabled();
+            if was_early_traffic {
+                if exts.early_data_extension_offered() {
+                    sess.early_data.accepted();
+                } else {
+                    sess.early_data.rejected();
+                }
+            }
+
+            if sess.early_data.is_accepted() {
+                let resuming = self.handshake.resuming_session.as_ref().unwrap();
+                if resuming.cipher_suite != sess.common.get_suite_assert().suite {
+                    return Err(illegal_param(sess, "server accepted early data with different suite"));
+                }
+
+                let resuming_alpn = resuming.alpn.as_ref().map(|p| p.0.as_slice());
+                let alpn = sess.alpn_protocol.as_ref().map(|p| p.as_bytes());
+                if resuming_alpn != alpn {
+                    return Err(illegal_param(sess, "server accepted early data with different ALPN"));
+                }
+            } else if was_early_traffic {
+                // Install the handshake keys we held back while sending
+                // early data.
+                let suite = sess.common.get_suite_assert();
+                let write_key = sess.common.get_key_schedule().current_client_traffic_secret.clone();
+                let enc = cipher::new_tls13_write(sess.common.provider(), suite, &write_key);
+                sess.common.set_message_encrypter(enc);
+            }
+
             let certv = verify::ServerCertVerified::assertion();
             let sigv =  verify::HandshakeSignatureValid::assertion();
             Ok(self.into_expect_tls13_finished_resume(certv, sigv))
@@ -980,6 +1098,10 @@ impl ExpectTLS13Certificate {
 }
 
 impl State for ExpectTLS13Certificate {
+    fn name(&self) -> &'static str {
+        "ExpectTLS13Certificate"
+    }
+
     fn check_message(&self, m: &Message) -> Result<(), TLSError> {
         check_handshake_message(m, &[HandshakeType::Certificate])
     }
@@ -1048,6 +1170,10 @@ impl ExpectTLS12Certificate {
 }
 
 impl State for ExpectTLS12Certificate {
+    fn name(&self) -> &'static str {
+        "ExpectTLS12Certificate"
+    }
+
     fn check_message(&self, m: &Message) -> Result<(), TLSError> {
         check_handshake_message(m, &[HandshakeType::Certificate])
     }
@@ -1083,6 +1209,10 @@ impl ExpectTLS12CertificateStatus {
 }
 
 impl State for ExpectTLS12CertificateStatus {
+    fn name(&self) -> &'static str {
+        "ExpectTLS12CertificateStatus"
+    }
+
     fn check_message(&self, m: &Message) -> Result<(), TLSError> {
         check_handshake_message(m, &[HandshakeType::CertificateStatus])
     }
@@ -1122,6 +1252,10 @@ impl ExpectTLS12CertificateStatusOrServerKX {
 }
 
 impl State for ExpectTLS12CertificateStatusOrServerKX {
+    fn name(&self) -> &'static str {
+        "ExpectTLS12CertificateStatusOrServerKX"
+    }
+
     fn check_message(&self, m: &Message) -> Result<(), TLSError> {
         check_handshake_message(m,
                                 &[HandshakeType::ServerKeyExchange,
@@ -1160,6 +1294,10 @@ impl ExpectTLS13CertificateOrCertReq {
 }
 
 impl State for ExpectTLS13CertificateOrCertReq {
+    fn name(&self) -> &'static str {
+        "ExpectTLS13CertificateOrCertReq"
+    }
+
     fn check_message(&self, m: &Message) -> Result<(), TLSError> {
         check_handshake_message(m,
                                 &[HandshakeType::Certificate,
@@ -1193,6 +1331,10 @@ impl ExpectTLS12ServerKX {
 }
 
 impl State for ExpectTLS12ServerKX {
+    fn name(&self) -> &'static str {
+        "ExpectTLS12ServerKX"
+    }
+
     fn check_message(&self, m: &Message) -> Result<(), TLSError> {
         check_handshake_message(m, &[HandshakeType::ServerKeyExchange])
     }
@@ -1247,6 +1389,9 @@ fn send_cert_error_alert(sess: &mut ClientSessionImpl, err: TLSError) -> TLSErro
         TLSError::WebPKIError(webpki::Error::BadDER) => {
             sess.common.send_fatal_alert(AlertDescription::DecodeError);
         }
+        TLSError::WebPKIError(webpki::Error::CertRevoked) => {
+            sess.common.send_fatal_alert(AlertDescription::CertificateRevoked);
+        }
         TLSError::PeerMisbehavedError(_) => {
             sess.common.send_fatal_alert(AlertDescription::IllegalParameter);
         }
@@ -1259,6 +1404,10 @@ fn send_cert_error_alert(sess: &mut ClientSessionImpl, err: TLSError) -> TLSErro
 }
 
 impl State for ExpectTLS13CertificateVerify {
+    fn name(&self) -> &'static str {
+        "ExpectTLS13CertificateVerify"
+    }
+
     fn check_message(&self, m: &Message) -> Result<(), TLSError> {
         check_handshake_message(m, &[HandshakeType::CertificateVerify])
     }
@@ -1277,7 +1426,7 @@ impl State for ExpectTLS13CertificateVerify {
             .get_verifier()
             .verify_server_cert(&sess.config.root_store,
                                 &self.server_cert.cert_chain,
-                                self.handshake.dns_name.as_ref(),
+                                self.handshake.server_name.as_ref(),
                                 &self.server_cert.ocsp_response)
             .map_err(|err| send_cert_error_alert(sess, err))?;
 
@@ -1290,13 +1439,15 @@ impl State for ExpectTLS13CertificateVerify {
             .map_err(|err| send_cert_error_alert(sess, err))?;
 
         // 3. Verify any included SCTs.
-        match (self.server_cert.scts.as_ref(), sess.config.ct_logs) {
-            (Some(scts), Some(logs)) => {
-                verify::verify_scts(&self.server_cert.cert_chain[0],
-                                    scts,
-                                    logs)?;
-            }
-            (_, _) => {}
+        if let Some(logs) = sess.config.ct_logs {
+            verify::verify_scts(&sess.config.root_store,
+                                &self.server_cert.cert_chain,
+                                self.server_cert.scts.as_ref(),
+                                &self.server_cert.ocsp_response,
+                                logs,
+                                &sess.config.ct_policy,
+                                sess.config.ct_enforcement)
+                .map_err(|err| send_cert_error_alert(sess, err))?;
         }
 
         sess.server_cert_chain = self.server_cert.take_chain();
@@ -1430,6 +1581,10 @@ impl ExpectTLS12CertificateRequest {
 }
 
 impl State for ExpectTLS12CertificateRequest {
+    fn name(&self) -> &'static str {
+        "ExpectTLS12CertificateRequest"
+    }
+
     fn check_message(&self, m: &Message) -> Result<(), TLSError> {
         check_handshake_message(m, &[HandshakeType::CertificateRequest])
     }
@@ -1489,6 +1644,10 @@ impl ExpectTLS13CertificateRequest {
 }
 
 impl State for ExpectTLS13CertificateRequest {
+    fn name(&self) -> &'static str {
+        "ExpectTLS13CertificateRequest"
+    }
+
     fn check_message(&self, m: &Message) -> Result<(), TLSError> {
         check_handshake_message(m, &[HandshakeType::CertificateRequest])
     }
@@ -1575,6 +1734,10 @@ impl ExpectTLS12ServerDoneOrCertReq {
 }
 
 impl State for ExpectTLS12ServerDoneOrCertReq {
+    fn name(&self) -> &'static str {
+        "ExpectTLS12ServerDoneOrCertReq"
+    }
+
     fn check_message(&self, m: &Message) -> Result<(), TLSError> {
         check_handshake_message(m,
                                 &[HandshakeType::CertificateRequest,
@@ -1626,6 +1789,10 @@ impl ExpectTLS12ServerDone {
 }
 
 impl State for ExpectTLS12ServerDone {
+    fn name(&self) -> &'static str {
+        "ExpectTLS12ServerDone"
+    }
+
     fn check_message(&self, m: &Message) -> Result<(), TLSError> {
         check_handshake_message(m, &[HandshakeType::ServerHelloDone])
     }
@@ -1635,7 +1802,7 @@ impl State for ExpectTLS12ServerDone {
         st.handshake.transcript.add_message(&m);
 
         debug!("Server cert is {:?}", st.server_cert.cert_chain);
-        debug!("Server DNS name is {:?}", st.handshake.dns_name);
+        debug!("Server name is {:?}", st.handshake.server_name);
 
         // 1. Verify the cert chain.
         // 2. Verify any SCTs provided with the certificate.
@@ -1658,18 +1825,20 @@ impl State for ExpectTLS12ServerDone {
             .get_verifier()
             .verify_server_cert(&sess.config.root_store,
                                 &st.server_cert.cert_chain,
-                                st.handshake.dns_name.as_ref(),
+                                st.handshake.server_name.as_ref(),
                                 &st.server_cert.ocsp_response)
             .map_err(|err| send_cert_error_alert(sess, err))?;
 
         // 2. Verify any included SCTs.
-        match (st.server_cert.scts.as_ref(), sess.config.ct_logs) {
-            (Some(scts), Some(logs)) => {
-                verify::verify_scts(&st.server_cert.cert_chain[0],
-                                    scts,
-                                    logs)?;
-            }
-            (_, _) => {}
+        if let Some(logs) = sess.config.ct_logs {
+            verify::verify_scts(&sess.config.root_store,
+                                &st.server_cert.cert_chain,
+                                st.server_cert.scts.as_ref(),
+                                &st.server_cert.ocsp_response,
+                                logs,
+                                &sess.config.ct_policy,
+                                sess.config.ct_enforcement)
+                .map_err(|err| send_cert_error_alert(sess, err))?;
         }
 
         // 3.
@@ -1684,7 +1853,7 @@ impl State for ExpectTLS12ServerDone {
             // Check the signature is compatible with the ciphersuite.
             let sig = &st.server_kx.kx_sig;
             let scs = sess.common.get_suite_assert();
-            if scs.sign != sig.scheme.sign() {
+            if !scs.usable_for_sigalg(sig.scheme.sign()) {
                 let error_message =
                     format!("peer signed kx with wrong algorithm (got {:?} expect {:?})",
                                       sig.scheme.sign(), scs.sign);
@@ -1707,7 +1876,7 @@ impl State for ExpectTLS12ServerDone {
 
         // 5a.
         let kxd = sess.common.get_suite_assert()
-            .do_client_kx(&st.server_kx.kx_params)
+            .do_client_kx(sess.common.provider(), &st.server_kx.kx_params)
             .ok_or_else(|| TLSError::PeerMisbehavedError("key exchange failed".to_string()))?;
 
         // 5b.
@@ -1726,7 +1895,7 @@ impl State for ExpectTLS12ServerDone {
         emit_ccs(sess);
 
         // 5e. Now commit secrets.
-        let hashalg = sess.common.get_suite_assert().get_hash();
+        let hashalg = sess.common.get_suite_hash();
         let secrets = if st.handshake.using_ems {
             SessionSecrets::new_ems(&st.handshake.randoms,
                                     &handshake_hash,
@@ -1775,6 +1944,10 @@ impl ExpectTLS12CCS {
 }
 
 impl State for ExpectTLS12CCS {
+    fn name(&self) -> &'static str {
+        "ExpectTLS12CCS"
+    }
+
     fn check_message(&self, m: &Message) -> Result<(), TLSError> {
         check_message(m, &[ContentType::ChangeCipherSpec], &[])
     }
@@ -1817,6 +1990,10 @@ impl ExpectTLS12NewTicket {
 }
 
 impl State for ExpectTLS12NewTicket {
+    fn name(&self) -> &'static str {
+        "ExpectTLS12NewTicket"
+    }
+
     fn check_message(&self, m: &Message) -> Result<(), TLSError> {
         check_handshake_message(m, &[HandshakeType::NewSessionTicket])
     }
@@ -1846,7 +2023,7 @@ fn save_session(handshake: &mut HandshakeDetails,
         return;
     }
 
-    let key = persist::ClientSessionKey::session_for_dns_name(handshake.dns_name.as_ref());
+    let key = persist::ClientSessionKey::session_for_server_name(handshake.server_name.as_ref());
 
     let scs = sess.common.get_suite_assert();
     let master_secret = sess.common.secrets.as_ref().unwrap().get_master_secret();
@@ -1862,6 +2039,9 @@ fn save_session(handshake: &mut HandshakeDetails,
     if handshake.using_ems {
         value.set_extended_ms_used();
     }
+    if sess.config.enforces_ct() {
+        value.set_ct_enforced();
+    }
 
     let worked = sess.config.session_persistence.put(key.get_encoding(),
                                                      value.get_encoding());
@@ -1956,6 +2136,21 @@ fn emit_finished_tls13(handshake: &mut HandshakeDetails,
     sess.common.send_msg(m, true);
 }
 
+fn emit_end_of_early_data_tls13(handshake: &mut HandshakeDetails,
+                                sess: &mut ClientSessionImpl) {
+    let m = Message {
+        typ: ContentType::Handshake,
+        version: ProtocolVersion::TLSv1_3,
+        payload: MessagePayload::Handshake(HandshakeMessagePayload {
+            typ: HandshakeType::EndOfEarlyData,
+            payload: HandshakePayload::EndOfEarlyData,
+        }),
+    };
+
+    handshake.transcript.add_message(&m);
+    sess.common.send_msg(m, true);
+}
+
 struct ExpectTLS13Finished {
     handshake: HandshakeDetails,
     client_auth: Option<ClientAuthDetails>,
@@ -1976,6 +2171,10 @@ impl ExpectTLS13Finished {
 }
 
 impl State for ExpectTLS13Finished {
+    fn name(&self) -> &'static str {
+        "ExpectTLS13Finished"
+    }
+
     fn check_message(&self, m: &Message) -> CheckResult {
         check_handshake_message(m, &[HandshakeType::Finished])
     }
@@ -2010,7 +2209,8 @@ impl State for ExpectTLS13Finished {
                                 &st.handshake.randoms.client,
                                 &read_key);
         let suite = sess.common.get_suite_assert();
-        sess.common.set_message_decrypter(cipher::new_tls13_read(suite, &read_key));
+        let dec = cipher::new_tls13_read(sess.common.provider(), suite, &read_key);
+        sess.common.set_message_decrypter(dec);
         sess.common
             .get_mut_key_schedule()
             .current_server_traffic_secret = read_key;
@@ -2023,6 +2223,17 @@ impl State for ExpectTLS13Finished {
             .get_mut_key_schedule()
             .current_exporter_secret = exporter_secret;
 
+        /* The server accepted our early data: close it with EndOfEarlyData,
+         * which is sent under the early traffic key. */
+        if sess.early_data.is_accepted() {
+            emit_end_of_early_data_tls13(&mut st.handshake, sess);
+            sess.early_data.finished();
+
+            let write_key = sess.common.get_key_schedule().current_client_traffic_secret.clone();
+            let enc = cipher::new_tls13_write(sess.common.provider(), suite, &write_key);
+            sess.common.set_message_encrypter(enc);
+        }
+
         /* Send our authentication/finished messages.  These are still encrypted
          * with our handshake keys. */
         if st.client_auth.is_some() {
@@ -2045,7 +2256,8 @@ impl State for ExpectTLS13Finished {
         sess.config.key_log.log("CLIENT_TRAFFIC_SECRET_0",
                                 &st.handshake.randoms.client,
                                 &write_key);
-        sess.common.set_message_encrypter(cipher::new_tls13_write(suite, &write_key));
+        let enc = cipher::new_tls13_write(sess.common.provider(), suite, &write_key);
+        sess.common.set_message_encrypter(enc);
         sess.common
             .get_mut_key_schedule()
             .current_client_traffic_secret = write_key;
@@ -2076,6 +2288,10 @@ impl ExpectTLS12Finished {
 }
 
 impl State for ExpectTLS12Finished {
+    fn name(&self) -> &'static str {
+        "ExpectTLS12Finished"
+    }
+
     fn check_message(&self, m: &Message) -> Result<(), TLSError> {
         check_handshake_message(m, &[HandshakeType::Finished])
     }
@@ -2126,6 +2342,10 @@ struct ExpectTLS12Traffic {
 }
 
 impl State for ExpectTLS12Traffic {
+    fn name(&self) -> &'static str {
+        "ExpectTLS12Traffic"
+    }
+
     fn check_message(&self, m: &Message) -> Result<(), TLSError> {
         check_message(m, &[ContentType::ApplicationData], &[])
     }
@@ -2166,7 +2386,19 @@ impl ExpectTLS13Traffic {
                         nst.lifetime,
                         nst.age_add);
 
-        let key = persist::ClientSessionKey::session_for_dns_name(self.handshake.dns_name.as_ref());
+        if let Some(sz) = nst.get_max_early_data_size() {
+            value.set_max_early_data_size(sz);
+        }
+
+        // The server must accept early data only for the ALPN
+        // protocol negotiated in this session.
+        value.set_alpn(sess.alpn_protocol.as_ref().map(|s| s.as_str()));
+
+        if sess.config.enforces_ct() {
+            value.set_ct_enforced();
+        }
+
+        let key = persist::ClientSessionKey::session_for_server_name(self.handshake.server_name.as_ref());
 
         let worked = sess.config.session_persistence.put(key.get_encoding(),
                                                          value.get_encoding());
@@ -2186,6 +2418,10 @@ impl ExpectTLS13Traffic {
 }
 
 impl State for ExpectTLS13Traffic {
+    fn name(&self) -> &'static str {
+        "ExpectTLS13Traffic"
+    }
+
     fn check_message(&self, m: &Message) -> Result<(), TLSError> {
         check_message(m,
                       &[ContentType::ApplicationData, ContentType::Handshake],
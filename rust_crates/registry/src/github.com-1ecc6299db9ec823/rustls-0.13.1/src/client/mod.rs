@@ -2,7 +2,9 @@ use msgs::enums::CipherSuite;
 use msgs::enums::{AlertDescription, HandshakeType};
 use session::{Session, SessionCommon};
 use keylog::{KeyLog, NoKeyLog};
+use observer::{ObservesSession, NoObserver, Direction};
 use suites::{SupportedCipherSuite, ALL_CIPHERSUITES};
+use crypto::{CryptoProvider, RingProvider};
 use msgs::handshake::CertificatePayload;
 use msgs::enums::SignatureScheme;
 use msgs::enums::{ContentType, ProtocolVersion};
@@ -18,6 +20,7 @@ use vecbuf::WriteV;
 use std::sync::Arc;
 use std::io;
 use std::fmt;
+use std::cmp;
 
 use sct;
 use webpki;
@@ -112,6 +115,23 @@ pub struct ClientConfig {
     /// checking is disabled.
     pub ct_logs: Option<&'static [&'static sct::Log<'static>]>,
 
+    /// The certificate transparency policy that server certificates'
+    /// SCTs must meet, when `ct_logs` is set.  SCTs are taken from the
+    /// certificate itself, the TLS extension and any stapled OCSP
+    /// response.
+    ///
+    /// The default policy has no disqualified logs.
+    pub ct_policy: sct::Policy,
+
+    /// What to do if the `ct_policy` isn't met.  The default is
+    /// `CTEnforcement::SoftFail`.
+    ///
+    /// Resumed sessions don't present certificates, so with
+    /// `CTEnforcement::HardFail` we only resume sessions that were
+    /// themselves established with `HardFail`.  Changes to `ct_logs`
+    /// or `ct_policy` alone don't invalidate stored sessions.
+    pub ct_enforcement: verify::CTEnforcement,
+
     /// Whether to send the Server Name Indication (SNI) extension
     /// during the client handshake.
     ///
@@ -124,6 +144,39 @@ pub struct ClientConfig {
     /// How to output key material for debugging.  The default
     /// does nothing.
     pub key_log: Arc<KeyLog>,
+
+    /// Where to report what sessions do, for debugging.  The
+    /// default does nothing.
+    pub observer: Arc<ObservesSession>,
+
+    /// How many records to send under one TLS1.3 traffic key.
+    /// Once this many have been sent, we replace the key by sending
+    /// a KeyUpdate.  None means no limit.
+    ///
+    /// The default is 2^24 records, the limit RFC8446 gives for
+    /// AES-GCM.
+    pub key_update_after_records: Option<u64>,
+
+    /// Like `key_update_after_records`, but counting bytes of
+    /// plaintext.  The default is None.
+    pub key_update_after_bytes: Option<u64>,
+
+    /// Whether to send data on the first flight ("early data") in
+    /// TLS1.3 handshakes, when resuming a session whose ticket
+    /// allows it.  Early data is written with `ClientSession::early_data`.
+    ///
+    /// Early data is not protected against replay, so only enable
+    /// this for application protocols where that is acceptable.
+    ///
+    /// The default is false.
+    pub enable_early_data: bool,
+
+    /// Where we get our cryptography from.  Ciphersuites in
+    /// `ciphersuites` that this provider does not support are
+    /// not offered.
+    ///
+    /// The default is `RingProvider`.
+    pub crypto_provider: Arc<CryptoProvider>,
 }
 
 impl ClientConfig {
@@ -143,9 +196,16 @@ impl ClientConfig {
             enable_tickets: true,
             versions: vec![ProtocolVersion::TLSv1_3, ProtocolVersion::TLSv1_2],
             ct_logs: None,
+            ct_policy: sct::Policy::new(),
+            ct_enforcement: verify::CTEnforcement::SoftFail,
             enable_sni: true,
             verifier: Arc::new(verify::WebPKIVerifier::new()),
             key_log: Arc::new(NoKeyLog {}),
+            observer: Arc::new(NoObserver {}),
+            key_update_after_records: Some(1 << 24),
+            key_update_after_bytes: None,
+            enable_early_data: false,
+            crypto_provider: Arc::new(RingProvider {}),
         }
     }
 
@@ -154,6 +214,11 @@ impl ClientConfig {
         self.verifier.as_ref()
     }
 
+    fn enforces_ct(&self) -> bool {
+        self.ct_logs.is_some() &&
+            self.ct_enforcement == verify::CTEnforcement::HardFail
+    }
+
     /// Set the ALPN protocol list to the given protocol names.
     /// Overwrites any existing configured protocols.
     /// The first element in the `protocols` list is the most
@@ -197,6 +262,23 @@ impl ClientConfig {
         self.client_auth_cert_resolver = Arc::new(resolver);
     }
 
+    /// Sets how the server's certificate chain is checked for revocation.
+    ///
+    /// `crls` is a vector of DER-encoded certificate revocation lists.
+    /// These are checked alongside any OCSP response the server staples.
+    /// `policy` says what to do if neither tells us whether the server's
+    /// certificate is revoked.
+    ///
+    /// This replaces any verifier set using `dangerous()`.
+    pub fn set_revocation_checking(&mut self,
+                                   crls: Vec<Vec<u8>>,
+                                   policy: verify::RevocationPolicy) {
+        let mut verifier = verify::WebPKIVerifier::new();
+        verifier.crls = crls;
+        verifier.revocation_policy = policy;
+        self.verifier = Arc::new(verifier);
+    }
+
     /// Access configuration options whose use is dangerous and requires
     /// extra care.
     #[cfg(feature = "dangerous_configuration")]
@@ -228,6 +310,120 @@ pub mod danger {
     }
 }
 
+#[derive(Debug, PartialEq)]
+enum EarlyDataState {
+    Disabled,
+    Ready,
+    Accepted,
+    AcceptedFinished,
+    Rejected,
+}
+
+/// Tracks our early data: whether we offered it, how much more
+/// the server will accept, and what the server made of it.
+pub struct EarlyData {
+    state: EarlyDataState,
+    left: usize,
+}
+
+impl EarlyData {
+    fn new() -> EarlyData {
+        EarlyData {
+            left: 0,
+            state: EarlyDataState::Disabled,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        match self.state {
+            EarlyDataState::Ready | EarlyDataState::Accepted => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_accepted(&self) -> bool {
+        match self.state {
+            EarlyDataState::Accepted | EarlyDataState::AcceptedFinished => true,
+            _ => false,
+        }
+    }
+
+    pub fn enable(&mut self, max_data: usize) {
+        assert_eq!(self.state, EarlyDataState::Disabled);
+        self.state = EarlyDataState::Ready;
+        self.left = max_data;
+    }
+
+    pub fn rejected(&mut self) {
+        trace!("EarlyData rejected");
+        self.state = EarlyDataState::Rejected;
+    }
+
+    pub fn accepted(&mut self) {
+        trace!("EarlyData accepted");
+        assert_eq!(self.state, EarlyDataState::Ready);
+        self.state = EarlyDataState::Accepted;
+    }
+
+    pub fn finished(&mut self) {
+        trace!("EarlyData finished");
+        self.state = match self.state {
+            EarlyDataState::Accepted => EarlyDataState::AcceptedFinished,
+            _ => panic!("bad EarlyData state"),
+        }
+    }
+
+    /// Returns how many of `sz` bytes may be written as early data.
+    fn check_write(&self, sz: usize) -> io::Result<usize> {
+        match self.state {
+            EarlyDataState::Disabled => unreachable!(),
+            EarlyDataState::Ready | EarlyDataState::Accepted => {
+                Ok(cmp::min(self.left, sz))
+            }
+            EarlyDataState::Rejected | EarlyDataState::AcceptedFinished => {
+                Err(io::Error::from(io::ErrorKind::InvalidInput))
+            }
+        }
+    }
+
+    /// Accounts for `sz` bytes of early data actually sent.
+    fn sent(&mut self, sz: usize) {
+        debug_assert!(sz <= self.left);
+        self.left -= sz;
+    }
+
+    fn bytes_left(&self) -> usize {
+        self.left
+    }
+}
+
+/// Stub that implements io::Write and dispatches to `write_early_data`.
+pub struct WriteEarlyData<'a> {
+    sess: &'a mut ClientSessionImpl,
+}
+
+impl<'a> WriteEarlyData<'a> {
+    fn new(sess: &'a mut ClientSessionImpl) -> WriteEarlyData<'a> {
+        WriteEarlyData { sess }
+    }
+
+    /// How many bytes you may send.  Writes will become short
+    /// once this reaches zero.
+    pub fn bytes_left(&self) -> usize {
+        self.sess.early_data.bytes_left()
+    }
+}
+
+impl<'a> io::Write for WriteEarlyData<'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.sess.write_early_data(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
 pub struct ClientSessionImpl {
     pub config: Arc<ClientConfig>,
     pub alpn_protocol: Option<String>,
@@ -236,6 +432,7 @@ pub struct ClientSessionImpl {
     pub error: Option<TLSError>,
     pub state: Option<Box<hs::State + Send + Sync>>,
     pub server_cert_chain: CertificatePayload,
+    pub early_data: EarlyData,
 }
 
 impl fmt::Debug for ClientSessionImpl {
@@ -246,26 +443,36 @@ impl fmt::Debug for ClientSessionImpl {
 
 impl ClientSessionImpl {
     pub fn new(config: &Arc<ClientConfig>) -> ClientSessionImpl {
+        let mut common = SessionCommon::new(config.mtu,
+                                            true,
+                                            config.crypto_provider.clone(),
+                                            config.observer.clone());
+        common.set_key_update_limits(config.key_update_after_records,
+                                     config.key_update_after_bytes);
+
         ClientSessionImpl {
             config: config.clone(),
             alpn_protocol: None,
             quic_params: None,
-            common: SessionCommon::new(config.mtu, true),
+            common,
             error: None,
             state: None,
             server_cert_chain: Vec::new(),
+            early_data: EarlyData::new(),
         }
     }
 
-    pub fn start_handshake(&mut self, hostname: webpki::DNSName, extra_exts: Vec<ClientExtension>) {
-        self.state = Some(hs::start_handshake(self, hostname, extra_exts));
+    pub fn start_handshake(&mut self, server_name: webpki::SubjectName, extra_exts: Vec<ClientExtension>) {
+        self.state = Some(hs::start_handshake(self, server_name, extra_exts));
     }
 
     pub fn get_cipher_suites(&self) -> Vec<CipherSuite> {
         let mut ret = Vec::new();
 
         for cs in &self.config.ciphersuites {
-            ret.push(cs.suite);
+            if cs.usable_with(&*self.config.crypto_provider) {
+                ret.push(cs.suite);
+            }
         }
 
         // We don't do renegotation at all, in fact.
@@ -276,7 +483,7 @@ impl ClientSessionImpl {
 
     pub fn find_cipher_suite(&self, suite: CipherSuite) -> Option<&'static SupportedCipherSuite> {
         for scs in &self.config.ciphersuites {
-            if scs.suite == suite {
+            if scs.suite == suite && scs.usable_with(&*self.config.crypto_provider) {
                 return Some(scs);
             }
         }
@@ -307,6 +514,8 @@ impl ClientSessionImpl {
     }
 
     pub fn process_msg(&mut self, mut msg: Message) -> Result<(), TLSError> {
+        self.common.observe_record(&msg);
+
         // TLS1.3: drop CCS at any time during handshaking
         if self.common.is_tls13()
             && msg.is_content_type(ContentType::ChangeCipherSpec)
@@ -376,6 +585,8 @@ impl ClientSessionImpl {
             return self.reject_renegotiation_attempt();
         }
 
+        self.common.observe_message(Direction::Received, &msg);
+
         let state = self.state.take().unwrap();
         state
             .check_message(&msg)
@@ -383,7 +594,11 @@ impl ClientSessionImpl {
                 self.queue_unexpected_alert();
                 err
             })?;
-        self.state = Some(state.handle(self, msg)?);
+
+        let from = state.name();
+        let next = state.handle(self, msg)?;
+        self.common.observe_state_change(from, next.name());
+        self.state = Some(next);
 
         Ok(())
     }
@@ -434,6 +649,13 @@ impl ClientSessionImpl {
     pub fn get_negotiated_ciphersuite(&self) -> Option<&'static SupportedCipherSuite> {
         self.common.get_suite()
     }
+
+    pub fn write_early_data(&mut self, data: &[u8]) -> io::Result<usize> {
+        let sz = self.early_data.check_write(data.len())?;
+        let sent = self.common.send_early_plaintext(&data[..sz]);
+        self.early_data.sent(sent);
+        Ok(sent)
+    }
 }
 
 /// This represents a single TLS client session.
@@ -445,13 +667,58 @@ pub struct ClientSession {
 
 impl ClientSession {
     /// Make a new ClientSession.  `config` controls how
-    /// we behave in the TLS protocol, `hostname` is the
-    /// hostname of who we want to talk to.
-    pub fn new(config: &Arc<ClientConfig>, hostname: webpki::DNSNameRef) -> ClientSession {
+    /// we behave in the TLS protocol, `server_name` is the
+    /// name of who we want to talk to: either a `webpki::DNSNameRef`,
+    /// or a `webpki::IpAddrRef` for servers addressed by IP address.
+    pub fn new<'a, N>(config: &Arc<ClientConfig>, server_name: N) -> ClientSession
+        where N: Into<webpki::SubjectNameRef<'a>>
+    {
         let mut imp = ClientSessionImpl::new(config);
-        imp.start_handshake(hostname.into(), vec![]);
+        imp.start_handshake(server_name.into().to_owned(), vec![]);
         ClientSession { imp }
     }
+
+    /// Returns an `io::Write` implementor you can write bytes to
+    /// to send TLS1.3 early data (a.k.a. "0-RTT data") to the server.
+    ///
+    /// This returns None in many circumstances when the capability to
+    /// send early data is not available, including but not limited to:
+    ///
+    /// - The server hasn't been talked to previously.
+    /// - The server does not support resumption.
+    /// - The server does not support early data.
+    /// - The resumption data for the server has expired.
+    /// - `ClientConfig::enable_early_data` is false.
+    ///
+    /// The server may reject early data: check `is_early_data_accepted`
+    /// once the handshake completes.  Rejected early data is discarded,
+    /// and should be sent again as normal application data.
+    pub fn early_data(&mut self) -> Option<WriteEarlyData> {
+        if self.imp.early_data.is_enabled() {
+            Some(WriteEarlyData::new(&mut self.imp))
+        } else {
+            None
+        }
+    }
+
+    /// Returns True if the server signalled it will process early data.
+    ///
+    /// If you sent early data and this returns false at the end of the
+    /// handshake then the server will not process the data.  This
+    /// is not an error, but you may wish to resend the data.
+    pub fn is_early_data_accepted(&self) -> bool {
+        self.imp.early_data.is_accepted()
+    }
+
+    /// Replaces the keys protecting data in both directions, by
+    /// sending a TLS1.3 KeyUpdate which asks the peer to do the same.
+    ///
+    /// The KeyUpdate is sent by the next `write_tls` call.  This
+    /// fails if the handshake is not complete, or if TLS1.3 was
+    /// not negotiated.
+    pub fn refresh_traffic_keys(&mut self) -> Result<(), TLSError> {
+        self.imp.common.refresh_traffic_keys()
+    }
 }
 
 impl Session for ClientSession {
@@ -0,0 +1,147 @@
+use suites::BulkAlgorithm;
+
+pub use msgs::enums::{HashAlgorithm, NamedGroup};
+
+mod ring_provider;
+pub use self::ring_provider::RingProvider;
+
+/// An opaque error from a cryptographic primitive.
+///
+/// Errors are deliberately not detailed: rustls treats them all the
+/// same way.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CryptoError;
+
+/// A source of cryptographic primitives.
+///
+/// Everything rustls does that involves cryptography -- apart from
+/// signing and certificate verification, which are pluggable by
+/// other means -- goes through one of these.  This covers the
+/// AEAD algorithms that protect records, the hash functions used
+/// for the transcript and key derivation, ephemeral key exchange
+/// and the generation of random values that appear on the wire.
+///
+/// Select one with the `crypto_provider` field of `ClientConfig`
+/// or `ServerConfig`.  The default is `RingProvider`.
+///
+/// Cipher suites are only offered or accepted if the provider
+/// supports them: see `SupportedCipherSuite::usable_with`.
+pub trait CryptoProvider : Send + Sync {
+    /// Fill the whole of `bytes` with random material.
+    fn fill_random(&self, bytes: &mut [u8]) -> Result<(), CryptoError>;
+
+    /// Return the hash function `alg`, or None if it is not
+    /// supported.  This must give the same answer every time
+    /// it is called with the same `alg`.
+    fn hash(&self, alg: HashAlgorithm) -> Option<&'static Hash>;
+
+    /// Return true if we can make keys for the bulk encryption
+    /// algorithm `alg`.
+    fn supports_bulk(&self, alg: &BulkAlgorithm) -> bool;
+
+    /// Make a key for bulk encryption algorithm `alg`.  `key` is the
+    /// raw key material, of the length the cipher suite dictates.
+    fn aead_key(&self, alg: &BulkAlgorithm, key: &[u8]) -> Result<Box<AeadKey>, CryptoError>;
+
+    /// Start an ephemeral key exchange in `group`, generating
+    /// our key share.  Returns None if the group is not supported.
+    fn start_key_exchange(&self, group: NamedGroup) -> Option<Box<ActiveKeyExchange>>;
+}
+
+/// A key for an AEAD algorithm, usable in both directions.
+///
+/// All AEADs used by TLS have 96-bit nonces.
+pub trait AeadKey : Send + Sync {
+    /// The length of the authentication tag appended by
+    /// `seal_in_place`.
+    fn tag_len(&self) -> usize;
+
+    /// Encrypt `in_out[..in_out.len() - self.tag_len()]` in place,
+    /// authenticating it along with `aad`.  The tag is written over
+    /// the final `tag_len()` bytes of `in_out`.
+    fn seal_in_place(&self,
+                     nonce: &[u8; 12],
+                     aad: &[u8],
+                     in_out: &mut [u8]) -> Result<(), CryptoError>;
+
+    /// Authenticate and decrypt `in_out[in_prefix_len..]` in place,
+    /// moving the plaintext to the start of `in_out`.  Returns the
+    /// length of the plaintext.
+    fn open_in_place(&self,
+                     nonce: &[u8; 12],
+                     aad: &[u8],
+                     in_prefix_len: usize,
+                     in_out: &mut [u8]) -> Result<usize, CryptoError>;
+}
+
+/// A hash function, and the HMAC and HKDF constructions over it.
+pub trait Hash : Send + Sync {
+    /// Which hash function this is.
+    fn algorithm(&self) -> HashAlgorithm;
+
+    /// The length of this hash function's output, in bytes.
+    fn output_len(&self) -> usize;
+
+    /// Start an incremental hash computation.
+    fn start(&self) -> Box<HashContext>;
+
+    /// Hash `data` in one shot.
+    fn hash(&self, data: &[u8]) -> Vec<u8> {
+        let mut ctx = self.start();
+        ctx.update(data);
+        ctx.finish()
+    }
+
+    /// Compute HMAC over the concatenation of `data`, using `key`.
+    fn hmac_sign(&self, key: &[u8], data: &[&[u8]]) -> Vec<u8>;
+
+    /// RFC5869 HKDF-Extract, returning the pseudorandom key.
+    fn hkdf_extract(&self, salt: &[u8], secret: &[u8]) -> Vec<u8> {
+        self.hmac_sign(salt, &[secret])
+    }
+
+    /// RFC5869 HKDF-Expand, filling the whole of `out`.
+    fn hkdf_expand(&self, prk: &[u8], info: &[u8], out: &mut [u8]) {
+        assert!(out.len() <= 255 * self.output_len());
+
+        let mut t = Vec::new();
+        let mut counter = 1u8;
+        let mut offs = 0;
+
+        while offs < out.len() {
+            t = self.hmac_sign(prk, &[&t, info, &[counter]]);
+            let take = ::std::cmp::min(t.len(), out.len() - offs);
+            out[offs..offs + take].copy_from_slice(&t[..take]);
+            offs += take;
+            counter = counter.wrapping_add(1);
+        }
+    }
+}
+
+/// An incremental hash computation.
+pub trait HashContext : Send + Sync {
+    /// Add `data` to the hash.
+    fn update(&mut self, data: &[u8]);
+
+    /// Make an independent copy of this computation, in its
+    /// current state.
+    fn fork(&self) -> Box<HashContext>;
+
+    /// Finish the computation, returning the hash value.
+    fn finish(self: Box<Self>) -> Vec<u8>;
+}
+
+/// An in-progress ephemeral key exchange.  This holds our private
+/// key until the peer's key share arrives.
+pub trait ActiveKeyExchange : Send + Sync {
+    /// The group this key exchange is in.
+    fn group(&self) -> NamedGroup;
+
+    /// Our public key share, encoded as it appears on the wire.
+    fn pub_key(&self) -> &[u8];
+
+    /// Complete the key exchange with the peer's public key share
+    /// `peer`, returning the shared secret.  This consumes our
+    /// private key.
+    fn complete(self: Box<Self>, peer: &[u8]) -> Result<Vec<u8>, CryptoError>;
+}
@@ -0,0 +1,183 @@
+use ring::{aead, agreement, digest, hmac};
+use ring::rand::{SecureRandom, SystemRandom};
+use untrusted;
+
+use suites::BulkAlgorithm;
+use super::{CryptoProvider, CryptoError, AeadKey, Hash, HashContext, ActiveKeyExchange};
+use super::{HashAlgorithm, NamedGroup};
+
+/// The default `CryptoProvider`, which uses *ring* for everything.
+pub struct RingProvider {}
+
+impl CryptoProvider for RingProvider {
+    fn fill_random(&self, bytes: &mut [u8]) -> Result<(), CryptoError> {
+        SystemRandom::new()
+            .fill(bytes)
+            .map_err(|_| CryptoError)
+    }
+
+    fn hash(&self, alg: HashAlgorithm) -> Option<&'static Hash> {
+        match alg {
+            HashAlgorithm::SHA256 => Some(&SHA256),
+            HashAlgorithm::SHA384 => Some(&SHA384),
+            HashAlgorithm::SHA512 => Some(&SHA512),
+            _ => None,
+        }
+    }
+
+    fn supports_bulk(&self, _alg: &BulkAlgorithm) -> bool {
+        true
+    }
+
+    fn aead_key(&self, alg: &BulkAlgorithm, key: &[u8]) -> Result<Box<AeadKey>, CryptoError> {
+        let alg = match *alg {
+            BulkAlgorithm::AES_128_GCM => &aead::AES_128_GCM,
+            BulkAlgorithm::AES_256_GCM => &aead::AES_256_GCM,
+            BulkAlgorithm::CHACHA20_POLY1305 => &aead::CHACHA20_POLY1305,
+        };
+
+        Ok(Box::new(RingAeadKey {
+            alg,
+            sealing: aead::SealingKey::new(alg, key).map_err(|_| CryptoError)?,
+            opening: aead::OpeningKey::new(alg, key).map_err(|_| CryptoError)?,
+        }))
+    }
+
+    fn start_key_exchange(&self, group: NamedGroup) -> Option<Box<ActiveKeyExchange>> {
+        let alg = match group {
+            NamedGroup::X25519 => &agreement::X25519,
+            NamedGroup::secp256r1 => &agreement::ECDH_P256,
+            NamedGroup::secp384r1 => &agreement::ECDH_P384,
+            _ => return None,
+        };
+
+        let rng = SystemRandom::new();
+        let privkey = agreement::EphemeralPrivateKey::generate(alg, &rng).ok()?;
+
+        let mut pubkey = Vec::new();
+        pubkey.resize(privkey.public_key_len(), 0u8);
+        privkey.compute_public_key(pubkey.as_mut_slice()).ok()?;
+
+        Some(Box::new(RingKeyExchange {
+            group,
+            alg,
+            privkey,
+            pubkey,
+        }))
+    }
+}
+
+struct RingAeadKey {
+    alg: &'static aead::Algorithm,
+    sealing: aead::SealingKey,
+    opening: aead::OpeningKey,
+}
+
+impl AeadKey for RingAeadKey {
+    fn tag_len(&self) -> usize {
+        self.alg.tag_len()
+    }
+
+    fn seal_in_place(&self,
+                     nonce: &[u8; 12],
+                     aad: &[u8],
+                     in_out: &mut [u8]) -> Result<(), CryptoError> {
+        aead::seal_in_place(&self.sealing, nonce, aad, in_out, self.alg.tag_len())
+            .map(|_| ())
+            .map_err(|_| CryptoError)
+    }
+
+    fn open_in_place(&self,
+                     nonce: &[u8; 12],
+                     aad: &[u8],
+                     in_prefix_len: usize,
+                     in_out: &mut [u8]) -> Result<usize, CryptoError> {
+        aead::open_in_place(&self.opening, nonce, aad, in_prefix_len, in_out)
+            .map(|plain| plain.len())
+            .map_err(|_| CryptoError)
+    }
+}
+
+struct RingHash {
+    alg: HashAlgorithm,
+    digest: &'static digest::Algorithm,
+}
+
+static SHA256: RingHash = RingHash {
+    alg: HashAlgorithm::SHA256,
+    digest: &digest::SHA256,
+};
+
+static SHA384: RingHash = RingHash {
+    alg: HashAlgorithm::SHA384,
+    digest: &digest::SHA384,
+};
+
+static SHA512: RingHash = RingHash {
+    alg: HashAlgorithm::SHA512,
+    digest: &digest::SHA512,
+};
+
+impl Hash for RingHash {
+    fn algorithm(&self) -> HashAlgorithm {
+        self.alg
+    }
+
+    fn output_len(&self) -> usize {
+        self.digest.output_len
+    }
+
+    fn start(&self) -> Box<HashContext> {
+        Box::new(RingHashContext(digest::Context::new(self.digest)))
+    }
+
+    fn hmac_sign(&self, key: &[u8], data: &[&[u8]]) -> Vec<u8> {
+        let key = hmac::SigningKey::new(self.digest, key);
+        let mut ctx = hmac::SigningContext::with_key(&key);
+        for d in data {
+            ctx.update(d);
+        }
+        ctx.sign().as_ref().to_vec()
+    }
+}
+
+struct RingHashContext(digest::Context);
+
+impl HashContext for RingHashContext {
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn fork(&self) -> Box<HashContext> {
+        Box::new(RingHashContext(self.0.clone()))
+    }
+
+    fn finish(self: Box<Self>) -> Vec<u8> {
+        self.0.finish().as_ref().to_vec()
+    }
+}
+
+struct RingKeyExchange {
+    group: NamedGroup,
+    alg: &'static agreement::Algorithm,
+    privkey: agreement::EphemeralPrivateKey,
+    pubkey: Vec<u8>,
+}
+
+impl ActiveKeyExchange for RingKeyExchange {
+    fn group(&self) -> NamedGroup {
+        self.group
+    }
+
+    fn pub_key(&self) -> &[u8] {
+        &self.pubkey
+    }
+
+    fn complete(self: Box<Self>, peer: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        agreement::agree_ephemeral(self.privkey,
+                                   self.alg,
+                                   untrusted::Input::from(peer),
+                                   CryptoError,
+                                   |secret| Ok(secret.to_vec()))
+    }
+}
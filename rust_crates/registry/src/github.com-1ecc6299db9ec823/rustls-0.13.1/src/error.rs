@@ -58,6 +58,10 @@ pub enum TLSError {
     /// The presented SCT(s) were invalid.
     InvalidSCT(sct::Error),
 
+    /// The presented SCT(s) didn't meet our certificate transparency
+    /// policy.
+    CTPolicyNotMet(sct::PolicyError),
+
     /// A catch-all error for unlikely errors.
     General(String),
 
@@ -132,6 +136,7 @@ impl Error for TLSError {
             TLSError::AlertReceived(_) => "received fatal alert",
             TLSError::WebPKIError(_) => "invalid certificate",
             TLSError::InvalidSCT(_) => "invalid certificate timestamp",
+            TLSError::CTPolicyNotMet(_) => "certificate transparency policy not met",
             TLSError::General(_) => "unexpected error", // (please file a bug),
             TLSError::FailedToGetCurrentTime => "failed to get current time",
             TLSError::InvalidDNSName(_) => "invalid DNS name",
@@ -168,6 +173,7 @@ mod tests {
                        TLSError::AlertReceived(AlertDescription::ExportRestriction),
                        TLSError::WebPKIError(webpki::Error::ExtensionValueInvalid),
                        TLSError::InvalidSCT(sct::Error::MalformedSCT),
+                       TLSError::CTPolicyNotMet(sct::PolicyError::SingleOperator),
                        TLSError::General("undocumented error".to_string()),
                        TLSError::FailedToGetCurrentTime,
                        TLSError::InvalidDNSName("dns something".to_string()),
@@ -1,4 +1,4 @@
-use ring::digest;
+use crypto::{Hash, HashContext};
 use std::mem;
 use msgs::codec::Codec;
 use msgs::message::{Message, MessagePayload};
@@ -13,10 +13,10 @@ use msgs::handshake::HandshakeMessagePayload;
 /// This is disabled in cases where client auth is not possible.
 pub struct HandshakeHash {
     /// None before we know what hash function we're using
-    alg: Option<&'static digest::Algorithm>,
+    alg: Option<&'static Hash>,
 
     /// None before we know what hash function we're using
-    ctx: Option<digest::Context>,
+    ctx: Option<Box<HashContext>>,
 
     /// true if we need to keep all messages
     client_auth_enabled: bool,
@@ -50,11 +50,11 @@ impl HandshakeHash {
     }
 
     /// We now know what hash function the verify_data will use.
-    pub fn start_hash(&mut self, alg: &'static digest::Algorithm) -> bool {
+    pub fn start_hash(&mut self, alg: &'static Hash) -> bool {
         match self.alg {
             None => {},
             Some(started) => {
-                if started != alg {
+                if started.algorithm() != alg.algorithm() {
                     // hash type is changing
                     warn!("altered hash to HandshakeHash::start_hash");
                     return false;
@@ -66,7 +66,7 @@ impl HandshakeHash {
         self.alg = Some(alg);
         debug_assert!(self.ctx.is_none());
 
-        let mut ctx = digest::Context::new(alg);
+        let mut ctx = alg.start();
         ctx.update(&self.buffer);
         self.ctx = Some(ctx);
 
@@ -104,20 +104,17 @@ impl HandshakeHash {
 
     /// Get the hash value if we were to hash `extra` too,
     /// using hash function `hash`.
-    pub fn get_hash_given(&self, hash: &'static digest::Algorithm, extra: &[u8]) -> Vec<u8> {
+    pub fn get_hash_given(&self, hash: &'static Hash, extra: &[u8]) -> Vec<u8> {
         let mut ctx = if self.ctx.is_none() {
-            let mut ctx = digest::Context::new(hash);
+            let mut ctx = hash.start();
             ctx.update(&self.buffer);
             ctx
         } else {
-            self.ctx.as_ref().unwrap().clone()
+            self.ctx.as_ref().unwrap().fork()
         };
 
         ctx.update(extra);
-        let hash = ctx.finish();
-        let mut ret = Vec::new();
-        ret.extend_from_slice(hash.as_ref());
-        ret
+        ctx.finish()
     }
 
     /// Take the current hash value, and encapsulate it in a
@@ -125,18 +122,15 @@ impl HandshakeHash {
     /// again, with that message at the front.
     pub fn rollup_for_hrr(&mut self) {
         let old_hash = self.ctx.take().unwrap().finish();
-        let old_handshake_hash_msg = HandshakeMessagePayload::build_handshake_hash(old_hash.as_ref());
+        let old_handshake_hash_msg = HandshakeMessagePayload::build_handshake_hash(&old_hash);
 
-        self.ctx = Some(digest::Context::new(self.alg.unwrap()));
+        self.ctx = Some(self.alg.unwrap().start());
         self.update_raw(&old_handshake_hash_msg.get_encoding());
     }
 
     /// Get the current hash value.
     pub fn get_current_hash(&self) -> Vec<u8> {
-        let hash = self.ctx.as_ref().unwrap().clone().finish();
-        let mut ret = Vec::new();
-        ret.extend_from_slice(hash.as_ref());
-        ret
+        self.ctx.as_ref().unwrap().fork().finish()
     }
 
     /// Takes this object's buffer containing all handshake messages
@@ -151,14 +145,14 @@ impl HandshakeHash {
 #[cfg(test)]
 mod test {
     use super::HandshakeHash;
-    use ring::digest;
+    use crypto::{CryptoProvider, RingProvider, HashAlgorithm};
 
     #[test]
     fn hashes_correctly() {
         let mut hh = HandshakeHash::new();
         hh.update_raw(b"hello");
         assert_eq!(hh.buffer.len(), 5);
-        hh.start_hash(&digest::SHA256);
+        hh.start_hash(RingProvider {}.hash(HashAlgorithm::SHA256).unwrap());
         assert_eq!(hh.buffer.len(), 0);
         hh.update_raw(b"world");
         let h = hh.get_current_hash();
@@ -174,7 +168,7 @@ mod test {
         hh.set_client_auth_enabled();
         hh.update_raw(b"hello");
         assert_eq!(hh.buffer.len(), 5);
-        hh.start_hash(&digest::SHA256);
+        hh.start_hash(RingProvider {}.hash(HashAlgorithm::SHA256).unwrap());
         assert_eq!(hh.buffer.len(), 5);
         hh.update_raw(b"world");
         assert_eq!(hh.buffer.len(), 10);
@@ -193,7 +187,7 @@ mod test {
         hh.set_client_auth_enabled();
         hh.update_raw(b"hello");
         assert_eq!(hh.buffer.len(), 5);
-        hh.start_hash(&digest::SHA256);
+        hh.start_hash(RingProvider {}.hash(HashAlgorithm::SHA256).unwrap());
         assert_eq!(hh.buffer.len(), 5);
         hh.abandon_client_auth();
         assert_eq!(hh.buffer.len(), 0);
@@ -2,11 +2,12 @@ use std::fmt;
 
 /// This type contains a private key by value.
 ///
-/// The private key must be DER-encoded ASN.1 in either
-/// PKCS#8 or PKCS#1 format.
+/// The private key must be DER-encoded ASN.1 in PKCS#8 format, in
+/// PKCS#1 format for RSA keys, or in SEC1 format for EC keys.
 ///
-/// `rustls::pemfile::pkcs8_private_keys` or `rustls::pemfile::rsa_private_keys`
-/// could be used to extract private keys from a PEM file in these formats.
+/// `rustls::pemfile::pkcs8_private_keys`, `rustls::pemfile::rsa_private_keys`
+/// or `rustls::pemfile::ec_private_keys` could be used to extract private keys
+/// from a PEM file in these formats.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct PrivateKey(pub Vec<u8>);
 
@@ -6,30 +6,39 @@
 //! ## Current features
 //!
 //! * TLS1.2 and TLS1.3 (draft 23) only.
-//! * ECDSA or RSA server authentication by clients.
-//! * ECDSA or RSA server authentication by servers.
+//! * ECDSA, Ed25519 or RSA server authentication by clients.
+//! * ECDSA, Ed25519 or RSA server authentication by servers.
 //! * Forward secrecy using ECDHE; with curve25519, nistp256 or nistp384 curves.
 //! * AES128-GCM and AES256-GCM bulk encryption, with safe nonces.
 //! * Chacha20Poly1305 bulk encryption.
+//! * Pluggable cryptography, with *ring* as the default provider.
 //! * ALPN support.
 //! * SNI support.
+//! * Servers can pause after the ClientHello, to choose a certificate asynchronously.
 //! * Tunable MTU to make TLS messages match size of underlying transport.
 //! * Optional use of vectored IO to minimise system calls.
 //! * TLS1.2 session resumption.
 //! * TLS1.2 resumption via tickets (RFC5077).
 //! * TLS1.3 resumption via tickets or session storage.
+//! * Client session storage in memory or on disk.
+//! * Ticket keys shared between servers, with scheduled rotation.
+//! * TLS1.3 early data ("0-RTT") by clients and servers.
 //! * Client authentication by clients.
 //! * Client authentication by servers.
 //! * Extended master secret support (RFC7627).
 //! * Exporters (RFC5705).
+//! * Tracing of handshake messages, alerts and records for debugging.
 //! * OCSP stapling by servers.
 //! * SCT stapling by servers.
-//! * SCT verification by clients.
+//! * SCT verification by clients, with a certificate transparency policy covering
+//!   SCTs embedded in certificates, sent in a TLS extension or stapled in OCSP responses.
+//! * Revocation checking by clients, using stapled OCSP responses and CRLs.
+//! * Connecting to servers by IP address, verified against iPAddress subjectAltNames.
+//! * Loading root certificates from the system store (`SSL_CERT_FILE`, `SSL_CERT_DIR`, `/etc/ssl/certs`).
 //!
 //! ## Possible future features
 //!
 //! * PSK support.
-//! * OCSP verification by clients.
 //! * Certificate pinning.
 //!
 //! ## Non-features
@@ -251,6 +260,8 @@ mod anchors;
 mod verify;
 #[cfg(test)]
 mod verifybench;
+#[cfg(test)]
+mod verify_test;
 mod handshake;
 mod suites;
 mod ticketer;
@@ -259,13 +270,15 @@ mod client;
 mod key;
 mod bs_debug;
 mod keylog;
+mod observer;
 
 /// Internal classes which may be useful outside the library.
 /// The contents of this section DO NOT form part of the stable interface.
 pub mod internal {
     /// Functions for parsing PEM files containing certificates/keys.
     pub mod pemfile {
-        pub use pemfile::{certs, rsa_private_keys, pkcs8_private_keys};
+        pub use pemfile::{certs, rsa_private_keys, pkcs8_private_keys, ec_private_keys};
+        pub use pemfile::{items, Item, PemItems, PemError};
     }
 
     /// Low-level TLS message parsing and encoding functions.
@@ -277,32 +290,45 @@ pub mod internal {
 // The public interface is:
 pub use msgs::enums::ProtocolVersion;
 pub use msgs::enums::SignatureScheme;
+pub use msgs::enums::SignatureAlgorithm;
 pub use msgs::enums::CipherSuite;
+pub use msgs::enums::{ContentType, HandshakeType, AlertLevel, AlertDescription};
 pub use error::TLSError;
 pub use session::Session;
 pub use stream::{Stream, StreamOwned};
 pub use anchors::{DistinguishedNames, RootCertStore};
+pub use anchors::{RootsLoadReport, SkippedCert, SkipReason};
 pub use client::StoresClientSessions;
 pub use client::handy::{NoClientSessionStorage, ClientSessionMemoryCache};
-pub use client::{ClientConfig, ClientSession};
+pub use client::handy::ClientSessionFileCache;
+pub use client::{ClientConfig, ClientSession, WriteEarlyData};
 pub use client::ResolvesClientCert;
-pub use server::StoresServerSessions;
+pub use server::{StoresServerSessions, DetectsEarlyDataReplay};
 pub use server::handy::{NoServerSessionStorage, ServerSessionMemoryCache};
-pub use server::{ServerConfig, ServerSession};
+pub use server::handy::EarlyDataReplayMemoryCache;
+pub use server::{ServerConfig, ServerSession, ReadEarlyData, ClientHello};
 pub use server::handy::ResolvesServerCertUsingSNI;
 pub use server::ResolvesServerCert;
 pub use server::ProducesTickets;
 pub use ticketer::Ticketer;
+pub use ticketer::{SharedTicketer, ProvidesTicketKeys, TicketKeyFile};
+pub use ticketer::{TicketKey, TicketKeySet};
 pub use verify::{NoClientAuth, AllowAnyAuthenticatedClient,
-                 AllowAnyAnonymousOrAuthenticatedClient};
+                 AllowAnyAnonymousOrAuthenticatedClient, RevocationPolicy,
+                 CTEnforcement};
 pub use suites::{ALL_CIPHERSUITES, BulkAlgorithm, SupportedCipherSuite};
 pub use key::{Certificate, PrivateKey};
 pub use keylog::{KeyLog, NoKeyLog, KeyLogFile};
+pub use observer::{ObservesSession, NoObserver, SessionEvent, Direction};
 pub use vecbuf::WriteV;
 
 /// Message signing interfaces and implementations.
 pub mod sign;
 
+/// Pluggable cryptographic primitives, and the default *ring*-based
+/// implementation.
+pub mod crypto;
+
 #[cfg(feature = "quic")]
 /// APIs for implementing QUIC TLS
 pub mod quic;
@@ -237,6 +237,8 @@ impl DecomposedSignatureScheme for SignatureScheme {
             SignatureScheme::ECDSA_NISTP256_SHA256 |
                 SignatureScheme::ECDSA_NISTP384_SHA384 |
                 SignatureScheme::ECDSA_NISTP521_SHA512 => SignatureAlgorithm::ECDSA,
+            SignatureScheme::ED25519 => SignatureAlgorithm::ED25519,
+            SignatureScheme::ED448 => SignatureAlgorithm::ED448,
             _ => SignatureAlgorithm::Unknown(0),
         }
     }
@@ -268,6 +270,7 @@ impl SupportedMandatedSignatureSchemes for SupportedSignatureSchemes {
     fn supported_verify() -> SupportedSignatureSchemes {
         vec![
             /* FIXME: ECDSA-P521-SHA512 */
+            SignatureScheme::ED25519,
             SignatureScheme::ECDSA_NISTP384_SHA384,
             SignatureScheme::ECDSA_NISTP256_SHA256,
 
@@ -285,6 +288,7 @@ impl SupportedMandatedSignatureSchemes for SupportedSignatureSchemes {
 
     fn supported_sign_tls13() -> SupportedSignatureSchemes {
         vec![
+            SignatureScheme::ED25519,
             SignatureScheme::ECDSA_NISTP384_SHA384,
             SignatureScheme::ECDSA_NISTP256_SHA256,
 
@@ -600,6 +604,7 @@ pub enum ClientExtension {
     CertificateStatusRequest(CertificateStatusRequest),
     SignedCertificateTimestampRequest,
     TransportParameters(Vec<u8>),
+    EarlyData,
     Unknown(UnknownExtension),
 }
 
@@ -622,6 +627,7 @@ impl ClientExtension {
             ClientExtension::CertificateStatusRequest(_) => ExtensionType::StatusRequest,
             ClientExtension::SignedCertificateTimestampRequest => ExtensionType::SCT,
             ClientExtension::TransportParameters(_) => ExtensionType::TransportParameters,
+            ClientExtension::EarlyData => ExtensionType::EarlyData,
             ClientExtension::Unknown(ref r) => r.typ,
         }
     }
@@ -639,7 +645,8 @@ impl Codec for ClientExtension {
             ClientExtension::ServerName(ref r) => r.encode(&mut sub),
             ClientExtension::SessionTicketRequest |
                 ClientExtension::ExtendedMasterSecretRequest |
-                ClientExtension::SignedCertificateTimestampRequest => (),
+                ClientExtension::SignedCertificateTimestampRequest |
+                ClientExtension::EarlyData => (),
             ClientExtension::SessionTicketOffer(ref r) => r.encode(&mut sub),
             ClientExtension::Protocols(ref r) => r.encode(&mut sub),
             ClientExtension::SupportedVersions(ref r) => r.encode(&mut sub),
@@ -711,6 +718,7 @@ impl Codec for ClientExtension {
             ExtensionType::TransportParameters => {
                 ClientExtension::TransportParameters(sub.rest().to_vec())
             }
+            ExtensionType::EarlyData if !sub.any_left() => ClientExtension::EarlyData,
             _ => ClientExtension::Unknown(UnknownExtension::read(typ, &mut sub)?),
         })
     }
@@ -742,6 +750,7 @@ pub enum ServerExtension {
     SignedCertificateTimestamp(SCTList),
     SupportedVersions(ProtocolVersion),
     TransportParameters(Vec<u8>),
+    EarlyData,
     Unknown(UnknownExtension),
 }
 
@@ -760,6 +769,7 @@ impl ServerExtension {
             ServerExtension::SignedCertificateTimestamp(_) => ExtensionType::SCT,
             ServerExtension::SupportedVersions(_) => ExtensionType::SupportedVersions,
             ServerExtension::TransportParameters(_) => ExtensionType::TransportParameters,
+            ServerExtension::EarlyData => ExtensionType::EarlyData,
             ServerExtension::Unknown(ref r) => r.typ,
         }
     }
@@ -775,7 +785,8 @@ impl Codec for ServerExtension {
             ServerExtension::ServerNameAck |
                 ServerExtension::SessionTicketAck |
                 ServerExtension::ExtendedMasterSecretAck |
-                ServerExtension::CertificateStatusAck => (),
+                ServerExtension::CertificateStatusAck |
+                ServerExtension::EarlyData => (),
             ServerExtension::RenegotiationInfo(ref r) => r.encode(&mut sub),
             ServerExtension::Protocols(ref r) => r.encode(&mut sub),
             ServerExtension::KeyShare(ref r) => r.encode(&mut sub),
@@ -825,6 +836,7 @@ impl Codec for ServerExtension {
             ExtensionType::TransportParameters => {
                 ServerExtension::TransportParameters(sub.rest().to_vec())
             }
+            ExtensionType::EarlyData if !sub.any_left() => ServerExtension::EarlyData,
             _ => ServerExtension::Unknown(UnknownExtension::read(typ, &mut sub)?),
         })
     }
@@ -1041,6 +1053,11 @@ impl ClientHelloPayload {
         self.find_extension(ExtensionType::ExtendedMasterSecret)
             .is_some()
     }
+
+    pub fn early_data_extension_offered(&self) -> bool {
+        self.find_extension(ExtensionType::EarlyData)
+            .is_some()
+    }
 }
 
 #[derive(Debug)]
@@ -1775,6 +1792,10 @@ pub trait HasServerExtensions {
             _ => None,
         }
     }
+
+    fn early_data_extension_offered(&self) -> bool {
+        self.find_extension(ExtensionType::EarlyData).is_some()
+    }
 }
 
 impl HasServerExtensions for EncryptedExtensions {
@@ -1949,12 +1970,14 @@ impl Codec for NewSessionTicketPayload {
 // -- NewSessionTicket electric boogaloo --
 #[derive(Debug)]
 pub enum NewSessionTicketExtension {
+    EarlyData(u32),
     Unknown(UnknownExtension),
 }
 
 impl NewSessionTicketExtension {
     pub fn get_type(&self) -> ExtensionType {
         match *self {
+            NewSessionTicketExtension::EarlyData(_) => ExtensionType::EarlyData,
             NewSessionTicketExtension::Unknown(ref r) => r.typ,
         }
     }
@@ -1966,6 +1989,7 @@ impl Codec for NewSessionTicketExtension {
 
         let mut sub: Vec<u8> = Vec::new();
         match *self {
+            NewSessionTicketExtension::EarlyData(r) => r.encode(&mut sub),
             NewSessionTicketExtension::Unknown(ref r) => r.encode(&mut sub),
         }
 
@@ -1979,6 +2003,7 @@ impl Codec for NewSessionTicketExtension {
         let mut sub = r.sub(len)?;
 
         Some(match typ {
+            ExtensionType::EarlyData => NewSessionTicketExtension::EarlyData(u32::read(&mut sub)?),
             _ => {
                 NewSessionTicketExtension::Unknown(UnknownExtension::read(typ, &mut sub)?)
             }
@@ -2010,6 +2035,18 @@ impl NewSessionTicketPayloadTLS13 {
             exts: vec![],
         }
     }
+
+    pub fn find_extension(&self, ext: ExtensionType) -> Option<&NewSessionTicketExtension> {
+        self.exts.iter().find(|x| x.get_type() == ext)
+    }
+
+    pub fn get_max_early_data_size(&self) -> Option<u32> {
+        let ext = self.find_extension(ExtensionType::EarlyData)?;
+        match *ext {
+            NewSessionTicketExtension::EarlyData(ref sz) => Some(*sz),
+            _ => None
+        }
+    }
 }
 
 impl Codec for NewSessionTicketPayloadTLS13 {
@@ -2083,6 +2120,7 @@ pub enum HandshakePayload {
     ClientHello(ClientHelloPayload),
     ServerHello(ServerHelloPayload),
     HelloRetryRequest(HelloRetryRequest),
+    EndOfEarlyData,
     Certificate(CertificatePayload),
     CertificateTLS13(CertificatePayloadTLS13),
     ServerKeyExchange(ServerKeyExchangePayload),
@@ -2105,7 +2143,8 @@ impl HandshakePayload {
     fn encode(&self, bytes: &mut Vec<u8>) {
         match *self {
             HandshakePayload::HelloRequest |
-                HandshakePayload::ServerHelloDone => {}
+                HandshakePayload::ServerHelloDone |
+                HandshakePayload::EndOfEarlyData => {}
             HandshakePayload::ClientHello(ref x) => x.encode(bytes),
             HandshakePayload::ServerHello(ref x) => x.encode(bytes),
             HandshakePayload::HelloRetryRequest(ref x) => x.encode(bytes),
@@ -2207,6 +2246,12 @@ impl HandshakeMessagePayload {
             HandshakeType::ClientKeyExchange => {
                 HandshakePayload::ClientKeyExchange(Payload::read(&mut sub)?)
             }
+            HandshakeType::EndOfEarlyData => {
+                if sub.any_left() {
+                    return None;
+                }
+                HandshakePayload::EndOfEarlyData
+            }
             HandshakeType::CertificateRequest if vers == ProtocolVersion::TLSv1_3 => {
                 let p = CertificateRequestPayloadTLS13::read(&mut sub)?;
                 HandshakePayload::CertificateRequestTLS13(p)
@@ -2266,6 +2311,13 @@ impl HandshakeMessagePayload {
         }
     }
 
+    pub fn build_key_update_request() -> HandshakeMessagePayload {
+        HandshakeMessagePayload {
+            typ: HandshakeType::KeyUpdate,
+            payload: HandshakePayload::KeyUpdate(KeyUpdateRequest::UpdateRequested),
+        }
+    }
+
     pub fn get_encoding_for_binder_signing(&self) -> Vec<u8> {
         let mut ret = self.get_encoding();
 
@@ -376,6 +376,7 @@ fn get_sample_clienthellopayload() -> ClientHelloPayload {
             ClientExtension::CertificateStatusRequest(CertificateStatusRequest::build_ocsp()),
             ClientExtension::SignedCertificateTimestampRequest,
             ClientExtension::TransportParameters(vec![ 1, 2, 3 ]),
+            ClientExtension::EarlyData,
             ClientExtension::Unknown(UnknownExtension {
                 typ: ExtensionType::Unknown(12345),
                 payload: Payload(vec![ 1, 2, 3 ])
@@ -622,6 +623,7 @@ fn get_sample_serverhellopayload() -> ServerHelloPayload {
             ServerExtension::SignedCertificateTimestamp(vec![ PayloadU16(vec![0]) ]),
             ServerExtension::SupportedVersions(ProtocolVersion::TLSv1_2),
             ServerExtension::TransportParameters(vec![ 1, 2, 3 ]),
+            ServerExtension::EarlyData,
             ServerExtension::Unknown(UnknownExtension {
                 typ: ExtensionType::Unknown(12345),
                 payload: Payload(vec![ 1, 2, 3 ])
@@ -734,6 +736,7 @@ fn get_sample_newsessionticketpayloadtls13() -> NewSessionTicketPayloadTLS13 {
         nonce: PayloadU8(vec![ 1, 2, 3 ]),
         ticket: PayloadU16(vec![ 4, 5, 6 ]),
         exts: vec![
+            NewSessionTicketExtension::EarlyData(1234),
             NewSessionTicketExtension::Unknown(UnknownExtension {
                 typ: ExtensionType::Unknown(12345),
                 payload: Payload(vec![ 1, 2, 3 ])
@@ -742,6 +745,15 @@ fn get_sample_newsessionticketpayloadtls13() -> NewSessionTicketPayloadTLS13 {
     }
 }
 
+#[test]
+fn newsessionticket_get_max_early_data_size() {
+    let mut nst = get_sample_newsessionticketpayloadtls13();
+    assert_eq!(nst.get_max_early_data_size(), Some(1234));
+
+    nst.exts.retain(|ext| ext.get_type() != ExtensionType::EarlyData);
+    assert_eq!(nst.get_max_early_data_size(), None);
+}
+
 fn get_sample_encryptedextensions() -> EncryptedExtensions {
     get_sample_serverhellopayload().extensions
 }
@@ -886,6 +898,10 @@ fn can_roundtrip_all_tls13_handshake_payloads() {
             typ: HandshakeType::NewSessionTicket,
             payload: HandshakePayload::NewSessionTicketTLS13(get_sample_newsessionticketpayloadtls13()),
         },
+        HandshakeMessagePayload {
+            typ: HandshakeType::EndOfEarlyData,
+            payload: HandshakePayload::EndOfEarlyData,
+        },
         HandshakeMessagePayload {
             typ: HandshakeType::EncryptedExtensions,
             payload: HandshakePayload::EncryptedExtensions(get_sample_encryptedextensions()),
@@ -205,6 +205,14 @@ impl Message {
             payload: MessagePayload::Handshake(HandshakeMessagePayload::build_key_update_notify()),
         }
     }
+
+    pub fn build_key_update_request() -> Message {
+        Message {
+            typ: ContentType::Handshake,
+            version: ProtocolVersion::TLSv1_3,
+            payload: MessagePayload::Handshake(HandshakeMessagePayload::build_key_update_request()),
+        }
+    }
 }
 
 impl<'a> Message {
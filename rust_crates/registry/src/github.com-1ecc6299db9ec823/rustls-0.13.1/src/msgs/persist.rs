@@ -18,13 +18,13 @@ use std::cmp;
 #[derive(Debug)]
 pub struct ClientSessionKey {
     kind: &'static [u8],
-    dns_name: PayloadU8,
+    server_name: PayloadU8,
 }
 
 impl Codec for ClientSessionKey {
     fn encode(&self, bytes: &mut Vec<u8>) {
         bytes.extend_from_slice(self.kind);
-        self.dns_name.encode(bytes);
+        self.server_name.encode(bytes);
     }
 
     // Don't need to read these.
@@ -34,19 +34,19 @@ impl Codec for ClientSessionKey {
 }
 
 impl ClientSessionKey {
-    pub fn session_for_dns_name(dns_name: webpki::DNSNameRef) -> ClientSessionKey {
-        let dns_name_str: &str = dns_name.into();
+    pub fn session_for_server_name(server_name: webpki::SubjectNameRef) -> ClientSessionKey {
+        let server_name_str: &str = server_name.into();
         ClientSessionKey {
             kind: b"session",
-            dns_name: PayloadU8::new(dns_name_str.as_bytes().to_vec()),
+            server_name: PayloadU8::new(server_name_str.as_bytes().to_vec()),
         }
     }
 
-    pub fn hint_for_dns_name(dns_name: webpki::DNSNameRef) -> ClientSessionKey {
-        let dns_name_str: &str = dns_name.into();
+    pub fn hint_for_server_name(server_name: webpki::SubjectNameRef) -> ClientSessionKey {
+        let server_name_str: &str = server_name.into();
         ClientSessionKey {
             kind: b"kx-hint",
-            dns_name: PayloadU8::new(dns_name_str.as_bytes().to_vec()),
+            server_name: PayloadU8::new(server_name_str.as_bytes().to_vec()),
         }
     }
 }
@@ -62,6 +62,9 @@ pub struct ClientSessionValue {
     pub lifetime: u32,
     pub age_add: u32,
     pub extended_ms: bool,
+    pub max_early_data_size: u32,
+    pub alpn: Option<PayloadU8>,
+    pub ct_enforced: bool,
 }
 
 impl Codec for ClientSessionValue {
@@ -75,6 +78,14 @@ impl Codec for ClientSessionValue {
         self.lifetime.encode(bytes);
         self.age_add.encode(bytes);
         (if self.extended_ms { 1u8 } else { 0u8 }).encode(bytes);
+        self.max_early_data_size.encode(bytes);
+        if let Some(ref alpn) = self.alpn {
+            1u8.encode(bytes);
+            alpn.encode(bytes);
+        } else {
+            0u8.encode(bytes);
+        }
+        (if self.ct_enforced { 1u8 } else { 0u8 }).encode(bytes);
     }
 
     fn read(r: &mut Reader) -> Option<ClientSessionValue> {
@@ -87,6 +98,13 @@ impl Codec for ClientSessionValue {
         let lifetime = u32::read(r)?;
         let age_add = u32::read(r)?;
         let extended_ms = u8::read(r)?;
+        let max_early_data_size = u32::read(r)?;
+        let alpn = if u8::read(r)? == 1 {
+            Some(PayloadU8::read(r)?)
+        } else {
+            None
+        };
+        let ct_enforced = u8::read(r)?;
 
         Some(ClientSessionValue {
             version: v,
@@ -98,6 +116,9 @@ impl Codec for ClientSessionValue {
             lifetime,
             age_add,
             extended_ms: extended_ms == 1u8,
+            max_early_data_size,
+            alpn,
+            ct_enforced: ct_enforced == 1u8,
         })
     }
 }
@@ -121,6 +142,9 @@ impl ClientSessionValue {
             lifetime: 0,
             age_add: 0,
             extended_ms: false,
+            max_early_data_size: 0,
+            alpn: None,
+            ct_enforced: false,
         }
     }
 
@@ -128,6 +152,18 @@ impl ClientSessionValue {
         self.extended_ms = true;
     }
 
+    pub fn set_ct_enforced(&mut self) {
+        self.ct_enforced = true;
+    }
+
+    pub fn set_max_early_data_size(&mut self, sz: u32) {
+        self.max_early_data_size = sz;
+    }
+
+    pub fn set_alpn(&mut self, alpn: Option<&str>) {
+        self.alpn = alpn.map(|proto| PayloadU8::new(proto.as_bytes().to_vec()));
+    }
+
     pub fn set_times(&mut self, receipt_time_secs: u64,
                      lifetime_secs: u32, age_add: u32) {
         self.epoch = receipt_time_secs;
@@ -142,7 +178,7 @@ impl ClientSessionValue {
     pub fn get_obfuscated_ticket_age(&self, time_now: u64) -> u32 {
         let age_secs = time_now.saturating_sub(self.epoch);
         let age_millis = age_secs as u32 * 1000;
-        age_millis.wrapping_sub(self.age_add)
+        age_millis.wrapping_add(self.age_add)
     }
 
     pub fn take_ticket(&mut self) -> Vec<u8> {
@@ -163,6 +199,13 @@ pub struct ServerSessionValue {
     pub master_secret: PayloadU8,
     pub extended_ms: bool,
     pub client_cert_chain: Option<CertificatePayload>,
+
+    // TLS1.3 only: used to decide whether to accept early data
+    // on resumption.
+    pub creation_time_sec: u64,
+    pub age_obfuscation_offset: u32,
+    pub max_early_data_size: u32,
+    pub alpn: Option<PayloadU8>,
 }
 
 impl Codec for ServerSessionValue {
@@ -178,6 +221,17 @@ impl Codec for ServerSessionValue {
         self.cipher_suite.encode(bytes);
         self.master_secret.encode(bytes);
         (if self.extended_ms { 1u8 } else { 0u8 }).encode(bytes);
+        if self.version == ProtocolVersion::TLSv1_3 {
+            self.creation_time_sec.encode(bytes);
+            self.age_obfuscation_offset.encode(bytes);
+            self.max_early_data_size.encode(bytes);
+            if let Some(ref alpn) = self.alpn {
+                1u8.encode(bytes);
+                alpn.encode(bytes);
+            } else {
+                0u8.encode(bytes);
+            }
+        }
         if self.client_cert_chain.is_some() {
             self.client_cert_chain.as_ref().unwrap().encode(bytes);
         }
@@ -197,6 +251,20 @@ impl Codec for ServerSessionValue {
         let cs = CipherSuite::read(r)?;
         let ms = PayloadU8::read(r)?;
         let ems = u8::read(r)?;
+
+        let mut creation_time_sec = 0;
+        let mut age_obfuscation_offset = 0;
+        let mut max_early_data_size = 0;
+        let mut alpn = None;
+        if v == ProtocolVersion::TLSv1_3 {
+            creation_time_sec = u64::read(r)?;
+            age_obfuscation_offset = u32::read(r)?;
+            max_early_data_size = u32::read(r)?;
+            if u8::read(r)? == 1 {
+                alpn = Some(PayloadU8::read(r)?);
+            }
+        }
+
         let ccert = if r.any_left() {
             CertificatePayload::read(r)
         } else {
@@ -210,6 +278,10 @@ impl Codec for ServerSessionValue {
             master_secret: ms,
             extended_ms: ems == 1u8,
             client_cert_chain: ccert,
+            creation_time_sec,
+            age_obfuscation_offset,
+            max_early_data_size,
+            alpn,
         })
     }
 }
@@ -228,10 +300,45 @@ impl ServerSessionValue {
             master_secret: PayloadU8::new(ms),
             extended_ms: false,
             client_cert_chain: cert_chain.clone(),
+            creation_time_sec: 0,
+            age_obfuscation_offset: 0,
+            max_early_data_size: 0,
+            alpn: None,
         }
     }
 
     pub fn set_extended_ms_used(&mut self) {
         self.extended_ms = true;
     }
+
+    pub fn set_times(&mut self, creation_time_sec: u64, age_obfuscation_offset: u32) {
+        self.creation_time_sec = creation_time_sec;
+        self.age_obfuscation_offset = age_obfuscation_offset;
+    }
+
+    pub fn set_max_early_data_size(&mut self, sz: u32) {
+        self.max_early_data_size = sz;
+    }
+
+    pub fn set_alpn(&mut self, alpn: Option<&str>) {
+        self.alpn = alpn.map(|proto| PayloadU8::new(proto.as_bytes().to_vec()));
+    }
+
+    /// Does the ticket age the client claims in `obfuscated_ticket_age`
+    /// agree with our own idea of the ticket's age at `time_now`, to
+    /// within `max_skew_ms`?
+    pub fn is_fresh(&self, obfuscated_ticket_age: u32,
+                    time_now: u64, max_skew_ms: u64) -> bool {
+        let client_age_ms = u64::from(obfuscated_ticket_age.wrapping_sub(self.age_obfuscation_offset));
+        let server_age_ms = time_now.saturating_sub(self.creation_time_sec)
+            .saturating_mul(1000);
+
+        let skew = if client_age_ms > server_age_ms {
+            client_age_ms - server_age_ms
+        } else {
+            server_age_ms - client_age_ms
+        };
+
+        skew <= max_skew_ms
+    }
 }
@@ -7,7 +7,7 @@ use webpki::DNSNameRef;
 #[test]
 fn clientsessionkey_is_debug() {
     let name = DNSNameRef::try_from_ascii_str("hello").unwrap();
-    let csk = ClientSessionKey::session_for_dns_name(name);
+    let csk = ClientSessionKey::session_for_server_name(name.into());
     println!("{:?}", csk);
 }
 
@@ -66,3 +66,64 @@ fn serversessionvalue_with_cert() {
     let ssv = ServerSessionValue::read(&mut rd).unwrap();
     assert_eq!(ssv.get_encoding(), bytes);
 }
+
+#[test]
+fn serversessionvalue_tls13_roundtrip() {
+    let mut ssv = ServerSessionValue::new(None,
+                                          ProtocolVersion::TLSv1_3,
+                                          CipherSuite::TLS13_AES_128_GCM_SHA256,
+                                          vec![1, 2, 3],
+                                          &None);
+    ssv.set_times(1234, 5678);
+    ssv.set_max_early_data_size(1024);
+    ssv.set_alpn(Some("h2"));
+
+    let bytes = ssv.get_encoding();
+    let other = ServerSessionValue::read_bytes(&bytes).unwrap();
+    assert_eq!(other.creation_time_sec, 1234);
+    assert_eq!(other.age_obfuscation_offset, 5678);
+    assert_eq!(other.max_early_data_size, 1024);
+    assert_eq!(other.alpn.as_ref().unwrap().0, b"h2".to_vec());
+    assert_eq!(other.get_encoding(), bytes);
+}
+
+#[test]
+fn clientsessionvalue_roundtrip() {
+    let mut csv = ClientSessionValue::new(ProtocolVersion::TLSv1_3,
+                                          CipherSuite::TLS13_AES_128_GCM_SHA256,
+                                          &SessionID::empty(),
+                                          vec![4, 5, 6],
+                                          vec![1, 2, 3]);
+    csv.set_max_early_data_size(1024);
+    csv.set_alpn(Some("h2"));
+    csv.set_ct_enforced();
+
+    let bytes = csv.get_encoding();
+    let other = ClientSessionValue::read_bytes(&bytes).unwrap();
+    assert_eq!(other.max_early_data_size, 1024);
+    assert_eq!(other.alpn.as_ref().unwrap().0, b"h2".to_vec());
+    assert!(other.ct_enforced);
+}
+
+#[test]
+fn ticket_age_freshness() {
+    let mut csv = ClientSessionValue::new(ProtocolVersion::TLSv1_3,
+                                          CipherSuite::TLS13_AES_128_GCM_SHA256,
+                                          &SessionID::empty(),
+                                          vec![4, 5, 6],
+                                          vec![1, 2, 3]);
+    csv.set_times(1000, 3600, 0xffff_0000);
+
+    let mut ssv = ServerSessionValue::new(None,
+                                          ProtocolVersion::TLSv1_3,
+                                          CipherSuite::TLS13_AES_128_GCM_SHA256,
+                                          vec![1, 2, 3],
+                                          &None);
+    ssv.set_times(1000, 0xffff_0000);
+
+    let age = csv.get_obfuscated_ticket_age(1005);
+    assert!(ssv.is_fresh(age, 1005, 0));
+    assert!(ssv.is_fresh(age, 1008, 5000));
+    assert!(!ssv.is_fresh(age, 1020, 10000));
+    assert!(!ssv.is_fresh(age.wrapping_add(60000), 1005, 10000));
+}
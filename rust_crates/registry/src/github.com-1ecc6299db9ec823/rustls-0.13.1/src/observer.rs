@@ -0,0 +1,88 @@
+use msgs::enums::{ContentType, HandshakeType, ProtocolVersion, CipherSuite};
+use msgs::enums::{AlertLevel, AlertDescription};
+
+/// Whether a message was sent or received by the session
+/// reporting it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    /// We sent it to the peer.
+    Sent,
+
+    /// The peer sent it to us.
+    Received,
+}
+
+/// Something which happened during a session, reported to an
+/// `ObservesSession` implementation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionEvent {
+    /// A TLS record, with the content type and payload length it has
+    /// on the wire.  Encrypted TLS1.3 records all appear to be
+    /// application data.
+    Record {
+        /// Which way the record went.
+        direction: Direction,
+        /// The record's content type.
+        typ: ContentType,
+        /// The record's payload length.
+        length: usize,
+    },
+
+    /// A complete handshake message, after any decryption and
+    /// reassembly of fragments.
+    HandshakeMessage {
+        /// Which way the message went.
+        direction: Direction,
+        /// The handshake message type.
+        typ: HandshakeType,
+    },
+
+    /// An alert.
+    Alert {
+        /// Which way the alert went.
+        direction: Direction,
+        /// Whether the alert is a warning or fatal.
+        level: AlertLevel,
+        /// What the alert says.
+        description: AlertDescription,
+    },
+
+    /// The protocol version and ciphersuite were chosen.
+    Negotiated {
+        /// The chosen protocol version.
+        version: ProtocolVersion,
+        /// The chosen ciphersuite.
+        suite: CipherSuite,
+    },
+
+    /// The handshake state machine moved from one state to another.
+    /// The names are only meaningful for debugging, and may change
+    /// between versions of this crate.
+    StateChange {
+        /// The name of the state we left.
+        from: &'static str,
+        /// The name of the state we entered.
+        to: &'static str,
+    },
+}
+
+/// This trait represents the ability to watch what a session
+/// does, such as to produce a trace of a failing handshake.
+///
+/// Events are reported in the order they happen.  The same
+/// observer is used by every session made from a config, so
+/// to trace a single connection give it a config of its own.
+///
+/// You'll likely want some interior mutability in your
+/// implementation to make this useful.
+pub trait ObservesSession : Send + Sync {
+    /// Report `event`.
+    fn observe(&self, event: &SessionEvent);
+}
+
+/// `ObservesSession` implementation that ignores everything.
+pub struct NoObserver {}
+
+impl ObservesSession for NoObserver {
+    fn observe(&self, _event: &SessionEvent) {}
+}
@@ -1,30 +1,20 @@
-use ring::digest;
-use ring::hmac;
+use crypto::Hash;
 
 use std::io::Write;
 
-fn concat_sign(key: &hmac::SigningKey, a: &[u8], b: &[u8]) -> hmac::Signature {
-    let mut ctx = hmac::SigningContext::with_key(key);
-    ctx.update(a);
-    ctx.update(b);
-    ctx.sign()
-}
-
-fn p(out: &mut [u8], hashalg: &'static digest::Algorithm, secret: &[u8], seed: &[u8]) {
-    let hmac_key = hmac::SigningKey::new(hashalg, secret);
-
+fn p(out: &mut [u8], hash: &Hash, secret: &[u8], seed: &[u8]) {
     // A(1)
-    let mut current_a = hmac::sign(&hmac_key, seed);
+    let mut current_a = hash.hmac_sign(secret, &[seed]);
 
     let mut offs = 0;
 
     while offs < out.len() {
         // P_hash[i] = HMAC_hash(secret, A(i) + seed)
-        let p_term = concat_sign(&hmac_key, current_a.as_ref(), seed);
-        offs += out[offs..].as_mut().write(p_term.as_ref()).unwrap();
+        let p_term = hash.hmac_sign(secret, &[&current_a, seed]);
+        offs += out[offs..].as_mut().write(&p_term).unwrap();
 
         // A(i+1) = HMAC_hash(secret, A(i))
-        current_a = hmac::sign(&hmac_key, current_a.as_ref());
+        current_a = hash.hmac_sign(secret, &[&current_a]);
     }
 }
 
@@ -36,17 +26,17 @@ fn concat(a: &[u8], b: &[u8]) -> Vec<u8> {
 }
 
 pub fn prf(out: &mut [u8],
-           hashalg: &'static digest::Algorithm,
+           hash: &Hash,
            secret: &[u8],
            label: &[u8],
            seed: &[u8]) {
     let joined_seed = concat(label, seed);
-    p(out, hashalg, secret, &joined_seed);
+    p(out, hash, secret, &joined_seed);
 }
 
 #[cfg(test)]
 mod tests {
-    use ring::digest::{SHA256, SHA512};
+    use crypto::{CryptoProvider, RingProvider, HashAlgorithm};
 
     #[test]
     fn check_sha256() {
@@ -56,7 +46,8 @@ mod tests {
         let expect = include_bytes!("testdata/prf-result.1.bin");
         let mut output = [0u8; 100];
 
-        super::prf(&mut output, &SHA256, secret, label, seed);
+        let sha256 = RingProvider {}.hash(HashAlgorithm::SHA256).unwrap();
+        super::prf(&mut output, sha256, secret, label, seed);
         assert_eq!(expect.len(), output.len());
         assert_eq!(expect.to_vec(), output.to_vec());
     }
@@ -69,7 +60,8 @@ mod tests {
         let expect = include_bytes!("testdata/prf-result.2.bin");
         let mut output = [0u8; 196];
 
-        super::prf(&mut output, &SHA512, secret, label, seed);
+        let sha512 = RingProvider {}.hash(HashAlgorithm::SHA512).unwrap();
+        super::prf(&mut output, sha512, secret, label, seed);
         assert_eq!(expect.len(), output.len());
         assert_eq!(expect.to_vec(), output.to_vec());
     }
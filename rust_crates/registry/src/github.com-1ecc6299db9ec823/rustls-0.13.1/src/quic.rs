@@ -31,10 +31,12 @@ pub trait ClientQuicExt {
     /// Make a new QUIC ClientSession. This differs from `ClientSession::new()`
     /// in that it takes an extra argument, `params`, which contains the
     /// TLS-encoded transport parameters to send.
-    fn new_quic(config: &Arc<ClientConfig>, hostname: webpki::DNSNameRef, params: Vec<u8>)
-                -> ClientSession {
+    fn new_quic<'a, N>(config: &Arc<ClientConfig>, server_name: N, params: Vec<u8>)
+                       -> ClientSession
+        where N: Into<webpki::SubjectNameRef<'a>>
+    {
         let mut imp = ClientSessionImpl::new(config);
-        imp.start_handshake(hostname.into(), vec![
+        imp.start_handshake(server_name.into().to_owned(), vec![
             ClientExtension::Unknown(UnknownExtension {
                 typ: ExtensionType::TransportParameters,
                 payload: Payload::new(params),
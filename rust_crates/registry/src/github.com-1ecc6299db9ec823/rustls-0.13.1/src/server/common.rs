@@ -1,4 +1,5 @@
 use session::SessionRandoms;
+use crypto::CryptoProvider;
 use msgs::handshake::{ServerExtension, SessionID};
 use hash_hs;
 use suites;
@@ -16,12 +17,12 @@ pub struct HandshakeDetails {
 }
 
 impl HandshakeDetails {
-    pub fn new(extra_exts: Vec<ServerExtension>) -> HandshakeDetails {
+    pub fn new(provider: &CryptoProvider, extra_exts: Vec<ServerExtension>) -> HandshakeDetails {
         HandshakeDetails {
             transcript: hash_hs::HandshakeHash::new(),
             hash_at_server_fin: Vec::new(),
             session_id: SessionID::empty(),
-            randoms: SessionRandoms::for_server(),
+            randoms: SessionRandoms::for_server(provider),
             using_ems: false,
             extra_exts,
         }
@@ -1,10 +1,13 @@
 use msgs::enums::SignatureScheme;
 use msgs::handshake::SessionID;
 use rand;
+use crypto::RingProvider;
 use sign;
 use key;
 use webpki;
 use server;
+use server::hs::MAX_TICKET_AGE_SKEW_MS;
+use ticketer;
 use error::TLSError;
 
 use std::collections;
@@ -56,7 +59,7 @@ impl ServerSessionMemoryCache {
 impl server::StoresServerSessions for ServerSessionMemoryCache {
     fn generate(&self) -> SessionID {
         let mut v = [0u8; 32];
-        rand::fill_random(&mut v);
+        rand::fill_random(&RingProvider {}, &mut v);
         SessionID::new(&v)
     }
 
@@ -75,6 +78,63 @@ impl server::StoresServerSessions for ServerSessionMemoryCache {
     }
 }
 
+/// An implementor of `DetectsEarlyDataReplay` that remembers
+/// recently accepted ClientHellos in memory.
+///
+/// We only accept early data for tickets whose age is within
+/// `MAX_TICKET_AGE_SKEW_MS` of what we expect, so entries need only
+/// be kept for twice that window.  If more than `size` ClientHellos
+/// are accepted inside the window, early data is rejected until
+/// older entries expire.
+pub struct EarlyDataReplayMemoryCache {
+    cache: Mutex<EarlyDataReplayEntries>,
+    max_entries: usize,
+}
+
+struct EarlyDataReplayEntries {
+    seen: collections::HashSet<Vec<u8>>,
+    order: collections::VecDeque<(u64, Vec<u8>)>,
+}
+
+impl EarlyDataReplayMemoryCache {
+    /// Make a new EarlyDataReplayMemoryCache.  `size` is the maximum
+    /// number of ClientHellos remembered at once.
+    pub fn new(size: usize) -> Arc<EarlyDataReplayMemoryCache> {
+        debug_assert!(size > 0);
+        Arc::new(EarlyDataReplayMemoryCache {
+            cache: Mutex::new(EarlyDataReplayEntries {
+                seen: collections::HashSet::new(),
+                order: collections::VecDeque::new(),
+            }),
+            max_entries: size,
+        })
+    }
+}
+
+impl server::DetectsEarlyDataReplay for EarlyDataReplayMemoryCache {
+    fn record(&self, key: &[u8]) -> bool {
+        let now = ticketer::timebase();
+        let window = (2 * MAX_TICKET_AGE_SKEW_MS + 999) / 1000;
+        let mut cache = self.cache.lock().unwrap();
+
+        while cache.order
+            .front()
+            .map(|&(time, _)| time + window < now)
+            .unwrap_or(false) {
+            let (_, old) = cache.order.pop_front().unwrap();
+            cache.seen.remove(&old);
+        }
+
+        if cache.seen.contains(key) || cache.order.len() >= self.max_entries {
+            return false;
+        }
+
+        cache.seen.insert(key.to_vec());
+        cache.order.push_back((now, key.to_vec()));
+        true
+    }
+}
+
 /// Something which never produces tickets.
 pub struct NeverProducesTickets {}
 
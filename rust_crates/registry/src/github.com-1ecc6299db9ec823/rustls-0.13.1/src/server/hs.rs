@@ -14,6 +14,7 @@ use msgs::handshake::{ServerECDHParams, DigitallySignedStruct};
 use msgs::handshake::{ServerKeyExchangePayload, ECDHEServerKeyExchange};
 use msgs::handshake::{CertificateRequestPayload, NewSessionTicketPayload};
 use msgs::handshake::{CertificateRequestPayloadTLS13, NewSessionTicketPayloadTLS13};
+use msgs::handshake::NewSessionTicketExtension;
 use msgs::handshake::{HelloRetryRequest, HelloRetryExtension, KeyShareEntry};
 use msgs::handshake::{CertificatePayloadTLS13, CertificateEntry};
 use msgs::handshake::{CertificateStatus, CertificateExtension};
@@ -29,7 +30,9 @@ use suites;
 use verify;
 use util;
 use rand;
+use crypto::CryptoProvider;
 use sign;
+use ticketer;
 use error::TLSError;
 use handshake::{check_handshake_message, check_message};
 use webpki;
@@ -40,6 +43,10 @@ use ring::constant_time;
 
 const TLS13_DRAFT: u16 = 0x7f17;
 
+/// How far the client's view of a ticket's age may differ from
+/// ours before we refuse early data sent with it.
+pub const MAX_TICKET_AGE_SKEW_MS: u64 = 10_000;
+
 macro_rules! extract_handshake(
   ( $m:expr, $t:path ) => (
     match $m.payload {
@@ -57,6 +64,8 @@ type NextState = Box<State + Send + Sync>;
 type NextStateOrError = Result<NextState, TLSError>;
 
 pub trait State {
+    /// A name for this state, for `SessionEvent::StateChange`.
+    fn name(&self) -> &'static str;
     fn check_message(&self, m: &Message) -> CheckResult;
     fn handle(self: Box<Self>, sess: &mut ServerSessionImpl, m: Message) -> NextStateOrError;
 }
@@ -133,9 +142,11 @@ pub struct ExpectClientHello {
 }
 
 impl ExpectClientHello {
-    pub fn new(perhaps_client_auth: bool, extra_exts: Vec<ServerExtension>) -> ExpectClientHello {
+    pub fn new(provider: &CryptoProvider,
+               perhaps_client_auth: bool,
+               extra_exts: Vec<ServerExtension>) -> ExpectClientHello {
         let mut ret = ExpectClientHello {
-            handshake: HandshakeDetails::new(extra_exts),
+            handshake: HandshakeDetails::new(provider, extra_exts),
             done_retry: false,
             send_cert_status: false,
             send_sct: false,
@@ -181,6 +192,13 @@ impl ExpectClientHello {
         })
     }
 
+    fn into_expect_tls13_early_data(self) -> NextState {
+        Box::new(ExpectTLS13EarlyData {
+            handshake: self.handshake,
+            send_ticket: self.send_ticket,
+        })
+    }
+
     fn into_expect_tls12_certificate(self, kx: suites::KeyExchange) -> NextState {
         Box::new(ExpectTLS12Certificate {
             handshake: self.handshake,
@@ -307,7 +325,7 @@ impl ExpectClientHello {
             _ => unreachable!(),
         };
 
-        let suite_hash = sess.common.get_suite_assert().get_hash();
+        let suite_hash = sess.common.get_suite_hash();
         let handshake_hash = self.handshake.transcript.get_hash_given(suite_hash, &binder_plaintext);
 
         let mut key_schedule = KeySchedule::new(suite_hash);
@@ -329,7 +347,7 @@ impl ExpectClientHello {
         let mut extensions = Vec::new();
 
         // Do key exchange
-        let kxr = suites::KeyExchange::start_ecdhe(share.group)
+        let kxr = suites::KeyExchange::start_ecdhe(sess.common.provider(), share.group)
             .and_then(|kx| kx.complete(&share.payload.0))
             .ok_or_else(|| TLSError::PeerMisbehavedError("key exchange failed".to_string()))?;
 
@@ -365,7 +383,7 @@ impl ExpectClientHello {
 
         // Start key schedule
         let suite = sess.common.get_suite_assert();
-        let mut key_schedule = KeySchedule::new(suite.get_hash());
+        let mut key_schedule = KeySchedule::new(suite.get_hash(sess.common.provider()));
         if let Some(psk) = resuming_psk {
             key_schedule.input_secret(&psk);
         } else {
@@ -376,8 +394,10 @@ impl ExpectClientHello {
         let handshake_hash = self.handshake.transcript.get_current_hash();
         let write_key = key_schedule.derive(SecretKind::ServerHandshakeTrafficSecret, &handshake_hash);
         let read_key = key_schedule.derive(SecretKind::ClientHandshakeTrafficSecret, &handshake_hash);
-        sess.common.set_message_encrypter(cipher::new_tls13_write(suite, &write_key));
-        sess.common.set_message_decrypter(cipher::new_tls13_read(suite, &read_key));
+        let enc = cipher::new_tls13_write(sess.common.provider(), suite, &write_key);
+        sess.common.set_message_encrypter(enc);
+        let dec = cipher::new_tls13_read(sess.common.provider(), suite, &read_key);
+        sess.common.set_message_decrypter(dec);
         sess.config.key_log.log("SERVER_HANDSHAKE_TRAFFIC_SECRET",
                                 &self.handshake.randoms.client,
                                 &write_key);
@@ -429,13 +449,73 @@ impl ExpectClientHello {
         sess.common.send_msg(m, false);
     }
 
+    fn can_accept_early_data(&self,
+                             sess: &ServerSessionImpl,
+                             hello: &ClientHelloPayload,
+                             resume: &persist::ServerSessionValue)
+                             -> bool {
+        if sess.config.max_early_data_size == 0 || resume.max_early_data_size == 0 {
+            return false;
+        }
+
+        // The client may send as much as its ticket allows, which
+        // must not be more than we accept now.
+        if resume.max_early_data_size > sess.config.max_early_data_size {
+            debug!("Rejecting early data: ticket allows more than our limit");
+            return false;
+        }
+
+        // Early data must be sent with the same suite and
+        // ALPN protocol as the session it resumes.
+        if resume.cipher_suite != sess.common.get_suite_assert().suite {
+            return false;
+        }
+
+        let resume_alpn = resume.alpn.as_ref().map(|p| p.0.as_slice());
+        let alpn = sess.alpn_protocol.as_ref().map(|p| p.as_bytes());
+        if resume_alpn != alpn {
+            return false;
+        }
+
+        // We only accept the first PSK, and it must not be
+        // too old: this bounds how long we must remember it
+        // for replay detection.
+        let psk_offer = hello.get_psk().unwrap();
+        if !resume.is_fresh(psk_offer.identities[0].obfuscated_ticket_age,
+                            ticketer::timebase(),
+                            MAX_TICKET_AGE_SKEW_MS) {
+            debug!("Rejecting early data: ticket age skew too large");
+            return false;
+        }
+
+        // Last, so we only remember ClientHellos we otherwise accept.
+        if !sess.config.early_data_replay.record(&psk_offer.binders[0].0) {
+            debug!("Rejecting early data: possible replay");
+            return false;
+        }
+
+        true
+    }
+
     fn emit_encrypted_extensions(&mut self,
                                  sess: &mut ServerSessionImpl,
                                  server_key: &mut sign::CertifiedKey,
                                  hello: &ClientHelloPayload,
-                                 for_resume: bool)
-                                 -> Result<(), TLSError> {
-        let encrypted_exts = self.process_extensions(sess, Some(server_key), hello, for_resume)?;
+                                 resumedata: Option<&persist::ServerSessionValue>,
+                                 try_early_data: bool)
+                                 -> Result<bool, TLSError> {
+        let mut encrypted_exts = self.process_extensions(sess, Some(server_key), hello,
+                                                         resumedata.is_some())?;
+
+        let early_data = match resumedata {
+            Some(resume) if try_early_data => self.can_accept_early_data(sess, hello, resume),
+            _ => false,
+        };
+
+        if early_data {
+            encrypted_exts.push(ServerExtension::EarlyData);
+        }
+
         let ee = Message {
             typ: ContentType::Handshake,
             version: ProtocolVersion::TLSv1_3,
@@ -448,7 +528,7 @@ impl ExpectClientHello {
         trace!("sending encrypted extensions {:?}", ee);
         self.handshake.transcript.add_message(&ee);
         sess.common.send_msg(ee, true);
-        Ok(())
+        Ok(early_data)
     }
 
     fn emit_certificate_req_tls13(&mut self, sess: &mut ServerSessionImpl) -> bool {
@@ -592,7 +672,8 @@ impl ExpectClientHello {
             .derive(SecretKind::ServerApplicationTrafficSecret,
                     &self.handshake.hash_at_server_fin);
         let suite = sess.common.get_suite_assert();
-        sess.common.set_message_encrypter(cipher::new_tls13_write(suite, &write_key));
+        let enc = cipher::new_tls13_write(sess.common.provider(), suite, &write_key);
+        sess.common.set_message_encrypter(enc);
         sess.config.key_log.log("SERVER_TRAFFIC_SECRET_0",
                                 &self.handshake.randoms.client,
                                 &write_key);
@@ -691,7 +772,7 @@ impl ExpectClientHello {
                       server_certkey: &mut sign::CertifiedKey)
                       -> Result<suites::KeyExchange, TLSError> {
         let kx = sess.common.get_suite_assert()
-            .start_server_kx(*group)
+            .start_server_kx(sess.common.provider(), *group)
             .ok_or_else(|| TLSError::PeerMisbehavedError("key exchange failed".to_string()))?;
         let secdh = ServerECDHParams::new(group, &kx.pubkey);
 
@@ -786,7 +867,7 @@ impl ExpectClientHello {
         self.handshake.session_id = *id;
         self.emit_server_hello(sess, None, client_hello, true)?;
 
-        let hashalg = sess.common.get_suite_assert().get_hash();
+        let hashalg = sess.common.get_suite_hash();
         let secrets = SessionSecrets::new_resume(&self.handshake.randoms,
                                                  hashalg,
                                                  &resumedata.master_secret.0);
@@ -819,6 +900,10 @@ impl ExpectClientHello {
             return Err(illegal_param(sess, "client offered wrong compressions"));
         }
 
+        if self.done_retry && client_hello.early_data_extension_offered() {
+            return Err(illegal_param(sess, "client offered early data after retry"));
+        }
+
         let groups_ext = client_hello.get_namedgroups_extension()
             .ok_or_else(|| incompatible(sess, "client didn't describe groups"))?;
 
@@ -852,6 +937,12 @@ impl ExpectClientHello {
                     return Err(illegal_param(sess, "did not follow retry request"));
                 }
 
+                // Any early data the client sent is lost.
+                if client_hello.early_data_extension_offered() {
+                    let skip_budget = sess.config.max_early_data_size as usize;
+                    sess.early_data.reject(skip_budget);
+                }
+
                 self.emit_hello_retry_request(sess, group);
                 self.emit_fake_ccs(sess);
                 return Ok(self.into_expect_retried_client_hello());
@@ -868,7 +959,7 @@ impl ExpectClientHello {
             .unwrap();
 
         let mut chosen_psk_index = None;
-        let mut resuming_psk = None;
+        let mut resumedata = None;
         if let Some(psk_offer) = client_hello.get_psk() {
             if !client_hello.check_psk_ext_is_last() {
                 return Err(illegal_param(sess, "psk extension in wrong position"));
@@ -900,7 +991,7 @@ impl ExpectClientHello {
                 }
 
                 chosen_psk_index = Some(i);
-                resuming_psk = Some(resume.master_secret.0);
+                resumedata = Some(resume);
                 break;
             }
         }
@@ -909,19 +1000,43 @@ impl ExpectClientHello {
             warn!("Resumption ignored, DHE_KE not offered");
             self.send_ticket = false;
             chosen_psk_index = None;
-            resuming_psk = None;
+            resumedata = None;
         } else {
             self.send_ticket = true;
         }
 
-        let full_handshake = resuming_psk.is_none();
+        let full_handshake = resumedata.is_none();
         self.handshake.transcript.add_message(chm);
+
+        // The early traffic secret is derived from the hash of the
+        // (first) ClientHello alone.
+        let client_hello_hash = self.handshake.transcript.get_current_hash();
+
+        let resuming_psk = resumedata.as_ref()
+            .map(|resume| resume.master_secret.0.clone());
         self.emit_server_hello_tls13(sess, &client_hello.session_id,
                                      chosen_share, chosen_psk_index, resuming_psk)?;
         if !self.done_retry {
             self.emit_fake_ccs(sess);
         }
-        self.emit_encrypted_extensions(sess, &mut server_key, client_hello, !full_handshake)?;
+
+        let early_data_offered = client_hello.early_data_extension_offered();
+        let try_early_data = early_data_offered && chosen_psk_index == Some(0);
+        let early_data = self.emit_encrypted_extensions(sess,
+                                                        &mut server_key,
+                                                        client_hello,
+                                                        resumedata.as_ref(),
+                                                        try_early_data)?;
+
+        if early_data_offered && !early_data {
+            // We must skip over early data we won't decrypt, but
+            // only as much as the client was permitted to send.
+            let ticket_max = resumedata.as_ref()
+                .map(|resume| resume.max_early_data_size)
+                .unwrap_or(0);
+            let skip_budget = sess.config.max_early_data_size.max(ticket_max);
+            sess.early_data.reject(skip_budget as usize);
+        }
 
         let doing_client_auth = if full_handshake {
             let client_auth = self.emit_certificate_req_tls13(sess);
@@ -935,7 +1050,27 @@ impl ExpectClientHello {
         check_aligned_handshake(sess)?;
         self.emit_finished_tls13(sess);
 
-        if doing_client_auth {
+        if early_data {
+            let resume = resumedata.unwrap();
+            let suite = sess.common.get_suite_assert();
+            let mut key_schedule = KeySchedule::new(suite.get_hash(sess.common.provider()));
+            key_schedule.input_secret(&resume.master_secret.0);
+            let client_early_traffic_secret = key_schedule
+                .derive(SecretKind::ClientEarlyTrafficSecret, &client_hello_hash);
+            sess.config.key_log.log("CLIENT_EARLY_TRAFFIC_SECRET",
+                                    &self.handshake.randoms.client,
+                                    &client_early_traffic_secret);
+
+            // Client handshake traffic is now preceded by early data,
+            // protected with the early traffic secret.  The handshake
+            // key stays in the key schedule until EndOfEarlyData.
+            let dec = cipher::new_tls13_read(sess.common.provider(),
+                                             suite,
+                                             &client_early_traffic_secret);
+            sess.common.set_message_decrypter(dec);
+            sess.early_data.accept(resume.max_early_data_size as usize);
+            Ok(self.into_expect_tls13_early_data())
+        } else if doing_client_auth {
             Ok(self.into_expect_tls13_certificate())
         } else {
             Ok(self.into_expect_tls13_finished())
@@ -953,6 +1088,10 @@ impl ExpectClientHello {
 }
 
 impl State for ExpectClientHello {
+    fn name(&self) -> &'static str {
+        "ExpectClientHello"
+    }
+
     fn check_message(&self, m: &Message) -> CheckResult {
         check_handshake_message(m, &[HandshakeType::ClientHello])
     }
@@ -1023,7 +1162,7 @@ impl State for ExpectClientHello {
             let sni_ref = sni.as_ref().map(|dns_name| dns_name.as_ref());
             trace!("sni {:?}", sni_ref);
             trace!("sig schemes {:?}", sigschemes_ext);
-            let certkey = sess.config.cert_resolver.resolve(sni_ref, sigschemes_ext);
+            let certkey = sess.resolve_cert(sni_ref, sigschemes_ext);
             certkey.ok_or_else(|| {
                 sess.common.send_fatal_alert(AlertDescription::AccessDenied);
                 TLSError::General("no server certificate chain resolved".to_string())
@@ -1039,6 +1178,10 @@ impl State for ExpectClientHello {
         let protocol_version = sess.common.negotiated_version.unwrap();
         let suitable_suites = suites::reduce_given_version(&suitable_suites, protocol_version);
 
+        // And what our crypto provider can do
+        let suitable_suites = suites::reduce_given_provider(&suitable_suites,
+                                                            sess.common.provider());
+
         let maybe_ciphersuite = if sess.config.ignore_client_order {
             suites::choose_ciphersuite_preferring_server(&client_hello.cipher_suites, &suitable_suites)
         } else {
@@ -1053,7 +1196,7 @@ impl State for ExpectClientHello {
         sess.common.set_suite(maybe_ciphersuite.unwrap());
 
         // Start handshake hash.
-        if !self.handshake.transcript.start_hash(sess.common.get_suite_assert().get_hash()) {
+        if !self.handshake.transcript.start_hash(sess.common.get_suite_hash()) {
             sess.common.send_fatal_alert(AlertDescription::IllegalParameter);
             return Err(TLSError::PeerIncompatibleError("hash differed on retry"
                 .to_string()));
@@ -1199,6 +1342,10 @@ impl ExpectTLS12Certificate {
 }
 
 impl State for ExpectTLS12Certificate {
+    fn name(&self) -> &'static str {
+        "ExpectTLS12Certificate"
+    }
+
     fn check_message(&self, m: &Message) -> CheckResult {
         check_handshake_message(m, &[HandshakeType::Certificate])
     }
@@ -1251,6 +1398,10 @@ impl ExpectTLS13Certificate {
 }
 
 impl State for ExpectTLS13Certificate {
+    fn name(&self) -> &'static str {
+        "ExpectTLS13Certificate"
+    }
+
     fn check_message(&self, m: &Message) -> CheckResult {
         check_handshake_message(m, &[HandshakeType::Certificate])
     }
@@ -1317,6 +1468,10 @@ impl ExpectTLS12ClientKX {
 }
 
 impl State for ExpectTLS12ClientKX {
+    fn name(&self) -> &'static str {
+        "ExpectTLS12ClientKX"
+    }
+
     fn check_message(&self, m: &Message) -> CheckResult {
         check_handshake_message(m, &[HandshakeType::ClientKeyExchange])
     }
@@ -1337,7 +1492,7 @@ impl State for ExpectTLS12ClientKX {
             .ok_or_else(|| TLSError::PeerMisbehavedError("key exchange completion failed"
                                                          .to_string()))?;
 
-        let hashalg = sess.common.get_suite_assert().get_hash();
+        let hashalg = sess.common.get_suite_hash();
         let secrets = if self.handshake.using_ems {
             let handshake_hash = self.handshake.transcript.get_current_hash();
             SessionSecrets::new_ems(&self.handshake.randoms,
@@ -1380,6 +1535,10 @@ impl ExpectTLS12CertificateVerify {
 }
 
 impl State for ExpectTLS12CertificateVerify {
+    fn name(&self) -> &'static str {
+        "ExpectTLS12CertificateVerify"
+    }
+
     fn check_message(&self, m: &Message) -> CheckResult {
         check_handshake_message(m, &[HandshakeType::CertificateVerify])
     }
@@ -1422,6 +1581,10 @@ impl ExpectTLS13CertificateVerify {
 }
 
 impl State for ExpectTLS13CertificateVerify {
+    fn name(&self) -> &'static str {
+        "ExpectTLS13CertificateVerify"
+    }
+
     fn check_message(&self, m: &Message) -> CheckResult {
         check_handshake_message(m, &[HandshakeType::CertificateVerify])
     }
@@ -1470,6 +1633,10 @@ impl ExpectTLS12CCS {
 }
 
 impl State for ExpectTLS12CCS {
+    fn name(&self) -> &'static str {
+        "ExpectTLS12CCS"
+    }
+
     fn check_message(&self, m: &Message) -> CheckResult {
         check_message(m, &[ContentType::ChangeCipherSpec], &[])
     }
@@ -1606,6 +1773,10 @@ impl ExpectTLS12Finished {
 }
 
 impl State for ExpectTLS12Finished {
+    fn name(&self) -> &'static str {
+        "ExpectTLS12Finished"
+    }
+
     fn check_message(&self, m: &Message) -> CheckResult {
         check_handshake_message(m, &[HandshakeType::Finished])
     }
@@ -1657,6 +1828,56 @@ impl State for ExpectTLS12Finished {
     }
 }
 
+// --- Process client's early data (TLS1.3) ---
+pub struct ExpectTLS13EarlyData {
+    handshake: HandshakeDetails,
+    send_ticket: bool,
+}
+
+impl ExpectTLS13EarlyData {
+    fn into_expect_tls13_finished(self) -> NextState {
+        Box::new(ExpectTLS13Finished {
+            handshake: self.handshake,
+            send_ticket: self.send_ticket,
+        })
+    }
+}
+
+impl State for ExpectTLS13EarlyData {
+    fn name(&self) -> &'static str {
+        "ExpectTLS13EarlyData"
+    }
+
+    fn check_message(&self, m: &Message) -> CheckResult {
+        check_message(m,
+                      &[ContentType::ApplicationData, ContentType::Handshake],
+                      &[HandshakeType::EndOfEarlyData])
+    }
+
+    fn handle(mut self: Box<Self>, sess: &mut ServerSessionImpl, mut m: Message) -> NextStateOrError {
+        if m.is_content_type(ContentType::ApplicationData) {
+            if !sess.early_data.take_received(m.take_opaque_payload().unwrap()) {
+                sess.common.send_fatal_alert(AlertDescription::UnexpectedMessage);
+                return Err(TLSError::PeerMisbehavedError("too much early_data received"
+                                                         .to_string()));
+            }
+
+            return Ok(self);
+        }
+
+        // EndOfEarlyData: client traffic moves to the handshake key.
+        check_aligned_handshake(sess)?;
+        self.handshake.transcript.add_message(&m);
+
+        let suite = sess.common.get_suite_assert();
+        let read_key = sess.common.get_key_schedule().current_client_traffic_secret.clone();
+        let dec = cipher::new_tls13_read(sess.common.provider(), suite, &read_key);
+        sess.common.set_message_decrypter(dec);
+
+        Ok(self.into_expect_tls13_finished())
+    }
+}
+
 pub struct ExpectTLS13Finished {
     handshake: HandshakeDetails,
     send_ticket: bool,
@@ -1674,9 +1895,16 @@ impl ExpectTLS13Finished {
             return;
         }
 
-        let nonce = rand::random_vec(32);
-        let plain = get_server_session_value_tls13(&self.handshake, sess, &nonce)
-            .get_encoding();
+        let nonce = rand::random_vec(sess.common.provider(), 32);
+        let age_add = rand::random_u32(sess.common.provider());
+        let max_early_data_size = sess.config.max_early_data_size;
+
+        let mut value = get_server_session_value_tls13(&self.handshake, sess, &nonce);
+        value.set_times(ticketer::timebase(), age_add);
+        value.set_max_early_data_size(max_early_data_size);
+        value.set_alpn(sess.alpn_protocol.as_ref().map(|s| s.as_str()));
+
+        let plain = value.get_encoding();
         let maybe_ticket = sess.config
             .ticketer
             .encrypt(&plain);
@@ -1687,8 +1915,10 @@ impl ExpectTLS13Finished {
         }
 
         let ticket = maybe_ticket.unwrap();
-        let age_add = rand::random_u32(); // nb, we don't do 0-RTT data, so whatever
-        let payload = NewSessionTicketPayloadTLS13::new(ticket_lifetime, age_add, nonce, ticket);
+        let mut payload = NewSessionTicketPayloadTLS13::new(ticket_lifetime, age_add, nonce, ticket);
+        if max_early_data_size > 0 {
+            payload.exts.push(NewSessionTicketExtension::EarlyData(max_early_data_size));
+        }
         let m = Message {
             typ: ContentType::Handshake,
             version: ProtocolVersion::TLSv1_3,
@@ -1705,6 +1935,10 @@ impl ExpectTLS13Finished {
 }
 
 impl State for ExpectTLS13Finished {
+    fn name(&self) -> &'static str {
+        "ExpectTLS13Finished"
+    }
+
     fn check_message(&self, m: &Message) -> CheckResult {
         check_handshake_message(m, &[HandshakeType::Finished])
     }
@@ -1741,7 +1975,8 @@ impl State for ExpectTLS13Finished {
 
         let suite = sess.common.get_suite_assert();
         check_aligned_handshake(sess)?;
-        sess.common.set_message_decrypter(cipher::new_tls13_read(suite, &read_key));
+        let dec = cipher::new_tls13_read(sess.common.provider(), suite, &read_key);
+        sess.common.set_message_decrypter(dec);
         sess.common
             .get_mut_key_schedule()
             .current_client_traffic_secret = read_key;
@@ -1765,6 +2000,10 @@ impl ExpectTLS12Traffic {
 }
 
 impl State for ExpectTLS12Traffic {
+    fn name(&self) -> &'static str {
+        "ExpectTLS12Traffic"
+    }
+
     fn check_message(&self, m: &Message) -> CheckResult {
         check_message(m, &[ContentType::ApplicationData], &[])
     }
@@ -1792,6 +2031,10 @@ impl ExpectTLS13Traffic {
 }
 
 impl State for ExpectTLS13Traffic {
+    fn name(&self) -> &'static str {
+        "ExpectTLS13Traffic"
+    }
+
     fn check_message(&self, m: &Message) -> CheckResult {
         check_message(m,
                       &[ContentType::ApplicationData, ContentType::Handshake],
@@ -1,15 +1,21 @@
 use session::{Session, SessionCommon};
 use keylog::{KeyLog, NoKeyLog};
+use observer::{ObservesSession, NoObserver, Direction};
 use suites::{SupportedCipherSuite, ALL_CIPHERSUITES};
-use msgs::enums::{ContentType, SignatureScheme};
+use crypto::{CryptoProvider, RingProvider};
+use msgs::enums::{ContentType, SignatureScheme, CipherSuite};
 use msgs::enums::{AlertDescription, HandshakeType, ProtocolVersion};
-use msgs::handshake::{ServerExtension, SessionID};
-use msgs::message::Message;
+use msgs::handshake::{ServerExtension, SessionID, ClientHelloPayload};
+use msgs::handshake::{HandshakePayload, ConvertProtocolNameList};
+use msgs::handshake::ConvertServerNameList;
+use msgs::message::{Message, MessagePayload};
+use msgs::fragmenter::{MessageFragmenter, MAX_FRAGMENT_LEN};
+use msgs::base::Payload;
 use error::TLSError;
 use sign;
 use verify;
 use key;
-use vecbuf::WriteV;
+use vecbuf::{WriteV, ChunkVecBuffer};
 
 use webpki;
 
@@ -79,6 +85,25 @@ pub trait ProducesTickets : Send + Sync {
     fn decrypt(&self, cipher: &[u8]) -> Option<Vec<u8>>;
 }
 
+/// A trait for the ability to detect replays of TLS1.3 early data.
+///
+/// Early data is sent before the server has contributed any
+/// randomness to the handshake, so an attacker can replay a
+/// ClientHello and its early data to another server sharing the
+/// same ticket keys.  Before accepting early data, the server
+/// records a value unique to the ClientHello here.
+///
+/// `record` is a mutating operation; as with `StoresServerSessions`,
+/// implementations are expected to use interior mutability.
+pub trait DetectsEarlyDataReplay : Send + Sync {
+    /// Record that early data is being accepted for the ClientHello
+    /// identified by `key`.  Return `true` if `key` has not been
+    /// seen before and was recorded.  Return `false` if it is a
+    /// replay, or if it could not be recorded: early data is then
+    /// rejected, and the handshake continues without it.
+    fn record(&self, key: &[u8]) -> bool;
+}
+
 /// How to choose a certificate chain and signing key for use
 /// in server authentication.
 pub trait ResolvesServerCert : Send + Sync {
@@ -113,7 +138,8 @@ pub struct ServerConfig {
     /// How to store client sessions.
     pub session_storage: Arc<StoresServerSessions + Send + Sync>,
 
-    /// How to produce tickets.
+    /// How to produce tickets.  `Ticketer::with_provider` makes one
+    /// that uses `crypto_provider`.
     pub ticketer: Arc<ProducesTickets>,
 
     /// How to choose a server cert and key.
@@ -133,6 +159,44 @@ pub struct ServerConfig {
     /// How to output key material for debugging.  The default
     /// does nothing.
     pub key_log: Arc<KeyLog>,
+
+    /// Where to report what sessions do, for debugging.  The
+    /// default does nothing.
+    pub observer: Arc<ObservesSession>,
+
+    /// How many records to send under one TLS1.3 traffic key.
+    /// Once this many have been sent, we replace the key by sending
+    /// a KeyUpdate.  None means no limit.
+    ///
+    /// The default is 2^24 records, the limit RFC8446 gives for
+    /// AES-GCM.
+    pub key_update_after_records: Option<u64>,
+
+    /// Like `key_update_after_records`, but counting bytes of
+    /// plaintext.  The default is None.
+    pub key_update_after_bytes: Option<u64>,
+
+    /// Amount of early data to accept from TLS1.3 clients resuming
+    /// with a ticket issued by this server; 0 disables early data.
+    /// Read early data with `ServerSession::early_data`.
+    ///
+    /// Early data can be replayed by an attacker, so only enable
+    /// this for application protocols where that is acceptable.
+    ///
+    /// The default is 0.
+    pub max_early_data_size: u32,
+
+    /// How to detect replayed early data.  The default remembers
+    /// recent ClientHellos in memory, which only protects a single
+    /// server process.
+    pub early_data_replay: Arc<DetectsEarlyDataReplay>,
+
+    /// Where we get our cryptography from.  Ciphersuites in
+    /// `ciphersuites` that this provider does not support are
+    /// never chosen.
+    ///
+    /// The default is `RingProvider`.
+    pub crypto_provider: Arc<CryptoProvider>,
 }
 
 impl ServerConfig {
@@ -161,6 +225,12 @@ impl ServerConfig {
             versions: vec![ ProtocolVersion::TLSv1_3, ProtocolVersion::TLSv1_2 ],
             verifier: client_cert_verifier,
             key_log: Arc::new(NoKeyLog {}),
+            observer: Arc::new(NoObserver {}),
+            key_update_after_records: Some(1 << 24),
+            key_update_after_bytes: None,
+            max_early_data_size: 0,
+            early_data_replay: handy::EarlyDataReplayMemoryCache::new(1024),
+            crypto_provider: Arc::new(RingProvider {}),
         }
     }
 
@@ -230,6 +300,157 @@ impl ServerConfig {
     }
 }
 
+// The AEAD tag and inner content type of a TLS1.3 record
+// are not counted against max_early_data_size.
+const TLS13_RECORD_OVERHEAD: usize = 16 + 1;
+
+#[derive(Debug, PartialEq)]
+enum EarlyDataState {
+    New,
+    Accepted,
+    Skipping,
+    Rejected,
+}
+
+/// Tracks the early data we accepted, or how much of the
+/// early data we rejected we are still prepared to skip.
+pub struct EarlyData {
+    state: EarlyDataState,
+    left: usize,
+    received: ChunkVecBuffer,
+}
+
+impl EarlyData {
+    fn new() -> EarlyData {
+        EarlyData {
+            state: EarlyDataState::New,
+            left: 0,
+            received: ChunkVecBuffer::new(),
+        }
+    }
+
+    pub fn accept(&mut self, max_size: usize) {
+        debug_assert_eq!(self.state, EarlyDataState::New);
+        self.state = EarlyDataState::Accepted;
+        self.left = max_size;
+    }
+
+    /// We rejected early data: skip over up to `max_size` bytes
+    /// of it until the client's next flight arrives.
+    pub fn reject(&mut self, max_size: usize) {
+        debug_assert_eq!(self.state, EarlyDataState::New);
+        self.state = EarlyDataState::Skipping;
+        self.left = max_size;
+    }
+
+    pub fn is_accepted(&self) -> bool {
+        self.state == EarlyDataState::Accepted
+    }
+
+    fn is_skipping(&self) -> bool {
+        self.state == EarlyDataState::Skipping
+    }
+
+    fn stop_skipping(&mut self) {
+        self.state = EarlyDataState::Rejected;
+    }
+
+    /// Take accepted early data.  Returns false if this exceeds
+    /// what we said we would accept.
+    pub fn take_received(&mut self, bytes: Payload) -> bool {
+        if bytes.0.len() > self.left {
+            return false;
+        }
+
+        self.left -= bytes.0.len();
+        self.received.append(bytes.0);
+        true
+    }
+
+    fn skip(&mut self, record_len: usize) -> bool {
+        let len = record_len.saturating_sub(TLS13_RECORD_OVERHEAD);
+        if len > self.left {
+            return false;
+        }
+
+        self.left -= len;
+        true
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.received.read(buf)
+    }
+}
+
+/// Stub that implements io::Read and reads early data.
+pub struct ReadEarlyData<'a> {
+    early_data: &'a mut EarlyData,
+}
+
+impl<'a> ReadEarlyData<'a> {
+    fn new(early_data: &'a mut EarlyData) -> ReadEarlyData<'a> {
+        ReadEarlyData { early_data }
+    }
+}
+
+impl<'a> io::Read for ReadEarlyData<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.early_data.read(buf)
+    }
+}
+
+/// The parts of a client's ClientHello which an application
+/// can inspect before deciding how to continue the handshake.
+///
+/// See `ServerSession::new_pausing`.
+#[derive(Debug)]
+pub struct ClientHello {
+    server_name: Option<webpki::DNSName>,
+    alpn: Vec<String>,
+    sigschemes: Vec<SignatureScheme>,
+    cipher_suites: Vec<CipherSuite>,
+}
+
+impl ClientHello {
+    fn new(payload: &ClientHelloPayload) -> ClientHello {
+        ClientHello {
+            server_name: payload.get_sni_extension()
+                .and_then(|sni| sni.get_hostname())
+                .map(|name| name.into()),
+            alpn: payload.get_alpn_extension()
+                .map(|protos| protos.to_strings())
+                .unwrap_or_else(Vec::new),
+            sigschemes: payload.get_sigalgs_extension()
+                .cloned()
+                .unwrap_or_else(Vec::new),
+            cipher_suites: payload.cipher_suites.clone(),
+        }
+    }
+
+    /// The server name the client asked for via SNI, if any.
+    pub fn server_name(&self) -> Option<webpki::DNSNameRef> {
+        self.server_name.as_ref().map(|name| name.as_ref())
+    }
+
+    /// The ALPN protocols offered by the client, in its order
+    /// of preference.  Empty if the client did not offer ALPN.
+    pub fn alpn(&self) -> &[String] {
+        &self.alpn
+    }
+
+    /// The signature schemes the client supports.  Empty if the
+    /// client did not send the signature_algorithms extension.
+    pub fn signature_schemes(&self) -> &[SignatureScheme] {
+        &self.sigschemes
+    }
+
+    /// The cipher suites offered by the client, in its order
+    /// of preference.
+    pub fn cipher_suites(&self) -> &[CipherSuite] {
+        &self.cipher_suites
+    }
+}
+
 pub struct ServerSessionImpl {
     pub config: Arc<ServerConfig>,
     pub common: SessionCommon,
@@ -239,6 +460,10 @@ pub struct ServerSessionImpl {
     pub error: Option<TLSError>,
     pub state: Option<Box<hs::State + Send + Sync>>,
     pub client_cert_chain: Option<Vec<key::Certificate>>,
+    pub early_data: EarlyData,
+    pause_at_client_hello: bool,
+    paused: Option<(ClientHello, Message)>,
+    chosen_certkey: Option<sign::CertifiedKey>,
 }
 
 impl fmt::Debug for ServerSessionImpl {
@@ -251,16 +476,30 @@ impl ServerSessionImpl {
     pub fn new(server_config: &Arc<ServerConfig>, extra_exts: Vec<ServerExtension>)
                -> ServerSessionImpl {
         let perhaps_client_auth = server_config.verifier.offer_client_auth();
+        let state = hs::ExpectClientHello::new(&*server_config.crypto_provider,
+                                               perhaps_client_auth,
+                                               extra_exts);
+
+        let mut common = SessionCommon::new(server_config.mtu,
+                                            false,
+                                            server_config.crypto_provider.clone(),
+                                            server_config.observer.clone());
+        common.set_key_update_limits(server_config.key_update_after_records,
+                                     server_config.key_update_after_bytes);
 
         ServerSessionImpl {
             config: server_config.clone(),
-            common: SessionCommon::new(server_config.mtu, false),
+            common,
             sni: None,
             alpn_protocol: None,
             quic_params: None,
             error: None,
-            state: Some(Box::new(hs::ExpectClientHello::new(perhaps_client_auth, extra_exts))),
+            state: Some(Box::new(state)),
             client_cert_chain: None,
+            early_data: EarlyData::new(),
+            pause_at_client_hello: false,
+            paused: None,
+            chosen_certkey: None,
         }
     }
 
@@ -287,6 +526,8 @@ impl ServerSessionImpl {
     }
 
     pub fn process_msg(&mut self, mut msg: Message) -> Result<(), TLSError> {
+        self.common.observe_record(&msg);
+
         // TLS1.3: drop CCS at any time during handshaking
         if self.common.is_tls13()
             && msg.is_content_type(ContentType::ChangeCipherSpec)
@@ -295,10 +536,27 @@ impl ServerSessionImpl {
             return Ok(());
         }
 
-        // Decrypt if demanded by current state.
+        // Decrypt if demanded by current state.  If we rejected
+        // early data, records we can't decrypt are skipped until
+        // the client's next flight arrives.
         if self.common.peer_encrypting {
-            let dm = self.common.decrypt_incoming(msg)?;
-            msg = dm;
+            if self.early_data.is_skipping() {
+                let len = msg.payload.length();
+                match self.common.trial_decrypt_incoming(msg)? {
+                    Some(dm) => {
+                        self.early_data.stop_skipping();
+                        msg = dm;
+                    }
+                    None => return self.skip_early_data(len),
+                }
+            } else {
+                let dm = self.common.decrypt_incoming(msg)?;
+                msg = dm;
+            }
+        } else if self.early_data.is_skipping() &&
+            msg.is_content_type(ContentType::ApplicationData) {
+            // Early data sent before our HelloRetryRequest.
+            return self.skip_early_data(msg.payload.length());
         }
 
         // For handshake messages, we need to join them before parsing
@@ -325,11 +583,25 @@ impl ServerSessionImpl {
     fn process_new_handshake_messages(&mut self) -> Result<(), TLSError> {
         while let Some(msg) = self.common.handshake_joiner.frames.pop_front() {
             self.process_main_protocol(msg)?;
+
+            if self.is_paused() {
+                break;
+            }
         }
 
         Ok(())
     }
 
+    fn skip_early_data(&mut self, len: usize) -> Result<(), TLSError> {
+        if self.early_data.skip(len) {
+            trace!("Skipping {} bytes of rejected early data", len);
+            Ok(())
+        } else {
+            self.common.send_fatal_alert(AlertDescription::BadRecordMac);
+            Err(TLSError::DecryptError)
+        }
+    }
+
     fn queue_unexpected_alert(&mut self) {
         self.common.send_fatal_alert(AlertDescription::UnexpectedMessage);
     }
@@ -341,11 +613,21 @@ impl ServerSessionImpl {
             return Ok(());
         }
 
+        if self.pause_at_client_hello &&
+           msg.is_handshake_type(HandshakeType::ClientHello) {
+            return self.pause(msg);
+        }
+
+        self.common.observe_message(Direction::Received, &msg);
+
         let st = self.state.take().unwrap();
         st.check_message(&msg)
             .map_err(|err| { self.queue_unexpected_alert(); err })?;
 
-        self.state = Some(st.handle(self, msg)?);
+        let from = st.name();
+        let next = st.handle(self, msg)?;
+        self.common.observe_state_change(from, next.name());
+        self.state = Some(next);
 
         Ok(())
     }
@@ -359,7 +641,14 @@ impl ServerSessionImpl {
             return Err(TLSError::CorruptMessage);
         }
 
-        while let Some(msg) = self.common.message_deframer.frames.pop_front() {
+        // Anything after a paused ClientHello stays queued until
+        // the application resumes the handshake.
+        while !self.is_paused() {
+            let msg = match self.common.message_deframer.frames.pop_front() {
+                Some(msg) => msg,
+                None => break,
+            };
+
             match self.process_msg(msg) {
                 Ok(_) => {}
                 Err(err) => {
@@ -373,6 +662,79 @@ impl ServerSessionImpl {
         Ok(())
     }
 
+    fn pause(&mut self, msg: Message) -> Result<(), TLSError> {
+        let client_hello = match msg.payload {
+            MessagePayload::Handshake(ref hsp) => match hsp.payload {
+                HandshakePayload::ClientHello(ref ch) => ClientHello::new(ch),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+
+        trace!("Pausing handshake at {:?}", client_hello);
+        self.pause_at_client_hello = false;
+        self.paused = Some((client_hello, msg));
+        Ok(())
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.is_some()
+    }
+
+    pub fn get_client_hello(&self) -> Option<&ClientHello> {
+        self.paused.as_ref().map(|&(ref client_hello, _)| client_hello)
+    }
+
+    pub fn resume(&mut self,
+                  config: &Arc<ServerConfig>,
+                  certkey: Option<sign::CertifiedKey>) -> Result<(), TLSError> {
+        let msg = match self.paused.take() {
+            Some((_, msg)) => msg,
+            None => {
+                return Err(TLSError::General("handshake is not paused".to_string()));
+            }
+        };
+
+        if !Arc::ptr_eq(&self.config, config) {
+            self.set_config(config);
+        }
+        self.chosen_certkey = certkey;
+
+        let result = self.process_main_protocol(msg)
+            .and_then(|_| self.process_new_handshake_messages());
+        if let Err(err) = result {
+            self.error = Some(err.clone());
+            return Err(err);
+        }
+
+        self.process_new_packets()
+    }
+
+    // Nothing in `common` or `state` depends on the config until the
+    // ClientHello is handled, so we can rebuild them here.
+    fn set_config(&mut self, config: &Arc<ServerConfig>) {
+        let state = hs::ExpectClientHello::new(&*config.crypto_provider,
+                                               config.verifier.offer_client_auth(),
+                                               vec![]);
+        self.state = Some(Box::new(state));
+        self.common.set_provider(config.crypto_provider.clone());
+        self.common.observer = config.observer.clone();
+        self.common.set_key_update_limits(config.key_update_after_records,
+                                          config.key_update_after_bytes);
+        self.common.message_fragmenter =
+            MessageFragmenter::new(config.mtu.unwrap_or(MAX_FRAGMENT_LEN));
+        self.config = config.clone();
+    }
+
+    pub fn resolve_cert(&self,
+                        server_name: Option<webpki::DNSNameRef>,
+                        sigschemes: &[SignatureScheme])
+                        -> Option<sign::CertifiedKey> {
+        self.chosen_certkey
+            .clone()
+            .or_else(|| self.config.cert_resolver.resolve(server_name, sigschemes))
+    }
+
     pub fn get_peer_certificates(&self) -> Option<Vec<key::Certificate>> {
         if self.client_cert_chain.is_none() {
             return None;
@@ -427,6 +789,48 @@ impl ServerSession {
         ServerSession { imp: ServerSessionImpl::new(config, vec![]) }
     }
 
+    /// Make a new ServerSession which stops once it has received
+    /// the client's ClientHello, before choosing a certificate or
+    /// ciphersuite.
+    ///
+    /// This lets the application look at the ClientHello using
+    /// `client_hello`, and then -- perhaps after some asynchronous
+    /// work, like fetching a certificate -- continue the handshake
+    /// with `resume_handshake`.  While paused, `process_new_packets`
+    /// leaves any further received records unprocessed, and the
+    /// session has nothing to write.
+    pub fn new_pausing(config: &Arc<ServerConfig>) -> ServerSession {
+        let mut imp = ServerSessionImpl::new(config, vec![]);
+        imp.pause_at_client_hello = true;
+        ServerSession { imp }
+    }
+
+    /// Returns the client's ClientHello while the handshake is
+    /// paused after receiving it, and None otherwise.
+    ///
+    /// This only ever returns Some for sessions made with
+    /// `new_pausing`.
+    pub fn client_hello(&self) -> Option<&ClientHello> {
+        self.imp.get_client_hello()
+    }
+
+    /// Continues a handshake paused after the ClientHello.
+    ///
+    /// `config` replaces the config given when the session was
+    /// made; pass the same one to keep it.  If `certkey` is
+    /// given, it is used for server authentication instead of
+    /// asking `config`'s `cert_resolver`.
+    ///
+    /// This processes the ClientHello and any records received
+    /// after it, exactly like `process_new_packets`.  It is an
+    /// error to call this when `client_hello` returns None.
+    pub fn resume_handshake(&mut self,
+                            config: &Arc<ServerConfig>,
+                            certkey: Option<sign::CertifiedKey>)
+                            -> Result<(), TLSError> {
+        self.imp.resume(config, certkey)
+    }
+
     /// Retrieves the SNI hostname, if any, used to select the certificate and
     /// private key.
     ///
@@ -446,6 +850,36 @@ impl ServerSession {
     pub fn get_sni_hostname(&self)-> Option<&str> {
         self.imp.get_sni().map(|s| s.as_ref().into())
     }
+
+    /// Returns an `io::Read` implementor you can read TLS1.3
+    /// early data (a.k.a. "0-RTT data") from.
+    ///
+    /// This returns None unless we accepted early data from
+    /// the client.  Early data arrives before the client's
+    /// Finished message, so it is available while the session
+    /// is still handshaking.
+    pub fn early_data(&mut self) -> Option<ReadEarlyData> {
+        if self.imp.early_data.is_accepted() {
+            Some(ReadEarlyData::new(&mut self.imp.early_data))
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if we accepted early data from the client.
+    pub fn is_early_data_accepted(&self) -> bool {
+        self.imp.early_data.is_accepted()
+    }
+
+    /// Replaces the keys protecting data in both directions, by
+    /// sending a TLS1.3 KeyUpdate which asks the peer to do the same.
+    ///
+    /// The KeyUpdate is sent by the next `write_tls` call.  This
+    /// fails if the handshake is not complete, or if TLS1.3 was
+    /// not negotiated.
+    pub fn refresh_traffic_keys(&mut self) -> Result<(), TLSError> {
+        self.imp.common.refresh_traffic_keys()
+    }
 }
 
 impl Session for ServerSession {
@@ -1,4 +1,3 @@
-use ring;
 use std::io::{Read, Write};
 use msgs::message::{BorrowMessage, Message, MessagePayload};
 use msgs::deframer::MessageDeframer;
@@ -17,9 +16,12 @@ use key_schedule::{SecretKind, KeySchedule};
 use prf;
 use rand;
 use quic;
+use crypto::{CryptoProvider, Hash};
+use observer::{ObservesSession, SessionEvent, Direction};
 
 use std::io;
 use std::collections::VecDeque;
+use std::sync::Arc;
 
 /// Generalises `ClientSession` and `ServerSession`
 pub trait Session: quic::QuicExt + Read + Write + Send + Sync {
@@ -212,25 +214,25 @@ pub struct SessionRandoms {
 }
 
 impl SessionRandoms {
-    pub fn for_server() -> SessionRandoms {
+    pub fn for_server(provider: &CryptoProvider) -> SessionRandoms {
         let mut ret = SessionRandoms {
             we_are_client: false,
             client: [0u8; 32],
             server: [0u8; 32],
         };
 
-        rand::fill_random(&mut ret.server);
+        rand::fill_random(provider, &mut ret.server);
         ret
     }
 
-    pub fn for_client() -> SessionRandoms {
+    pub fn for_client(provider: &CryptoProvider) -> SessionRandoms {
         let mut ret = SessionRandoms {
             we_are_client: true,
             client: [0u8; 32],
             server: [0u8; 32],
         };
 
-        rand::fill_random(&mut ret.client);
+        rand::fill_random(provider, &mut ret.client);
         ret
     }
 }
@@ -244,13 +246,13 @@ fn join_randoms(first: &[u8], second: &[u8]) -> [u8; 64] {
 
 pub struct SessionSecrets {
     pub randoms: SessionRandoms,
-    hash: &'static ring::digest::Algorithm,
+    hash: &'static Hash,
     pub master_secret: [u8; 48],
 }
 
 impl SessionSecrets {
     pub fn new(randoms: &SessionRandoms,
-               hashalg: &'static ring::digest::Algorithm,
+               hashalg: &'static Hash,
                pms: &[u8])
                -> SessionSecrets {
         let mut ret = SessionSecrets {
@@ -270,7 +272,7 @@ impl SessionSecrets {
 
     pub fn new_ems(randoms: &SessionRandoms,
                    hs_hash: &[u8],
-                   hashalg: &'static ring::digest::Algorithm,
+                   hashalg: &'static Hash,
                    pms: &[u8]) -> SessionSecrets {
         let mut ret = SessionSecrets {
             randoms: randoms.clone(),
@@ -287,7 +289,7 @@ impl SessionSecrets {
     }
 
     pub fn new_resume(randoms: &SessionRandoms,
-                      hashalg: &'static ring::digest::Algorithm,
+                      hashalg: &'static Hash,
                       master_secret: &[u8])
                       -> SessionSecrets {
         let mut ret = SessionSecrets {
@@ -374,6 +376,7 @@ enum Limit {
 pub struct SessionCommon {
     pub negotiated_version: Option<ProtocolVersion>,
     pub is_client: bool,
+    provider: Arc<CryptoProvider>,
     message_encrypter: Box<MessageEncrypter>,
     message_decrypter: Box<MessageDecrypter>,
     pub secrets: Option<SessionSecrets>,
@@ -386,19 +389,29 @@ pub struct SessionCommon {
     pub we_encrypting: bool,
     pub traffic: bool,
     pub want_write_key_update: bool,
+    request_peer_key_update: bool,
+    key_update_after_records: Option<u64>,
+    key_update_after_bytes: Option<u64>,
+    records_since_key_update: u64,
+    bytes_since_key_update: u64,
     pub message_deframer: MessageDeframer,
     pub handshake_joiner: HandshakeJoiner,
     pub message_fragmenter: MessageFragmenter,
     received_plaintext: ChunkVecBuffer,
     sendable_plaintext: ChunkVecBuffer,
     pub sendable_tls: ChunkVecBuffer,
+    pub observer: Arc<ObservesSession>,
 }
 
 impl SessionCommon {
-    pub fn new(mtu: Option<usize>, client: bool) -> SessionCommon {
+    pub fn new(mtu: Option<usize>,
+               client: bool,
+               provider: Arc<CryptoProvider>,
+               observer: Arc<ObservesSession>) -> SessionCommon {
         SessionCommon {
             negotiated_version: None,
             is_client: client,
+            provider,
             suite: None,
             message_encrypter: MessageEncrypter::invalid(),
             message_decrypter: MessageDecrypter::invalid(),
@@ -411,12 +424,57 @@ impl SessionCommon {
             we_encrypting: false,
             traffic: false,
             want_write_key_update: false,
+            request_peer_key_update: false,
+            key_update_after_records: None,
+            key_update_after_bytes: None,
+            records_since_key_update: 0,
+            bytes_since_key_update: 0,
             message_deframer: MessageDeframer::new(),
             handshake_joiner: HandshakeJoiner::new(),
             message_fragmenter: MessageFragmenter::new(mtu.unwrap_or(MAX_FRAGMENT_LEN)),
             received_plaintext: ChunkVecBuffer::new(),
             sendable_plaintext: ChunkVecBuffer::new(),
             sendable_tls: ChunkVecBuffer::new(),
+            observer,
+        }
+    }
+
+    pub fn observe(&self, event: SessionEvent) {
+        self.observer.observe(&event);
+    }
+
+    /// Report a received record, before any decryption.
+    pub fn observe_record(&self, m: &Message) {
+        self.observe(SessionEvent::Record {
+            direction: Direction::Received,
+            typ: m.typ,
+            length: m.payload.length(),
+        });
+    }
+
+    pub fn observe_state_change(&self, from: &'static str, to: &'static str) {
+        if from != to {
+            self.observe(SessionEvent::StateChange { from, to });
+        }
+    }
+
+    /// Report `m` if it's a handshake message or alert.
+    pub fn observe_message(&self, direction: Direction, m: &Message) {
+        match m.payload {
+            MessagePayload::Handshake(ref hsp) => {
+                self.observe(SessionEvent::HandshakeMessage {
+                    direction,
+                    typ: hsp.typ,
+                });
+            }
+            MessagePayload::Alert(ref alert) => {
+                self.observe(SessionEvent::Alert {
+                    direction,
+                    level: alert.level,
+                    description: alert.description,
+                });
+            }
+            _ => {}
         }
     }
 
@@ -427,6 +485,14 @@ impl SessionCommon {
       }
     }
 
+    pub fn provider(&self) -> &CryptoProvider {
+        &*self.provider
+    }
+
+    pub fn set_provider(&mut self, provider: Arc<CryptoProvider>) {
+        self.provider = provider;
+    }
+
     pub fn get_suite(&self) -> Option<&'static SupportedCipherSuite> {
         self.suite
     }
@@ -435,10 +501,21 @@ impl SessionCommon {
         self.suite.as_ref().unwrap()
     }
 
+    /// The hash function of the negotiated suite.
+    pub fn get_suite_hash(&self) -> &'static Hash {
+        self.get_suite_assert().get_hash(self.provider())
+    }
+
     pub fn set_suite(&mut self, suite: &'static SupportedCipherSuite) -> bool {
         match self.suite {
             None => {
                 self.suite = Some(suite);
+                if let Some(version) = self.negotiated_version {
+                    self.observe(SessionEvent::Negotiated {
+                        version,
+                        suite: suite.suite,
+                    });
+                }
                 true
             }
             Some(s) if s == suite => {
@@ -465,6 +542,8 @@ impl SessionCommon {
                                  cipher: Box<MessageEncrypter>) {
         self.message_encrypter = cipher;
         self.write_seq = 0;
+        self.records_since_key_update = 0;
+        self.bytes_since_key_update = 0;
         self.we_encrypting = true;
     }
 
@@ -509,7 +588,26 @@ impl SessionCommon {
         ret
     }
 
+    /// Like `decrypt_incoming`, but a record which fails to decrypt
+    /// is reported as `Ok(None)` and does not consume a sequence number.
+    ///
+    /// TLS1.3 servers use this to skip over early data they have
+    /// rejected, which is protected with keys they do not have.
+    pub fn trial_decrypt_incoming(&mut self, encr: Message) -> Result<Option<Message>, TLSError> {
+        let seq = self.read_seq;
+        match self.message_decrypter.decrypt(encr, seq) {
+            Ok(plain) => {
+                self.read_seq += 1;
+                Ok(Some(plain))
+            }
+            Err(TLSError::DecryptError) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
     pub fn process_alert(&mut self, msg: Message) -> Result<(), TLSError> {
+        self.observe_message(Direction::Received, &msg);
+
         if let MessagePayload::Alert(ref alert) = msg.payload {
             // Reject unknown AlertLevels.
             if let AlertLevel::Unknown(_) = alert.level {
@@ -540,6 +638,38 @@ impl SessionCommon {
         }
     }
 
+    /// Sets how much data we encrypt with one TLS1.3 traffic key
+    /// before replacing it.
+    pub fn set_key_update_limits(&mut self, records: Option<u64>, bytes: Option<u64>) {
+        self.key_update_after_records = records;
+        self.key_update_after_bytes = bytes;
+    }
+
+    fn key_update_due(&self) -> bool {
+        if !self.traffic || !self.is_tls13() {
+            return false;
+        }
+
+        self.key_update_after_records
+            .map_or(false, |limit| self.records_since_key_update >= limit) ||
+            self.key_update_after_bytes
+                .map_or(false, |limit| self.bytes_since_key_update >= limit)
+    }
+
+    pub fn refresh_traffic_keys(&mut self) -> Result<(), TLSError> {
+        if !self.traffic {
+            return Err(TLSError::HandshakeNotComplete);
+        }
+
+        if !self.is_tls13() {
+            return Err(TLSError::General("KeyUpdate requires TLS1.3".to_string()));
+        }
+
+        self.request_peer_key_update = true;
+        self.do_write_key_update();
+        Ok(())
+    }
+
     fn do_write_key_update(&mut self) {
         // TLS1.3 putting key update triggering here breaks layering
         // between the handshake and record layer.
@@ -549,12 +679,23 @@ impl SessionCommon {
             SecretKind::ServerApplicationTrafficSecret
         };
 
+        let msg = if self.request_peer_key_update {
+            Message::build_key_update_request()
+        } else {
+            Message::build_key_update_notify()
+        };
+
+        // Reset these now, so sending the KeyUpdate doesn't
+        // itself look like it needs a key update.
         self.want_write_key_update = false;
-        self.send_msg_encrypt(Message::build_key_update_notify());
+        self.request_peer_key_update = false;
+        self.records_since_key_update = 0;
+        self.bytes_since_key_update = 0;
+        self.send_msg_encrypt(msg);
 
         let write_key = self.get_key_schedule().derive_next(kind);
         let scs = self.get_suite_assert();
-        self.set_message_encrypter(cipher::new_tls13_write(scs, &write_key));
+        self.set_message_encrypter(cipher::new_tls13_write(&*self.provider, scs, &write_key));
 
         if self.is_client {
             self.get_mut_key_schedule().current_client_traffic_secret = write_key;
@@ -566,6 +707,8 @@ impl SessionCommon {
     /// Fragment `m`, encrypt the fragments, and then queue
     /// the encrypted fragments for sending.
     pub fn send_msg_encrypt(&mut self, m: Message) {
+        self.observe_message(Direction::Sent, &m);
+
         if self.want_write_key_update {
             self.do_write_key_update();
         }
@@ -621,6 +764,13 @@ impl SessionCommon {
             return;
         }
 
+        // Limit how much we encrypt with one key, if we can.
+        if self.key_update_due() {
+            self.do_write_key_update();
+        }
+
+        self.records_since_key_update += 1;
+        self.bytes_since_key_update += m.payload.len() as u64;
         let em = self.encrypt_outgoing(m);
         self.queue_tls_message(em);
     }
@@ -657,6 +807,20 @@ impl SessionCommon {
     }
 
 
+    /// Send TLS1.3 early data.  The caller is responsible for having
+    /// installed the early traffic keys, and for bounding `data` by the
+    /// server's `max_early_data_size`.
+    pub fn send_early_plaintext(&mut self, data: &[u8]) -> usize {
+        debug_assert!(self.we_encrypting);
+
+        if data.is_empty() {
+            // Don't send empty fragments.
+            return 0;
+        }
+
+        self.send_appdata_encrypt(data, Limit::Yes)
+    }
+
     fn send_plain(&mut self, data: &[u8], limit: Limit) -> io::Result<usize> {
         if !self.traffic {
             // If we haven't completed handshaking, buffer
@@ -699,12 +863,19 @@ impl SessionCommon {
 
     // Put m into sendable_tls for writing.
     fn queue_tls_message(&mut self, m: Message) {
+        self.observe(SessionEvent::Record {
+            direction: Direction::Sent,
+            typ: m.typ,
+            length: m.payload.length(),
+        });
         self.sendable_tls.append(m.get_encoding());
     }
 
     /// Send a raw TLS message, fragmenting it if needed.
     pub fn send_msg(&mut self, m: Message, must_encrypt: bool) {
         if !must_encrypt {
+            self.observe_message(Direction::Sent, &m);
+
             let mut to_send = VecDeque::new();
             self.message_fragmenter.fragment(m, &mut to_send);
             for mm in to_send {
@@ -731,7 +902,7 @@ impl SessionCommon {
     }
 
     pub fn start_encryption_tls12(&mut self, secrets: SessionSecrets) {
-        let (dec, enc) = cipher::new_tls12(self.get_suite_assert(), &secrets);
+        let (dec, enc) = cipher::new_tls12(&*self.provider, self.get_suite_assert(), &secrets);
         self.message_encrypter = enc;
         self.message_decrypter = dec;
         self.secrets = Some(secrets);
@@ -788,7 +959,7 @@ impl SessionCommon {
         let new_read_key = self.get_key_schedule()
             .derive_next(read_kind);
         let suite = self.get_suite_assert();
-        self.set_message_decrypter(cipher::new_tls13_read(suite, &new_read_key));
+        self.set_message_decrypter(cipher::new_tls13_read(&*self.provider, suite, &new_read_key));
 
         if read_kind == SecretKind::ServerApplicationTrafficSecret {
             self.get_mut_key_schedule().current_server_traffic_secret = new_read_key;
@@ -142,10 +142,16 @@ pub fn any_supported_type(der: &key::PrivateKey) -> Result<Box<SigningKey>, ()>
         return Ok(Box::new(rsa));
     }
 
-    any_ecdsa_type(der)
+    if let Ok(ecdsa) = any_ecdsa_type(der) {
+        return Ok(ecdsa);
+    }
+
+    any_eddsa_type(der)
 }
 
 /// Parse `der` as any ECDSA key type, returning the first which works.
+///
+/// `der` may be in PKCS#8 format or SEC1 (`BEGIN EC PRIVATE KEY`) format.
 pub fn any_ecdsa_type(der: &key::PrivateKey) -> Result<Box<SigningKey>, ()> {
     if let Ok(ecdsa_p256) = SingleSchemeSigningKey::new(der,
                                                         SignatureScheme::ECDSA_NISTP256_SHA256,
@@ -162,6 +168,17 @@ pub fn any_ecdsa_type(der: &key::PrivateKey) -> Result<Box<SigningKey>, ()> {
     Err(())
 }
 
+/// Parse `der` as any EdDSA key type, returning the first which works.
+///
+/// `der` must be in PKCS#8 format (`BEGIN PRIVATE KEY`).
+pub fn any_eddsa_type(der: &key::PrivateKey) -> Result<Box<SigningKey>, ()> {
+    if let Ok(ed25519) = Ed25519SigningKey::new(der) {
+        return Ok(Box::new(ed25519));
+    }
+
+    Err(())
+}
+
 /// A `SigningKey` for RSA-PKCS1 or RSA-PSS
 pub struct RSASigningKey {
     key: Arc<RSAKeyPair>,
@@ -252,25 +269,86 @@ impl Signer for RSASigner {
 /// (PSS signatures) -- nobody is willing to obtain certificates for
 /// different protocol versions.
 ///
-/// Currently this is only implemented for ECDSA keys.
+/// Currently this is only implemented for ECDSA keys.  Ed25519 keys
+/// are similar, but see `Ed25519SigningKey`.
 struct SingleSchemeSigningKey {
     key: Arc<signature::KeyPair>,
     scheme: SignatureScheme,
 }
 
 impl SingleSchemeSigningKey {
-    /// Make a new `ECDSASigningKey` from a DER encoding in PKCS#8 format,
-    /// expecting a key usable with precisely the given signature scheme.
+    /// Make a new `ECDSASigningKey` from a DER encoding in PKCS#8 or
+    /// SEC1 format, expecting a key usable with precisely the given
+    /// signature scheme.
     pub fn new(der: &key::PrivateKey,
                scheme: SignatureScheme,
                sigalg: &'static signature::SigningAlgorithm) -> Result<SingleSchemeSigningKey, ()> {
         signature::key_pair_from_pkcs8(sigalg,
                                        untrusted::Input::from(&der.0))
+            .or_else(|_| {
+                let algorithm_id = ec_algorithm_id(scheme).ok_or(())?;
+                let pkcs8 = sec1_to_pkcs8(algorithm_id, &der.0).ok_or(())?;
+                signature::key_pair_from_pkcs8(sigalg,
+                                               untrusted::Input::from(&pkcs8))
+                    .map_err(|_| ())
+            })
             .map(|kp| SingleSchemeSigningKey { key: Arc::new(kp), scheme })
             .map_err(|_| ())
     }
 }
 
+/// DER-encoded AlgorithmIdentifiers for EC public keys on each curve.
+static EC_P256_ALGORITHM_ID: &'static [u8] = &[
+    0x30, 0x13,
+    0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, // id-ecPublicKey
+    0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07, // secp256r1
+];
+
+static EC_P384_ALGORITHM_ID: &'static [u8] = &[
+    0x30, 0x10,
+    0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, // id-ecPublicKey
+    0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x22, // secp384r1
+];
+
+fn ec_algorithm_id(scheme: SignatureScheme) -> Option<&'static [u8]> {
+    match scheme {
+        SignatureScheme::ECDSA_NISTP256_SHA256 => Some(EC_P256_ALGORITHM_ID),
+        SignatureScheme::ECDSA_NISTP384_SHA384 => Some(EC_P384_ALGORITHM_ID),
+        _ => None,
+    }
+}
+
+/// Wrap `sec1`, a DER-encoded RFC5915 ECPrivateKey, in a PKCS#8
+/// PrivateKeyInfo with the given AlgorithmIdentifier.  *ring* only
+/// loads private keys in PKCS#8 format.
+///
+/// Returns None if `sec1` is too long to be a real key.
+fn sec1_to_pkcs8(algorithm_id: &[u8], sec1: &[u8]) -> Option<Vec<u8>> {
+    let mut info = vec![0x02, 0x01, 0x00]; // version 0
+    info.extend_from_slice(algorithm_id);
+    der_wrap(0x04, sec1, &mut info)?;
+
+    let mut pkcs8 = Vec::new();
+    der_wrap(0x30, &info, &mut pkcs8)?;
+    Some(pkcs8)
+}
+
+fn der_wrap(tag: u8, contents: &[u8], out: &mut Vec<u8>) -> Option<()> {
+    let len = contents.len();
+    out.push(tag);
+    if len < 0x80 {
+        out.push(len as u8);
+    } else if len < 0x100 {
+        out.extend_from_slice(&[0x81, len as u8]);
+    } else if len < 0x10000 {
+        out.extend_from_slice(&[0x82, (len >> 8) as u8, len as u8]);
+    } else {
+        return None;
+    }
+    out.extend_from_slice(contents);
+    Some(())
+}
+
 impl SigningKey for SingleSchemeSigningKey {
     fn choose_scheme(&self, offered: &[SignatureScheme]) -> Option<Box<Signer>> {
         if offered.contains(&self.scheme) {
@@ -305,3 +383,50 @@ impl Signer for SingleSchemeSigner {
         self.scheme
     }
 }
+
+/// A `SigningKey` for Ed25519.
+///
+/// *ring* doesn't offer Ed25519 through `signature::SigningAlgorithm`,
+/// so this can't be a `SingleSchemeSigningKey`.
+pub struct Ed25519SigningKey {
+    key: Arc<signature::Ed25519KeyPair>,
+}
+
+impl Ed25519SigningKey {
+    /// Make a new `Ed25519SigningKey` from a DER encoding in PKCS#8
+    /// format.  Both v1 (as written by OpenSSL) and v2 documents
+    /// are accepted.
+    pub fn new(der: &key::PrivateKey) -> Result<Ed25519SigningKey, ()> {
+        signature::Ed25519KeyPair::from_pkcs8_maybe_unchecked(untrusted::Input::from(&der.0))
+            .map(|kp| Ed25519SigningKey { key: Arc::new(kp) })
+            .map_err(|_| ())
+    }
+}
+
+impl SigningKey for Ed25519SigningKey {
+    fn choose_scheme(&self, offered: &[SignatureScheme]) -> Option<Box<Signer>> {
+        if offered.contains(&SignatureScheme::ED25519) {
+            Some(Box::new(Ed25519Signer { key: self.key.clone() }))
+        } else {
+            None
+        }
+    }
+
+    fn algorithm(&self) -> SignatureAlgorithm {
+        SignatureAlgorithm::ED25519
+    }
+}
+
+struct Ed25519Signer {
+    key: Arc<signature::Ed25519KeyPair>,
+}
+
+impl Signer for Ed25519Signer {
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, TLSError> {
+        Ok(self.key.sign(message).as_ref().into())
+    }
+
+    fn get_scheme(&self) -> SignatureScheme {
+        SignatureScheme::ED25519
+    }
+}
@@ -5,8 +5,7 @@ use msgs::handshake::DecomposedSignatureScheme;
 use msgs::handshake::{ClientECDHParams, ServerECDHParams};
 use msgs::codec::{Reader, Codec};
 
-use ring;
-use untrusted;
+use crypto::{CryptoProvider, ActiveKeyExchange, Hash};
 
 /// Bulk symmetric encryption scheme used by a cipher suite.
 #[allow(non_camel_case_types)]
@@ -29,47 +28,31 @@ pub struct KeyExchangeResult {
     pub premaster_secret: Vec<u8>,
 }
 
-/// An in-progress key exchange.  This has the algorithm,
+/// An in-progress key exchange.  This has the group,
 /// our private key, and our public key.
 pub struct KeyExchange {
     pub group: NamedGroup,
-    alg: &'static ring::agreement::Algorithm,
-    privkey: ring::agreement::EphemeralPrivateKey,
+    kx: Box<ActiveKeyExchange>,
     pub pubkey: Vec<u8>,
 }
 
 impl KeyExchange {
-    pub fn named_group_to_ecdh_alg(group: NamedGroup)
-                                   -> Option<&'static ring::agreement::Algorithm> {
-        match group {
-            NamedGroup::X25519 => Some(&ring::agreement::X25519),
-            NamedGroup::secp256r1 => Some(&ring::agreement::ECDH_P256),
-            NamedGroup::secp384r1 => Some(&ring::agreement::ECDH_P384),
-            _ => None,
-        }
-    }
-
-    pub fn client_ecdhe(kx_params: &[u8]) -> Option<KeyExchangeResult> {
+    pub fn client_ecdhe(provider: &CryptoProvider, kx_params: &[u8]) -> Option<KeyExchangeResult> {
         let mut rd = Reader::init(kx_params);
         let ecdh_params = ServerECDHParams::read(&mut rd)?;
 
-        KeyExchange::start_ecdhe(ecdh_params.curve_params.named_group)?
+        KeyExchange::start_ecdhe(provider, ecdh_params.curve_params.named_group)?
             .complete(&ecdh_params.public.0)
     }
 
-    pub fn start_ecdhe(named_group: NamedGroup) -> Option<KeyExchange> {
-        let alg = KeyExchange::named_group_to_ecdh_alg(named_group)?;
-        let rng = ring::rand::SystemRandom::new();
-        let ours = ring::agreement::EphemeralPrivateKey::generate(alg, &rng).unwrap();
-
-        let mut pubkey = Vec::new();
-        pubkey.resize(ours.public_key_len(), 0u8);
-        ours.compute_public_key(pubkey.as_mut_slice()).unwrap();
+    pub fn start_ecdhe(provider: &CryptoProvider, named_group: NamedGroup) -> Option<KeyExchange> {
+        let kx = provider.start_key_exchange(named_group)?;
+        debug_assert_eq!(kx.group(), named_group);
+        let pubkey = kx.pub_key().to_vec();
 
         Some(KeyExchange {
             group: named_group,
-            alg,
-            privkey: ours,
+            kx,
             pubkey,
         })
     }
@@ -94,15 +77,7 @@ impl KeyExchange {
     }
 
     pub fn complete(self, peer: &[u8]) -> Option<KeyExchangeResult> {
-        let secret = ring::agreement::agree_ephemeral(self.privkey,
-                                                      self.alg,
-                                                      untrusted::Input::from(peer),
-                                                      (),
-                                                      |v| {
-                                                          let mut r = Vec::new();
-                                                          r.extend_from_slice(v);
-                                                          Ok(r)
-                                                      });
+        let secret = self.kx.complete(peer);
 
         if secret.is_err() {
             return None;
@@ -159,32 +134,45 @@ impl PartialEq for SupportedCipherSuite {
 }
 
 impl SupportedCipherSuite {
-    /// Which hash function to use with this suite.
-    pub fn get_hash(&self) -> &'static ring::digest::Algorithm {
-        match self.hash {
-            HashAlgorithm::SHA1 => &ring::digest::SHA1,
-            HashAlgorithm::SHA256 => &ring::digest::SHA256,
-            HashAlgorithm::SHA384 => &ring::digest::SHA384,
-            HashAlgorithm::SHA512 => &ring::digest::SHA512,
-            _ => unreachable!(),
-        }
+    /// Return true if `provider` has everything needed to use
+    /// this suite.
+    ///
+    /// Suites are removed from the configured list with this before
+    /// they are offered or chosen, so only suites for which it is
+    /// true are ever negotiated.
+    pub fn usable_with(&self, provider: &CryptoProvider) -> bool {
+        provider.hash(self.hash).is_some() && provider.supports_bulk(&self.bulk)
+    }
+
+    /// Which hash function to use with this suite, as implemented
+    /// by `provider`.
+    ///
+    /// Only suites where `usable_with(provider)` is true are ever
+    /// negotiated, so this cannot fail.
+    pub fn get_hash(&self, provider: &CryptoProvider) -> &'static Hash {
+        provider.hash(self.hash)
+            .expect("negotiated suite not usable with provider")
     }
 
     /// We have parameters and a verified public key in `kx_params`.
     /// Generate an ephemeral key, generate the shared secret, and
     /// return it and the public half in a `KeyExchangeResult`.
-    pub fn do_client_kx(&self, kx_params: &[u8]) -> Option<KeyExchangeResult> {
+    pub fn do_client_kx(&self,
+                        provider: &CryptoProvider,
+                        kx_params: &[u8]) -> Option<KeyExchangeResult> {
         match self.kx {
-            KeyExchangeAlgorithm::ECDHE => KeyExchange::client_ecdhe(kx_params),
+            KeyExchangeAlgorithm::ECDHE => KeyExchange::client_ecdhe(provider, kx_params),
             _ => None,
         }
     }
 
     /// Start the KX process with the given group.  This generates
     /// the server's share, but we don't yet have the client's share.
-    pub fn start_server_kx(&self, named_group: NamedGroup) -> Option<KeyExchange> {
+    pub fn start_server_kx(&self,
+                           provider: &CryptoProvider,
+                           named_group: NamedGroup) -> Option<KeyExchange> {
         match self.kx {
-            KeyExchangeAlgorithm::ECDHE => KeyExchange::start_ecdhe(named_group),
+            KeyExchangeAlgorithm::ECDHE => KeyExchange::start_ecdhe(provider, named_group),
             _ => None,
         }
     }
@@ -214,25 +202,32 @@ impl SupportedCipherSuite {
             our_preference.push(SignatureScheme::RSA_PSS_SHA256);
         }
 
+        // ECDSA suites also carry Ed25519 signatures; see RFC8422.
+        if self.sign == SignatureAlgorithm::ECDSA {
+            our_preference.push(SignatureScheme::ED25519);
+        }
+
         our_preference.retain(|pref| offered.contains(pref));
         our_preference
     }
 
-    /// Which AEAD algorithm to use for this suite.
-    pub fn get_aead_alg(&self) -> &'static ring::aead::Algorithm {
-        match self.bulk {
-            BulkAlgorithm::AES_128_GCM => &ring::aead::AES_128_GCM,
-            BulkAlgorithm::AES_256_GCM => &ring::aead::AES_256_GCM,
-            BulkAlgorithm::CHACHA20_POLY1305 => &ring::aead::CHACHA20_POLY1305,
-        }
-    }
-
     /// Length of key block that needs to be output by the key
     /// derivation phase for this suite.
     pub fn key_block_len(&self) -> usize {
         (self.enc_key_len + self.fixed_iv_len) * 2 + self.explicit_nonce_len
     }
 
+    /// Return true if this suite is usable with a key of type
+    /// `sigalg`.  Anonymous (TLS1.3) suites are usable with any key.
+    pub fn usable_for_sigalg(&self, sigalg: SignatureAlgorithm) -> bool {
+        match self.sign {
+            SignatureAlgorithm::Anonymous => true,
+            SignatureAlgorithm::ECDSA => sigalg == SignatureAlgorithm::ECDSA ||
+                sigalg == SignatureAlgorithm::ED25519,
+            sign => sign == sigalg,
+        }
+    }
+
     /// Return true if this suite is usable for TLS `version`.
     pub fn usable_for_version(&self, version: ProtocolVersion) -> bool {
         match version {
@@ -406,7 +401,7 @@ pub fn reduce_given_sigalg(all: &[&'static SupportedCipherSuite],
                            sigalg: &SignatureAlgorithm)
                            -> Vec<&'static SupportedCipherSuite> {
     all.iter()
-        .filter(|&&suite| suite.sign == SignatureAlgorithm::Anonymous || &suite.sign == sigalg)
+        .filter(|&&suite| suite.usable_for_sigalg(*sigalg))
         .cloned()
         .collect()
 }
@@ -422,6 +417,17 @@ pub fn reduce_given_version(all: &[&'static SupportedCipherSuite],
         .collect()
 }
 
+/// Return a list of the ciphersuites in `all` with the suites
+/// `provider` cannot implement removed.
+pub fn reduce_given_provider(all: &[&'static SupportedCipherSuite],
+                             provider: &CryptoProvider)
+                             -> Vec<&'static SupportedCipherSuite> {
+    all.iter()
+        .filter(|&&suite| suite.usable_with(provider))
+        .cloned()
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
     use msgs::enums::CipherSuite;
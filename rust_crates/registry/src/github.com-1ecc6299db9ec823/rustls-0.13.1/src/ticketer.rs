@@ -1,11 +1,17 @@
-
 use server::ProducesTickets;
 use rand;
+use crypto::{CryptoProvider, RingProvider, AeadKey};
+use suites::BulkAlgorithm;
+use error::TLSError;
+use msgs::codec::{Codec, Reader};
+use util;
 
+use std::fs;
+use std::io::{self, Read};
 use std::mem;
+use std::path::{Path, PathBuf};
 use std::sync::{Mutex, Arc};
 use std::time;
-use ring::aead;
 
 /// The timebase for expiring and rolling tickets and ticketing
 /// keys.  This is UNIX wall time in seconds.
@@ -16,39 +22,49 @@ pub fn timebase() -> u64 {
         .as_secs()
 }
 
+/// The AEAD algorithm we use for tickets: ChaCha20Poly1305, or
+/// AES-256-GCM if `provider` can't do that.  Both take 32 byte keys.
+fn ticket_algorithm(provider: &CryptoProvider) -> BulkAlgorithm {
+    if provider.supports_bulk(&BulkAlgorithm::CHACHA20_POLY1305) {
+        BulkAlgorithm::CHACHA20_POLY1305
+    } else {
+        BulkAlgorithm::AES_256_GCM
+    }
+}
+
 /// This is a `ProducesTickets` implementation which uses
-/// any *ring* `aead::Algorithm` to encrypt and authentication
-/// the ticket payload.  It does not enforce any lifetime
-/// constraint.
+/// an AEAD algorithm from a `CryptoProvider` to encrypt and
+/// authenticate the ticket payload.  It does not enforce any
+/// lifetime constraint.
 pub struct AEADTicketer {
-    alg: &'static aead::Algorithm,
-    enc: aead::SealingKey,
-    dec: aead::OpeningKey,
+    provider: Arc<CryptoProvider>,
+    key: Box<AeadKey>,
     lifetime: u32,
 }
 
 impl AEADTicketer {
-    /// Make a new `AEADTicketer` using the given `alg`, `key` material
-    /// and advertised `lifetime_seconds`.  Note that `lifetime_seconds`
-    /// does not affect the lifetime of the key.  `key` must be the
-    /// right length for `alg` or this will panic.
-    pub fn new_custom(alg: &'static aead::Algorithm,
+    /// Make a new `AEADTicketer` using `provider`'s implementation of
+    /// `alg`, the given `key` material and advertised `lifetime_seconds`.
+    /// Note that `lifetime_seconds` does not affect the lifetime of the
+    /// key.  `key` must be the right length for `alg` or this will panic.
+    pub fn new_custom(provider: Arc<CryptoProvider>,
+                      alg: &BulkAlgorithm,
                       key: &[u8],
                       lifetime_seconds: u32)
                       -> AEADTicketer {
         AEADTicketer {
-            alg,
-            enc: aead::SealingKey::new(alg, key).unwrap(),
-            dec: aead::OpeningKey::new(alg, key).unwrap(),
+            key: provider.aead_key(alg, key).unwrap(),
+            provider,
             lifetime: lifetime_seconds,
         }
     }
 
     /// Make a ticketer with recommended configuration and a random key.
-    pub fn new() -> AEADTicketer {
+    pub fn new(provider: Arc<CryptoProvider>) -> AEADTicketer {
         let mut key = [0u8; 32];
-        rand::fill_random(&mut key);
-        AEADTicketer::new_custom(&aead::CHACHA20_POLY1305, &key, 60 * 60 * 12)
+        rand::fill_random(&*provider, &mut key);
+        let alg = ticket_algorithm(&*provider);
+        AEADTicketer::new_custom(provider, &alg, &key, 60 * 60 * 12)
     }
 }
 
@@ -64,36 +80,35 @@ impl ProducesTickets for AEADTicketer {
     fn encrypt(&self, message: &[u8]) -> Option<Vec<u8>> {
         // Random nonce, because a counter is a privacy leak.
         let mut nonce = [0u8; 12];
-        rand::fill_random(&mut nonce);
+        rand::fill_random(&*self.provider, &mut nonce);
 
         let mut out = Vec::new();
         out.extend_from_slice(&nonce);
         out.extend_from_slice(message);
-        out.resize(nonce.len() + message.len() + self.alg.tag_len(), 0u8);
+        out.resize(nonce.len() + message.len() + self.key.tag_len(), 0u8);
 
-        let rc = aead::seal_in_place(&self.enc,
-                                     &nonce,
-                                     &[],
-                                     &mut out[nonce.len()..],
-                                     self.alg.tag_len());
+        let rc = self.key.seal_in_place(&nonce,
+                                        &[],
+                                        &mut out[nonce.len()..]);
         if rc.is_err() { None } else { Some(out) }
     }
 
     /// Decrypt `ciphertext` and recover the original message.
     fn decrypt(&self, ciphertext: &[u8]) -> Option<Vec<u8>> {
-        let nonce_len = self.alg.nonce_len();
-        let tag_len = self.alg.tag_len();
+        let mut nonce = [0u8; 12];
+        let nonce_len = nonce.len();
+        let tag_len = self.key.tag_len();
 
         if ciphertext.len() < nonce_len + tag_len {
             return None;
         }
 
-        let nonce = &ciphertext[0..nonce_len];
+        nonce.copy_from_slice(&ciphertext[0..nonce_len]);
         let mut out = Vec::new();
         out.extend_from_slice(&ciphertext[nonce_len..]);
 
-        let plain_len = match aead::open_in_place(&self.dec, nonce, &[], 0, &mut out) {
-            Ok(plaintext) => plaintext.len(),
+        let plain_len = match self.key.open_in_place(&nonce, &[], 0, &mut out) {
+            Ok(plain_len) => plain_len,
             Err(..) => { return None; }
         };
 
@@ -112,7 +127,7 @@ struct TicketSwitcherState {
 /// 'previous' ticketer.  It creates a new ticketer every so
 /// often, demoting the current ticketer.
 pub struct TicketSwitcher {
-    generator: fn() -> Box<ProducesTickets>,
+    generator: Box<Fn() -> Box<ProducesTickets> + Send + Sync>,
     lifetime: u32,
     state: Mutex<TicketSwitcherState>,
 }
@@ -122,17 +137,17 @@ impl TicketSwitcher {
     /// is used to generate new tickets.  Tickets are accepted for no
     /// longer than twice this duration.  `generator` produces a new
     /// `ProducesTickets` implementation.
-    pub fn new(lifetime: u32,
-               generator: fn() -> Box<ProducesTickets>)
-               -> TicketSwitcher {
+    pub fn new<F>(lifetime: u32, generator: F) -> TicketSwitcher
+        where F: Fn() -> Box<ProducesTickets> + Send + Sync + 'static
+    {
         TicketSwitcher {
-            generator,
             lifetime,
             state: Mutex::new(TicketSwitcherState {
                 current: generator(),
                 previous: None,
                 next_switch_time: timebase() + u64::from(lifetime),
             }),
+            generator: Box::new(generator),
         }
     }
 
@@ -189,16 +204,381 @@ impl ProducesTickets for TicketSwitcher {
 /// A concrete, safe ticket creation mechanism.
 pub struct Ticketer {}
 
-fn generate_inner() -> Box<ProducesTickets> {
-    Box::new(AEADTicketer::new())
-}
-
 impl Ticketer {
     /// Make the recommended Ticketer.  This produces tickets
     /// with a 12 hour life and randomly generated keys.
     ///
-    /// The encryption mechanism used in Chacha20Poly1305.
+    /// The encryption mechanism used in Chacha20Poly1305, from
+    /// `RingProvider`.
     pub fn new() -> Arc<ProducesTickets> {
-        Arc::new(TicketSwitcher::new(6 * 60 * 60, generate_inner))
+        Ticketer::with_provider(Arc::new(RingProvider {}))
+    }
+
+    /// Like `new`, but gets its cryptography from `provider`.  Pass
+    /// the `crypto_provider` of the `ServerConfig` this ticketer is for.
+    ///
+    /// The encryption mechanism used is Chacha20Poly1305, or
+    /// AES-256-GCM if `provider` doesn't support that.
+    pub fn with_provider(provider: Arc<CryptoProvider>) -> Arc<ProducesTickets> {
+        Arc::new(TicketSwitcher::new(6 * 60 * 60, move || {
+            Box::new(AEADTicketer::new(provider.clone()))
+        }))
+    }
+}
+
+/// A named key for encrypting tickets, for use with `SharedTicketer`.
+///
+/// The name goes in the clear at the start of each ticket, so a
+/// server can tell which key to decrypt it with.
+#[derive(Clone)]
+pub struct TicketKey {
+    name: [u8; 16],
+    key: [u8; 32],
+}
+
+impl TicketKey {
+    /// Make a `TicketKey` from the given `name` and `key` material.
+    pub fn new(name: [u8; 16], key: [u8; 32]) -> TicketKey {
+        TicketKey { name, key }
+    }
+
+    /// Make a `TicketKey` with a random name and key, using
+    /// randomness from `provider`.
+    pub fn generate(provider: &CryptoProvider) -> TicketKey {
+        let mut ret = TicketKey::new([0u8; 16], [0u8; 32]);
+        rand::fill_random(provider, &mut ret.name);
+        rand::fill_random(provider, &mut ret.key);
+        ret
+    }
+
+    /// This key's name.
+    pub fn name(&self) -> &[u8] {
+        &self.name
+    }
+
+    fn read(r: &mut Reader) -> Option<TicketKey> {
+        let mut ret = TicketKey::new([0u8; 16], [0u8; 32]);
+        ret.name.copy_from_slice(r.take(16)?);
+        ret.key.copy_from_slice(r.take(32)?);
+        Some(ret)
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.name);
+        bytes.extend_from_slice(&self.key);
+    }
+}
+
+const TICKET_KEY_SET_VERSION: u8 = 1;
+
+/// The ticket keys in use by a fleet of servers at some point
+/// in time: a `current` key for making new tickets, and perhaps
+/// a `previous` one that still decrypts tickets made before the
+/// last rotation.
+///
+/// To rotate, call `rotate` and distribute the result.  Tickets
+/// remain usable for one rotation period, so the period should
+/// not be shorter than the ticket lifetime.
+#[derive(Clone)]
+pub struct TicketKeySet {
+    /// The key used to make new tickets.
+    pub current: TicketKey,
+
+    /// The key that was current before the last rotation.
+    pub previous: Option<TicketKey>,
+}
+
+impl TicketKeySet {
+    /// Make a key set with a random `current` key.
+    pub fn generate(provider: &CryptoProvider) -> TicketKeySet {
+        TicketKeySet {
+            current: TicketKey::generate(provider),
+            previous: None,
+        }
+    }
+
+    /// Returns the key set following this one: a new random
+    /// `current` key, with our `current` key demoted to `previous`.
+    pub fn rotate(&self, provider: &CryptoProvider) -> TicketKeySet {
+        TicketKeySet {
+            current: TicketKey::generate(provider),
+            previous: Some(self.current.clone()),
+        }
+    }
+
+    /// Encode this key set, for `from_bytes`.
+    ///
+    /// The result contains secret keys.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        TICKET_KEY_SET_VERSION.encode(&mut bytes);
+        self.current.encode(&mut bytes);
+        if let Some(ref previous) = self.previous {
+            previous.encode(&mut bytes);
+        }
+        bytes
+    }
+
+    /// Decode a key set produced by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Option<TicketKeySet> {
+        let mut rd = Reader::init(bytes);
+        if u8::read(&mut rd)? != TICKET_KEY_SET_VERSION {
+            return None;
+        }
+
+        let current = TicketKey::read(&mut rd)?;
+        let previous = if rd.any_left() {
+            Some(TicketKey::read(&mut rd)?)
+        } else {
+            None
+        };
+
+        if rd.any_left() {
+            return None;
+        }
+
+        Some(TicketKeySet { current, previous })
+    }
+
+    /// Read a key set written by `write_file`.
+    pub fn read_file<P: AsRef<Path>>(path: P) -> Result<TicketKeySet, TLSError> {
+        let mut bytes = Vec::new();
+        fs::File::open(path.as_ref())
+            .and_then(|mut f| f.read_to_end(&mut bytes))
+            .map_err(|err| TLSError::General(format!("cannot read ticket keys: {}", err)))?;
+
+        TicketKeySet::from_bytes(&bytes)
+            .ok_or_else(|| TLSError::General("malformed ticket key file".to_string()))
+    }
+
+    /// Write this key set to `path`, for `read_file`.
+    ///
+    /// The file is replaced atomically, so servers reading it
+    /// concurrently see either the old or new key set.  On unix,
+    /// it is only readable by its owner.
+    pub fn write_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        util::write_private_file(path.as_ref(), &self.to_bytes())
+    }
+}
+
+/// A source of ticket keys for `SharedTicketer`.
+///
+/// Implement this to fetch keys from wherever your fleet
+/// of servers keeps them.
+pub trait ProvidesTicketKeys : Send + Sync {
+    /// Return the current key set.
+    fn load(&self) -> Result<TicketKeySet, TLSError>;
+}
+
+/// A `ProvidesTicketKeys` implementation which reads a file
+/// written by `TicketKeySet::write_file`.
+///
+/// Exactly one server should rotate the keys, either by making
+/// its `TicketKeyFile` with `rotating`, or by calling `rotate`
+/// on a schedule of its own.  The others should use `new`.
+pub struct TicketKeyFile {
+    path: PathBuf,
+    rotation: Option<(Arc<CryptoProvider>, u64)>,
+}
+
+impl TicketKeyFile {
+    /// Read keys from the file at `path`.
+    pub fn new<P: AsRef<Path>>(path: P) -> TicketKeyFile {
+        TicketKeyFile {
+            path: path.as_ref().to_path_buf(),
+            rotation: None,
+        }
+    }
+
+    /// Read keys from the file at `path`, first rotating them if
+    /// the file is more than `interval` seconds old or doesn't
+    /// exist.  New keys are generated using `provider`.
+    ///
+    /// When used with `SharedTicketer`, the file is checked each
+    /// time keys are reloaded, so rotation happens within its
+    /// `reload_interval` of falling due.
+    pub fn rotating<P: AsRef<Path>>(path: P,
+                                    provider: Arc<CryptoProvider>,
+                                    interval: u32)
+                                    -> TicketKeyFile {
+        TicketKeyFile {
+            path: path.as_ref().to_path_buf(),
+            rotation: Some((provider, u64::from(interval))),
+        }
+    }
+
+    fn is_due_for_rotation(&self, interval: u64) -> bool {
+        match fs::metadata(&self.path).and_then(|meta| meta.modified()) {
+            Ok(modified) => {
+                time::SystemTime::now()
+                    .duration_since(modified)
+                    .map(|age| age.as_secs() >= interval)
+                    .unwrap_or(false)
+            }
+            Err(_) => true,
+        }
+    }
+
+    /// Rotate the keys in the file, making it first if it
+    /// doesn't exist.  Call this from exactly one place, on
+    /// a schedule.  New keys are generated using `provider`.
+    pub fn rotate(&self, provider: &CryptoProvider) -> Result<(), TLSError> {
+        let keys = if self.path.exists() {
+            TicketKeySet::read_file(&self.path)?.rotate(provider)
+        } else {
+            TicketKeySet::generate(provider)
+        };
+
+        keys.write_file(&self.path)
+            .map_err(|err| TLSError::General(format!("cannot write ticket keys: {}", err)))
+    }
+}
+
+impl ProvidesTicketKeys for TicketKeyFile {
+    fn load(&self) -> Result<TicketKeySet, TLSError> {
+        if let Some((ref provider, interval)) = self.rotation {
+            if self.is_due_for_rotation(interval) {
+                self.rotate(&**provider)?;
+            }
+        }
+
+        TicketKeySet::read_file(&self.path)
+    }
+}
+
+struct NamedTicketer {
+    name: [u8; 16],
+    ticketer: AEADTicketer,
+}
+
+impl NamedTicketer {
+    fn new(provider: &Arc<CryptoProvider>, key: &TicketKey, lifetime: u32) -> NamedTicketer {
+        let alg = ticket_algorithm(&**provider);
+
+        NamedTicketer {
+            name: key.name,
+            ticketer: AEADTicketer::new_custom(provider.clone(), &alg, &key.key, lifetime),
+        }
+    }
+}
+
+struct SharedTicketerState {
+    current: NamedTicketer,
+    previous: Option<NamedTicketer>,
+    next_reload_time: u64,
+}
+
+/// A ticketer whose keys come from a `ProvidesTicketKeys`, so that
+/// servers sharing the same keys can resume each other's sessions.
+///
+/// Keys are reloaded every so often.  If reloading fails, the
+/// last keys loaded remain in use.
+///
+/// Servers sharing keys must use crypto providers that agree on
+/// whether ChaCha20Poly1305 is supported: see `Ticketer::with_provider`.
+pub struct SharedTicketer {
+    provider: Arc<CryptoProvider>,
+    source: Box<ProvidesTicketKeys>,
+    lifetime: u32,
+    reload_interval: u32,
+    state: Mutex<SharedTicketerState>,
+}
+
+impl SharedTicketer {
+    /// Make a ticketer using keys from `source`, which is consulted
+    /// again every `reload_interval` seconds.  Tickets are advertised
+    /// to clients with a lifetime of `lifetime` seconds.  Tickets are
+    /// encrypted using `provider`: pass the `crypto_provider` of the
+    /// `ServerConfig` this ticketer is for.
+    ///
+    /// This fails if `source` can't provide keys now.
+    pub fn new(provider: Arc<CryptoProvider>,
+               source: Box<ProvidesTicketKeys>,
+               lifetime: u32,
+               reload_interval: u32)
+               -> Result<SharedTicketer, TLSError> {
+        let keys = source.load()?;
+
+        let state = SharedTicketerState {
+            current: NamedTicketer::new(&provider, &keys.current, lifetime),
+            previous: keys.previous
+                .as_ref()
+                .map(|key| NamedTicketer::new(&provider, key, lifetime)),
+            next_reload_time: timebase() + u64::from(reload_interval),
+        };
+
+        Ok(SharedTicketer {
+            provider,
+            source,
+            lifetime,
+            reload_interval,
+            state: Mutex::new(state),
+        })
+    }
+
+    /// If it's time, reload the keys from our source.
+    ///
+    /// Calling this regularly will ensure timely key erasure.  Otherwise,
+    /// it happens during the next encrypt/decrypt call.
+    pub fn maybe_reload(&self) {
+        let mut state = self.state.lock().unwrap();
+        let now = timebase();
+
+        if now < state.next_reload_time {
+            return;
+        }
+
+        state.next_reload_time = now + u64::from(self.reload_interval);
+        match self.source.load() {
+            Ok(keys) => {
+                state.current = NamedTicketer::new(&self.provider, &keys.current, self.lifetime);
+                state.previous = keys.previous
+                    .as_ref()
+                    .map(|key| NamedTicketer::new(&self.provider, key, self.lifetime));
+            }
+            Err(err) => warn!("Cannot reload ticket keys: {:?}", err),
+        }
+    }
+}
+
+impl ProducesTickets for SharedTicketer {
+    fn get_lifetime(&self) -> u32 {
+        self.lifetime
+    }
+
+    fn enabled(&self) -> bool {
+        true
+    }
+
+    fn encrypt(&self, message: &[u8]) -> Option<Vec<u8>> {
+        self.maybe_reload();
+
+        let state = self.state.lock().unwrap();
+        let mut out = Vec::new();
+        out.extend_from_slice(&state.current.name);
+        out.extend_from_slice(&state.current.ticketer.encrypt(message)?);
+        Some(out)
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Option<Vec<u8>> {
+        self.maybe_reload();
+
+        if ciphertext.len() < 16 {
+            return None;
+        }
+
+        let (name, ciphertext) = ciphertext.split_at(16);
+        let state = self.state.lock().unwrap();
+
+        if name == state.current.name {
+            return state.current.ticketer.decrypt(ciphertext);
+        }
+
+        match state.previous {
+            Some(ref previous) if name == previous.name => {
+                previous.ticketer.decrypt(ciphertext)
+            }
+            _ => None,
+        }
     }
 }
@@ -1,3 +1,7 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
 /// Return the first member of `prefs` that appears in `avail`.
 pub fn first_in_both<T: Clone + PartialEq>(prefs: &[T], avail: &[T]) -> Option<T> {
     for p in prefs {
@@ -8,3 +12,64 @@ pub fn first_in_both<T: Clone + PartialEq>(prefs: &[T], avail: &[T]) -> Option<T
 
     None
 }
+
+/// Replace the file at `path` with `contents`.  This is atomic:
+/// concurrent readers see either the old or new contents, never
+/// a mixture.
+///
+/// The contents are secret, so on unix the file is only readable
+/// by its owner.  They are on disk before the file is renamed into
+/// place, so a crash doesn't leave a truncated file behind.
+pub fn write_private_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    // A leftover temporary file might have looser permissions
+    // than we'd create it with.
+    if let Err(err) = fs::remove_file(&tmp) {
+        if err.kind() != io::ErrorKind::NotFound {
+            return Err(err);
+        }
+    }
+
+    {
+        let mut f = private_file_options()
+            .write(true)
+            .create_new(true)
+            .open(&tmp)?;
+        f.write_all(contents)?;
+        f.sync_all()?;
+    }
+
+    fs::rename(&tmp, path)?;
+    sync_parent_dir(path)
+}
+
+#[cfg(unix)]
+fn private_file_options() -> fs::OpenOptions {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let mut options = fs::OpenOptions::new();
+    options.mode(0o600);
+    options
+}
+
+#[cfg(not(unix))]
+fn private_file_options() -> fs::OpenOptions {
+    fs::OpenOptions::new()
+}
+
+// Makes the rename durable.
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(dir) if dir != Path::new("") => fs::File::open(dir)?.sync_all(),
+        _ => fs::File::open(".")?.sync_all(),
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
@@ -22,6 +22,10 @@ static SUPPORTED_SIG_ALGS: SignatureAlgorithms = &[&webpki::ECDSA_P256_SHA256,
                                                    &webpki::RSA_PSS_2048_8192_SHA256_LEGACY_KEY,
                                                    &webpki::RSA_PSS_2048_8192_SHA384_LEGACY_KEY,
                                                    &webpki::RSA_PSS_2048_8192_SHA512_LEGACY_KEY,
+                                                   &webpki::RSA_PSS_2048_8192_SHA256,
+                                                   &webpki::RSA_PSS_2048_8192_SHA384,
+                                                   &webpki::RSA_PSS_2048_8192_SHA512,
+                                                   &webpki::ED25519,
                                                    &webpki::RSA_PKCS1_2048_8192_SHA1,
                                                    &webpki::RSA_PKCS1_2048_8192_SHA256,
                                                    &webpki::RSA_PKCS1_2048_8192_SHA384,
@@ -60,12 +64,13 @@ impl ClientCertVerified {
 /// Something that can verify a server certificate chain
 pub trait ServerCertVerifier : Send + Sync {
     /// Verify a the certificate chain `presented_certs` against the roots
-    /// configured in `roots`.  Make sure that `dns_name` is quoted by
-    /// the top certificate in the chain.
+    /// configured in `roots`.  Make sure that `server_name` is quoted by
+    /// the top certificate in the chain: either as a DNS name, or as
+    /// an iPAddress subjectAltName if we're talking to a bare IP address.
     fn verify_server_cert(&self,
                           roots: &RootCertStore,
                           presented_certs: &[Certificate],
-                          dns_name: webpki::DNSNameRef,
+                          server_name: webpki::SubjectNameRef,
                           ocsp_response: &[u8]) -> Result<ServerCertVerified, TLSError>;
 }
 
@@ -89,15 +94,37 @@ pub trait ClientCertVerifier : Send + Sync {
                           presented_certs: &[Certificate]) -> Result<ClientCertVerified, TLSError>;
 }
 
+/// What to do when a server certificate's revocation status can't be
+/// determined.
+///
+/// A certificate that is known to be revoked is always rejected.  A
+/// certificate's status is known if the server staples a valid OCSP
+/// response for it, or if a configured CRL from its issuer is valid.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RevocationPolicy {
+    /// Accept the certificate anyway.  This is the default, and means
+    /// nothing changes for servers that don't staple OCSP responses.
+    SoftFail,
+
+    /// Reject the certificate.
+    HardFail,
+}
+
 pub struct WebPKIVerifier {
     pub time: fn() -> Result<webpki::Time, TLSError>,
+
+    /// DER-encoded CRLs to check the server's certificate chain against.
+    pub crls: Vec<Vec<u8>>,
+
+    /// What to do if revocation status can't be determined.
+    pub revocation_policy: RevocationPolicy,
 }
 
 impl ServerCertVerifier for WebPKIVerifier {
     fn verify_server_cert(&self,
                           roots: &RootCertStore,
                           presented_certs: &[Certificate],
-                          dns_name: webpki::DNSNameRef,
+                          server_name: webpki::SubjectNameRef,
                           ocsp_response: &[u8]) -> Result<ServerCertVerified, TLSError> {
         let (cert, chain, trustroots) = prepare(roots, presented_certs)?;
         let now = (self.time)()?;
@@ -106,11 +133,9 @@ impl ServerCertVerifier for WebPKIVerifier {
             .map_err(TLSError::WebPKIError)
             .map(|_| cert)?;
 
-        if !ocsp_response.is_empty() {
-            debug!("Unvalidated OCSP response: {:?}", ocsp_response.to_vec());
-        }
+        self.check_revocation(&cert, &chain, &trustroots, ocsp_response, now)?;
 
-        cert.verify_is_valid_for_dns_name(dns_name)
+        cert.verify_is_valid_for_subject_name(server_name)
             .map_err(TLSError::WebPKIError)
             .map(|_| ServerCertVerified::assertion())
     }
@@ -120,8 +145,61 @@ impl WebPKIVerifier {
     pub fn new() -> WebPKIVerifier {
         WebPKIVerifier {
             time: try_now,
+            crls: Vec::new(),
+            revocation_policy: RevocationPolicy::SoftFail,
         }
     }
+
+    /// Check the revocation status of `cert` (which must already have
+    /// been validated using `chain` and `trustroots`) using the stapled
+    /// `ocsp_response`, if any, and our CRLs.
+    fn check_revocation(&self,
+                        cert: &webpki::EndEntityCert,
+                        chain: &[untrusted::Input],
+                        trustroots: &[webpki::TrustAnchor],
+                        ocsp_response: &[u8],
+                        now: webpki::Time) -> Result<(), TLSError> {
+        let mut results = Vec::new();
+
+        if !ocsp_response.is_empty() {
+            let result = cert.verify_ocsp_response(SUPPORTED_SIG_ALGS,
+                                                   trustroots,
+                                                   chain,
+                                                   untrusted::Input::from(ocsp_response),
+                                                   now);
+            debug!("Stapled OCSP response check: {:?}", result);
+            results.push(result);
+        }
+
+        if !self.crls.is_empty() {
+            let result = self.crls
+                .iter()
+                .map(|crl| webpki::CertRevocationList::from(untrusted::Input::from(crl)))
+                .collect::<Result<Vec<_>, _>>()
+                .and_then(|crls| cert.verify_is_not_revoked(SUPPORTED_SIG_ALGS,
+                                                            trustroots,
+                                                            chain,
+                                                            &crls,
+                                                            now));
+            debug!("CRL check: {:?}", result);
+            results.push(result);
+        }
+
+        if results.contains(&Err(webpki::Error::CertRevoked)) {
+            return Err(TLSError::WebPKIError(webpki::Error::CertRevoked));
+        }
+
+        if results.iter().any(|r| r.is_ok()) ||
+            self.revocation_policy == RevocationPolicy::SoftFail {
+            return Ok(());
+        }
+
+        let err = results.into_iter()
+            .filter_map(|r| r.err())
+            .next()
+            .unwrap_or(webpki::Error::UnknownRevocationStatus);
+        Err(TLSError::WebPKIError(err))
+    }
 }
 
 fn prepare<'a, 'b>(roots: &'b RootCertStore, presented_certs: &'a [Certificate])
@@ -253,6 +331,8 @@ static ECDSA_SHA256: SignatureAlgorithms = &[&webpki::ECDSA_P256_SHA256,
 static ECDSA_SHA384: SignatureAlgorithms = &[&webpki::ECDSA_P256_SHA384,
                                              &webpki::ECDSA_P384_SHA384];
 
+static ED25519: SignatureAlgorithms = &[&webpki::ED25519];
+
 static RSA_SHA1: SignatureAlgorithms = &[&webpki::RSA_PKCS1_2048_8192_SHA1];
 static RSA_SHA256: SignatureAlgorithms = &[&webpki::RSA_PKCS1_2048_8192_SHA256];
 static RSA_SHA384: SignatureAlgorithms = &[&webpki::RSA_PKCS1_2048_8192_SHA384];
@@ -276,6 +356,8 @@ fn convert_scheme(scheme: SignatureScheme) -> Result<SignatureAlgorithms, TLSErr
         SignatureScheme::RSA_PSS_SHA384 => Ok(RSA_PSS_SHA384),
         SignatureScheme::RSA_PSS_SHA512 => Ok(RSA_PSS_SHA512),
 
+        SignatureScheme::ED25519 => Ok(ED25519),
+
         _ => {
             let error_msg = format!("received unadvertised sig scheme {:?}", scheme);
             Err(TLSError::PeerMisbehavedError(error_msg))
@@ -332,6 +414,7 @@ fn convert_alg_tls13(scheme: SignatureScheme)
         RSA_PSS_SHA256 => Ok(&webpki::RSA_PSS_2048_8192_SHA256_LEGACY_KEY),
         RSA_PSS_SHA384 => Ok(&webpki::RSA_PSS_2048_8192_SHA384_LEGACY_KEY),
         RSA_PSS_SHA512 => Ok(&webpki::RSA_PSS_2048_8192_SHA512_LEGACY_KEY),
+        ED25519 => Ok(&webpki::ED25519),
         _ => {
             let error_msg = format!("received unsupported sig scheme {:?}", scheme);
             Err(TLSError::PeerMisbehavedError(error_msg))
@@ -371,36 +454,135 @@ fn unix_time_millis() -> Result<u64, TLSError> {
                   .ok_or(TLSError::FailedToGetCurrentTime))
 }
 
-pub fn verify_scts(cert: &Certificate,
-                   scts: &SCTList,
-                   logs: &[&sct::Log]) -> Result<(), TLSError> {
-    let mut valid_scts = 0;
+/// What to do when a server certificate's SCTs don't meet the
+/// certificate transparency policy, `ClientConfig::ct_policy`.
+///
+/// SCTs which are present but invalid always fail the handshake.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CTEnforcement {
+    /// Log a warning and accept the certificate anyway.  This is the
+    /// default.
+    SoftFail,
+
+    /// Reject the certificate.
+    HardFail,
+}
+
+/// Verify the SCTs for the end-entity certificate in `chain`, from
+/// wherever the server put them: embedded in the certificate, in
+/// the TLS extension `tls_scts`, or in the stapled `ocsp_response`.
+/// Then check they meet `policy`.
+///
+/// Embedded SCTs are checked against the issuer that the certificate
+/// chains to, found among `roots` and `chain`.  With
+/// `CTEnforcement::SoftFail`, SCTs that can't be parsed or don't
+/// verify are logged along with the policy failure, rather than
+/// failing the handshake.
+pub fn verify_scts(roots: &RootCertStore,
+                   chain: &[Certificate],
+                   tls_scts: Option<&SCTList>,
+                   ocsp_response: &[u8],
+                   logs: &[&sct::Log],
+                   policy: &sct::Policy,
+                   enforcement: CTEnforcement) -> Result<(), TLSError> {
+    match check_scts(roots, chain, tls_scts, ocsp_response, logs, policy) {
+        Ok(()) => Ok(()),
+        Err(err) if enforcement == CTEnforcement::HardFail => Err(err),
+        Err(err) => {
+            warn!("Certificate transparency policy not met: {:?}", err);
+            Ok(())
+        }
+    }
+}
+
+fn check_scts(roots: &RootCertStore,
+              chain: &[Certificate],
+              tls_scts: Option<&SCTList>,
+              ocsp_response: &[u8],
+              logs: &[&sct::Log],
+              policy: &sct::Policy) -> Result<(), TLSError> {
+    let (cert, intermediates, trustroots) = prepare(roots, chain)?;
+    let cert_der = &chain[0].0;
     let now = unix_time_millis()?;
+    let mut embedded = Vec::new();
+    let mut delivered = Vec::new();
+    let mut sct_count = 0;
     let mut last_sct_error = None;
 
-    for sct in scts {
-        match sct::verify_sct(&cert.0, &sct.0, now, logs) {
-            Ok(index) => {
-                debug!("Valid SCT signed by {} on {}",
-                      logs[index].operated_by, logs[index].description);
-                valid_scts += 1;
-            }
-            Err(e) => {
-                if e.should_be_fatal() {
-                    return Err(TLSError::InvalidSCT(e));
+    let embedded_scts = sct::embedded_scts(cert_der)
+        .map_err(TLSError::InvalidSCT)?;
+    if !embedded_scts.is_empty() {
+        let precert = cert.with_issuer_spki(SUPPORTED_SIG_ALGS, &trustroots,
+                                            &intermediates,
+                                            |spki| sct::Precert::with_issuer_spki(cert_der, spki));
+        match precert {
+            Ok(precert) => {
+                let precert = precert.map_err(TLSError::InvalidSCT)?;
+                for sct in embedded_scts {
+                    sct_count += 1;
+                    check_sct(sct::Entry::Precert(&precert), sct, now, logs,
+                              &mut embedded, &mut last_sct_error)?;
                 }
-                debug!("SCT ignored because {:?}", e);
-                last_sct_error = Some(e);
             }
+            Err(err) => debug!("Ignoring embedded SCTs: no issuer ({:?})", err),
         }
     }
 
+    let ocsp_scts = if ocsp_response.is_empty() {
+        Vec::new()
+    } else {
+        sct::ocsp_scts(ocsp_response)
+            .map_err(TLSError::InvalidSCT)?
+    };
+
+    let tls_scts = tls_scts.into_iter()
+        .flat_map(|scts| scts.iter())
+        .map(|sct| sct.0.as_slice());
+
+    for sct in tls_scts.chain(ocsp_scts) {
+        sct_count += 1;
+        check_sct(sct::Entry::X509(cert_der), sct, now, logs,
+                  &mut delivered, &mut last_sct_error)?;
+    }
+
     /* If we were supplied with some logs, and some SCTs,
-     * but couldn't verify any of them, fail the handshake. */
-    if !logs.is_empty() && !scts.is_empty() && valid_scts == 0 {
+     * but couldn't verify any of them, fail. */
+    if !logs.is_empty() && sct_count > 0 && embedded.is_empty() && delivered.is_empty() {
         warn!("No valid SCTs provided");
         return Err(TLSError::InvalidSCT(last_sct_error.unwrap()));
     }
 
+    let lifetime = cert.validity()
+        .map(|validity| {
+            validity.not_after.as_seconds_since_unix_epoch()
+                .saturating_sub(validity.not_before.as_seconds_since_unix_epoch())
+        })
+        .map_err(TLSError::WebPKIError)?;
+
+    policy.check(logs, &embedded, &delivered, lifetime, now)
+        .map_err(TLSError::CTPolicyNotMet)
+}
+
+fn check_sct(entry: sct::Entry,
+             sct: &[u8],
+             now: u64,
+             logs: &[&sct::Log],
+             valid: &mut Vec<sct::VerifiedSCT>,
+             last_sct_error: &mut Option<sct::Error>) -> Result<(), TLSError> {
+    match sct::verify_sct_for_entry(entry, sct, now, logs) {
+        Ok(verified) => {
+            debug!("Valid SCT signed by {} on {}",
+                   logs[verified.log].operated_by, logs[verified.log].description);
+            valid.push(verified);
+        }
+        Err(e) => {
+            if e.should_be_fatal() {
+                return Err(TLSError::InvalidSCT(e));
+            }
+            debug!("SCT ignored because {:?}", e);
+            *last_sct_error = Some(e);
+        }
+    }
+
     Ok(())
 }
@@ -0,0 +1,56 @@
+use anchors::RootCertStore;
+use error::TLSError;
+use key::Certificate;
+use msgs::handshake::SCTList;
+use msgs::base::PayloadU16;
+use verify::{self, CTEnforcement};
+use sct;
+
+extern crate ct_logs;
+
+fn github_chain() -> Vec<Certificate> {
+    vec![
+        Certificate(include_bytes!("testdata/cert-github.0.der").to_vec()),
+        Certificate(include_bytes!("testdata/cert-github.1.der").to_vec()),
+    ]
+}
+
+fn verify_scts(chain: &[Certificate],
+               tls_scts: Option<&SCTList>,
+               ocsp_response: &[u8],
+               enforcement: CTEnforcement) -> Result<(), TLSError> {
+    verify::verify_scts(&RootCertStore::empty(), chain, tls_scts, ocsp_response,
+                        &ct_logs::LOGS, &sct::Policy::new(), enforcement)
+}
+
+#[test]
+fn embedded_scts_verify() {
+    assert_eq!(verify_scts(&github_chain(), None, &[], CTEnforcement::HardFail),
+               Ok(()));
+}
+
+#[test]
+fn embedded_scts_use_the_issuer_from_the_chain() {
+    // The issuer isn't the second certificate the server sent.
+    let mut chain = github_chain();
+    let unrelated = Certificate(include_bytes!("testdata/cert-reddit.1.der").to_vec());
+    chain.insert(1, unrelated);
+    assert_eq!(verify_scts(&chain, None, &[], CTEnforcement::HardFail),
+               Ok(()));
+}
+
+#[test]
+fn malformed_scts_only_fail_hard_fail() {
+    let garbage_ocsp = b"not an OCSP response";
+    assert_eq!(verify_scts(&github_chain(), None, garbage_ocsp, CTEnforcement::SoftFail),
+               Ok(()));
+    assert_eq!(verify_scts(&github_chain(), None, garbage_ocsp, CTEnforcement::HardFail),
+               Err(TLSError::InvalidSCT(sct::Error::MalformedOCSPResponse)));
+
+    // A version 1 SCT, truncated after the version.
+    let truncated_sct = vec![PayloadU16::new(vec![0])];
+    assert_eq!(verify_scts(&github_chain(), Some(&truncated_sct), &[], CTEnforcement::SoftFail),
+               Ok(()));
+    assert_eq!(verify_scts(&github_chain(), Some(&truncated_sct), &[], CTEnforcement::HardFail),
+               Err(TLSError::InvalidSCT(sct::Error::MalformedSCT)));
+}
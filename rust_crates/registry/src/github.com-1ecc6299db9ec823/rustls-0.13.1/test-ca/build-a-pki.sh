@@ -2,8 +2,8 @@
 
 set -xe
 
-rm -rf rsa/ ecdsa/
-mkdir -p rsa/ ecdsa/
+rm -rf rsa/ ecdsa/ eddsa/
+mkdir -p rsa/ ecdsa/ eddsa/
 
 openssl req -nodes \
           -x509 \
@@ -79,6 +79,10 @@ openssl req -nodes \
           -days 2000 \
           -subj "/CN=testserver.com"
 
+openssl ec \
+          -in ecdsa/end.key \
+          -out ecdsa/end.sec1
+
 openssl req -nodes \
           -newkey ec:ecdsa/nistp384.pem \
           -keyout ecdsa/client.key \
@@ -88,7 +92,38 @@ openssl req -nodes \
           -days 2000 \
           -subj "/CN=ponytown client"
 
-for kt in rsa ecdsa ; do
+# eddsa
+openssl genpkey -algorithm ed25519 -out eddsa/ca.key
+openssl genpkey -algorithm ed25519 -out eddsa/inter.key
+openssl genpkey -algorithm ed25519 -out eddsa/end.key
+openssl genpkey -algorithm ed25519 -out eddsa/client.key
+
+openssl req -x509 \
+          -key eddsa/ca.key \
+          -out eddsa/ca.cert \
+          -batch \
+          -days 3650 \
+          -subj "/CN=ponytown EdDSA CA"
+
+openssl req -new \
+          -key eddsa/inter.key \
+          -out eddsa/inter.req \
+          -batch \
+          -subj "/CN=ponytown EdDSA level 2 intermediate"
+
+openssl req -new \
+          -key eddsa/end.key \
+          -out eddsa/end.req \
+          -batch \
+          -subj "/CN=testserver.com"
+
+openssl req -new \
+          -key eddsa/client.key \
+          -out eddsa/client.req \
+          -batch \
+          -subj "/CN=ponytown client"
+
+for kt in rsa ecdsa eddsa ; do
   openssl x509 -req \
             -in $kt/inter.req \
             -out $kt/inter.cert \
@@ -127,3 +162,53 @@ for kt in rsa ecdsa ; do
 
   openssl asn1parse -in $kt/ca.cert -out $kt/ca.der > /dev/null
 done
+
+# revocation data: CRLs and OCSP responses from the intermediate, first
+# with nothing revoked and then with end.cert revoked.
+for kt in rsa ecdsa eddsa ; do
+  export CA_DB=$kt/index.txt
+  rm -f $CA_DB
+  touch $CA_DB
+
+  openssl ocsp \
+            -issuer $kt/inter.cert \
+            -cert $kt/end.cert \
+            -no_nonce \
+            -reqout $kt/end.ocspreq
+
+  # the responder only says "good" for certificates it knows are valid.
+  openssl ca -config openssl.cnf \
+             -keyfile $kt/inter.key \
+             -cert $kt/inter.cert \
+             -valid $kt/end.cert
+
+  for status in good revoked ; do
+    if [ $status = revoked ] ; then
+      openssl ca -config openssl.cnf \
+                 -keyfile $kt/inter.key \
+                 -cert $kt/inter.cert \
+                 -revoke $kt/end.cert
+    fi
+
+    openssl ca -config openssl.cnf \
+               -keyfile $kt/inter.key \
+               -cert $kt/inter.cert \
+               -gencrl \
+               -out $kt/inter.$status.crl.pem
+
+    openssl crl -in $kt/inter.$status.crl.pem \
+                -outform DER \
+                -out $kt/inter.$status.crl
+
+    openssl ocsp \
+              -index $CA_DB \
+              -rsigner $kt/inter.cert \
+              -rkey $kt/inter.key \
+              -CA $kt/inter.cert \
+              -reqin $kt/end.ocspreq \
+              -respout $kt/end.$status.ocsp \
+              -ndays 3650
+  done
+
+  rm -f $kt/index.txt* $kt/end.ocspreq $kt/inter.*.crl.pem
+done
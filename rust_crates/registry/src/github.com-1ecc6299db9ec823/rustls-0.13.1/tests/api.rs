@@ -12,16 +12,27 @@ use rustls::{ClientConfig, ClientSession, ResolvesClientCert};
 use rustls::{ServerConfig, ServerSession, ResolvesServerCert};
 use rustls::Session;
 use rustls::{Stream, StreamOwned};
-use rustls::{ProtocolVersion, SignatureScheme, CipherSuite};
+use rustls::{ProtocolVersion, SignatureScheme, SignatureAlgorithm, CipherSuite};
 use rustls::TLSError;
 use rustls::sign;
 use rustls::{ALL_CIPHERSUITES, SupportedCipherSuite};
 use rustls::{Certificate, PrivateKey};
 use rustls::internal::pemfile;
 use rustls::{RootCertStore, NoClientAuth, AllowAnyAuthenticatedClient};
+use rustls::{SkippedCert, SkipReason};
 use rustls::KeyLog;
+use rustls::{ObservesSession, SessionEvent, Direction};
+use rustls::{ContentType, HandshakeType, AlertLevel, AlertDescription};
+use rustls::{RevocationPolicy, CTEnforcement};
+use rustls::{SharedTicketer, ProvidesTicketKeys, TicketKeyFile, TicketKeySet};
+use rustls::{ClientSessionFileCache, StoresClientSessions};
+use rustls::ClientSessionMemoryCache;
+use rustls::BulkAlgorithm;
+use rustls::crypto::{CryptoProvider, RingProvider, CryptoError};
+use rustls::crypto::{AeadKey, Hash, ActiveKeyExchange, HashAlgorithm, NamedGroup};
 
 extern crate webpki;
+extern crate sct;
 
 fn transfer(left: &mut Session, right: &mut Session) {
     let mut buf = [0u8; 262144];
@@ -45,16 +56,18 @@ fn transfer(left: &mut Session, right: &mut Session) {
 #[derive(Clone, Copy)]
 enum KeyType {
     RSA,
-    ECDSA
+    ECDSA,
+    ED25519
 }
 
-static ALL_KEY_TYPES: [KeyType; 2] = [ KeyType::RSA, KeyType::ECDSA ];
+static ALL_KEY_TYPES: [KeyType; 3] = [ KeyType::RSA, KeyType::ECDSA, KeyType::ED25519 ];
 
 impl KeyType {
     fn path_for(&self, part: &str) -> String {
         match self {
             KeyType::RSA => format!("test-ca/rsa/{}", part),
             KeyType::ECDSA => format!("test-ca/ecdsa/{}", part),
+            KeyType::ED25519 => format!("test-ca/eddsa/{}", part),
         }
     }
 
@@ -83,6 +96,15 @@ impl KeyType {
                 .unwrap()[0]
             .clone()
     }
+
+    fn get_file(&self, part: &str) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        fs::File::open(self.path_for(part))
+            .unwrap()
+            .read_to_end(&mut bytes)
+            .unwrap();
+        bytes
+    }
 }
 
 fn make_server_config(kt: KeyType) -> ServerConfig {
@@ -202,6 +224,10 @@ fn dns_name(name: &'static str) -> webpki::DNSNameRef {
     webpki::DNSNameRef::try_from_ascii_str(name).unwrap()
 }
 
+fn ip_address(addr: &'static str) -> webpki::IpAddrRef {
+    webpki::IpAddrRef::try_from_ascii_str(addr).unwrap()
+}
+
 fn alpn_test(server_protos: Vec<String>, client_protos: Vec<String>, agreed: Option<&str>) {
     let mut client_config = make_client_config(KeyType::RSA);
     let mut server_config = make_server_config(KeyType::RSA);
@@ -565,6 +591,203 @@ fn client_with_sni_disabled_does_not_send_sni() {
     }
 }
 
+struct ServerNeverResolves {}
+
+impl ResolvesServerCert for ServerNeverResolves {
+    fn resolve(&self,
+               _server_name: Option<webpki::DNSNameRef>,
+               _sigschemes: &[SignatureScheme])
+        -> Option<sign::CertifiedKey> {
+        None
+    }
+}
+
+fn pause_at_client_hello(client: &mut ClientSession, server: &mut ServerSession) {
+    transfer(client, server);
+    server.process_new_packets().unwrap();
+    assert!(server.client_hello().is_some());
+    assert!(server.is_handshaking());
+    assert!(!server.wants_write());
+}
+
+#[test]
+fn paused_server_exposes_client_hello() {
+    let mut client_config = make_client_config(KeyType::RSA);
+    client_config.alpn_protocols = vec!["h2".to_string(), "http/1.1".to_string()];
+    let server_config = Arc::new(make_server_config(KeyType::RSA));
+
+    let mut client = ClientSession::new(&Arc::new(client_config), dns_name("localhost"));
+    let mut server = ServerSession::new_pausing(&server_config);
+    assert!(server.client_hello().is_none());
+
+    pause_at_client_hello(&mut client, &mut server);
+    {
+        let hello = server.client_hello().unwrap();
+        let sni: &str = hello.server_name().unwrap().into();
+        assert_eq!(sni, "localhost");
+        assert_eq!(hello.alpn(), &["h2".to_string(), "http/1.1".to_string()]);
+        assert!(hello.signature_schemes().contains(&SignatureScheme::RSA_PSS_SHA256));
+        assert!(hello.cipher_suites().contains(&CipherSuite::TLS13_AES_128_GCM_SHA256));
+    }
+
+    // Pausing again does nothing until we resume.
+    server.process_new_packets().unwrap();
+    assert!(server.client_hello().is_some());
+
+    server.resume_handshake(&server_config, None).unwrap();
+    assert!(server.client_hello().is_none());
+    do_handshake(&mut client, &mut server);
+}
+
+#[test]
+fn paused_server_resumes_with_supplied_cert() {
+    for kt in ALL_KEY_TYPES.iter() {
+        let mut server_config = make_server_config(*kt);
+        server_config.cert_resolver = Arc::new(ServerNeverResolves {});
+        let server_config = Arc::new(server_config);
+
+        for client_config in AllClientVersions::new(make_client_config(*kt)) {
+            let mut client = ClientSession::new(&Arc::new(client_config), dns_name("localhost"));
+            let mut server = ServerSession::new_pausing(&server_config);
+            pause_at_client_hello(&mut client, &mut server);
+
+            let key = sign::any_supported_type(&kt.get_key()).unwrap();
+            let certkey = sign::CertifiedKey::new(kt.get_chain(), Arc::new(key));
+            server.resume_handshake(&server_config, Some(certkey)).unwrap();
+            do_handshake(&mut client, &mut server);
+        }
+    }
+}
+
+#[test]
+fn paused_server_resumes_with_new_config() {
+    let mut client_config = make_client_config(KeyType::RSA);
+    client_config.alpn_protocols = vec!["h2".to_string()];
+
+    let mut first_config = make_server_config(KeyType::RSA);
+    first_config.cert_resolver = Arc::new(ServerNeverResolves {});
+    let mut second_config = make_server_config(KeyType::RSA);
+    second_config.alpn_protocols = vec!["h2".to_string()];
+
+    for client_config in AllClientVersions::new(client_config) {
+        let mut client = ClientSession::new(&Arc::new(client_config), dns_name("localhost"));
+        let mut server = ServerSession::new_pausing(&Arc::new(first_config.clone()));
+        pause_at_client_hello(&mut client, &mut server);
+
+        server.resume_handshake(&Arc::new(second_config.clone()), None).unwrap();
+        do_handshake(&mut client, &mut server);
+        assert_eq!(server.get_alpn_protocol(), Some("h2"));
+        assert_eq!(client.get_alpn_protocol(), Some("h2"));
+    }
+}
+
+#[test]
+fn resume_handshake_requires_pause() {
+    let server_config = Arc::new(make_server_config(KeyType::RSA));
+    let (mut client, mut server) = make_pair_for_arc_configs(
+        &Arc::new(make_client_config(KeyType::RSA)), &server_config);
+
+    assert!(server.resume_handshake(&server_config, None).is_err());
+    do_handshake(&mut client, &mut server);
+    assert!(server.client_hello().is_none());
+}
+
+#[test]
+fn server_can_use_sec1_ec_key() {
+    let kt = KeyType::ECDSA;
+    let mut rd = io::BufReader::new(fs::File::open(kt.path_for("end.sec1")).unwrap());
+    let keys = pemfile::ec_private_keys(&mut rd).unwrap();
+    assert_eq!(keys.len(), 1);
+
+    let mut server_config = ServerConfig::new(NoClientAuth::new());
+    server_config.set_single_cert(kt.get_chain(), keys[0].clone()).unwrap();
+
+    let (mut client, mut server) = make_pair_for_configs(make_client_config(kt),
+                                                         server_config);
+    do_handshake(&mut client, &mut server);
+}
+
+#[test]
+fn malformed_keys_are_rejected() {
+    for len in &[0, 1, 200, 1000, 0x10000, 0x20000] {
+        let key = PrivateKey(vec![0x30; *len]);
+        assert!(sign::any_ecdsa_type(&key).is_err());
+        assert!(sign::any_supported_type(&key).is_err());
+    }
+}
+
+#[test]
+fn ed25519_keys_are_supported() {
+    let key = KeyType::ED25519.get_key();
+    assert!(sign::any_ecdsa_type(&key).is_err());
+    assert_eq!(sign::any_eddsa_type(&key).unwrap().algorithm(),
+               SignatureAlgorithm::ED25519);
+    assert_eq!(sign::any_supported_type(&key).unwrap().algorithm(),
+               SignatureAlgorithm::ED25519);
+
+    let ecdsa_key = KeyType::ECDSA.get_key();
+    assert!(sign::any_eddsa_type(&ecdsa_key).is_err());
+}
+
+#[test]
+fn server_can_use_ed25519_key() {
+    let kt = KeyType::ED25519;
+    let server_config = Arc::new(make_server_config(kt));
+
+    for client_config in AllClientVersions::new(make_client_config(kt)) {
+        let (mut client, mut server) = make_pair_for_arc_configs(&Arc::new(client_config),
+                                                                 &server_config);
+        do_handshake(&mut client, &mut server);
+
+        // TLS1.2 carries Ed25519 signatures in ECDSA suites.
+        let suite = client.get_negotiated_ciphersuite().unwrap();
+        if client.get_protocol_version() == Some(ProtocolVersion::TLSv1_2) {
+            assert_eq!(suite.sign, SignatureAlgorithm::ECDSA);
+        }
+    }
+}
+
+#[test]
+fn client_rejects_ed25519_server_without_ed25519_scheme() {
+    let kt = KeyType::ED25519;
+    let server_config = Arc::new(make_server_config(kt));
+    let mut client_config = make_client_config(kt);
+    client_config.ciphersuites = ALL_CIPHERSUITES.iter()
+        .filter(|suite| suite.sign != SignatureAlgorithm::ECDSA)
+        .cloned()
+        .collect();
+    client_config.versions = vec![ProtocolVersion::TLSv1_2];
+
+    let (mut client, mut server) = make_pair_for_arc_configs(&Arc::new(client_config),
+                                                             &server_config);
+    assert_eq!(do_handshake_until_error(&mut client, &mut server),
+               Err(TLSErrorFromPeer::Server(TLSError::PeerIncompatibleError(
+                   "no ciphersuites in common".to_string()))));
+}
+
+#[test]
+fn pem_items_reads_mixed_bundle() {
+    let kt = KeyType::ECDSA;
+    let mut bundle = kt.get_file("end.sec1");
+    bundle.extend_from_slice(&kt.get_file("end.fullchain"));
+
+    let items = pemfile::items(&mut io::BufReader::new(&bundle[..]))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(items.len(), 4);
+    match items[0] {
+        pemfile::Item::ECKey(_) => {}
+        ref other => panic!("unexpected item {:?}", other),
+    }
+    let certs = items[1..].iter()
+        .map(|item| match *item {
+            pemfile::Item::X509Certificate(ref cert) => cert.clone(),
+            ref other => panic!("unexpected item {:?}", other),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(certs, kt.get_chain());
+}
+
 #[test]
 fn client_checks_server_certificate_with_given_name() {
     for kt in ALL_KEY_TYPES.iter() {
@@ -586,6 +809,182 @@ fn client_checks_server_certificate_with_given_name() {
     }
 }
 
+#[test]
+fn client_checks_server_certificate_with_given_ip_address() {
+    for kt in ALL_KEY_TYPES.iter() {
+        let client_config = make_client_config(*kt);
+        let server_config = Arc::new(make_server_config(*kt));
+
+        for client_config in AllClientVersions::new(client_config) {
+            let client_config = Arc::new(client_config);
+
+            for addr in &["127.0.0.1", "::1"] {
+                let mut client = ClientSession::new(&client_config, ip_address(addr));
+                let mut server = ServerSession::new(&server_config);
+                do_handshake(&mut client, &mut server);
+            }
+
+            let mut client = ClientSession::new(&client_config, ip_address("127.0.0.2"));
+            let mut server = ServerSession::new(&server_config);
+
+            let err = do_handshake_until_error(&mut client, &mut server);
+            assert_eq!(err,
+                       Err(TLSErrorFromPeer::Client(
+                               TLSError::WebPKIError(webpki::Error::CertNotValidForName))
+                           )
+                       );
+        }
+    }
+}
+
+#[test]
+fn client_does_not_send_sni_for_ip_address() {
+    let kt = KeyType::RSA;
+    let mut client = ClientSession::new(&Arc::new(make_client_config(kt)),
+                                        ip_address("127.0.0.1"));
+    let mut server = ServerSession::new(&Arc::new(make_server_config(kt)));
+
+    do_handshake(&mut client, &mut server);
+    assert_eq!(None, server.get_sni_hostname());
+}
+
+fn revocation_test(kt: KeyType,
+                   crls: Vec<&str>,
+                   policy: RevocationPolicy,
+                   ocsp: Option<&str>,
+                   expect: Result<(), webpki::Error>) {
+    let mut client_config = make_client_config(kt);
+    let crls = crls.iter()
+        .map(|crl| kt.get_file(crl))
+        .collect();
+    client_config.set_revocation_checking(crls, policy);
+
+    let mut server_config = make_server_config(kt);
+    if let Some(ocsp) = ocsp {
+        server_config.set_single_cert_with_ocsp_and_sct(kt.get_chain(),
+                                                        kt.get_key(),
+                                                        kt.get_file(ocsp),
+                                                        vec![])
+            .unwrap();
+    }
+    let server_config = Arc::new(server_config);
+
+    for client_config in AllClientVersions::new(client_config) {
+        let mut client = ClientSession::new(&Arc::new(client_config),
+                                            dns_name("localhost"));
+        let mut server = ServerSession::new(&server_config);
+
+        let result = do_handshake_until_error(&mut client, &mut server);
+        match expect {
+            Ok(()) => assert_eq!(result, Ok(())),
+            Err(err) => assert_eq!(result,
+                                   Err(TLSErrorFromPeer::Client(TLSError::WebPKIError(err)))),
+        }
+    }
+}
+
+#[test]
+fn client_rejects_server_cert_revoked_by_crl() {
+    for kt in ALL_KEY_TYPES.iter() {
+        revocation_test(*kt, vec!["inter.revoked.crl"], RevocationPolicy::SoftFail,
+                        None, Err(webpki::Error::CertRevoked));
+    }
+}
+
+#[test]
+fn client_accepts_server_cert_not_revoked_by_crl() {
+    for kt in ALL_KEY_TYPES.iter() {
+        revocation_test(*kt, vec!["inter.good.crl"], RevocationPolicy::HardFail,
+                        None, Ok(()));
+    }
+}
+
+#[test]
+fn client_rejects_server_cert_revoked_by_stapled_ocsp() {
+    for kt in ALL_KEY_TYPES.iter() {
+        revocation_test(*kt, vec![], RevocationPolicy::SoftFail,
+                        Some("end.revoked.ocsp"), Err(webpki::Error::CertRevoked));
+    }
+}
+
+#[test]
+fn client_accepts_server_cert_with_good_stapled_ocsp() {
+    for kt in ALL_KEY_TYPES.iter() {
+        revocation_test(*kt, vec![], RevocationPolicy::HardFail,
+                        Some("end.good.ocsp"), Ok(()));
+    }
+}
+
+#[test]
+fn client_hard_fail_rejects_server_cert_with_unknown_status() {
+    for kt in ALL_KEY_TYPES.iter() {
+        revocation_test(*kt, vec![], RevocationPolicy::HardFail,
+                        None, Err(webpki::Error::UnknownRevocationStatus));
+        revocation_test(*kt, vec![], RevocationPolicy::SoftFail,
+                        None, Ok(()));
+    }
+}
+
+static NO_CT_LOGS: [&sct::Log; 0] = [];
+
+#[test]
+fn client_enforces_ct_policy() {
+    for kt in ALL_KEY_TYPES.iter() {
+        let mut client_config = make_client_config(*kt);
+        client_config.ct_logs = Some(&NO_CT_LOGS);
+        let server_config = Arc::new(make_server_config(*kt));
+
+        // The server has no SCTs, so the policy isn't met.
+        for mut client_config in AllClientVersions::new(client_config.clone()) {
+            client_config.set_persistence(ClientSessionMemoryCache::new(32));
+            let mut client = ClientSession::new(&Arc::new(client_config),
+                                                dns_name("localhost"));
+            let mut server = ServerSession::new(&server_config);
+            assert_eq!(do_handshake_until_error(&mut client, &mut server), Ok(()));
+        }
+
+        client_config.ct_enforcement = CTEnforcement::HardFail;
+        for mut client_config in AllClientVersions::new(client_config) {
+            client_config.set_persistence(ClientSessionMemoryCache::new(32));
+            let mut client = ClientSession::new(&Arc::new(client_config),
+                                                dns_name("localhost"));
+            let mut server = ServerSession::new(&server_config);
+            let expect = sct::PolicyError::NotEnoughSCTs { required: 2, found: 0 };
+            assert_eq!(do_handshake_until_error(&mut client, &mut server),
+                       Err(TLSErrorFromPeer::Client(TLSError::CTPolicyNotMet(expect))));
+        }
+    }
+}
+
+#[test]
+fn client_does_not_resume_soft_fail_session_under_hard_fail() {
+    for kt in ALL_KEY_TYPES.iter() {
+        let mut client_config = make_client_config(*kt);
+        client_config.ct_logs = Some(&NO_CT_LOGS);
+        let server_config = Arc::new(make_server_config(*kt));
+
+        for mut soft_config in AllClientVersions::new(client_config.clone()) {
+            soft_config.set_persistence(ClientSessionMemoryCache::new(32));
+            let mut hard_config = soft_config.clone();
+            hard_config.ct_enforcement = CTEnforcement::HardFail;
+
+            let mut client = ClientSession::new(&Arc::new(soft_config),
+                                                dns_name("localhost"));
+            let mut server = ServerSession::new(&server_config);
+            assert_eq!(do_handshake_until_error(&mut client, &mut server), Ok(()));
+            transfer(&mut server, &mut client);
+            client.process_new_packets().unwrap();
+
+            let mut client = ClientSession::new(&Arc::new(hard_config),
+                                                dns_name("localhost"));
+            let mut server = ServerSession::new(&server_config);
+            let expect = sct::PolicyError::NotEnoughSCTs { required: 2, found: 0 };
+            assert_eq!(do_handshake_until_error(&mut client, &mut server),
+                       Err(TLSErrorFromPeer::Client(TLSError::CTPolicyNotMet(expect))));
+        }
+    }
+}
+
 struct ClientCheckCertResolve {
     query_count: atomic::AtomicUsize,
     expect_queries: usize
@@ -1743,3 +2142,760 @@ fn vectored_write_with_slow_client() {
     }
     check_read(&mut client, b"01234567890123456789");
 }
+
+fn make_early_data_configs(max_early_data_size: u32) -> (Arc<ClientConfig>, Arc<ServerConfig>) {
+    let kt = KeyType::RSA;
+    let mut client_config = make_client_config(kt);
+    client_config.versions = vec![ ProtocolVersion::TLSv1_3 ];
+    client_config.enable_early_data = true;
+
+    let mut server_config = make_server_config(kt);
+    server_config.ticketer = rustls::Ticketer::new();
+    server_config.max_early_data_size = max_early_data_size;
+
+    (Arc::new(client_config), Arc::new(server_config))
+}
+
+#[test]
+fn early_data_not_available_without_ticket() {
+    let (client_config, server_config) = make_early_data_configs(1234);
+    let (mut client, mut server) = make_pair_for_arc_configs(&client_config, &server_config);
+
+    assert!(client.early_data().is_none());
+    do_handshake(&mut client, &mut server);
+    assert!(!client.is_early_data_accepted());
+    assert!(!server.is_early_data_accepted());
+}
+
+#[test]
+fn early_data_is_accepted_on_resumption() {
+    let (client_config, server_config) = make_early_data_configs(1234);
+
+    let (mut client, mut server) = make_pair_for_arc_configs(&client_config, &server_config);
+    do_handshake(&mut client, &mut server);
+
+    let (mut client, mut server) = make_pair_for_arc_configs(&client_config, &server_config);
+    assert_eq!(client.early_data().unwrap().bytes_left(), 1234);
+    assert_eq!(client.early_data().unwrap().write(b"hello").unwrap(), 5);
+    assert_eq!(client.early_data().unwrap().bytes_left(), 1229);
+
+    transfer(&mut client, &mut server);
+    server.process_new_packets().unwrap();
+    assert!(server.is_early_data_accepted());
+    check_read(&mut server.early_data().unwrap(), b"hello");
+
+    do_handshake(&mut client, &mut server);
+    assert!(client.is_early_data_accepted());
+    assert!(client.early_data().is_none());
+
+    client.write(b"world").unwrap();
+    transfer(&mut client, &mut server);
+    server.process_new_packets().unwrap();
+    check_read(&mut server, b"world");
+}
+
+#[test]
+fn early_data_writes_are_limited() {
+    let (client_config, server_config) = make_early_data_configs(4);
+
+    let (mut client, mut server) = make_pair_for_arc_configs(&client_config, &server_config);
+    do_handshake(&mut client, &mut server);
+
+    let (mut client, mut server) = make_pair_for_arc_configs(&client_config, &server_config);
+    assert_eq!(client.early_data().unwrap().write(b"hello").unwrap(), 4);
+    assert_eq!(client.early_data().unwrap().write(b"o").unwrap(), 0);
+
+    do_handshake(&mut client, &mut server);
+    assert!(client.is_early_data_accepted());
+    check_read(&mut server.early_data().unwrap(), b"hell");
+}
+
+struct FixedTicketKeys(TicketKeySet);
+
+impl ProvidesTicketKeys for FixedTicketKeys {
+    fn load(&self) -> Result<TicketKeySet, TLSError> {
+        Ok(self.0.clone())
+    }
+}
+
+fn make_server_config_with_ticket_keys(keys: &TicketKeySet) -> Arc<ServerConfig> {
+    let mut server_config = make_server_config(KeyType::RSA);
+    let ticketer = SharedTicketer::new(server_config.crypto_provider.clone(),
+                                       Box::new(FixedTicketKeys(keys.clone())),
+                                       3600,
+                                       60)
+        .unwrap();
+    server_config.ticketer = Arc::new(ticketer);
+    server_config.max_early_data_size = 1234;
+    Arc::new(server_config)
+}
+
+// Early data is only accepted if the server could decrypt the ticket.
+fn resumes_across_servers(first: &Arc<ServerConfig>, second: &Arc<ServerConfig>) -> bool {
+    let (client_config, _) = make_early_data_configs(1234);
+
+    let (mut client, mut server) = make_pair_for_arc_configs(&client_config, first);
+    do_handshake(&mut client, &mut server);
+
+    let (mut client, mut server) = make_pair_for_arc_configs(&client_config, second);
+    client.early_data().unwrap().write(b"hello").unwrap();
+    do_handshake(&mut client, &mut server);
+    server.is_early_data_accepted()
+}
+
+#[test]
+fn shared_ticket_keys_resume_across_servers() {
+    let provider = RingProvider {};
+    let keys = TicketKeySet::generate(&provider);
+    let first = make_server_config_with_ticket_keys(&keys);
+
+    assert!(resumes_across_servers(&first, &make_server_config_with_ticket_keys(&keys)));
+
+    // After a rotation, the previous key still works.
+    let rotated = make_server_config_with_ticket_keys(&keys.rotate(&provider));
+    assert!(resumes_across_servers(&first, &rotated));
+
+    // But not after two.
+    let rotated_twice = make_server_config_with_ticket_keys(&keys.rotate(&provider).rotate(&provider));
+    assert!(!resumes_across_servers(&first, &rotated_twice));
+
+    // Nor with unrelated keys.
+    let unrelated = make_server_config_with_ticket_keys(&TicketKeySet::generate(&provider));
+    assert!(!resumes_across_servers(&first, &unrelated));
+}
+
+#[test]
+fn ticket_key_set_encoding() {
+    let provider = RingProvider {};
+    let keys = TicketKeySet::generate(&provider).rotate(&provider);
+    let decoded = TicketKeySet::from_bytes(&keys.to_bytes()).unwrap();
+    assert_eq!(decoded.current.name(), keys.current.name());
+    assert_eq!(decoded.previous.unwrap().name(),
+               keys.previous.as_ref().unwrap().name());
+
+    let fresh = TicketKeySet::generate(&provider);
+    assert!(TicketKeySet::from_bytes(&fresh.to_bytes()).unwrap().previous.is_none());
+
+    let bytes = keys.to_bytes();
+    assert!(TicketKeySet::from_bytes(&bytes[..bytes.len() - 1]).is_none());
+    assert!(TicketKeySet::from_bytes(&[]).is_none());
+}
+
+#[test]
+fn ticket_key_file_rotation() {
+    let path = std::env::temp_dir().join(format!("rustls-ticket-keys-{}", std::process::id()));
+    let _ = fs::remove_file(&path);
+    let file = TicketKeyFile::new(&path);
+    assert!(file.load().is_err());
+
+    file.rotate(&RingProvider {}).unwrap();
+    let first = file.load().unwrap();
+    assert!(first.previous.is_none());
+
+    file.rotate(&RingProvider {}).unwrap();
+    let second = file.load().unwrap();
+    assert_eq!(second.previous.unwrap().name(), first.current.name());
+
+    check_private_file(&path);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn ticket_key_file_rotates_when_due() {
+    let path = std::env::temp_dir().join(format!("rustls-rotating-ticket-keys-{}",
+                                                 std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    // Not yet due: the file is made, and then left alone.
+    let file = TicketKeyFile::rotating(&path, Arc::new(RingProvider {}), 3600);
+    let first = file.load().unwrap();
+    assert!(first.previous.is_none());
+    assert_eq!(file.load().unwrap().current.name(), first.current.name());
+
+    // Always due.
+    let file = TicketKeyFile::rotating(&path, Arc::new(RingProvider {}), 0);
+    let second = file.load().unwrap();
+    assert_eq!(second.previous.unwrap().name(), first.current.name());
+
+    // Readers never rotate.
+    let reader = TicketKeyFile::new(&path);
+    assert_eq!(reader.load().unwrap().current.name(), second.current.name());
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[cfg(unix)]
+fn check_private_file(path: &std::path::Path) {
+    use std::os::unix::fs::PermissionsExt;
+    assert_eq!(fs::metadata(path).unwrap().permissions().mode() & 0o777, 0o600);
+}
+
+#[cfg(not(unix))]
+fn check_private_file(_path: &std::path::Path) {}
+
+fn temp_cache_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir()
+        .join(format!("rustls-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn file_session_cache_resumes_across_processes() {
+    let dir = temp_cache_dir("session-cache");
+    let (client_config, server_config) = make_early_data_configs(1234);
+
+    // Each "process" gets its own cache instance over the same directory.
+    let process_config = || {
+        let mut config = (*client_config).clone();
+        config.session_persistence = ClientSessionFileCache::new(&dir, 8);
+        Arc::new(config)
+    };
+
+    let (mut client, mut server) = make_pair_for_arc_configs(&process_config(), &server_config);
+    do_handshake(&mut client, &mut server);
+
+    let (mut client, mut server) = make_pair_for_arc_configs(&process_config(), &server_config);
+    client.early_data().unwrap().write(b"hello").unwrap();
+    do_handshake(&mut client, &mut server);
+    assert!(server.is_early_data_accepted());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn file_session_cache_evicts_least_recently_used() {
+    let dir = temp_cache_dir("session-cache-lru");
+    let cache = ClientSessionFileCache::new(&dir, 2);
+
+    assert!(cache.put(b"a".to_vec(), b"1".to_vec()));
+    assert!(cache.put(b"b".to_vec(), b"2".to_vec()));
+    assert_eq!(cache.get(b"a"), Some(b"1".to_vec()));
+    assert!(cache.put(b"c".to_vec(), b"3".to_vec()));
+
+    let cache = ClientSessionFileCache::new(&dir, 2);
+    assert_eq!(cache.get(b"a"), Some(b"1".to_vec()));
+    assert_eq!(cache.get(b"b"), None);
+    assert_eq!(cache.get(b"c"), Some(b"3".to_vec()));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+fn read_cache_file(dir: &std::path::Path) -> (std::path::PathBuf, Vec<u8>) {
+    let path = fs::read_dir(dir).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().is_none())
+        .unwrap();
+    let bytes = fs::read(&path).unwrap();
+    (path, bytes)
+}
+
+#[test]
+fn file_session_cache_get_does_not_write() {
+    let dir = temp_cache_dir("session-cache-get");
+    let cache = ClientSessionFileCache::new(&dir, 2);
+
+    assert!(cache.put(b"a".to_vec(), b"1".to_vec()));
+    assert!(cache.put(b"b".to_vec(), b"2".to_vec()));
+    let (path, before) = read_cache_file(&dir);
+    check_private_file(&path);
+
+    assert_eq!(cache.get(b"a"), Some(b"1".to_vec()));
+    assert_eq!(cache.get(b"b"), Some(b"2".to_vec()));
+    assert_eq!(cache.get(b"a"), Some(b"1".to_vec()));
+    assert_eq!(read_cache_file(&dir).1, before);
+
+    // The uses are written when the cache is dropped, so "b" is now
+    // the least recently used.
+    drop(cache);
+    assert_ne!(read_cache_file(&dir).1, before);
+
+    let cache = ClientSessionFileCache::new(&dir, 2);
+    assert!(cache.put(b"c".to_vec(), b"3".to_vec()));
+    assert_eq!(cache.get(b"a"), Some(b"1".to_vec()));
+    assert_eq!(cache.get(b"b"), None);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+fn make_roots_dir(name: &str) -> std::path::PathBuf {
+    let dir = temp_cache_dir(name);
+    fs::create_dir_all(&dir).unwrap();
+
+    let mut bundle = Vec::new();
+    fs::File::open(KeyType::RSA.path_for("ca.cert")).unwrap()
+        .read_to_end(&mut bundle).unwrap();
+    bundle.extend_from_slice(b"-----BEGIN CERTIFICATE-----\n\
+                               bm90IGEgY2VydGlmaWNhdGU=\n\
+                               -----END CERTIFICATE-----\n");
+    fs::write(dir.join("bundle.pem"), &bundle).unwrap();
+
+    fs::copy(KeyType::RSA.path_for("ca.cert"), dir.join("rsa.pem")).unwrap();
+    fs::copy(KeyType::ECDSA.path_for("ca.cert"), dir.join("ecdsa.pem")).unwrap();
+    fs::write(dir.join("truncated.pem"), b"-----BEGIN CERTIFICATE-----\nAAAA\n").unwrap();
+    fs::create_dir(dir.join("subdir")).unwrap();
+    dir
+}
+
+#[test]
+fn root_store_loads_pem_directory() {
+    let dir = make_roots_dir("roots-dir");
+    let mut store = RootCertStore::empty();
+    let report = store.add_pem_path(&dir);
+
+    assert_eq!(store.len(), 2);
+    assert_eq!(report.added, 2);
+    assert_eq!(report.duplicates, 1);
+    assert_eq!(report.sources.len(), 4);
+
+    assert_eq!(report.skipped.len(), 2);
+    assert_eq!(report.skipped[0].path, dir.join("bundle.pem"));
+    match report.skipped[0].reason {
+        SkipReason::BadCertificate { index: 1, .. } => {}
+        ref other => panic!("unexpected skip {:?}", other),
+    }
+    assert_eq!(report.skipped[1],
+               SkippedCert {
+                   path: dir.join("truncated.pem"),
+                   reason: SkipReason::Pem(pemfile::PemError::MissingEnd {
+                       label: "CERTIFICATE".to_string(),
+                       line: 1,
+                   }),
+               });
+
+    // Loading again adds nothing new.
+    let report = store.add_pem_path(dir.join("rsa.pem"));
+    assert_eq!((report.added, report.duplicates), (0, 1));
+    assert_eq!(store.len(), 2);
+
+    let report = store.add_pem_path(dir.join("missing.pem"));
+    assert_eq!(report.sources.len(), 0);
+    assert_eq!(report.skipped[0].reason, SkipReason::Io(io::ErrorKind::NotFound));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn root_store_loads_system_roots_from_environment() {
+    let dir = make_roots_dir("roots-env");
+    let missing = dir.join("missing");
+    std::env::set_var("SSL_CERT_FILE", dir.join("rsa.pem"));
+    std::env::set_var("SSL_CERT_DIR",
+                      std::env::join_paths(&[dir.join("subdir"), missing.clone()]).unwrap());
+
+    let (roots, report) = RootCertStore::load_system_roots();
+    assert_eq!(roots.len(), 1);
+    assert_eq!(report.sources, vec![dir.join("rsa.pem")]);
+    assert_eq!(report.skipped,
+               vec![SkippedCert { path: missing, reason: SkipReason::Io(io::ErrorKind::NotFound) }]);
+
+    let mut client_config = ClientConfig::new();
+    client_config.root_store = roots;
+    let (mut client, mut server) = make_pair_for_configs(client_config,
+                                                         make_server_config(KeyType::RSA));
+    do_handshake(&mut client, &mut server);
+
+    std::env::remove_var("SSL_CERT_FILE");
+    std::env::remove_var("SSL_CERT_DIR");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn early_data_is_rejected_when_server_disables_it() {
+    let (client_config, server_config) = make_early_data_configs(1234);
+
+    let (mut client, mut server) = make_pair_for_arc_configs(&client_config, &server_config);
+    do_handshake(&mut client, &mut server);
+
+    // Same ticket keys, but no early data.
+    let mut server_config = (*server_config).clone();
+    server_config.max_early_data_size = 0;
+    let server_config = Arc::new(server_config);
+
+    let (mut client, mut server) = make_pair_for_arc_configs(&client_config, &server_config);
+    assert_eq!(client.early_data().unwrap().write(b"hello").unwrap(), 5);
+
+    do_handshake(&mut client, &mut server);
+    assert!(!client.is_early_data_accepted());
+    assert!(!server.is_early_data_accepted());
+    assert!(server.early_data().is_none());
+
+    // The handshake completed with a resumed session; the
+    // rejected data must be sent again.
+    client.write(b"hello").unwrap();
+    transfer(&mut client, &mut server);
+    server.process_new_packets().unwrap();
+    check_read(&mut server, b"hello");
+}
+
+#[test]
+fn early_data_is_rejected_when_server_limit_shrinks() {
+    let (client_config, server_config) = make_early_data_configs(1234);
+
+    let (mut client, mut server) = make_pair_for_arc_configs(&client_config, &server_config);
+    do_handshake(&mut client, &mut server);
+
+    // Same ticket keys, but a smaller limit than the ticket allows.
+    let mut server_config = (*server_config).clone();
+    server_config.max_early_data_size = 4;
+    let server_config = Arc::new(server_config);
+
+    let (mut client, mut server) = make_pair_for_arc_configs(&client_config, &server_config);
+    assert_eq!(client.early_data().unwrap().write(b"hello").unwrap(), 5);
+
+    do_handshake(&mut client, &mut server);
+    assert!(!client.is_early_data_accepted());
+    assert!(!server.is_early_data_accepted());
+
+    client.write(b"hello").unwrap();
+    transfer(&mut client, &mut server);
+    server.process_new_packets().unwrap();
+    check_read(&mut server, b"hello");
+}
+
+#[test]
+fn early_data_is_rejected_when_replayed() {
+    let (client_config, server_config) = make_early_data_configs(1234);
+
+    let (mut client, mut server) = make_pair_for_arc_configs(&client_config, &server_config);
+    do_handshake(&mut client, &mut server);
+
+    let mut client = ClientSession::new(&client_config, dns_name("localhost"));
+    client.early_data().unwrap().write(b"hello").unwrap();
+
+    let mut first_flight = Vec::new();
+    client.write_tls(&mut first_flight).unwrap();
+
+    let mut server = ServerSession::new(&server_config);
+    server.read_tls(&mut first_flight.as_slice()).unwrap();
+    server.process_new_packets().unwrap();
+    assert!(server.is_early_data_accepted());
+
+    let mut replay = ServerSession::new(&server_config);
+    replay.read_tls(&mut first_flight.as_slice()).unwrap();
+    replay.process_new_packets().unwrap();
+    assert!(!replay.is_early_data_accepted());
+    assert!(replay.early_data().is_none());
+}
+
+/// A provider with predictable randomness, which can't do
+/// ChaCha20Poly1305.
+struct DeterministicProvider {
+    inner: RingProvider,
+}
+
+impl CryptoProvider for DeterministicProvider {
+    fn fill_random(&self, bytes: &mut [u8]) -> Result<(), CryptoError> {
+        for b in bytes.iter_mut() {
+            *b = 0x5a;
+        }
+        Ok(())
+    }
+
+    fn hash(&self, alg: HashAlgorithm) -> Option<&'static Hash> {
+        self.inner.hash(alg)
+    }
+
+    fn supports_bulk(&self, alg: &BulkAlgorithm) -> bool {
+        *alg != BulkAlgorithm::CHACHA20_POLY1305
+    }
+
+    fn aead_key(&self, alg: &BulkAlgorithm, key: &[u8]) -> Result<Box<AeadKey>, CryptoError> {
+        self.inner.aead_key(alg, key)
+    }
+
+    fn start_key_exchange(&self, group: NamedGroup) -> Option<Box<ActiveKeyExchange>> {
+        self.inner.start_key_exchange(group)
+    }
+}
+
+#[test]
+fn crypto_provider_is_used() {
+    for version in &[ProtocolVersion::TLSv1_3, ProtocolVersion::TLSv1_2] {
+        let provider = Arc::new(DeterministicProvider { inner: RingProvider {} });
+        let client_key_log = Arc::new(KeyLogToVec::new());
+
+        let mut client_config = make_client_config(KeyType::RSA);
+        client_config.versions = vec![ *version ];
+        client_config.key_log = client_key_log.clone();
+        client_config.crypto_provider = provider.clone();
+
+        let mut server_config = make_server_config(KeyType::RSA);
+        server_config.crypto_provider = provider;
+
+        let (mut client, mut server) = make_pair_for_configs(client_config, server_config);
+        do_handshake(&mut client, &mut server);
+
+        // ChaCha20Poly1305 suites are preferred by default, but the
+        // provider can't do them.
+        let suite = client.get_negotiated_ciphersuite().unwrap();
+        assert_ne!(BulkAlgorithm::CHACHA20_POLY1305, suite.bulk);
+        assert_eq!(Some(suite), server.get_negotiated_ciphersuite());
+
+        let log = client_key_log.take();
+        assert!(!log.is_empty());
+        for item in log {
+            assert_eq!(vec![0x5a; 32], item.client_random);
+        }
+    }
+}
+
+#[test]
+fn ticketer_uses_crypto_provider() {
+    // This provider can't do ChaCha20Poly1305, so the ticketer must
+    // fall back to something it can do.
+    let provider = Arc::new(DeterministicProvider { inner: RingProvider {} });
+    let ticketer = rustls::Ticketer::with_provider(provider);
+
+    let ticket = ticketer.encrypt(b"hello").unwrap();
+    assert_eq!(&ticket[..12], &[0x5a; 12]);
+    assert_eq!(ticketer.decrypt(&ticket), Some(b"hello".to_vec()));
+    assert_eq!(rustls::Ticketer::new().decrypt(&ticket), None);
+}
+
+/// A provider without SHA384.
+struct NoSha384Provider {
+    inner: RingProvider,
+}
+
+impl CryptoProvider for NoSha384Provider {
+    fn fill_random(&self, bytes: &mut [u8]) -> Result<(), CryptoError> {
+        self.inner.fill_random(bytes)
+    }
+
+    fn hash(&self, alg: HashAlgorithm) -> Option<&'static Hash> {
+        match alg {
+            HashAlgorithm::SHA384 => None,
+            _ => self.inner.hash(alg),
+        }
+    }
+
+    fn supports_bulk(&self, alg: &BulkAlgorithm) -> bool {
+        self.inner.supports_bulk(alg)
+    }
+
+    fn aead_key(&self, alg: &BulkAlgorithm, key: &[u8]) -> Result<Box<AeadKey>, CryptoError> {
+        self.inner.aead_key(alg, key)
+    }
+
+    fn start_key_exchange(&self, group: NamedGroup) -> Option<Box<ActiveKeyExchange>> {
+        self.inner.start_key_exchange(group)
+    }
+}
+
+#[test]
+fn suites_with_unsupported_hash_are_not_used() {
+    for suite in ALL_CIPHERSUITES.iter() {
+        if suite.hash != HashAlgorithm::SHA384 {
+            continue;
+        }
+
+        let mut client_config = make_client_config(KeyType::RSA);
+        client_config.ciphersuites = vec![ *suite ];
+        client_config.crypto_provider = Arc::new(NoSha384Provider { inner: RingProvider {} });
+
+        let (mut client, mut server) = make_pair_for_configs(client_config,
+                                                             make_server_config(KeyType::RSA));
+        assert_eq!(do_handshake_until_error(&mut client, &mut server),
+                   Err(TLSErrorFromPeer::Server(TLSError::PeerIncompatibleError(
+                       "no ciphersuites in common".into()))));
+
+        let mut server_config = make_server_config(KeyType::RSA);
+        server_config.ciphersuites = vec![ *suite ];
+        server_config.crypto_provider = Arc::new(NoSha384Provider { inner: RingProvider {} });
+
+        let (mut client, mut server) = make_pair_for_configs(make_client_config(KeyType::RSA),
+                                                             server_config);
+        assert_eq!(do_handshake_until_error(&mut client, &mut server),
+                   Err(TLSErrorFromPeer::Server(TLSError::PeerIncompatibleError(
+                       "no ciphersuites in common".into()))));
+    }
+}
+
+struct RecordingObserver {
+    events: Mutex<Vec<SessionEvent>>,
+}
+
+impl RecordingObserver {
+    fn new() -> Arc<RecordingObserver> {
+        Arc::new(RecordingObserver { events: Mutex::new(Vec::new()) })
+    }
+
+    fn take(&self) -> Vec<SessionEvent> {
+        mem::replace(&mut *self.events.lock().unwrap(), Vec::new())
+    }
+}
+
+impl ObservesSession for RecordingObserver {
+    fn observe(&self, event: &SessionEvent) {
+        self.events.lock().unwrap().push(event.clone());
+    }
+}
+
+fn handshake_messages(events: &[SessionEvent], want: Direction) -> Vec<HandshakeType> {
+    events.iter()
+        .filter_map(|ev| match *ev {
+            SessionEvent::HandshakeMessage { direction, typ } if direction == want => Some(typ),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn observers_see_handshake() {
+    let client_observer = RecordingObserver::new();
+    let server_observer = RecordingObserver::new();
+
+    let mut client_config = make_client_config(KeyType::RSA);
+    client_config.versions = vec![ ProtocolVersion::TLSv1_2 ];
+    client_config.observer = client_observer.clone();
+    let mut server_config = make_server_config(KeyType::RSA);
+    server_config.observer = server_observer.clone();
+
+    let (mut client, mut server) = make_pair_for_configs(client_config, server_config);
+    do_handshake(&mut client, &mut server);
+
+    let client_events = client_observer.take();
+    let server_events = server_observer.take();
+
+    let client_sent = handshake_messages(&client_events, Direction::Sent);
+    assert_eq!(client_sent,
+               vec![ HandshakeType::ClientHello, HandshakeType::ClientKeyExchange,
+                     HandshakeType::Finished ]);
+    assert_eq!(handshake_messages(&server_events, Direction::Received), client_sent);
+
+    let server_sent = handshake_messages(&server_events, Direction::Sent);
+    assert_eq!(server_sent[0], HandshakeType::ServerHello);
+    assert_eq!(handshake_messages(&client_events, Direction::Received), server_sent);
+
+    let negotiated = SessionEvent::Negotiated {
+        version: ProtocolVersion::TLSv1_2,
+        suite: client.get_negotiated_ciphersuite().unwrap().suite,
+    };
+    assert!(client_events.contains(&negotiated));
+    assert!(server_events.contains(&negotiated));
+
+    assert!(server_events.contains(&SessionEvent::StateChange {
+        from: "ExpectClientHello",
+        to: "ExpectTLS12ClientKX",
+    }));
+    assert!(client_events.iter().any(|ev| match *ev {
+        SessionEvent::Record { direction: Direction::Received, typ: ContentType::Handshake, length } => length > 0,
+        _ => false,
+    }));
+}
+
+#[test]
+fn observers_see_alerts() {
+    let client_observer = RecordingObserver::new();
+    let server_observer = RecordingObserver::new();
+
+    let mut client_config = make_client_config(KeyType::RSA);
+    client_config.observer = client_observer.clone();
+    let mut server_config = make_server_config(KeyType::RSA);
+    server_config.observer = server_observer.clone();
+
+    let mut client = ClientSession::new(&Arc::new(client_config), dns_name("not-the-right-name"));
+    let mut server = ServerSession::new(&Arc::new(server_config));
+    assert!(do_handshake_until_error(&mut client, &mut server).is_err());
+    transfer(&mut client, &mut server);
+    assert!(server.process_new_packets().is_err());
+
+    let alert = |direction| SessionEvent::Alert {
+        direction,
+        level: AlertLevel::Fatal,
+        description: AlertDescription::BadCertificate,
+    };
+    assert!(client_observer.take().contains(&alert(Direction::Sent)));
+    assert!(server_observer.take().contains(&alert(Direction::Received)));
+}
+
+fn count_key_updates(events: &[SessionEvent], want: Direction) -> usize {
+    handshake_messages(events, want)
+        .into_iter()
+        .filter(|typ| *typ == HandshakeType::KeyUpdate)
+        .count()
+}
+
+fn send_and_check<A: Session, B: Session>(sender: &mut A, receiver: &mut B, data: &[u8]) {
+    sender.write_all(data).unwrap();
+    transfer(sender, receiver);
+    receiver.process_new_packets().unwrap();
+    check_read(receiver, data);
+}
+
+#[test]
+fn refresh_traffic_keys_requires_tls13_traffic() {
+    let mut client_config = make_client_config(KeyType::RSA);
+    client_config.versions = vec![ ProtocolVersion::TLSv1_2 ];
+    let (mut client, mut server) = make_pair_for_configs(client_config,
+                                                         make_server_config(KeyType::RSA));
+    assert_eq!(client.refresh_traffic_keys(), Err(TLSError::HandshakeNotComplete));
+
+    do_handshake(&mut client, &mut server);
+    assert!(client.refresh_traffic_keys().is_err());
+    assert!(server.refresh_traffic_keys().is_err());
+}
+
+#[test]
+fn refresh_traffic_keys_updates_both_directions() {
+    let client_observer = RecordingObserver::new();
+    let server_observer = RecordingObserver::new();
+
+    let mut client_config = make_client_config(KeyType::RSA);
+    client_config.observer = client_observer.clone();
+    let mut server_config = make_server_config(KeyType::RSA);
+    server_config.observer = server_observer.clone();
+
+    let (mut client, mut server) = make_pair_for_configs(client_config, server_config);
+    do_handshake(&mut client, &mut server);
+    client_observer.take();
+    server_observer.take();
+
+    client.refresh_traffic_keys().unwrap();
+    send_and_check(&mut client, &mut server, b"hello");
+    send_and_check(&mut server, &mut client, b"world");
+    send_and_check(&mut client, &mut server, b"again");
+
+    let client_events = client_observer.take();
+    let server_events = server_observer.take();
+    assert_eq!(count_key_updates(&client_events, Direction::Sent), 1);
+    assert_eq!(count_key_updates(&server_events, Direction::Received), 1);
+
+    // The server answers with its own KeyUpdate.
+    assert_eq!(count_key_updates(&server_events, Direction::Sent), 1);
+    assert_eq!(count_key_updates(&client_events, Direction::Received), 1);
+}
+
+#[test]
+fn long_streams_update_keys_automatically() {
+    let client_observer = RecordingObserver::new();
+    let server_observer = RecordingObserver::new();
+
+    let mut client_config = make_client_config(KeyType::RSA);
+    client_config.observer = client_observer.clone();
+    client_config.key_update_after_records = Some(4);
+    let mut server_config = make_server_config(KeyType::RSA);
+    server_config.observer = server_observer.clone();
+    server_config.key_update_after_bytes = Some(100);
+
+    let (mut client, mut server) = make_pair_for_configs(client_config, server_config);
+    do_handshake(&mut client, &mut server);
+    client_observer.take();
+
+    for i in 0..20u8 {
+        send_and_check(&mut client, &mut server, &[i; 50]);
+        send_and_check(&mut server, &mut client, &[i; 50]);
+    }
+
+    // The client sent 20 records, four to a key.
+    let client_events = client_observer.take();
+    assert_eq!(count_key_updates(&client_events, Direction::Sent), 4);
+
+    // The server sent 1000 bytes of data, and perhaps a ticket,
+    // no more than 100 bytes to a key.
+    let server_events = server_observer.take();
+    let server_updates = count_key_updates(&server_events, Direction::Sent);
+    assert!(server_updates >= 9);
+    assert_eq!(count_key_updates(&client_events, Direction::Received), server_updates);
+}
@@ -7,6 +7,11 @@
 //!
 //! It is intended to be useful to libraries which perform certificate
 //! validation, OCSP libraries, and TLS libraries.
+//!
+//! SCTs can be delivered embedded in the certificate, in a stapled
+//! OCSP response, or in a TLS extension; functions to find them are
+//! provided for each.  `Policy` decides whether the SCTs which verify
+//! are enough to trust that a certificate was publicly logged.
 
 #![forbid(unsafe_code,
           unstable_features)]
@@ -20,6 +25,12 @@
 extern crate ring;
 extern crate untrusted;
 
+mod policy;
+mod sources;
+
+pub use policy::{DisqualifiedLog, Policy, PolicyError};
+pub use sources::{Precert, embedded_scts, ocsp_scts, parse_sct_list};
+
 /// Describes a CT log
 ///
 /// This structure contains some metadata fields not used by the library.
@@ -71,6 +82,12 @@ pub enum Error {
 
     /// The SCT was refers to an unknown log.
     UnknownLog,
+
+    /// A certificate we looked for SCTs in was misencoded.
+    MalformedCertificate,
+
+    /// An OCSP response we looked for SCTs in was misencoded.
+    MalformedOCSPResponse,
 }
 
 impl Error {
@@ -143,6 +160,30 @@ fn write_u16(v: u16, out: &mut Vec<u8>) {
     out.push(v as u8);
 }
 
+/// What an SCT says was submitted to a log.
+#[derive(Debug, Clone, Copy)]
+pub enum Entry<'a> {
+    /// A DER-encoded X.509 certificate.  This is what SCTs delivered
+    /// in a TLS extension or OCSP response are for.
+    X509(&'a [u8]),
+
+    /// The precertificate corresponding to a certificate.  This is what
+    /// SCTs embedded in a certificate are for.
+    Precert(&'a Precert),
+}
+
+/// An SCT which verified successfully.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct VerifiedSCT {
+    /// The log which signed the SCT, as an index into the `logs`
+    /// it was verified against.
+    pub log: usize,
+
+    /// When the log says it saw the entry, in milliseconds since
+    /// the Unix epoch.
+    pub timestamp: u64,
+}
+
 struct SCT<'a> {
     log_id: &'a [u8],
     timestamp: u64,
@@ -158,9 +199,10 @@ const RSA_PKCS1_SHA384: u16 = 0x0501;
 const SCT_V1: u8 = 0u8;
 const SCT_TIMESTAMP: u8 = 0u8;
 const SCT_X509_ENTRY: [u8; 2] = [0, 0];
+const SCT_PRECERT_ENTRY: [u8; 2] = [0, 1];
 
 impl<'a> SCT<'a> {
-    fn verify(&self, key: &[u8], cert: &[u8]) -> Result<(), Error> {
+    fn verify(&self, key: &[u8], entry: Entry) -> Result<(), Error> {
         let alg: &ring::signature::VerificationAlgorithm = match self.sig_alg {
             ECDSA_SHA256 => &ring::signature::ECDSA_P256_SHA256_ASN1,
             ECDSA_SHA384 => &ring::signature::ECDSA_P384_SHA384_ASN1,
@@ -173,9 +215,19 @@ impl<'a> SCT<'a> {
         data.push(SCT_V1);
         data.push(SCT_TIMESTAMP);
         write_u64(self.timestamp, &mut data);
-        data.extend_from_slice(&SCT_X509_ENTRY);
-        write_u24(cert.len() as u32, &mut data);
-        data.extend_from_slice(cert);
+        match entry {
+            Entry::X509(cert) => {
+                data.extend_from_slice(&SCT_X509_ENTRY);
+                write_u24(cert.len() as u32, &mut data);
+                data.extend_from_slice(cert);
+            }
+            Entry::Precert(precert) => {
+                data.extend_from_slice(&SCT_PRECERT_ENTRY);
+                data.extend_from_slice(precert.issuer_key_hash());
+                write_u24(precert.tbs_certificate().len() as u32, &mut data);
+                data.extend_from_slice(precert.tbs_certificate());
+            }
+        }
         write_u16(self.exts.len() as u16, &mut data);
         data.extend_from_slice(self.exts);
 
@@ -244,16 +296,32 @@ pub fn verify_sct(cert: &[u8],
                   sct: &[u8],
                   at_time: u64,
                   logs: &[&Log]) -> Result<usize, Error> {
+    verify_sct_for_entry(Entry::X509(cert), sct, at_time, logs)
+        .map(|verified| verified.log)
+}
+
+/// Like `verify_sct`, but for any kind of log entry.  Use this with
+/// `Entry::Precert` to verify SCTs found by `embedded_scts`.
+///
+/// On success, this function returns the log used and the SCT's
+/// timestamp, which are what `Policy` needs to know.
+pub fn verify_sct_for_entry(entry: Entry,
+                            sct: &[u8],
+                            at_time: u64,
+                            logs: &[&Log]) -> Result<VerifiedSCT, Error> {
     let sct = SCT::parse(sct)?;
     let i = lookup(logs, &sct.log_id)?;
     let log = logs[i];
-    sct.verify(log.key, cert)?;
+    sct.verify(log.key, entry)?;
 
     if sct.timestamp > at_time {
         return Err(Error::TimestampInFuture);
     }
 
-    Ok(i)
+    Ok(VerifiedSCT {
+        log: i,
+        timestamp: sct.timestamp,
+    })
 }
 
 #[cfg(test)]
@@ -262,3 +330,7 @@ mod tests_google;
 mod tests_generated;
 #[cfg(test)]
 mod tests;
+#[cfg(test)]
+mod tests_embedded;
+#[cfg(test)]
+mod tests_policy;
@@ -0,0 +1,179 @@
+// Deciding whether a certificate's SCTs are enough to believe it was
+// publicly logged.  The rules follow Chrome's Certificate Transparency
+// policy.
+
+use super::{Log, VerifiedSCT};
+
+/// Average length of a month, in seconds.
+const MONTH: u64 = 2_629_746;
+
+/// A log which is no longer trusted, such as one which has been frozen
+/// or which failed to meet its obligations.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct DisqualifiedLog {
+    /// The log's `Log::id`.
+    pub id: [u8; 32],
+
+    /// When the log was disqualified, in milliseconds since the Unix
+    /// epoch.  SCTs the log issued before this can still count towards
+    /// the policy, if embedded in a certificate.
+    pub disqualified_at: u64,
+}
+
+/// Why a certificate's SCTs didn't meet a `Policy`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PolicyError {
+    /// There weren't SCTs from enough different qualified logs.
+    NotEnoughSCTs {
+        /// How many are needed.
+        required: usize,
+        /// How many there were.
+        found: usize,
+    },
+
+    /// The qualified SCTs all came from logs with the same operator.
+    SingleOperator,
+
+    /// None of the embedded SCTs came from a log which is qualified
+    /// now.
+    NoCurrentlyQualifiedLog,
+}
+
+/// A Certificate Transparency policy.
+///
+/// A certificate complies if either its embedded SCTs, or the SCTs
+/// delivered alongside it (in a TLS extension or OCSP response), meet
+/// these rules:
+///
+/// * Only one SCT counts per log, and only from qualified logs.  A log
+///   is qualified until it is disqualified; an embedded SCT counts if
+///   its log was qualified at the SCT's timestamp, a delivered SCT if
+///   its log is qualified now.
+/// * There must be enough of them.  Two delivered SCTs are needed.
+///   The number of embedded SCTs needed depends on the certificate's
+///   lifetime: 2 for less than 15 months, 3 for less than 27 months,
+///   4 for less than 39 months, and 5 otherwise.
+/// * They must come from logs with at least two different operators.
+/// * At least one embedded SCT must come from a log that is qualified
+///   now.
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    /// Logs which are no longer trusted.
+    pub disqualified_logs: Vec<DisqualifiedLog>,
+}
+
+impl Policy {
+    /// Make a new policy with no disqualified logs.
+    pub fn new() -> Policy {
+        Policy { disqualified_logs: Vec::new() }
+    }
+
+    /// Check the SCTs for a certificate against the policy.
+    ///
+    /// `embedded` are SCTs embedded in the certificate, and `delivered`
+    /// those that came with it; both were verified against `logs`.
+    /// `cert_lifetime` is the number of seconds between the
+    /// certificate's notBefore and notAfter times, and `at_time`
+    /// is the current time in milliseconds since the Unix epoch.
+    pub fn check(&self,
+                 logs: &[&Log],
+                 embedded: &[VerifiedSCT],
+                 delivered: &[VerifiedSCT],
+                 cert_lifetime: u64,
+                 at_time: u64) -> Result<(), PolicyError> {
+        let embedded_result = self.check_embedded(logs, embedded, cert_lifetime, at_time);
+        if embedded_result.is_ok() {
+            return embedded_result;
+        }
+
+        let delivered_result = self.check_delivered(logs, delivered, at_time);
+        if delivered_result.is_ok() || embedded.is_empty() {
+            return delivered_result;
+        }
+
+        embedded_result
+    }
+
+    fn check_embedded(&self,
+                      logs: &[&Log],
+                      scts: &[VerifiedSCT],
+                      cert_lifetime: u64,
+                      at_time: u64) -> Result<(), PolicyError> {
+        let mut counted = Vec::new();
+        let mut currently_qualified = false;
+
+        for sct in scts {
+            let log = logs[sct.log];
+            if self.is_qualified(log, sct.timestamp) {
+                add_log(&mut counted, log);
+                currently_qualified |= self.is_qualified(log, at_time);
+            }
+        }
+
+        check_logs(&counted, embedded_scts_required(cert_lifetime))?;
+
+        if !currently_qualified {
+            return Err(PolicyError::NoCurrentlyQualifiedLog);
+        }
+
+        Ok(())
+    }
+
+    fn check_delivered(&self,
+                       logs: &[&Log],
+                       scts: &[VerifiedSCT],
+                       at_time: u64) -> Result<(), PolicyError> {
+        let mut counted = Vec::new();
+
+        for sct in scts {
+            let log = logs[sct.log];
+            if self.is_qualified(log, at_time) {
+                add_log(&mut counted, log);
+            }
+        }
+
+        check_logs(&counted, 2)
+    }
+
+    fn is_qualified(&self, log: &Log, at_time: u64) -> bool {
+        self.disqualified_logs
+            .iter()
+            .find(|dq| dq.id == log.id)
+            .map(|dq| at_time < dq.disqualified_at)
+            .unwrap_or(true)
+    }
+}
+
+fn embedded_scts_required(cert_lifetime: u64) -> usize {
+    if cert_lifetime < 15 * MONTH {
+        2
+    } else if cert_lifetime < 27 * MONTH {
+        3
+    } else if cert_lifetime < 39 * MONTH {
+        4
+    } else {
+        5
+    }
+}
+
+fn add_log<'a, 'b>(counted: &mut Vec<&'b Log<'a>>, log: &'b Log<'a>) {
+    if !counted.iter().any(|l| l.id == log.id) {
+        counted.push(log);
+    }
+}
+
+fn check_logs(counted: &[&Log], required: usize) -> Result<(), PolicyError> {
+    if counted.len() < required {
+        return Err(PolicyError::NotEnoughSCTs {
+            required: required,
+            found: counted.len(),
+        });
+    }
+
+    let operator = counted[0].operated_by;
+    if counted.iter().all(|log| log.operated_by == operator) {
+        return Err(PolicyError::SingleOperator);
+    }
+
+    Ok(())
+}
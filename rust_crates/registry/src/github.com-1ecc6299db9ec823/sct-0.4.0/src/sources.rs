@@ -0,0 +1,411 @@
+// Finding SCTs in the places RFC6962 section 3.3 allows servers to
+// put them, and reconstructing the precertificate that embedded SCTs
+// were issued for.
+
+use ring::der;
+use ring::digest;
+use ring::error::Unspecified;
+use untrusted;
+
+use super::{Error, decode_u16, write_u16, write_u24};
+
+/// 1.3.6.1.4.1.11129.2.4.2: a certificate extension holding a
+/// SignedCertificateTimestampList.
+const EMBEDDED_SCT_LIST_OID: &[u8] = &[0x2b, 0x06, 0x01, 0x04, 0x01, 0xd6, 0x79, 0x02, 0x04, 0x02];
+
+/// 1.3.6.1.4.1.11129.2.4.5: the same, as an OCSP SingleResponse
+/// extension.
+const OCSP_SCT_LIST_OID: &[u8] = &[0x2b, 0x06, 0x01, 0x04, 0x01, 0xd6, 0x79, 0x02, 0x04, 0x05];
+
+/// 1.3.6.1.5.5.7.48.1.1: id-pkix-ocsp-basic.
+const OCSP_BASIC_OID: &[u8] = &[0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x30, 0x01, 0x01];
+
+const ENUMERATED: u8 = 0x0a;
+const OCSP_SUCCESSFUL: &[u8] = &[0];
+
+const CONTEXT_0: u8 = der::CONTEXT_SPECIFIC | der::CONSTRUCTED | 0;
+const CONTEXT_1: u8 = der::CONTEXT_SPECIFIC | der::CONSTRUCTED | 1;
+const CONTEXT_3: u8 = der::CONTEXT_SPECIFIC | der::CONSTRUCTED | 3;
+
+/// The precertificate which a CA submitted to logs before issuing a
+/// certificate.  SCTs embedded in a certificate are signatures over
+/// this, rather than over the certificate itself.
+///
+/// It's reconstructed from the certificate as RFC6962 section 3.2
+/// describes: the certificate's TBSCertificate without the SCT list
+/// extension, and a hash of the issuer's public key.  Precertificates
+/// signed by a special-purpose Precertificate Signing Certificate
+/// aren't supported.
+#[derive(Debug, Clone)]
+pub struct Precert {
+    issuer_key_hash: [u8; 32],
+    tbs_certificate: Vec<u8>,
+}
+
+impl Precert {
+    /// Reconstruct the precertificate for `cert`, which was issued
+    /// by `issuer`.  Both are DER-encoded X.509 certificates.
+    pub fn new(cert: &[u8], issuer: &[u8]) -> Result<Precert, Error> {
+        let spki = tbs_certificate_of(issuer)
+            .and_then(subject_public_key_info)
+            .map_err(|_| Error::MalformedCertificate)?;
+        Precert::from_encoded_spki(cert, spki)
+    }
+
+    /// Reconstruct the precertificate for `cert`, which was issued by
+    /// the holder of `issuer_spki`.  `cert` is a DER-encoded X.509
+    /// certificate, and `issuer_spki` is the contents of the issuer's
+    /// SubjectPublicKeyInfo SEQUENCE, as kept by webpki's `TrustAnchor`.
+    pub fn with_issuer_spki(cert: &[u8], issuer_spki: &[u8]) -> Result<Precert, Error> {
+        let mut spki = Vec::new();
+        write_element(der::Tag::Sequence as u8, issuer_spki, &mut spki);
+        Precert::from_encoded_spki(cert, &spki)
+    }
+
+    fn from_encoded_spki(cert: &[u8], spki: &[u8]) -> Result<Precert, Error> {
+        let tbs_certificate = tbs_certificate(untrusted::Input::from(cert))
+            .and_then(remove_sct_list)
+            .map_err(|_| Error::MalformedCertificate)?;
+
+        let mut issuer_key_hash = [0u8; 32];
+        issuer_key_hash.copy_from_slice(digest::digest(&digest::SHA256, spki).as_ref());
+
+        Ok(Precert {
+            issuer_key_hash: issuer_key_hash,
+            tbs_certificate: tbs_certificate,
+        })
+    }
+
+    /// The SHA256 hash of the issuer's SubjectPublicKeyInfo.
+    pub fn issuer_key_hash(&self) -> &[u8; 32] {
+        &self.issuer_key_hash
+    }
+
+    /// The DER encoding of the precertificate's TBSCertificate.
+    pub fn tbs_certificate(&self) -> &[u8] {
+        &self.tbs_certificate
+    }
+}
+
+/// Parse a TLS-encoded SignedCertificateTimestampList, as found in the
+/// `signed_certificate_timestamp` TLS extension, and return the
+/// SCTs it contains.
+pub fn parse_sct_list(list: &[u8]) -> Result<Vec<&[u8]>, Error> {
+    untrusted::Input::from(list).read_all(Error::MalformedSCT, |rd| {
+        let len = rd.skip_and_get_input(2)
+            .map_err(|_| Error::MalformedSCT)
+            .map(decode_u16)?;
+        let items = rd.skip_and_get_input(len as usize)
+            .map_err(|_| Error::MalformedSCT)?;
+
+        items.read_all(Error::MalformedSCT, |rd| {
+            let mut scts = Vec::new();
+
+            while !rd.at_end() {
+                let len = rd.skip_and_get_input(2)
+                    .map_err(|_| Error::MalformedSCT)
+                    .map(decode_u16)?;
+                if len == 0 {
+                    return Err(Error::MalformedSCT);
+                }
+
+                let sct = rd.skip_and_get_input(len as usize)
+                    .map_err(|_| Error::MalformedSCT)?;
+                scts.push(sct.as_slice_less_safe());
+            }
+
+            if scts.is_empty() {
+                return Err(Error::MalformedSCT);
+            }
+
+            Ok(scts)
+        })
+    })
+}
+
+/// Return the SCTs embedded in `cert`, a DER-encoded X.509 certificate.
+/// These must be verified against the certificate's `Precert`.
+///
+/// The result is empty if `cert` has no SCT list extension.
+pub fn embedded_scts(cert: &[u8]) -> Result<Vec<&[u8]>, Error> {
+    let list = tbs_certificate_of(cert)
+        .and_then(extensions)
+        .and_then(|exts| match exts {
+            Some(exts) => find_sct_list(exts, EMBEDDED_SCT_LIST_OID),
+            None => Ok(None),
+        })
+        .map_err(|_| Error::MalformedCertificate)?;
+
+    match list {
+        Some(list) => parse_sct_list(list.as_slice_less_safe()),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Return the SCTs in `response`, a DER-encoded OCSP response.  These
+/// are verified against the certificate itself, like those delivered
+/// in a TLS extension.
+///
+/// SCTs are taken from every SingleResponse in the response; checking
+/// that the response is for the right certificate is left to OCSP
+/// verification.  The result is empty if the response wasn't
+/// successful, or has no SCT list extensions.
+pub fn ocsp_scts(response: &[u8]) -> Result<Vec<&[u8]>, Error> {
+    let lists = ocsp_sct_lists(untrusted::Input::from(response))
+        .map_err(|_| Error::MalformedOCSPResponse)?;
+
+    let mut scts = Vec::new();
+    for list in lists {
+        scts.extend(parse_sct_list(list.as_slice_less_safe())?);
+    }
+
+    Ok(scts)
+}
+
+/// Read one DER element, returning its tag, its value and its whole
+/// encoding.
+fn read_element<'a>(rd: &mut untrusted::Reader<'a>)
+                    -> Result<(u8, untrusted::Input<'a>, &'a [u8]), Unspecified> {
+    let start = rd.mark();
+    let (tag, value) = der::read_tag_and_get_value(rd)?;
+    let encoded = rd.get_input_between_marks(start, rd.mark())?;
+    Ok((tag, value, encoded.as_slice_less_safe()))
+}
+
+fn write_element(tag: u8, value: &[u8], out: &mut Vec<u8>) {
+    out.push(tag);
+
+    let len = value.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else if len < 0x100 {
+        out.push(0x81);
+        out.push(len as u8);
+    } else if len < 0x10000 {
+        out.push(0x82);
+        write_u16(len as u16, out);
+    } else {
+        out.push(0x83);
+        write_u24(len as u32, out);
+    }
+
+    out.extend_from_slice(value);
+}
+
+fn tbs_certificate<'a>(cert: untrusted::Input<'a>) -> Result<untrusted::Input<'a>, Unspecified> {
+    cert.read_all(Unspecified, |rd| {
+        der::nested(rd, der::Tag::Sequence, Unspecified, |cert| {
+            let tbs = der::expect_tag_and_get_value(cert, der::Tag::Sequence)?;
+            cert.skip_to_end(); // signatureAlgorithm, signatureValue
+            Ok(tbs)
+        })
+    })
+}
+
+fn tbs_certificate_of<'a>(cert: &'a [u8]) -> Result<untrusted::Input<'a>, Unspecified> {
+    tbs_certificate(untrusted::Input::from(cert))
+}
+
+fn subject_public_key_info<'a>(tbs: untrusted::Input<'a>) -> Result<&'a [u8], Unspecified> {
+    tbs.read_all(Unspecified, |rd| {
+        if rd.peek(CONTEXT_0) {
+            read_element(rd)?; // version
+        }
+
+        // serialNumber, signature, issuer, validity, subject
+        for _ in 0..5 {
+            read_element(rd)?;
+        }
+
+        let (_, _, spki) = read_element(rd)?;
+        rd.skip_to_end();
+        Ok(spki)
+    })
+}
+
+/// Return the contents of the Extensions SEQUENCE in `tbs`, if
+/// there is one.
+fn extensions<'a>(tbs: untrusted::Input<'a>)
+                  -> Result<Option<untrusted::Input<'a>>, Unspecified> {
+    tbs.read_all(Unspecified, |rd| {
+        let mut found = None;
+
+        while !rd.at_end() {
+            let (tag, value) = der::read_tag_and_get_value(rd)?;
+            if tag == CONTEXT_3 {
+                found = Some(value.read_all(Unspecified, |rd| {
+                    der::expect_tag_and_get_value(rd, der::Tag::Sequence)
+                })?);
+            }
+        }
+
+        Ok(found)
+    })
+}
+
+/// Read an Extension, returning its id, its value and its whole
+/// encoding.
+fn read_extension<'a>(rd: &mut untrusted::Reader<'a>)
+                      -> Result<(untrusted::Input<'a>, untrusted::Input<'a>, &'a [u8]),
+                                Unspecified> {
+    let start = rd.mark();
+    let (id, value) = der::nested(rd, der::Tag::Sequence, Unspecified, |ext| {
+        let id = der::expect_tag_and_get_value(ext, der::Tag::OID)?;
+        if ext.peek(der::Tag::Boolean as u8) {
+            der::expect_tag_and_get_value(ext, der::Tag::Boolean)?; // critical
+        }
+        let value = der::expect_tag_and_get_value(ext, der::Tag::OctetString)?;
+        Ok((id, value))
+    })?;
+    let encoded = rd.get_input_between_marks(start, rd.mark())?;
+    Ok((id, value, encoded.as_slice_less_safe()))
+}
+
+/// Find the extension `oid` among `extensions`, and return the
+/// SignedCertificateTimestampList inside it.
+fn find_sct_list<'a>(extensions: untrusted::Input<'a>, oid: &[u8])
+                     -> Result<Option<untrusted::Input<'a>>, Unspecified> {
+    extensions.read_all(Unspecified, |rd| {
+        let mut found = None;
+
+        while !rd.at_end() {
+            let (id, value, _) = read_extension(rd)?;
+            if id.as_slice_less_safe() == oid {
+                // The list is an OCTET STRING inside the extension's
+                // OCTET STRING.
+                found = Some(value.read_all(Unspecified, |rd| {
+                    der::expect_tag_and_get_value(rd, der::Tag::OctetString)
+                })?);
+            }
+        }
+
+        Ok(found)
+    })
+}
+
+/// Re-encode `tbs` without the SCT list extension.  If that was the
+/// only extension, the extensions field is dropped altogether, since
+/// it can't be empty.
+fn remove_sct_list(tbs: untrusted::Input) -> Result<Vec<u8>, Unspecified> {
+    let mut fields = Vec::new();
+
+    tbs.read_all(Unspecified, |rd| {
+        while !rd.at_end() {
+            let (tag, value, encoded) = read_element(rd)?;
+            if tag != CONTEXT_3 {
+                fields.extend_from_slice(encoded);
+                continue;
+            }
+
+            let kept = value.read_all(Unspecified, |rd| {
+                der::nested(rd, der::Tag::Sequence, Unspecified, |rd| {
+                    let mut kept = Vec::new();
+                    while !rd.at_end() {
+                        let (id, _, encoded) = read_extension(rd)?;
+                        if id.as_slice_less_safe() != EMBEDDED_SCT_LIST_OID {
+                            kept.extend_from_slice(encoded);
+                        }
+                    }
+                    Ok(kept)
+                })
+            })?;
+
+            if !kept.is_empty() {
+                let mut sequence = Vec::new();
+                write_element(der::Tag::Sequence as u8, &kept, &mut sequence);
+                write_element(CONTEXT_3, &sequence, &mut fields);
+            }
+        }
+
+        Ok(())
+    })?;
+
+    let mut tbs_certificate = Vec::new();
+    write_element(der::Tag::Sequence as u8, &fields, &mut tbs_certificate);
+    Ok(tbs_certificate)
+}
+
+/// Return the SCT list extension values in each SingleResponse of the
+/// OCSP response `response`.
+fn ocsp_sct_lists<'a>(response: untrusted::Input<'a>)
+                      -> Result<Vec<untrusted::Input<'a>>, Unspecified> {
+    // OCSPResponse
+    let basic = response.read_all(Unspecified, |rd| {
+        der::nested(rd, der::Tag::Sequence, Unspecified, |rd| {
+            let (tag, status) = der::read_tag_and_get_value(rd)?;
+            if tag != ENUMERATED {
+                return Err(Unspecified);
+            }
+            if status.as_slice_less_safe() != OCSP_SUCCESSFUL {
+                rd.skip_to_end();
+                return Ok(None);
+            }
+
+            // responseBytes
+            let basic = der::nested(rd, der::Tag::ContextSpecificConstructed0, Unspecified, |rd| {
+                der::nested(rd, der::Tag::Sequence, Unspecified, |rd| {
+                    let response_type = der::expect_tag_and_get_value(rd, der::Tag::OID)?;
+                    if response_type.as_slice_less_safe() != OCSP_BASIC_OID {
+                        return Err(Unspecified);
+                    }
+                    der::expect_tag_and_get_value(rd, der::Tag::OctetString)
+                })
+            })?;
+            Ok(Some(basic))
+        })
+    })?;
+
+    let basic = match basic {
+        Some(basic) => basic,
+        None => return Ok(Vec::new()),
+    };
+
+    // BasicOCSPResponse
+    let responses = basic.read_all(Unspecified, |rd| {
+        der::nested(rd, der::Tag::Sequence, Unspecified, |rd| {
+            let responses = der::nested(rd, der::Tag::Sequence, Unspecified, |data| {
+                if data.peek(CONTEXT_0) {
+                    read_element(data)?; // version
+                }
+                read_element(data)?; // responderID
+                der::expect_tag_and_get_value(data, der::Tag::GeneralizedTime)?; // producedAt
+                let responses = der::expect_tag_and_get_value(data, der::Tag::Sequence)?;
+                data.skip_to_end(); // responseExtensions
+                Ok(responses)
+            })?;
+            rd.skip_to_end(); // signatureAlgorithm, signature, certs
+            Ok(responses)
+        })
+    })?;
+
+    let mut lists = Vec::new();
+
+    responses.read_all(Unspecified, |rd| {
+        while !rd.at_end() {
+            // SingleResponse
+            der::nested(rd, der::Tag::Sequence, Unspecified, |rd| {
+                read_element(rd)?; // certID
+                read_element(rd)?; // certStatus
+                der::expect_tag_and_get_value(rd, der::Tag::GeneralizedTime)?; // thisUpdate
+                if rd.peek(CONTEXT_0) {
+                    read_element(rd)?; // nextUpdate
+                }
+
+                if rd.peek(CONTEXT_1) {
+                    let exts = der::nested(rd, der::Tag::ContextSpecificConstructed1, Unspecified, |rd| {
+                        der::expect_tag_and_get_value(rd, der::Tag::Sequence)
+                    })?;
+                    if let Some(list) = find_sct_list(exts, OCSP_SCT_LIST_OID)? {
+                        lists.push(list);
+                    }
+                }
+
+                Ok(())
+            })?;
+        }
+
+        Ok(())
+    })?;
+
+    Ok(lists)
+}
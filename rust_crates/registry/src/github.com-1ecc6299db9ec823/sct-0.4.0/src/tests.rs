@@ -15,4 +15,6 @@ fn test_other_errors_are_fatal() {
     assert_eq!(true, Error::MalformedSCT.should_be_fatal());
     assert_eq!(true, Error::InvalidSignature.should_be_fatal());
     assert_eq!(true, Error::TimestampInFuture.should_be_fatal());
+    assert_eq!(true, Error::MalformedCertificate.should_be_fatal());
+    assert_eq!(true, Error::MalformedOCSPResponse.should_be_fatal());
 }
@@ -0,0 +1,142 @@
+use super::{Log, Error, Entry, Precert, VerifiedSCT};
+use super::{verify_sct, verify_sct_for_entry, embedded_scts, ocsp_scts, parse_sct_list};
+use ring::der;
+use ring::error::Unspecified;
+use untrusted;
+
+// Generated by test/mkembedded.py.
+
+static TEST_LOG_ECDSA_P256: Log = Log {
+    description: "fake test ecdsa_p256 log",
+    url: "",
+    operated_by: "random python script",
+    max_merge_delay: 0,
+    key: include_bytes!("testdata/ecdsa-prime256v1-pub.raw"),
+    id: [0x71, 0xdc, 0x5e, 0xdb, 0xf0, 0x13, 0xd3, 0x88, 0x8a, 0x14, 0x6f, 0x49, 0x3d, 0xbe, 0x33, 0x94, 0xbb, 0x5a, 0xdb, 0x65, 0xb2, 0x6a, 0x96, 0xe2, 0x38, 0x35, 0x4e, 0xd4, 0x8f, 0xeb, 0xb2, 0x4f],
+};
+
+static TEST_LOG_ECDSA_P384: Log = Log {
+    description: "fake test ecdsa_p384 log",
+    url: "",
+    operated_by: "another python script",
+    max_merge_delay: 0,
+    key: include_bytes!("testdata/ecdsa-secp384r1-pub.raw"),
+    id: [0x29, 0xbb, 0xef, 0x00, 0xba, 0xd9, 0x3d, 0x5d, 0x4c, 0x03, 0xc7, 0x29, 0xe9, 0x4d, 0xb6, 0xac, 0x00, 0xe0, 0xfd, 0x28, 0xf6, 0x46, 0x56, 0x37, 0x24, 0xac, 0x58, 0xdc, 0x66, 0xb1, 0x99, 0xe9],
+};
+
+static CERT: &[u8] = include_bytes!("testdata/embedded-cert.der");
+static ISSUER: &[u8] = include_bytes!("testdata/embedded-issuer.der");
+static OCSP: &[u8] = include_bytes!("testdata/embedded-ocsp.der");
+
+const TIMESTAMP: u64 = 1500000000000;
+const NOW: u64 = 1501545600000;
+
+#[test]
+fn embedded_scts_verify_against_precert() {
+    let logs = [&TEST_LOG_ECDSA_P256, &TEST_LOG_ECDSA_P384];
+    let scts = embedded_scts(CERT).unwrap();
+    assert_eq!(2, scts.len());
+
+    let precert = Precert::new(CERT, ISSUER).unwrap();
+    for (i, sct) in scts.iter().enumerate() {
+        assert_eq!(Ok(VerifiedSCT { log: i, timestamp: TIMESTAMP }),
+                   verify_sct_for_entry(Entry::Precert(&precert), sct, NOW, &logs));
+        assert_eq!(Err(Error::InvalidSignature),
+                   verify_sct(CERT, sct, NOW, &logs));
+    }
+}
+
+#[test]
+fn embedded_scts_need_the_right_issuer() {
+    let logs = [&TEST_LOG_ECDSA_P256, &TEST_LOG_ECDSA_P384];
+    let scts = embedded_scts(CERT).unwrap();
+
+    // Self-issued, so its issuer key hash is wrong.
+    let precert = Precert::new(CERT, CERT).unwrap();
+    assert_eq!(Err(Error::InvalidSignature),
+               verify_sct_for_entry(Entry::Precert(&precert), scts[0], NOW, &logs));
+}
+
+#[test]
+fn precert_from_issuer_spki() {
+    let logs = [&TEST_LOG_ECDSA_P256, &TEST_LOG_ECDSA_P384];
+    let scts = embedded_scts(CERT).unwrap();
+
+    let precert = Precert::with_issuer_spki(CERT, issuer_spki()).unwrap();
+    assert_eq!(Precert::new(CERT, ISSUER).unwrap().issuer_key_hash(),
+               precert.issuer_key_hash());
+    for (i, sct) in scts.iter().enumerate() {
+        assert_eq!(Ok(VerifiedSCT { log: i, timestamp: TIMESTAMP }),
+                   verify_sct_for_entry(Entry::Precert(&precert), sct, NOW, &logs));
+    }
+}
+
+// The contents of ISSUER's SubjectPublicKeyInfo SEQUENCE.
+fn issuer_spki() -> &'static [u8] {
+    untrusted::Input::from(ISSUER).read_all(Unspecified, |cert| {
+        der::nested(cert, der::Tag::Sequence, Unspecified, |cert| {
+            let tbs = der::expect_tag_and_get_value(cert, der::Tag::Sequence)?;
+            cert.skip_to_end();
+            tbs.read_all(Unspecified, |tbs| {
+                // version, serialNumber, signature, issuer, validity, subject
+                for _ in 0..6 {
+                    der::read_tag_and_get_value(tbs)?;
+                }
+                let spki = der::expect_tag_and_get_value(tbs, der::Tag::Sequence)?;
+                tbs.skip_to_end();
+                Ok(spki.as_slice_less_safe())
+            })
+        })
+    }).unwrap()
+}
+
+#[test]
+fn no_embedded_scts() {
+    assert_eq!(Ok(vec![]), embedded_scts(ISSUER));
+
+    // Without an SCT list, the precertificate's TBSCertificate is the
+    // certificate's: here a SEQUENCE with a one-byte length.
+    let precert = Precert::new(ISSUER, ISSUER).unwrap();
+    let tbs_len = 3 + ISSUER[6] as usize;
+    assert_eq!(&ISSUER[4..4 + tbs_len], precert.tbs_certificate());
+}
+
+#[test]
+fn malformed_certificates() {
+    assert_eq!(Err(Error::MalformedCertificate), embedded_scts(b"cert"));
+    assert_eq!(Err(Error::MalformedCertificate), embedded_scts(&CERT[..CERT.len() - 1]));
+    assert!(Precert::new(b"cert", ISSUER).is_err());
+    assert!(Precert::new(CERT, b"issuer").is_err());
+}
+
+#[test]
+fn ocsp_scts_verify_against_cert() {
+    let logs = [&TEST_LOG_ECDSA_P256, &TEST_LOG_ECDSA_P384];
+    let scts = ocsp_scts(OCSP).unwrap();
+    assert_eq!(1, scts.len());
+    assert_eq!(Ok(0), verify_sct(CERT, scts[0], NOW, &logs));
+}
+
+#[test]
+fn unsuccessful_ocsp_response_has_no_scts() {
+    // OCSPResponse { responseStatus: tryLater }
+    assert_eq!(Ok(vec![]), ocsp_scts(&[0x30, 0x03, 0x0a, 0x01, 0x03]));
+}
+
+#[test]
+fn malformed_ocsp_responses() {
+    assert_eq!(Err(Error::MalformedOCSPResponse), ocsp_scts(b"ocsp"));
+    assert_eq!(Err(Error::MalformedOCSPResponse), ocsp_scts(&OCSP[..OCSP.len() - 1]));
+}
+
+#[test]
+fn sct_lists() {
+    assert_eq!(Ok(vec![&b"a"[..], &b"bc"[..]]),
+               parse_sct_list(&[0x00, 0x07, 0x00, 0x01, b'a', 0x00, 0x02, b'b', b'c']));
+
+    assert_eq!(Err(Error::MalformedSCT), parse_sct_list(&[]));
+    assert_eq!(Err(Error::MalformedSCT), parse_sct_list(&[0x00, 0x00]));
+    assert_eq!(Err(Error::MalformedSCT), parse_sct_list(&[0x00, 0x02, 0x00, 0x00]));
+    assert_eq!(Err(Error::MalformedSCT), parse_sct_list(&[0x00, 0x03, 0x00, 0x02, b'a']));
+    assert_eq!(Err(Error::MalformedSCT), parse_sct_list(&[0x00, 0x03, 0x00, 0x01, b'a', 0x00]));
+}
@@ -0,0 +1,106 @@
+use super::{Log, VerifiedSCT, Policy, PolicyError, DisqualifiedLog};
+
+fn log(id: u8, operated_by: &'static str) -> Log<'static> {
+    Log {
+        description: "fake test log",
+        url: "",
+        operated_by: operated_by,
+        key: b"",
+        id: [id; 32],
+        max_merge_delay: 0,
+    }
+}
+
+fn sct(log: usize, timestamp: u64) -> VerifiedSCT {
+    VerifiedSCT { log: log, timestamp: timestamp }
+}
+
+const YEAR: u64 = 365 * 24 * 60 * 60;
+const NOW: u64 = 1_500_000_000_000;
+const BEFORE: u64 = NOW - 1000;
+
+#[test]
+fn embedded_scts_from_two_operators() {
+    let (a, b, c) = (log(1, "A"), log(2, "B"), log(3, "A"));
+    let logs = [&a, &b, &c];
+    let policy = Policy::new();
+
+    assert_eq!(Ok(()),
+               policy.check(&logs, &[sct(0, BEFORE), sct(1, BEFORE)], &[], YEAR, NOW));
+    assert_eq!(Err(PolicyError::SingleOperator),
+               policy.check(&logs, &[sct(0, BEFORE), sct(2, BEFORE)], &[], YEAR, NOW));
+    assert_eq!(Err(PolicyError::NotEnoughSCTs { required: 2, found: 1 }),
+               policy.check(&logs, &[sct(0, BEFORE), sct(0, BEFORE)], &[], YEAR, NOW));
+}
+
+#[test]
+fn embedded_scts_required_by_lifetime() {
+    let (a, b, c, d, e) = (log(1, "A"), log(2, "B"), log(3, "C"), log(4, "D"), log(5, "E"));
+    let logs = [&a, &b, &c, &d, &e];
+    let scts = [sct(0, BEFORE), sct(1, BEFORE), sct(2, BEFORE), sct(3, BEFORE), sct(4, BEFORE)];
+    let policy = Policy::new();
+
+    for &(lifetime, required) in &[(YEAR, 2), (2 * YEAR, 3), (3 * YEAR, 4), (4 * YEAR, 5)] {
+        assert_eq!(Err(PolicyError::NotEnoughSCTs { required: required, found: required - 1 }),
+                   policy.check(&logs, &scts[..required - 1], &[], lifetime, NOW));
+        assert_eq!(Ok(()),
+                   policy.check(&logs, &scts[..required], &[], lifetime, NOW));
+    }
+}
+
+#[test]
+fn delivered_scts() {
+    let (a, b, c) = (log(1, "A"), log(2, "B"), log(3, "A"));
+    let logs = [&a, &b, &c];
+    let policy = Policy::new();
+
+    // The certificate's lifetime doesn't matter.
+    assert_eq!(Ok(()),
+               policy.check(&logs, &[], &[sct(0, BEFORE), sct(1, BEFORE)], 4 * YEAR, NOW));
+    assert_eq!(Err(PolicyError::SingleOperator),
+               policy.check(&logs, &[], &[sct(0, BEFORE), sct(2, BEFORE)], YEAR, NOW));
+    assert_eq!(Err(PolicyError::NotEnoughSCTs { required: 2, found: 0 }),
+               policy.check(&logs, &[], &[], YEAR, NOW));
+
+    // Either set of SCTs can meet the policy.
+    assert_eq!(Ok(()),
+               policy.check(&logs, &[sct(0, BEFORE)], &[sct(0, BEFORE), sct(1, BEFORE)], YEAR, NOW));
+
+    // If neither does, the error is about the embedded ones.
+    assert_eq!(Err(PolicyError::SingleOperator),
+               policy.check(&logs, &[sct(0, BEFORE), sct(2, BEFORE)], &[sct(1, BEFORE)], YEAR, NOW));
+}
+
+#[test]
+fn disqualified_logs() {
+    let (a, b, c) = (log(1, "A"), log(2, "B"), log(3, "C"));
+    let logs = [&a, &b, &c];
+    let mut policy = Policy::new();
+    policy.disqualified_logs.push(DisqualifiedLog { id: [2; 32], disqualified_at: BEFORE });
+
+    // Embedded SCTs from before disqualification still count...
+    assert_eq!(Ok(()),
+               policy.check(&logs, &[sct(0, BEFORE - 1), sct(1, BEFORE - 1)], &[], YEAR, NOW));
+
+    // ...but not those from after.
+    assert_eq!(Err(PolicyError::NotEnoughSCTs { required: 2, found: 1 }),
+               policy.check(&logs, &[sct(0, BEFORE), sct(1, BEFORE)], &[], YEAR, NOW));
+
+    // Delivered SCTs only count if the log is still qualified.
+    assert_eq!(Err(PolicyError::NotEnoughSCTs { required: 2, found: 1 }),
+               policy.check(&logs, &[], &[sct(0, BEFORE - 1), sct(1, BEFORE - 1)], YEAR, NOW));
+    assert_eq!(Ok(()),
+               policy.check(&logs, &[], &[sct(0, BEFORE), sct(2, BEFORE)], YEAR, NOW));
+}
+
+#[test]
+fn embedded_scts_need_a_currently_qualified_log() {
+    let (a, b) = (log(1, "A"), log(2, "B"));
+    let logs = [&a, &b];
+    let mut policy = Policy::new();
+    policy.disqualified_logs.push(DisqualifiedLog { id: [1; 32], disqualified_at: BEFORE });
+    policy.disqualified_logs.push(DisqualifiedLog { id: [2; 32], disqualified_at: BEFORE });
+
+    assert_eq!(Err(PolicyError::NoCurrentlyQualifiedLog),
+               policy.check(&logs, &[sct(0, BEFORE - 1), sct(1, BEFORE - 1)], &[], YEAR, NOW));
+}
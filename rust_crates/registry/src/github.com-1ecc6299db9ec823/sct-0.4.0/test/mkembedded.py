@@ -0,0 +1,129 @@
+# Generates test data for SCTs embedded in certificates and OCSP
+# responses.  Run from this directory; needs python3 and the
+# `cryptography` package.  The logs are the ecdsa_p256 and ecdsa_p384
+# test logs made by mktest.py.
+import datetime
+import hashlib
+import struct
+import subprocess
+
+from cryptography import x509
+from cryptography.hazmat.primitives import hashes, serialization
+from cryptography.hazmat.primitives.asymmetric import ec
+from cryptography.x509.oid import NameOID, ObjectIdentifier
+
+EMBEDDED_SCT_LIST = ObjectIdentifier('1.3.6.1.4.1.11129.2.4.2')
+
+SIGALG_ECDSA_SHA256 = 0x0403
+SIGALG_ECDSA_SHA384 = 0x0503
+
+LOGS = [
+    ('ecdsa-prime256v1-priv.pem', 'ecdsa-prime256v1-pub.pem', SIGALG_ECDSA_SHA256, 'sha256'),
+    ('ecdsa-secp384r1-priv.pem', 'ecdsa-secp384r1-pub.pem', SIGALG_ECDSA_SHA384, 'sha384'),
+]
+
+TIMESTAMP = 1500000000000
+
+def der(tag, value):
+    if len(value) < 0x80:
+        length = bytes([len(value)])
+    elif len(value) < 0x100:
+        length = bytes([0x81, len(value)])
+    else:
+        length = bytes([0x82]) + struct.pack('!H', len(value))
+    return bytes([tag]) + length + value
+
+def seq(*items):
+    return der(0x30, b''.join(items))
+
+def log_id(pub):
+    spki = serialization.load_pem_public_key(open(pub, 'rb').read()) \
+        .public_bytes(serialization.Encoding.DER, serialization.PublicFormat.SubjectPublicKeyInfo)
+    return hashlib.sha256(spki).digest()
+
+def sign_sct(log, enttype, entry):
+    priv, pub, alg, hash = log
+    to_sign = struct.pack('!BBQH', 0, 0, TIMESTAMP, enttype) + entry + b'\x00\x00'
+    open('sigin.bin', 'wb').write(to_sign)
+    sig = subprocess.check_output(['openssl', 'dgst', '-' + hash, '-sign', priv, 'sigin.bin'])
+    return struct.pack('!B32sQ', 0, log_id(pub), TIMESTAMP) + b'\x00\x00' + \
+        struct.pack('!HH', alg, len(sig)) + sig
+
+def sct_list(scts):
+    body = b''.join(struct.pack('!H', len(sct)) + sct for sct in scts)
+    return struct.pack('!H', len(body)) + body
+
+def u24(n):
+    return struct.pack('!I', n)[1:]
+
+def name(cn):
+    return x509.Name([x509.NameAttribute(NameOID.COMMON_NAME, cn)])
+
+issuer_key = ec.generate_private_key(ec.SECP256R1())
+issuer = x509.CertificateBuilder() \
+    .subject_name(name('sct test CA')) \
+    .issuer_name(name('sct test CA')) \
+    .public_key(issuer_key.public_key()) \
+    .serial_number(1) \
+    .not_valid_before(datetime.datetime(2017, 1, 1)) \
+    .not_valid_after(datetime.datetime(2027, 1, 1)) \
+    .add_extension(x509.BasicConstraints(ca=True, path_length=None), critical=True) \
+    .sign(issuer_key, hashes.SHA256())
+
+key = ec.generate_private_key(ec.SECP256R1())
+builder = x509.CertificateBuilder() \
+    .subject_name(name('sct.example.com')) \
+    .issuer_name(issuer.subject) \
+    .public_key(key.public_key()) \
+    .serial_number(2) \
+    .not_valid_before(datetime.datetime(2017, 7, 1)) \
+    .not_valid_after(datetime.datetime(2018, 7, 1)) \
+    .add_extension(x509.SubjectAlternativeName([x509.DNSName('sct.example.com')]), critical=False)
+
+# The precertificate's TBSCertificate is the certificate's, less the
+# SCT list extension.  As that extension is added last, it's the
+# TBSCertificate of the same certificate without it.
+precert_tbs = builder.sign(issuer_key, hashes.SHA256()).tbs_certificate_bytes
+issuer_key_hash = hashlib.sha256(
+    issuer.public_key().public_bytes(serialization.Encoding.DER,
+                                     serialization.PublicFormat.SubjectPublicKeyInfo)).digest()
+precert_entry = issuer_key_hash + u24(len(precert_tbs)) + precert_tbs
+
+embedded = sct_list([sign_sct(log, 1, precert_entry) for log in LOGS])
+cert = builder \
+    .add_extension(x509.UnrecognizedExtension(EMBEDDED_SCT_LIST, der(0x04, embedded)), critical=False) \
+    .sign(issuer_key, hashes.SHA256())
+cert_der = cert.public_bytes(serialization.Encoding.DER)
+assert hashlib.sha256(precert_tbs).digest() != hashlib.sha256(cert.tbs_certificate_bytes).digest()
+
+# An OCSP response for the certificate, with an SCT for the
+# certificate itself in its SingleResponse's extensions.
+ocsp_list = sct_list([sign_sct(LOGS[0], 0, u24(len(cert_der)) + cert_der)])
+ocsp_sct_ext = seq(der(0x06, bytes([0x2b, 0x06, 0x01, 0x04, 0x01, 0xd6, 0x79, 0x02, 0x04, 0x05])),
+                   der(0x04, der(0x04, ocsp_list)))
+sha1 = lambda b: hashlib.sha1(b).digest()
+issuer_name_der = issuer.subject.public_bytes()
+issuer_spk = issuer.public_key().public_bytes(serialization.Encoding.X962,
+                                              serialization.PublicFormat.UncompressedPoint)
+cert_id = seq(seq(der(0x06, bytes([0x2b, 0x0e, 0x03, 0x02, 0x1a])), der(0x05, b'')),
+              der(0x04, sha1(issuer_name_der)),
+              der(0x04, sha1(issuer_spk)),
+              der(0x02, b'\x02'))
+single = seq(cert_id,
+             der(0x80, b''),
+             der(0x18, b'20170801000000Z'),
+             der(0xa0, der(0x18, b'20170808000000Z')),
+             der(0xa1, seq(ocsp_sct_ext)))
+response_data = seq(der(0xa1, issuer_name_der),
+                    der(0x18, b'20170801000000Z'),
+                    seq(single))
+signature = issuer_key.sign(response_data, ec.ECDSA(hashes.SHA256()))
+ecdsa_with_sha256 = seq(der(0x06, bytes([0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02])))
+basic = seq(response_data, ecdsa_with_sha256, der(0x03, b'\x00' + signature))
+ocsp = seq(der(0x0a, b'\x00'),
+           der(0xa0, seq(der(0x06, bytes([0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x30, 0x01, 0x01])),
+                         der(0x04, basic))))
+
+open('../src/testdata/embedded-issuer.der', 'wb').write(issuer.public_bytes(serialization.Encoding.DER))
+open('../src/testdata/embedded-cert.der', 'wb').write(cert_der)
+open('../src/testdata/embedded-ocsp.der', 'wb').write(ocsp)
@@ -71,8 +71,9 @@ mod runtime;
 
 pub use self::builder::Builder;
 pub use self::runtime::{Runtime, Handle};
-pub use tokio_current_thread::spawn;
+pub use tokio_current_thread::{spawn, spawn_handle};
 pub use tokio_current_thread::TaskExecutor;
+pub use tokio_current_thread::{JoinHandle, JoinError};
 
 use futures::Future;
 
@@ -1,4 +1,4 @@
-use tokio_current_thread::{self as current_thread, CurrentThread};
+use tokio_current_thread::{self as current_thread, CurrentThread, JoinHandle};
 use tokio_current_thread::Handle as ExecutorHandle;
 use runtime::current_thread::Builder;
 
@@ -43,6 +43,21 @@ impl Handle {
         self.0.spawn(future)
     }
 
+    /// Spawn a future onto the `CurrentThread` runtime instance corresponding
+    /// to this handle, returning a handle to its result.
+    ///
+    /// See [`Runtime::spawn_handle`] for more details.
+    ///
+    /// [`Runtime::spawn_handle`]: struct.Runtime.html#method.spawn_handle
+    pub fn spawn_handle<F>(&self, future: F)
+        -> Result<JoinHandle<F::Item, F::Error>, tokio_executor::SpawnError>
+    where F: Future + Send + 'static,
+          F::Item: Send + 'static,
+          F::Error: Send + 'static,
+    {
+        self.0.spawn_handle(future)
+    }
+
     /// Provides a best effort **hint** to whether or not `spawn` will succeed.
     ///
     /// This function may return both false positives **and** false negatives.
@@ -163,6 +178,49 @@ impl Runtime {
         self
     }
 
+    /// Spawn a future onto the single-threaded Tokio runtime, returning a
+    /// handle to its result.
+    ///
+    /// This is like [`spawn`], but the future may complete with any item and
+    /// error, which are yielded by the returned [`JoinHandle`]. If the future
+    /// panics, the panic is caught and the `JoinHandle` fails with a
+    /// [`JoinError`]. The task can be aborted using [`JoinHandle::abort`].
+    ///
+    /// [`spawn`]: #method.spawn
+    /// [`JoinHandle`]: struct.JoinHandle.html
+    /// [`JoinError`]: struct.JoinError.html
+    /// [`JoinHandle::abort`]: struct.JoinHandle.html#method.abort
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate tokio;
+    /// # extern crate futures;
+    /// # use futures::{future, Future, Stream};
+    /// use tokio::runtime::current_thread::Runtime;
+    ///
+    /// # fn dox() {
+    /// // Create the runtime
+    /// let mut rt = Runtime::new().unwrap();
+    ///
+    /// // Spawn a future onto the runtime
+    /// let handle = rt.spawn_handle(future::lazy(|| {
+    ///     if true { panic!("boom") }
+    ///     Ok::<(), ()>(())
+    /// }));
+    ///
+    /// // The panic is reported through the handle
+    /// let err = rt.block_on(handle).unwrap_err();
+    /// assert!(err.is_panic());
+    /// # }
+    /// # pub fn main() {}
+    /// ```
+    pub fn spawn_handle<F>(&mut self, future: F) -> JoinHandle<F::Item, F::Error>
+    where F: Future + 'static,
+    {
+        self.executor.spawn_handle(future)
+    }
+
     /// Runs the provided future, blocking the current thread until the future
     /// completes.
     ///
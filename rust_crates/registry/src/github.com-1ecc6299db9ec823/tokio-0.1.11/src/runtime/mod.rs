@@ -120,6 +120,8 @@ mod task_executor;
 pub use self::builder::Builder;
 pub use self::shutdown::Shutdown;
 pub use self::task_executor::TaskExecutor;
+pub use tokio_threadpool::{JoinHandle, JoinError};
+pub use tokio_threadpool::{Metrics, WorkerMetrics};
 
 use reactor::{Background, Handle};
 
@@ -300,6 +302,33 @@ impl Runtime {
         TaskExecutor { inner }
     }
 
+    /// Returns a snapshot of the runtime's thread pool metrics.
+    ///
+    /// The snapshot includes per-worker counters, such as the number of tasks
+    /// polled and stolen, as well as pool-wide values, such as the number of
+    /// queued tasks and threads currently in a `blocking` section. See
+    /// [`Metrics`] for details.
+    ///
+    /// [`Metrics`]: struct.Metrics.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio::runtime::Runtime;
+    ///
+    /// let rt = Runtime::new()
+    ///     .unwrap();
+    ///
+    /// let metrics = rt.metrics();
+    ///
+    /// for worker in metrics.workers() {
+    ///     println!("{:?}: polled {} tasks", worker.id(), worker.polls());
+    /// }
+    /// ```
+    pub fn metrics(&self) -> Metrics {
+        self.inner().pool.metrics()
+    }
+
     /// Spawn a future onto the Tokio runtime.
     ///
     /// This spawns the given future onto the runtime's executor, usually a
@@ -342,6 +371,55 @@ impl Runtime {
         self
     }
 
+    /// Spawn a future onto the Tokio runtime, returning a handle to its
+    /// result.
+    ///
+    /// This is like [`spawn`], but the future may complete with any item and
+    /// error, which are yielded by the returned [`JoinHandle`]. If the future
+    /// panics, the panic is caught and the `JoinHandle` fails with a
+    /// [`JoinError`]. The task can be aborted using [`JoinHandle::abort`].
+    ///
+    /// [`spawn`]: #method.spawn
+    /// [`JoinHandle`]: struct.JoinHandle.html
+    /// [`JoinError`]: struct.JoinError.html
+    /// [`JoinHandle::abort`]: struct.JoinHandle.html#method.abort
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate tokio;
+    /// # extern crate futures;
+    /// # use futures::{future, Future, Stream};
+    /// use tokio::runtime::Runtime;
+    ///
+    /// # fn dox() {
+    /// // Create the runtime
+    /// let mut rt = Runtime::new().unwrap();
+    ///
+    /// // Spawn a future onto the runtime
+    /// let handle = rt.spawn_handle(future::lazy(|| {
+    ///     Ok::<_, ()>("computed on a worker thread")
+    /// }));
+    ///
+    /// // Wait for its result
+    /// let res = rt.block_on(handle).unwrap();
+    /// assert_eq!(Ok("computed on a worker thread"), res);
+    /// # }
+    /// # pub fn main() {}
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the spawn fails. Failure occurs if the executor
+    /// is currently at capacity and is unable to spawn a new future.
+    pub fn spawn_handle<F>(&mut self, future: F) -> JoinHandle<F::Item, F::Error>
+    where F: Future + Send + 'static,
+          F::Item: Send + 'static,
+          F::Error: Send + 'static,
+    {
+        self.inner_mut().pool.sender().spawn_handle(future).unwrap()
+    }
+
     /// Run a future to completion on the Tokio runtime.
     ///
     /// This runs the given future on the runtime, blocking until it is
@@ -1,5 +1,5 @@
 
-use tokio_threadpool::Sender;
+use tokio_threadpool::{JoinHandle, Sender};
 
 use futures::future::{self, Future};
 
@@ -56,6 +56,48 @@ impl TaskExecutor {
     {
         self.inner.spawn(future).unwrap();
     }
+
+    /// Spawn a future onto the Tokio runtime, returning a handle to its
+    /// result.
+    ///
+    /// See [`Runtime::spawn_handle`] for more details.
+    ///
+    /// [`Runtime::spawn_handle`]: struct.Runtime.html#method.spawn_handle
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate tokio;
+    /// # extern crate futures;
+    /// # use futures::{future, Future, Stream};
+    /// use tokio::runtime::Runtime;
+    ///
+    /// # fn dox() {
+    /// // Create the runtime
+    /// let mut rt = Runtime::new().unwrap();
+    /// let executor = rt.executor();
+    ///
+    /// // Spawn a future onto the runtime, then abort it
+    /// let handle = executor.spawn_handle(future::empty::<(), ()>());
+    /// handle.abort();
+    ///
+    /// let err = rt.block_on(handle).unwrap_err();
+    /// assert!(err.is_cancelled());
+    /// # }
+    /// # pub fn main() {}
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the spawn fails. Failure occurs if the executor
+    /// is currently at capacity and is unable to spawn a new future.
+    pub fn spawn_handle<F>(&self, future: F) -> JoinHandle<F::Item, F::Error>
+    where F: Future + Send + 'static,
+          F::Item: Send + 'static,
+          F::Error: Send + 'static,
+    {
+        self.inner.spawn_handle(future).unwrap()
+    }
 }
 
 impl<T> future::Executor<T> for TaskExecutor
@@ -12,6 +12,8 @@
 //!   [`block_on_all`] with an initial task to seed the executor.
 //!   All tasks that are being managed by a [`CurrentThread`] executor are able to
 //!   spawn additional tasks by calling [`spawn`].
+//! * [`JoinHandle`] is returned by [`spawn_handle`], to wait for the result of
+//!   a spawned task or to abort it.
 //!
 //!
 //! Application authors will not use this crate directly. Instead, they will use the
@@ -22,14 +24,20 @@
 //!
 //! [`CurrentThread`]: struct.CurrentThread.html
 //! [`spawn`]: fn.spawn.html
+//! [`spawn_handle`]: fn.spawn_handle.html
+//! [`JoinHandle`]: struct.JoinHandle.html
 //! [`block_on_all`]: fn.block_on_all.html
 //! [executor module]: https://docs.rs/tokio/0.1/tokio/executor/index.html
 
 extern crate futures;
 extern crate tokio_executor;
 
+#[path = "../../tokio-threadpool-0.1.6/src/join_handle.rs"]
+mod join_handle;
 mod scheduler;
 
+pub use self::join_handle::{JoinHandle, JoinError};
+
 use self::scheduler::Scheduler;
 
 use tokio_executor::{Enter, SpawnError};
@@ -253,6 +261,46 @@ where F: Future<Item = (), Error = ()> + 'static
         .unwrap();
 }
 
+/// Executes a future on the current thread, returning a handle to its result.
+///
+/// This is like [`spawn`], but the future may complete with any item and
+/// error, which are yielded by the returned [`JoinHandle`]. If the future
+/// panics, the panic is caught and the `JoinHandle` fails with a
+/// [`JoinError`]. The task can be aborted using [`JoinHandle::abort`].
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate tokio_current_thread;
+/// # extern crate futures;
+/// use futures::future::{self, Future};
+///
+/// # pub fn main() {
+/// let res = tokio_current_thread::block_on_all(future::lazy(|| {
+///     tokio_current_thread::spawn_handle(future::ok::<_, ()>(1 + 1))
+/// }));
+///
+/// assert_eq!(Ok(2), res.unwrap());
+/// # }
+/// ```
+///
+/// # Panics
+///
+/// This function can only be invoked from the context of a `run` call; any
+/// other use will result in a panic.
+///
+/// [`spawn`]: fn.spawn.html
+/// [`JoinHandle`]: struct.JoinHandle.html
+/// [`JoinError`]: struct.JoinError.html
+/// [`JoinHandle::abort`]: struct.JoinHandle.html#method.abort
+pub fn spawn_handle<F>(future: F) -> JoinHandle<F::Item, F::Error>
+where F: Future + 'static,
+{
+    let (task, handle) = join_handle::joinable(future);
+    spawn(task);
+    handle
+}
+
 // ===== impl CurrentThread =====
 
 impl CurrentThread<ParkThread> {
@@ -318,6 +366,20 @@ impl<P: Park> CurrentThread<P> {
         self
     }
 
+    /// Spawn the future on the executor, returning a handle to its result.
+    ///
+    /// This internally queues the future to be executed once `run` is called.
+    /// See [`spawn_handle`] for more details.
+    ///
+    /// [`spawn_handle`]: fn.spawn_handle.html
+    pub fn spawn_handle<F>(&mut self, future: F) -> JoinHandle<F::Item, F::Error>
+    where F: Future + 'static,
+    {
+        let (task, handle) = join_handle::joinable(future);
+        self.spawn(task);
+        handle
+    }
+
     /// Synchronously waits for the provided `future` to complete.
     ///
     /// This function can be used to synchronously block the current thread
@@ -454,6 +516,20 @@ impl<'a, P: Park> Entered<'a, P> {
         self
     }
 
+    /// Spawn the future on the executor, returning a handle to its result.
+    ///
+    /// This internally queues the future to be executed once `run` is called.
+    /// See [`spawn_handle`] for more details.
+    ///
+    /// [`spawn_handle`]: fn.spawn_handle.html
+    pub fn spawn_handle<F>(&mut self, future: F) -> JoinHandle<F::Item, F::Error>
+    where F: Future + 'static,
+    {
+        let (task, handle) = join_handle::joinable(future);
+        self.spawn(task);
+        handle
+    }
+
     /// Synchronously waits for the provided `future` to complete.
     ///
     /// This function can be used to synchronously block the current thread
@@ -683,6 +759,24 @@ impl Handle {
         Ok(())
     }
 
+    /// Spawn a future onto the `CurrentThread` instance corresponding to this
+    /// handle, returning a handle to its result.
+    ///
+    /// See [`spawn_handle`] for more details.
+    ///
+    /// [`spawn_handle`]: fn.spawn_handle.html
+    pub fn spawn_handle<F>(&self, future: F)
+        -> Result<JoinHandle<F::Item, F::Error>, SpawnError>
+    where
+        F: Future + Send + 'static,
+        F::Item: Send + 'static,
+        F::Error: Send + 'static,
+    {
+        let (task, handle) = join_handle::joinable(future);
+        self.spawn(task)?;
+        Ok(handle)
+    }
+
     /// Provides a best effort **hint** to whether or not `spawn` will succeed.
     ///
     /// This function may return both false positives **and** false negatives.
@@ -778,6 +778,108 @@ fn spawn_from_other_thread_unpark() {
     ).unwrap();
 }
 
+#[test]
+fn spawn_handle_yields_result() {
+    let res = block_on_all(lazy(|| {
+        let ok = tokio_current_thread::spawn_handle(future::ok::<_, ()>(1));
+        let err = tokio_current_thread::spawn_handle(future::err::<(), _>("nope"));
+        ok.join(err)
+    })).unwrap();
+
+    assert_eq!((Ok(1), Err("nope")), res);
+}
+
+#[test]
+fn spawn_handle_not_send() {
+    let mut current_thread = CurrentThread::new();
+
+    let handle = current_thread.spawn_handle(lazy(|| {
+        Ok::<_, ()>(Rc::new("not send"))
+    }));
+
+    let res = current_thread.block_on(handle).unwrap();
+    assert_eq!("not send", *res.unwrap());
+}
+
+#[test]
+fn spawn_handle_catches_panic() {
+    let mut current_thread = CurrentThread::new();
+
+    let handle = current_thread.spawn_handle(lazy(|| {
+        if true {
+            panic!("boom");
+        }
+        Ok::<(), ()>(())
+    }));
+
+    let err = current_thread.block_on(handle).unwrap_err().into_inner().unwrap();
+    assert!(err.is_panic());
+    assert!(!err.is_cancelled());
+    assert_eq!(Some(&"boom"), err.into_panic().unwrap().downcast_ref::<&str>());
+
+    // The executor keeps running after the panic.
+    current_thread.run().unwrap();
+}
+
+#[test]
+fn spawn_handle_abort() {
+    struct SetOnDrop(Rc<Cell<bool>>);
+
+    impl Drop for SetOnDrop {
+        fn drop(&mut self) {
+            self.0.set(true);
+        }
+    }
+
+    let dropped = Rc::new(Cell::new(false));
+    let guard = SetOnDrop(dropped.clone());
+
+    let mut current_thread = CurrentThread::new();
+
+    let handle = current_thread.spawn_handle(future::empty::<(), ()>().then(move |res| {
+        drop(guard);
+        res
+    }));
+
+    // Let the task be polled once before it is aborted.
+    current_thread.turn(Some(Duration::from_millis(0))).unwrap();
+    assert!(!dropped.get());
+
+    handle.abort();
+
+    let err = current_thread.block_on(handle).unwrap_err().into_inner().unwrap();
+    assert!(err.is_cancelled());
+    assert!(dropped.get());
+
+    current_thread.run().unwrap();
+}
+
+#[test]
+fn spawn_handle_dropped_executor_cancels() {
+    let mut current_thread = CurrentThread::new();
+
+    let handle = current_thread.spawn_handle(future::empty::<(), ()>());
+
+    drop(current_thread);
+
+    assert!(handle.wait().unwrap_err().is_cancelled());
+}
+
+#[test]
+fn spawn_handle_from_other_thread() {
+    let mut current_thread = CurrentThread::new();
+    let handle = current_thread.handle();
+
+    let join_handle = thread::spawn(move || {
+        handle.spawn_handle(lazy(|| {
+            Ok::<_, ()>(thread::current().name().map(String::from))
+        })).unwrap()
+    }).join().unwrap();
+
+    let name = current_thread.block_on(join_handle).unwrap().unwrap();
+    assert_eq!(thread::current().name().map(String::from), name);
+}
+
 fn ok() -> future::FutureResult<(), ()> {
     future::ok(())
 }
@@ -23,8 +23,9 @@ use std::sync::Arc;
 use webpki::DNSNameRef;
 use rustls::{
     Session, ClientSession, ServerSession,
-    ClientConfig, ServerConfig,
+    ClientConfig, ServerConfig, ClientHello, TLSError,
 };
+use rustls::sign::CertifiedKey;
 use common::Stream;
 
 
@@ -40,6 +41,12 @@ pub trait ServerConfigExt: sealed::Sealed {
     fn accept_async<S>(&self, stream: S)
         -> AcceptAsync<S>
         where S: io::Read + io::Write;
+
+    /// Like `accept_async`, but stops once the client's ClientHello
+    /// has arrived so the application can choose how to continue.
+    fn start_accept_async<S>(&self, stream: S)
+        -> StartAcceptAsync<S>
+        where S: io::Read + io::Write;
 }
 
 
@@ -51,6 +58,16 @@ pub struct ConnectAsync<S>(MidHandshake<S, ClientSession>);
 /// once the accept handshake has finished.
 pub struct AcceptAsync<S>(MidHandshake<S, ServerSession>);
 
+/// Future returned from `ServerConfigExt::start_accept_async` which will
+/// resolve once the client's ClientHello has been received.
+pub struct StartAcceptAsync<S>(Option<TlsStream<S, ServerSession>>);
+
+/// A server handshake paused after receiving the client's ClientHello.
+///
+/// Inspect it with `client_hello`, then continue the handshake with
+/// `resume`.
+pub struct PausedAccept<S>(TlsStream<S, ServerSession>);
+
 impl sealed::Sealed for Arc<ClientConfig> {}
 
 impl ClientConfigExt for Arc<ClientConfig> {
@@ -81,6 +98,18 @@ impl ServerConfigExt for Arc<ServerConfig> {
     {
         accept_async_with_session(stream, ServerSession::new(self))
     }
+
+    fn start_accept_async<S>(&self, stream: S)
+        -> StartAcceptAsync<S>
+        where S: io::Read + io::Write
+    {
+        StartAcceptAsync(Some(TlsStream {
+            session: ServerSession::new_pausing(self),
+            io: stream,
+            is_shutdown: false,
+            eof: false
+        }))
+    }
 }
 
 #[inline]
@@ -93,6 +122,40 @@ pub fn accept_async_with_session<S>(stream: S, session: ServerSession)
     })
 }
 
+impl<S> PausedAccept<S>
+    where S: io::Read + io::Write
+{
+    /// The ClientHello sent by the client.
+    pub fn client_hello(&self) -> &ClientHello {
+        self.0.session.client_hello().unwrap()
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &S {
+        &self.0.io
+    }
+
+    /// Continues the handshake using `config`, and `certkey` if given.
+    /// See `rustls::ServerSession::resume_handshake`.
+    pub fn resume(self, config: &Arc<ServerConfig>, certkey: Option<CertifiedKey>)
+        -> io::Result<AcceptAsync<S>>
+    {
+        let mut stream = self.0;
+
+        if let Err(e) = stream.session.resume_handshake(config, certkey) {
+            // Send any alert describing the error.
+            let _ignored = stream.session.write_tls(&mut stream.io);
+            return Err(tls_error(e));
+        }
+
+        Ok(AcceptAsync(MidHandshake { inner: Some(stream) }))
+    }
+}
+
+fn tls_error(err: TLSError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
 
 struct MidHandshake<S, C> {
     inner: Option<TlsStream<S, C>>
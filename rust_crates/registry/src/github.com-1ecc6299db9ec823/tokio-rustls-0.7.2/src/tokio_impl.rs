@@ -23,6 +23,33 @@ impl<S: AsyncRead + AsyncWrite> Future for AcceptAsync<S> {
     }
 }
 
+impl<S: AsyncRead + AsyncWrite> Future for StartAcceptAsync<S> {
+    type Item = PausedAccept<S>;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        {
+            let stream = self.0.as_mut().unwrap();
+
+            while stream.session.client_hello().is_none() {
+                match stream.session.read_tls(&mut stream.io) {
+                    Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                    Ok(_) => (),
+                    Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(Async::NotReady),
+                    Err(e) => return Err(e)
+                }
+
+                if let Err(e) = stream.session.process_new_packets() {
+                    let _ignored = stream.session.write_tls(&mut stream.io);
+                    return Err(tls_error(e));
+                }
+            }
+        }
+
+        Ok(Async::Ready(PausedAccept(self.0.take().unwrap())))
+    }
+}
+
 impl<S, C> Future for MidHandshake<S, C>
     where S: io::Read + io::Write, C: Session
 {
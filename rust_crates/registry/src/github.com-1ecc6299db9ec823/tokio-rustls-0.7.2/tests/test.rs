@@ -59,6 +59,56 @@ lazy_static!{
         let addr = recv.recv().unwrap();
         (addr, "localhost", CHAIN)
     };
+
+    static ref PAUSING_SERVER: (SocketAddr, &'static str, &'static str) = {
+        use tokio::prelude::*;
+        use tokio::io as aio;
+
+        let cert = certs(&mut BufReader::new(Cursor::new(CERT))).unwrap();
+        let mut keys = rsa_private_keys(&mut BufReader::new(Cursor::new(RSA))).unwrap();
+
+        let mut config = ServerConfig::new(rustls::NoClientAuth::new());
+        config.set_single_cert(cert, keys.pop().unwrap())
+            .expect("invalid key or certificate");
+        let config = Arc::new(config);
+
+        let (send, recv) = channel();
+
+        thread::spawn(move || {
+            let addr = SocketAddr::from(([127, 0, 0, 1], 0));
+            let listener = TcpListener::bind(&addr).unwrap();
+
+            send.send(listener.local_addr().unwrap()).unwrap();
+
+            let done = listener.incoming()
+                .for_each(move |stream| {
+                    let config = config.clone();
+                    let done = config.start_accept_async(stream)
+                        .and_then(move |paused| {
+                            let sni: Option<String> = paused.client_hello()
+                                .server_name()
+                                .map(|name| { let name: &str = name.into(); name.to_string() });
+                            assert_eq!(sni.as_ref().map(|s| s.as_str()), Some("localhost"));
+                            paused.resume(&config, None)
+                        })
+                        .flatten()
+                        .and_then(|stream| {
+                            let (reader, writer) = stream.split();
+                            aio::copy(reader, writer)
+                        })
+                        .then(|_| Ok(()));
+
+                    tokio::spawn(done);
+                    Ok(())
+                })
+                .map_err(|err| panic!("{:?}", err));
+
+            tokio::run(done);
+        });
+
+        let addr = recv.recv().unwrap();
+        (addr, "localhost", CHAIN)
+    };
 }
 
 
@@ -99,6 +149,13 @@ fn pass() {
     start_client(addr, domain, chain).unwrap();
 }
 
+#[test]
+fn pass_with_pausing_server() {
+    let (addr, domain, chain) = &*PAUSING_SERVER;
+
+    start_client(addr, domain, chain).unwrap();
+}
+
 #[test]
 fn fail() {
     let (addr, domain, chain) = start_server();
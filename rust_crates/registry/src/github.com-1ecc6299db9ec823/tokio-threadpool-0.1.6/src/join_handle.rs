@@ -0,0 +1,206 @@
+// This module is also compiled into tokio-current-thread, so that both
+// executors hand out the same `JoinHandle`. It only depends on `futures`.
+
+use futures::{Future, Poll, Async};
+use futures::sync::oneshot;
+use futures::task::AtomicTask;
+
+use std::any::Any;
+use std::error::Error;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::{Acquire, Release};
+
+/// A handle to a task spawned with `spawn_handle`.
+///
+/// `JoinHandle` is a future that completes with the result of the spawned
+/// future. If the spawned future panics, or if the task is dropped before it
+/// completes, either because it was aborted or because the executor shut
+/// down, the `JoinHandle` fails with a [`JoinError`].
+///
+/// Dropping a `JoinHandle` does not cancel the task. The task keeps running
+/// and its result is discarded.
+///
+/// [`JoinError`]: struct.JoinError.html
+pub struct JoinHandle<T, E> {
+    rx: oneshot::Receiver<Result<Result<T, E>, JoinError>>,
+    abort: Arc<Abort>,
+}
+
+/// Error returned by a [`JoinHandle`] when the task did not complete.
+///
+/// [`JoinHandle`]: struct.JoinHandle.html
+pub struct JoinError {
+    repr: Repr,
+}
+
+enum Repr {
+    Cancelled,
+    Panic(Box<Any + Send>),
+}
+
+/// Harness around a future spawned with a `JoinHandle`, sending the future's
+/// result to the handle.
+pub(crate) struct Joinable<F: Future> {
+    future: F,
+    tx: Option<oneshot::Sender<Result<Result<F::Item, F::Error>, JoinError>>>,
+    abort: Arc<Abort>,
+}
+
+struct Abort {
+    aborted: AtomicBool,
+    task: AtomicTask,
+}
+
+/// Wraps `future` so that its result is sent to the returned `JoinHandle`.
+pub(crate) fn joinable<F>(future: F) -> (Joinable<F>, JoinHandle<F::Item, F::Error>)
+where F: Future,
+{
+    let (tx, rx) = oneshot::channel();
+    let abort = Arc::new(Abort {
+        aborted: AtomicBool::new(false),
+        task: AtomicTask::new(),
+    });
+
+    let joinable = Joinable {
+        future,
+        tx: Some(tx),
+        abort: abort.clone(),
+    };
+
+    (joinable, JoinHandle { rx, abort })
+}
+
+// ===== impl JoinHandle =====
+
+impl<T, E> JoinHandle<T, E> {
+    /// Abort the task.
+    ///
+    /// The task is dropped the next time it would be polled, without polling
+    /// the future again. If the task has not completed by then, the
+    /// `JoinHandle` fails with an error for which [`is_cancelled`] returns
+    /// `true`.
+    ///
+    /// [`is_cancelled`]: struct.JoinError.html#method.is_cancelled
+    pub fn abort(&self) {
+        self.abort.aborted.store(true, Release);
+        self.abort.task.notify();
+    }
+}
+
+impl<T, E> Future for JoinHandle<T, E> {
+    type Item = Result<T, E>;
+    type Error = JoinError;
+
+    fn poll(&mut self) -> Poll<Result<T, E>, JoinError> {
+        match self.rx.poll() {
+            Ok(Async::Ready(Ok(res))) => Ok(Async::Ready(res)),
+            Ok(Async::Ready(Err(e))) => Err(e),
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            // The task was dropped without completing.
+            Err(oneshot::Canceled) => Err(JoinError { repr: Repr::Cancelled }),
+        }
+    }
+}
+
+impl<T, E> fmt::Debug for JoinHandle<T, E> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("JoinHandle")
+            .field("aborted", &self.abort.aborted.load(Acquire))
+            .finish()
+    }
+}
+
+// ===== impl JoinError =====
+
+impl JoinError {
+    /// Returns `true` if the task was dropped before it completed, either
+    /// because it was aborted or because the executor shut down.
+    pub fn is_cancelled(&self) -> bool {
+        match self.repr {
+            Repr::Cancelled => true,
+            Repr::Panic(_) => false,
+        }
+    }
+
+    /// Returns `true` if the task panicked.
+    pub fn is_panic(&self) -> bool {
+        match self.repr {
+            Repr::Cancelled => false,
+            Repr::Panic(_) => true,
+        }
+    }
+
+    /// Consumes the error, returning the value the task panicked with, if
+    /// it panicked.
+    ///
+    /// The value can be passed to `std::panic::resume_unwind` to propagate
+    /// the panic.
+    pub fn into_panic(self) -> Option<Box<Any + Send>> {
+        match self.repr {
+            Repr::Cancelled => None,
+            Repr::Panic(payload) => Some(payload),
+        }
+    }
+}
+
+impl fmt::Display for JoinError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.description())
+    }
+}
+
+impl fmt::Debug for JoinError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let repr = match self.repr {
+            Repr::Cancelled => "Cancelled",
+            Repr::Panic(_) => "Panic",
+        };
+
+        fmt.debug_tuple("JoinError")
+            .field(&format_args!("{}", repr))
+            .finish()
+    }
+}
+
+impl Error for JoinError {
+    fn description(&self) -> &str {
+        match self.repr {
+            Repr::Cancelled => "task was cancelled",
+            Repr::Panic(_) => "task panicked",
+        }
+    }
+}
+
+// ===== impl Joinable =====
+
+impl<F: Future> Future for Joinable<F> {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<(), ()> {
+        // Register before checking the flag so that an `abort` concurrent
+        // with this poll is not missed.
+        self.abort.task.register();
+
+        if self.abort.aborted.load(Acquire) {
+            return Ok(Async::Ready(()));
+        }
+
+        let future = &mut self.future;
+        let res = match panic::catch_unwind(AssertUnwindSafe(|| future.poll())) {
+            Ok(Ok(Async::NotReady)) => return Ok(Async::NotReady),
+            Ok(Ok(Async::Ready(item))) => Ok(Ok(item)),
+            Ok(Err(e)) => Ok(Err(e)),
+            Err(payload) => Err(JoinError { repr: Repr::Panic(payload) }),
+        };
+
+        // The result is discarded if the `JoinHandle` has been dropped.
+        let tx = self.tx.take().expect("polled after completion");
+        let _ = tx.send(res);
+
+        Ok(Async::Ready(()))
+    }
+}
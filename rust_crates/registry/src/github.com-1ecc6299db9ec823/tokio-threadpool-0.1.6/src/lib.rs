@@ -145,6 +145,8 @@ mod blocking;
 mod builder;
 mod callback;
 mod config;
+mod join_handle;
+mod metrics;
 mod notifier;
 mod pool;
 mod sender;
@@ -156,6 +158,8 @@ mod worker;
 
 pub use blocking::{blocking, BlockingError};
 pub use builder::Builder;
+pub use join_handle::{JoinHandle, JoinError};
+pub use metrics::{Metrics, WorkerMetrics};
 pub use sender::Sender;
 pub use shutdown::Shutdown;
 pub use thread_pool::ThreadPool;
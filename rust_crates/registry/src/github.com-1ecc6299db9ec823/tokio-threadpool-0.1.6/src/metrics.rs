@@ -0,0 +1,290 @@
+use pool::{self, Pool};
+use worker::WorkerId;
+
+use std::sync::atomic::{AtomicU64, AtomicUsize};
+use std::sync::atomic::Ordering::{Acquire, Relaxed};
+use std::time::Duration;
+
+/// A snapshot of a thread pool's metrics.
+///
+/// Returned by [`ThreadPool::metrics`] and [`Sender::metrics`]. Counters are
+/// cumulative since the thread pool was created and gauges reflect the
+/// instant the snapshot was taken. The values are read independently of each
+/// other, so a snapshot of a busy pool is not guaranteed to be consistent
+/// across fields.
+///
+/// [`ThreadPool::metrics`]: struct.ThreadPool.html#method.metrics
+/// [`Sender::metrics`]: struct.Sender.html#method.metrics
+#[derive(Debug, Clone)]
+pub struct Metrics {
+    workers: Vec<WorkerMetrics>,
+    num_futures: usize,
+    queue_depth: usize,
+    blocking_threads: usize,
+    max_blocking: usize,
+    blocking_sections: u64,
+}
+
+/// A snapshot of a single worker's metrics.
+///
+/// See [`Metrics::workers`].
+///
+/// [`Metrics::workers`]: struct.Metrics.html#method.workers
+#[derive(Debug, Clone)]
+pub struct WorkerMetrics {
+    id: WorkerId,
+    polls: u64,
+    steals: u64,
+    parks: u64,
+    unparks: u64,
+    busy: Duration,
+}
+
+/// Counters updated by a worker. Stored on the worker's entry.
+#[derive(Debug)]
+pub(crate) struct WorkerStats {
+    polls: AtomicU64,
+    steals: AtomicU64,
+    parks: AtomicU64,
+    unparks: AtomicU64,
+    busy_nanos: AtomicU64,
+}
+
+/// Pool-wide counters. Stored on the pool.
+#[derive(Debug)]
+pub(crate) struct PoolStats {
+    // Number of tasks sitting in worker queues, waiting to be polled.
+    queued: AtomicUsize,
+
+    // Number of threads currently in a `blocking` section.
+    blocking: AtomicUsize,
+
+    // Total number of `blocking` sections entered.
+    blocking_sections: AtomicU64,
+}
+
+// ===== impl Metrics =====
+
+impl Metrics {
+    pub(crate) fn new(pool: &Pool) -> Metrics {
+        let state: pool::State = pool.state.load(Acquire).into();
+
+        let workers = pool.workers.iter()
+            .enumerate()
+            .map(|(idx, entry)| entry.stats.snapshot(WorkerId::new(idx)))
+            .collect();
+
+        Metrics {
+            workers,
+            num_futures: state.num_futures(),
+            queue_depth: pool.stats.queued.load(Relaxed),
+            blocking_threads: pool.stats.blocking.load(Relaxed),
+            max_blocking: pool.max_blocking,
+            blocking_sections: pool.stats.blocking_sections.load(Relaxed),
+        }
+    }
+
+    /// Returns the metrics of each worker, ordered by worker index.
+    pub fn workers(&self) -> &[WorkerMetrics] {
+        &self.workers
+    }
+
+    /// Returns the number of futures that have been spawned onto the pool and
+    /// have not yet completed.
+    pub fn num_futures(&self) -> usize {
+        self.num_futures
+    }
+
+    /// Returns the number of tasks queued on workers, waiting to be polled.
+    pub fn queue_depth(&self) -> usize {
+        self.queue_depth
+    }
+
+    /// Returns the number of threads currently executing a [`blocking`]
+    /// section.
+    ///
+    /// [`blocking`]: fn.blocking.html
+    pub fn blocking_threads(&self) -> usize {
+        self.blocking_threads
+    }
+
+    /// Returns the maximum number of threads that may execute a [`blocking`]
+    /// section at the same time.
+    ///
+    /// See [`Builder::max_blocking`].
+    ///
+    /// [`blocking`]: fn.blocking.html
+    /// [`Builder::max_blocking`]: struct.Builder.html#method.max_blocking
+    pub fn max_blocking(&self) -> usize {
+        self.max_blocking
+    }
+
+    /// Returns the total number of [`blocking`] sections entered.
+    ///
+    /// [`blocking`]: fn.blocking.html
+    pub fn blocking_sections(&self) -> u64 {
+        self.blocking_sections
+    }
+
+    /// Returns the total number of times a task was polled, across all
+    /// workers.
+    pub fn polls(&self) -> u64 {
+        self.workers.iter().map(|w| w.polls).sum()
+    }
+
+    /// Returns the total number of tasks stolen, across all workers.
+    pub fn steals(&self) -> u64 {
+        self.workers.iter().map(|w| w.steals).sum()
+    }
+
+    /// Returns the total number of times a worker parked, across all workers.
+    pub fn parks(&self) -> u64 {
+        self.workers.iter().map(|w| w.parks).sum()
+    }
+
+    /// Returns the total number of times a worker was unparked, across all
+    /// workers.
+    pub fn unparks(&self) -> u64 {
+        self.workers.iter().map(|w| w.unparks).sum()
+    }
+
+    /// Returns the total time spent polling tasks, across all workers.
+    pub fn busy_duration(&self) -> Duration {
+        self.workers.iter().map(|w| w.busy).fold(Duration::from_secs(0), |a, b| a + b)
+    }
+}
+
+// ===== impl WorkerMetrics =====
+
+impl WorkerMetrics {
+    /// Returns the identifier of the worker.
+    pub fn id(&self) -> &WorkerId {
+        &self.id
+    }
+
+    /// Returns the number of times the worker polled a task.
+    pub fn polls(&self) -> u64 {
+        self.polls
+    }
+
+    /// Returns the number of times the worker stole a task from another
+    /// worker.
+    pub fn steals(&self) -> u64 {
+        self.steals
+    }
+
+    /// Returns the number of times the worker parked its thread because it had
+    /// no work.
+    pub fn parks(&self) -> u64 {
+        self.parks
+    }
+
+    /// Returns the number of times the worker was unparked by another thread.
+    pub fn unparks(&self) -> u64 {
+        self.unparks
+    }
+
+    /// Returns the time the worker spent polling tasks.
+    ///
+    /// This includes time spent in [`blocking`] sections.
+    ///
+    /// [`blocking`]: fn.blocking.html
+    pub fn busy_duration(&self) -> Duration {
+        self.busy
+    }
+}
+
+// ===== impl WorkerStats =====
+
+impl WorkerStats {
+    pub fn new() -> WorkerStats {
+        WorkerStats {
+            polls: AtomicU64::new(0),
+            steals: AtomicU64::new(0),
+            parks: AtomicU64::new(0),
+            unparks: AtomicU64::new(0),
+            busy_nanos: AtomicU64::new(0),
+        }
+    }
+
+    #[inline]
+    pub fn inc_polls(&self) {
+        self.polls.fetch_add(1, Relaxed);
+    }
+
+    #[inline]
+    pub fn inc_steals(&self) {
+        self.steals.fetch_add(1, Relaxed);
+    }
+
+    #[inline]
+    pub fn inc_parks(&self) {
+        self.parks.fetch_add(1, Relaxed);
+    }
+
+    #[inline]
+    pub fn inc_unparks(&self) {
+        self.unparks.fetch_add(1, Relaxed);
+    }
+
+    #[inline]
+    pub fn add_busy(&self, dur: Duration) {
+        let nanos = dur.as_secs()
+            .saturating_mul(1_000_000_000)
+            .saturating_add(u64::from(dur.subsec_nanos()));
+
+        self.busy_nanos.fetch_add(nanos, Relaxed);
+    }
+
+    fn snapshot(&self, id: WorkerId) -> WorkerMetrics {
+        let busy_nanos = self.busy_nanos.load(Relaxed);
+
+        WorkerMetrics {
+            id,
+            polls: self.polls.load(Relaxed),
+            steals: self.steals.load(Relaxed),
+            parks: self.parks.load(Relaxed),
+            unparks: self.unparks.load(Relaxed),
+            busy: Duration::new(busy_nanos / 1_000_000_000,
+                                (busy_nanos % 1_000_000_000) as u32),
+        }
+    }
+}
+
+// ===== impl PoolStats =====
+
+impl PoolStats {
+    pub fn new() -> PoolStats {
+        PoolStats {
+            queued: AtomicUsize::new(0),
+            blocking: AtomicUsize::new(0),
+            blocking_sections: AtomicU64::new(0),
+        }
+    }
+
+    /// Track a task being pushed onto a worker queue.
+    ///
+    /// This must be called *before* the task is pushed so that the matching
+    /// `dec_queued` never observes a count of zero.
+    #[inline]
+    pub fn inc_queued(&self) {
+        self.queued.fetch_add(1, Relaxed);
+    }
+
+    /// Track tasks being removed from worker queues.
+    #[inline]
+    pub fn dec_queued(&self, n: usize) {
+        self.queued.fetch_sub(n, Relaxed);
+    }
+
+    #[inline]
+    pub fn enter_blocking(&self) {
+        self.blocking.fetch_add(1, Relaxed);
+        self.blocking_sections.fetch_add(1, Relaxed);
+    }
+
+    #[inline]
+    pub fn exit_blocking(&self) {
+        self.blocking.fetch_sub(1, Relaxed);
+    }
+}
@@ -14,6 +14,7 @@ use self::backup::Handoff;
 use self::backup_stack::BackupStack;
 
 use config::Config;
+use metrics::PoolStats;
 use shutdown_task::ShutdownTask;
 use task::{Task, Blocking};
 use worker::{self, Worker, WorkerId};
@@ -74,6 +75,12 @@ pub(crate) struct Pool {
     // are pending blocking capacity.
     blocking: Blocking,
 
+    // Maximum number of threads that can be in a blocking section.
+    pub max_blocking: usize,
+
+    // Pool-wide metrics
+    pub stats: PoolStats,
+
     // Task notified when the worker shuts down
     pub shutdown_task: ShutdownTask,
 
@@ -115,6 +122,8 @@ impl Pool {
             backup,
             backup_stack,
             blocking,
+            max_blocking,
+            stats: PoolStats::new(),
             shutdown_task: ShutdownTask {
                 task: AtomicTask::new(),
             },
@@ -288,6 +297,7 @@ impl Pool {
 
                     trace!("    -> submit internal; idx={}", idx);
 
+                    self.stats.inc_queued();
                     worker.inner.workers[idx].submit_internal(task);
                     worker.inner.signal_work(inner);
                     return;
@@ -336,6 +346,8 @@ impl Pool {
 
         let entry = &self.workers[idx];
 
+        self.stats.inc_queued();
+
         if !entry.submit_external(task, state) {
             self.spawn_thread(WorkerId::new(idx), inner);
         }
@@ -415,6 +427,9 @@ impl Pool {
                     break;
                 }
 
+                // The blocking section has completed.
+                inner.stats.exit_blocking();
+
                 debug_assert!(!inner.backup[backup_id.0].is_pushed());
 
                 // Push the thread back onto the backup stack. This makes it
@@ -1,3 +1,5 @@
+use join_handle::{self, JoinHandle};
+use metrics::Metrics;
 use pool::{self, Pool, Lifecycle, MAX_FUTURES};
 use task::Task;
 
@@ -83,6 +85,81 @@ impl Sender {
         tokio_executor::Executor::spawn(&mut s, Box::new(future))
     }
 
+    /// Spawn a future onto the thread pool, returning a handle to its result.
+    ///
+    /// This is like [`spawn`], but the future may complete with any item and
+    /// error, which are yielded by the returned [`JoinHandle`]. If the future
+    /// panics, the panic is caught and the `JoinHandle` fails with a
+    /// [`JoinError`]. The task can be aborted using [`JoinHandle::abort`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate tokio_threadpool;
+    /// # extern crate futures;
+    /// # use tokio_threadpool::ThreadPool;
+    /// use futures::future::{Future, lazy};
+    ///
+    /// # pub fn main() {
+    /// let thread_pool = ThreadPool::new();
+    ///
+    /// let handle = thread_pool.sender().spawn_handle(lazy(|| {
+    ///     Ok::<_, ()>(1 + 1)
+    /// })).unwrap();
+    ///
+    /// assert_eq!(Ok(2), handle.wait().unwrap());
+    ///
+    /// thread_pool.shutdown().wait().unwrap();
+    /// # }
+    /// ```
+    ///
+    /// [`spawn`]: #method.spawn
+    /// [`JoinHandle`]: struct.JoinHandle.html
+    /// [`JoinError`]: struct.JoinError.html
+    /// [`JoinHandle::abort`]: struct.JoinHandle.html#method.abort
+    pub fn spawn_handle<F>(&self, future: F)
+        -> Result<JoinHandle<F::Item, F::Error>, SpawnError>
+    where F: Future + Send + 'static,
+          F::Item: Send + 'static,
+          F::Error: Send + 'static,
+    {
+        let (task, handle) = join_handle::joinable(future);
+        self.spawn(task)?;
+        Ok(handle)
+    }
+
+    /// Returns a snapshot of the thread pool's metrics.
+    ///
+    /// The counters are maintained with relaxed atomic operations, so taking a
+    /// snapshot does not interfere with the workers.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate tokio_threadpool;
+    /// # extern crate futures;
+    /// # use tokio_threadpool::ThreadPool;
+    /// use futures::future::{Future, lazy};
+    ///
+    /// # pub fn main() {
+    /// let thread_pool = ThreadPool::new();
+    ///
+    /// thread_pool.spawn_handle(lazy(|| Ok::<_, ()>(()))).wait().unwrap();
+    ///
+    /// let metrics = thread_pool.sender().metrics();
+    /// assert!(metrics.polls() >= 1);
+    ///
+    /// for worker in metrics.workers() {
+    ///     println!("{:?}: busy for {:?}", worker.id(), worker.busy_duration());
+    /// }
+    ///
+    /// thread_pool.shutdown().wait().unwrap();
+    /// # }
+    /// ```
+    pub fn metrics(&self) -> Metrics {
+        Metrics::new(&self.inner)
+    }
+
     /// Logic to prepare for spawning
     fn prepare_for_spawn(&self) -> Result<(), SpawnError> {
         let mut state: pool::State = self.inner.state.load(Acquire).into();
@@ -1,4 +1,6 @@
 use builder::Builder;
+use join_handle::JoinHandle;
+use metrics::Metrics;
 use pool::Pool;
 use sender::Sender;
 use shutdown::Shutdown;
@@ -64,6 +66,55 @@ impl ThreadPool {
         self.sender().spawn(future).unwrap();
     }
 
+    /// Spawn a future onto the thread pool, returning a handle to its result.
+    ///
+    /// See [`Sender::spawn_handle`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # extern crate tokio_threadpool;
+    /// # extern crate futures;
+    /// # use tokio_threadpool::ThreadPool;
+    /// use futures::future::{Future, lazy};
+    ///
+    /// # pub fn main() {
+    /// let thread_pool = ThreadPool::new();
+    ///
+    /// let handle = thread_pool.spawn_handle(lazy(|| {
+    ///     if true { panic!("boom") }
+    ///     Ok::<(), ()>(())
+    /// }));
+    ///
+    /// assert!(handle.wait().unwrap_err().is_panic());
+    ///
+    /// thread_pool.shutdown().wait().unwrap();
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the spawn fails. Use [`Sender::spawn_handle`]
+    /// for a version that returns a `Result` instead of panicking.
+    ///
+    /// [`Sender::spawn_handle`]: struct.Sender.html#method.spawn_handle
+    pub fn spawn_handle<F>(&self, future: F) -> JoinHandle<F::Item, F::Error>
+    where F: Future + Send + 'static,
+          F::Item: Send + 'static,
+          F::Error: Send + 'static,
+    {
+        self.sender().spawn_handle(future).unwrap()
+    }
+
+    /// Returns a snapshot of the thread pool's metrics.
+    ///
+    /// See [`Sender::metrics`] for more details.
+    ///
+    /// [`Sender::metrics`]: struct.Sender.html#method.metrics
+    pub fn metrics(&self) -> Metrics {
+        self.sender().metrics()
+    }
+
     /// Return a reference to the sender handle
     ///
     /// The handle is used to spawn futures onto the thread pool. It also
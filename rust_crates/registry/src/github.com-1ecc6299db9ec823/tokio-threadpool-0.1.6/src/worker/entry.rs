@@ -1,3 +1,4 @@
+use metrics::WorkerStats;
 use park::{BoxPark, BoxUnpark};
 use task::{Task, Queue};
 use worker::state::{State, PUSHED_MASK};
@@ -39,6 +40,9 @@ pub(crate) struct WorkerEntry {
 
     // MPSC queue of jobs submitted to the worker from an external source.
     pub inbound: Queue,
+
+    // Worker metrics
+    pub stats: CachePadded<WorkerStats>,
 }
 
 impl WorkerEntry {
@@ -53,6 +57,7 @@ impl WorkerEntry {
             inbound: Queue::new(),
             park: UnsafeCell::new(park),
             unpark,
+            stats: CachePadded::new(WorkerStats::new()),
         }
     }
 
@@ -207,16 +212,22 @@ impl WorkerEntry {
     /// Drain (and drop) all tasks that are queued for work.
     ///
     /// This is called when the pool is shutting down.
-    pub fn drain_tasks(&self) {
+    ///
+    /// Returns the number of tasks dropped.
+    pub fn drain_tasks(&self) -> usize {
         use deque::Pop;
 
+        let mut num = 0;
+
         loop {
             match self.worker.pop() {
-                Pop::Data(_) => {}
+                Pop::Data(_) => num += 1,
                 Pop::Empty => break,
                 Pop::Retry => {}
             }
         }
+
+        num
     }
 
     #[inline]
@@ -231,6 +242,7 @@ impl WorkerEntry {
 
     #[inline]
     pub fn wakeup(&self) {
+        self.stats.inc_unparks();
         self.unpark.unpark();
     }
 
@@ -255,6 +267,7 @@ impl fmt::Debug for WorkerEntry {
             .field("park", &"UnsafeCell<BoxPark>")
             .field("unpark", &"BoxUnpark")
             .field("inbound", &self.inbound)
+            .field("stats", &self.stats)
             .finish()
     }
 }
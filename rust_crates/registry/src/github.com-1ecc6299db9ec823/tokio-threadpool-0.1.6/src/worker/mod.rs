@@ -196,6 +196,7 @@ impl Worker {
 
         // Track that the thread has now fully entered the blocking state.
         self.is_blocking.set(true);
+        self.inner.stats.enter_blocking();
 
         Ok(().into())
     }
@@ -388,6 +389,7 @@ impl Worker {
         // Poll the internal queue for a task to run
         match self.entry().pop_task() {
             Pop::Data(task) => {
+                self.inner.stats.dec_queued(1);
                 self.run_task(task, notify);
                 true
             }
@@ -415,6 +417,9 @@ impl Worker {
                     Steal::Data(task) => {
                         trace!("stole task");
 
+                        self.inner.stats.dec_queued(1);
+                        self.entry().stats.inc_steals();
+
                         self.run_task(task, notify);
 
                         trace!("try_steal_task -- signal_work; self={}; from={}",
@@ -468,6 +473,7 @@ impl Worker {
                     // here because `self` is still set as the current worker.
                     self.inner.submit_external(task, &self.inner);
                 } else {
+                    self.inner.stats.inc_queued();
                     self.entry().push_internal(task);
                 }
             }
@@ -547,7 +553,16 @@ impl Worker {
             allocated_at_run: can_block == CanBlock::Allocated
         };
 
-        task.run(notify)
+        // The entry is looked up before running the task, as the task may
+        // transition the thread to blocking, handing off the worker.
+        let stats = &self.entry().stats;
+        stats.inc_polls();
+
+        let start = Instant::now();
+        let run = task.run(notify);
+        stats.add_busy(start.elapsed());
+
+        run
     }
 
     /// Drains all tasks on the extern queue and pushes them onto the internal
@@ -677,6 +692,8 @@ impl Worker {
         loop {
             let mut drop_thread = false;
 
+            self.entry().stats.inc_parks();
+
             match sleep_until {
                 Some(when) => {
                     let now = Instant::now();
@@ -842,7 +859,8 @@ impl Drop for Worker {
             self.drain_inbound();
 
             // Drain the work queue
-            self.entry().drain_tasks();
+            let num = self.entry().drain_tasks();
+            self.inner.stats.dec_queued(num);
 
             // TODO: Drain the work queue...
         }
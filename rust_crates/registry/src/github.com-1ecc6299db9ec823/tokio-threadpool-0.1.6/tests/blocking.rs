@@ -408,3 +408,37 @@ fn hammer() {
         }
     }
 }
+
+#[test]
+fn metrics_track_blocking() {
+    let _ = ::env_logger::try_init();
+
+    let pool = Builder::new()
+        .pool_size(1)
+        .max_blocking(2)
+        .build();
+
+    let (tx, rx) = mpsc::channel();
+
+    pool.spawn(lazy(move || {
+        blocking(|| rx.recv().unwrap()).unwrap();
+        Ok(())
+    }));
+
+    while pool.metrics().blocking_threads() == 0 {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let metrics = pool.metrics();
+    assert_eq!(1, metrics.blocking_threads());
+    assert_eq!(2, metrics.max_blocking());
+    assert_eq!(1, metrics.blocking_sections());
+
+    tx.send(()).unwrap();
+
+    while pool.metrics().blocking_threads() != 0 {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    assert_eq!(1, pool.metrics().blocking_sections());
+}
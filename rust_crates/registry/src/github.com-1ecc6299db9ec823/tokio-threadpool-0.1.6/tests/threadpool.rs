@@ -464,3 +464,164 @@ fn multi_threadpool() {
 
     done_rx.recv().unwrap();
 }
+
+#[test]
+fn spawn_handle_yields_result() {
+    let pool = ThreadPool::new();
+
+    let ok = pool.spawn_handle(lazy(|| Ok::<_, &'static str>(42)));
+    let err = pool.sender().spawn_handle(lazy(|| Err::<u32, _>("nope"))).unwrap();
+
+    assert_eq!(Ok(42), ok.wait().unwrap());
+    assert_eq!(Err("nope"), err.wait().unwrap());
+
+    pool.shutdown_on_idle().wait().unwrap();
+}
+
+#[test]
+fn spawn_handle_catches_panic() {
+    let pool = ThreadPool::new();
+
+    let handle = pool.spawn_handle(lazy(|| {
+        if true {
+            panic!("boom");
+        }
+        Ok::<(), ()>(())
+    }));
+
+    let err = handle.wait().unwrap_err();
+    assert!(err.is_panic());
+    assert!(!err.is_cancelled());
+    assert_eq!(Some(&"boom"), err.into_panic().unwrap().downcast_ref::<&str>());
+
+    // The pool keeps running after the panic.
+    let handle = pool.spawn_handle(lazy(|| Ok::<_, ()>("still running")));
+    assert_eq!(Ok("still running"), handle.wait().unwrap());
+
+    pool.shutdown_on_idle().wait().unwrap();
+}
+
+struct SetOnDrop(Arc<AtomicBool>);
+
+impl Drop for SetOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Relaxed);
+    }
+}
+
+#[test]
+fn spawn_handle_abort() {
+    let pool = ThreadPool::new();
+    let dropped = Arc::new(AtomicBool::new(false));
+
+    let handle = {
+        let guard = SetOnDrop(dropped.clone());
+        pool.spawn_handle(futures::future::empty::<(), ()>().then(move |res| {
+            drop(guard);
+            res
+        }))
+    };
+
+    handle.abort();
+
+    let err = handle.wait().unwrap_err();
+    assert!(err.is_cancelled());
+    assert!(!err.is_panic());
+    assert!(dropped.load(Relaxed));
+
+    pool.shutdown_on_idle().wait().unwrap();
+}
+
+#[test]
+fn spawn_handle_shutdown_now_cancels() {
+    let pool = ThreadPool::new();
+
+    let handle = pool.spawn_handle(futures::future::empty::<(), ()>());
+
+    pool.shutdown_now().wait().unwrap();
+
+    assert!(handle.wait().unwrap_err().is_cancelled());
+}
+
+#[test]
+fn spawn_handle_dropped_handle_detaches() {
+    let pool = ThreadPool::new();
+    let (tx, rx) = mpsc::channel();
+
+    drop(pool.spawn_handle(lazy(move || {
+        tx.send("ran").unwrap();
+        Ok::<(), ()>(())
+    })));
+
+    assert_eq!("ran", rx.recv().unwrap());
+
+    pool.shutdown_on_idle().wait().unwrap();
+}
+
+#[test]
+fn metrics_track_polls_and_busy_time() {
+    let _ = ::env_logger::try_init();
+
+    const NUM: usize = 10;
+
+    let pool = Builder::new()
+        .pool_size(2)
+        .build();
+
+    let handles: Vec<_> = (0..NUM).map(|_| {
+        pool.spawn_handle(lazy(|| {
+            ::std::thread::sleep(Duration::from_millis(1));
+            Ok::<(), ()>(())
+        }))
+    }).collect();
+
+    for handle in handles {
+        handle.wait().unwrap().unwrap();
+    }
+
+    let metrics = pool.metrics();
+
+    assert_eq!(2, metrics.workers().len());
+    assert!(metrics.polls() >= NUM as u64);
+    assert!(metrics.busy_duration() >= Duration::from_millis(NUM as u64));
+    assert_eq!(0, metrics.blocking_threads());
+
+    let polls: u64 = metrics.workers().iter().map(|w| w.polls()).sum();
+    assert_eq!(polls, metrics.polls());
+
+    // A task's result is sent before the pool observes its completion, so
+    // wait for the pool to catch up.
+    let mut metrics = metrics;
+
+    while metrics.num_futures() != 0 {
+        ::std::thread::sleep(Duration::from_millis(10));
+        metrics = pool.metrics();
+    }
+
+    assert_eq!(0, metrics.queue_depth());
+}
+
+#[test]
+fn metrics_track_parks() {
+    let _ = ::env_logger::try_init();
+
+    let pool = Builder::new()
+        .pool_size(1)
+        .build();
+
+    pool.spawn_handle(lazy(|| Ok::<(), ()>(()))).wait().unwrap().unwrap();
+
+    // Wait for the worker to run out of work and park.
+    while pool.metrics().parks() == 0 {
+        ::std::thread::sleep(Duration::from_millis(10));
+    }
+
+    let unparks = pool.metrics().unparks();
+
+    // Spawning onto a parked worker unparks it.
+    pool.spawn_handle(lazy(|| Ok::<(), ()>(()))).wait().unwrap().unwrap();
+
+    let metrics = pool.metrics();
+    assert!(metrics.unparks() > unparks);
+    assert_eq!(metrics.unparks(), metrics.workers()[0].unparks());
+}
@@ -24,11 +24,15 @@ pub struct Cert<'a> {
     pub ee_or_ca: EndEntityOrCA<'a>,
 
     pub signed_data: signed_data::SignedData<'a>,
+    pub serial: untrusted::Input<'a>,
     pub issuer: untrusted::Input<'a>,
     pub validity: untrusted::Input<'a>,
     pub subject: untrusted::Input<'a>,
     pub spki: untrusted::Input<'a>,
 
+    // The contents of the Extensions SEQUENCE, kept for `inspect`.
+    pub extensions: untrusted::Input<'a>,
+
     pub basic_constraints: Option<untrusted::Input<'a>>,
     pub eku: Option<untrusted::Input<'a>>,
     pub name_constraints: Option<untrusted::Input<'a>>,
@@ -46,7 +50,7 @@ pub fn parse_cert<'a>(cert_der: untrusted::Input<'a>,
 pub fn parse_cert_internal<'a>(
         cert_der: untrusted::Input<'a>, ee_or_ca: EndEntityOrCA<'a>,
         serial_number: fn(input: &mut untrusted::Reader<'a>)
-                          -> Result<(), Error>)
+                          -> Result<untrusted::Input<'a>, Error>)
         -> Result<Cert<'a>, Error> {
     let (tbs, signed_data) = cert_der.read_all(Error::BadDER, |cert_der| {
         der::nested(cert_der, der::Tag::Sequence, Error::BadDER,
@@ -55,7 +59,7 @@ pub fn parse_cert_internal<'a>(
 
     tbs.read_all(Error::BadDER, |tbs| {
         version3(tbs)?;
-        serial_number(tbs)?;
+        let serial = serial_number(tbs)?;
 
         let signature = der::expect_tag_and_get_value(tbs, der::Tag::Sequence)?;
         // TODO: In mozilla::pkix, the comparison is done based on the
@@ -79,11 +83,14 @@ pub fn parse_cert_internal<'a>(
             ee_or_ca: ee_or_ca,
 
             signed_data: signed_data,
+            serial: serial,
             issuer: issuer,
             validity: validity,
             subject: subject,
             spki: spki,
 
+            extensions: untrusted::Input::from(&[]),
+
             basic_constraints: None,
             eku: None,
             name_constraints: None,
@@ -96,29 +103,62 @@ pub fn parse_cert_internal<'a>(
         // special logic for handling critical Netscape Cert Type extensions.
         // That has been intentionally omitted.
 
-        der::nested_mut(tbs, der::Tag::ContextSpecificConstructed3,
-                        Error::BadDER, |tagged| {
-            der::nested_of_mut(tagged, der::Tag::Sequence, der::Tag::Sequence,
-                               Error::BadDER, |extension| {
-                let extn_id =
-                    der::expect_tag_and_get_value(extension, der::Tag::OID)?;
-                let critical = der::optional_boolean(extension)?;
-                let extn_value: untrusted::Input<'a> =
-                    der::expect_tag_and_get_value(extension,
-                                                  der::Tag::OctetString)?;
-                match remember_extension(&mut cert, extn_id, extn_value)? {
-                    Understood::No if critical => {
-                        Err(Error::UnsupportedCriticalExtension)
-                    },
-                    _ => Ok(())
+        let extensions = der::nested(
+            tbs, der::Tag::ContextSpecificConstructed3, Error::BadDER,
+            |tagged| der::expect_tag_and_get_value(tagged, der::Tag::Sequence))?;
+        cert.extensions = extensions;
+
+        extensions.read_all(Error::BadDER, |extensions| {
+            loop {
+                der::nested_mut(extensions, der::Tag::Sequence, Error::BadDER,
+                                |extension| {
+                    let extn_id =
+                        der::expect_tag_and_get_value(extension, der::Tag::OID)?;
+                    let critical = der::optional_boolean(extension)?;
+                    let extn_value: untrusted::Input<'a> =
+                        der::expect_tag_and_get_value(extension,
+                                                      der::Tag::OctetString)?;
+                    match remember_extension(&mut cert, extn_id, extn_value)? {
+                        Understood::No if critical => {
+                            Err(Error::UnsupportedCriticalExtension)
+                        },
+                        _ => Ok(())
+                    }
+                })?;
+                if extensions.at_end() {
+                    return Ok(());
                 }
-            })
+            }
         })?;
 
         Ok(cert)
     })
 }
 
+/// Returns the subject of `cert_der`, reading no more of it than is needed
+/// to find it. This lets path building skip certificates that can't be the
+/// issuer without parsing (and so rejecting) them.
+pub fn parse_subject<'a>(cert_der: untrusted::Input<'a>)
+                         -> Result<untrusted::Input<'a>, Error> {
+    let certificate = cert_der.read_all(Error::BadDER, |cert_der| {
+        der::expect_tag_and_get_value(cert_der, der::Tag::Sequence)
+    })?;
+    let mut certificate = untrusted::Reader::new(certificate);
+    let tbs = der::expect_tag_and_get_value(&mut certificate,
+                                            der::Tag::Sequence)?;
+
+    let mut tbs = untrusted::Reader::new(tbs);
+    if tbs.peek(der::Tag::ContextSpecificConstructed0 as u8) {
+        der::expect_tag_and_get_value(&mut tbs,
+                                      der::Tag::ContextSpecificConstructed0)?;
+    }
+    der::expect_tag_and_get_value(&mut tbs, der::Tag::Integer)?; // serial
+    der::expect_tag_and_get_value(&mut tbs, der::Tag::Sequence)?; // signature
+    der::expect_tag_and_get_value(&mut tbs, der::Tag::Sequence)?; // issuer
+    der::expect_tag_and_get_value(&mut tbs, der::Tag::Sequence)?; // validity
+    der::expect_tag_and_get_value(&mut tbs, der::Tag::Sequence)
+}
+
 // mozilla::pkix supports v1, v2, v3, and v4, including both the implicit
 // (correct) and explicit (incorrect) encoding of v1. We allow only v3.
 fn version3(input: &mut untrusted::Reader) -> Result<(), Error> {
@@ -133,7 +173,7 @@ fn version3(input: &mut untrusted::Reader) -> Result<(), Error> {
 }
 
 pub fn certificate_serial_number<'a>(input: &mut untrusted::Reader<'a>)
-                                     -> Result<(), Error> {
+                                     -> Result<untrusted::Input<'a>, Error> {
     // https://tools.ietf.org/html/rfc5280#section-4.1.2.2:
     // * Conforming CAs MUST NOT use serialNumber values longer than 20 octets."
     // * "The serial number MUST be a positive integer [...]"
@@ -142,7 +182,7 @@ pub fn certificate_serial_number<'a>(input: &mut untrusted::Reader<'a>)
     if value.len() > 20 {
         return Err(Error::BadDER);
     }
-    Ok(())
+    Ok(value)
 }
 
 enum Understood { Yes, No }
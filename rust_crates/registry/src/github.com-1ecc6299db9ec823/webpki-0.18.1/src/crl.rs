@@ -0,0 +1,211 @@
+// Copyright 2018 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Certificate revocation lists: https://tools.ietf.org/html/rfc5280#section-5
+
+use untrusted;
+use {CertRevocationList, der, Error, signed_data, SignatureAlgorithm, time,
+     TrustAnchor};
+use cert::Cert;
+use verify_cert;
+
+pub struct CRL<'a> {
+    pub signed_data: signed_data::SignedData<'a>,
+    pub issuer: untrusted::Input<'a>,
+    pub this_update: time::Time,
+    pub next_update: Option<time::Time>,
+
+    /// The contents of `revokedCertificates`, if present.
+    pub revoked_certs: Option<untrusted::Input<'a>>,
+}
+
+// CertificateList  ::=  SEQUENCE  {
+//      tbsCertList          TBSCertList,
+//      signatureAlgorithm   AlgorithmIdentifier,
+//      signatureValue       BIT STRING  }
+//
+// TBSCertList  ::=  SEQUENCE  {
+//      version                 Version OPTIONAL,
+//                                   -- if present, MUST be v2
+//      signature               AlgorithmIdentifier,
+//      issuer                  Name,
+//      thisUpdate              Time,
+//      nextUpdate              Time OPTIONAL,
+//      revokedCertificates     SEQUENCE OF SEQUENCE  { ... } OPTIONAL,
+//      crlExtensions           [0]  EXPLICIT Extensions OPTIONAL
+//                                   -- if present, version MUST be v2
+//                            }
+pub fn parse_crl<'a>(crl_der: untrusted::Input<'a>) -> Result<CRL<'a>, Error> {
+    let (tbs, signed_data) = crl_der.read_all(Error::BadDER, |crl_der| {
+        der::nested(crl_der, der::Tag::Sequence, Error::BadDER,
+                    signed_data::parse_signed_data)
+    })?;
+
+    tbs.read_all(Error::BadDER, |tbs| {
+        if tbs.peek(der::Tag::Integer as u8) {
+            let version = der::small_nonnegative_integer(tbs)?;
+            if version != 1 { // v2
+                return Err(Error::UnsupportedCertVersion);
+            }
+        }
+
+        let signature = der::expect_tag_and_get_value(tbs, der::Tag::Sequence)?;
+        if signature != signed_data.algorithm {
+            return Err(Error::SignatureAlgorithmMismatch);
+        }
+
+        let issuer = der::expect_tag_and_get_value(tbs, der::Tag::Sequence)?;
+        let this_update = der::time_choice(tbs)?;
+        let next_update =
+            if tbs.peek(der::Tag::UTCTime as u8) ||
+               tbs.peek(der::Tag::GeneralizedTime as u8) {
+                Some(der::time_choice(tbs)?)
+            } else {
+                None
+            };
+
+        let revoked_certs = if tbs.peek(der::Tag::Sequence as u8) {
+            Some(der::expect_tag_and_get_value(tbs, der::Tag::Sequence)?)
+        } else {
+            None
+        };
+
+        // We don't understand any CRL extensions, so the CRL is unusable if
+        // any of them are critical. Notably this rejects delta CRLs and
+        // partitioned CRLs (via the issuing distribution point extension),
+        // which we would otherwise misinterpret as complete.
+        if !tbs.at_end() {
+            der::nested(tbs, der::Tag::ContextSpecificConstructed0,
+                        Error::BadDER, reject_critical_extensions)?;
+        }
+
+        Ok(CRL {
+            signed_data: signed_data,
+            issuer: issuer,
+            this_update: this_update,
+            next_update: next_update,
+            revoked_certs: revoked_certs,
+        })
+    })
+}
+
+/// Checks `cert` against those of `crls` that were issued by `cert`'s issuer,
+/// which is found among `trust_anchors` and `intermediate_certs`.
+///
+/// Each such CRL must be signed by the same key that signed `cert` and be
+/// current at `time`. If none of `crls` were issued by `cert`'s issuer then
+/// the result is `Error::UnknownRevocationStatus`.
+pub fn check_crls(supported_sig_algs: &[&SignatureAlgorithm],
+                  trust_anchors: &[TrustAnchor],
+                  intermediate_certs: &[untrusted::Input], cert: &Cert,
+                  crls: &[CertRevocationList], time: time::Time)
+                  -> Result<(), Error> {
+    verify_cert::with_issuer(supported_sig_algs, trust_anchors,
+                             intermediate_certs, cert,
+                             |issuer_subject, issuer_spki| {
+        let mut result = Err(Error::UnknownRevocationStatus);
+
+        for crl in crls.iter()
+                       .map(|crl| &crl.inner)
+                       .filter(|crl| crl.issuer == issuer_subject) {
+            signed_data::verify_signed_data(supported_sig_algs, issuer_spki,
+                                            &crl.signed_data)?;
+
+            if time < crl.this_update {
+                return Err(Error::RevocationDataNotCurrent);
+            }
+            match crl.next_update {
+                Some(next_update) if time > next_update => {
+                    return Err(Error::RevocationDataNotCurrent);
+                },
+                _ => {}
+            }
+
+            untrusted::read_all_optional(crl.revoked_certs, Error::BadDER,
+                                         |revoked_certs| {
+                check_revoked_certs(revoked_certs, cert.serial)
+            })?;
+            result = Ok(());
+        }
+
+        result
+    })
+}
+
+//  revokedCertificates     SEQUENCE OF SEQUENCE  {
+//       userCertificate         CertificateSerialNumber,
+//       revocationDate          Time,
+//       crlEntryExtensions      Extensions OPTIONAL
+//                                -- if present, version MUST be v2
+//                            }  OPTIONAL,
+fn check_revoked_certs(revoked_certs: Option<&mut untrusted::Reader>,
+                       serial: untrusted::Input) -> Result<(), Error> {
+    let revoked_certs = match revoked_certs {
+        Some(revoked_certs) => revoked_certs,
+        None => { return Ok(()); }
+    };
+
+    let mut result = Ok(());
+    while !revoked_certs.at_end() {
+        der::nested(revoked_certs, der::Tag::Sequence, Error::BadDER,
+                    |entry| {
+            // Serial numbers are compared as `positive_integer` returns them
+            // (without any leading zero), just like certificates' are.
+            let user_certificate = der::positive_integer(entry)?;
+            let _revocation_date = der::time_choice(entry)?;
+            if !entry.at_end() {
+                // An indirect CRL would say via the critical certificate
+                // issuer entry extension that this entry is for a certificate
+                // from some other issuer, so we can't ignore it.
+                der::nested(entry, der::Tag::Sequence, Error::BadDER,
+                            reject_critical_extension_list)?;
+            }
+            if user_certificate == serial {
+                result = Err(Error::CertRevoked);
+            }
+            Ok(())
+        })?;
+    }
+    result
+}
+
+// The contents of an EXPLICIT-tagged `Extensions`.
+fn reject_critical_extensions(input: &mut untrusted::Reader)
+                              -> Result<(), Error> {
+    der::nested(input, der::Tag::Sequence, Error::BadDER,
+                reject_critical_extension_list)
+}
+
+// Extension  ::=  SEQUENCE  {
+//      extnID      OBJECT IDENTIFIER,
+//      critical    BOOLEAN DEFAULT FALSE,
+//      extnValue   OCTET STRING }
+fn reject_critical_extension_list(extensions: &mut untrusted::Reader)
+                                  -> Result<(), Error> {
+    while !extensions.at_end() {
+        der::nested(extensions, der::Tag::Sequence, Error::BadDER,
+                    |extension| {
+            let _extn_id =
+                der::expect_tag_and_get_value(extension, der::Tag::OID)?;
+            if der::optional_boolean(extension)? {
+                return Err(Error::UnsupportedCriticalExtension);
+            }
+            let _extn_value =
+                der::expect_tag_and_get_value(extension,
+                                              der::Tag::OctetString)?;
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
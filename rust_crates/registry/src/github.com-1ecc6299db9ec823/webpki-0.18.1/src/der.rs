@@ -15,6 +15,7 @@
 use ring;
 pub use ring::der::{
     CONSTRUCTED,
+    CONTEXT_SPECIFIC,
 
     Tag,
 
@@ -38,6 +39,18 @@ pub fn read_tag_and_get_value<'a>(input: &mut untrusted::Reader<'a>)
     ring::der::read_tag_and_get_value(input).map_err(|_| Error::BadDER)
 }
 
+// Like `expect_tag_and_get_value`, but for tags that `Tag` doesn't name, such
+// as ENUMERATED and the IMPLICIT context-specific tags used by OCSP.
+pub fn expect_raw_tag_and_get_value<'a>(input: &mut untrusted::Reader<'a>,
+                                        tag: u8) ->
+                                        Result<untrusted::Input<'a>, Error> {
+    let (actual_tag, value) = read_tag_and_get_value(input)?;
+    if actual_tag != tag {
+        return Err(Error::BadDER);
+    }
+    Ok(value)
+}
+
 // TODO: investigate taking decoder as a reference to reduce generated code
 // size.
 #[inline(always)]
@@ -50,24 +63,6 @@ pub fn nested_mut<'a, F, R, E: Copy>(input: &mut untrusted::Reader<'a>,
     inner.read_all_mut(error, decoder).map_err(|_| error)
 }
 
-// TODO: investigate taking decoder as a reference to reduce generated code
-// size.
-pub fn nested_of_mut<'a, F, E: Copy>(input: &mut untrusted::Reader<'a>,
-                                     outer_tag: Tag, inner_tag: Tag, error: E,
-                                     mut decoder: F) -> Result<(), E>
-                                     where F : FnMut(&mut untrusted::Reader<'a>)
-                                                     -> Result<(), E> {
-    nested_mut(input, outer_tag, error, |outer| {
-        loop {
-            nested_mut(outer, inner_tag, error, |inner| decoder(inner))?;
-            if outer.at_end() {
-                break;
-            }
-        }
-        Ok(())
-    })
-}
-
 pub fn bit_string_with_no_unused_bits<'a>(input: &mut untrusted::Reader<'a>)
                                           -> Result<untrusted::Input<'a>,
                                                     Error> {
@@ -95,7 +90,7 @@ pub fn optional_boolean(input: &mut untrusted::Reader) -> Result<bool, Error> {
     })
 }
 
-pub fn positive_integer<'a>(input: &'a mut untrusted::Reader)
+pub fn positive_integer<'a>(input: &mut untrusted::Reader<'a>)
                             -> Result<untrusted::Input<'a>, Error> {
     ring::der::positive_integer(input).map_err(|_| Error::BadDER)
 }
@@ -0,0 +1,483 @@
+// Copyright 2018 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+// Read-only views of the contents of a certificate. None of this is used
+// for validation; it lets applications describe a certificate to users
+// without needing a second X.509 parser.
+//
+// Each accessor checks the encoding of everything it returns up front, so
+// that iterating over the result can't fail. The iterators stop early
+// rather than panicking if that assumption is ever violated.
+
+use {Error, der, time};
+use core;
+use untrusted;
+
+// ASN.1 universal tags that `der::Tag` doesn't name.
+const SET_TAG: u8 = der::CONSTRUCTED | 0x11;
+const UTF8_STRING_TAG: u8 = 0x0c;
+const PRINTABLE_STRING_TAG: u8 = 0x13;
+const IA5_STRING_TAG: u8 = 0x16;
+
+// id-at-commonName 2.5.4.3
+static ID_AT_COMMON_NAME: [u8; 3] = oid![2, 5, 4, 3];
+
+// id-ce-keyUsage 2.5.29.15
+static ID_CE_KEY_USAGE: [u8; 3] = oid![2, 5, 29, 15];
+
+/// An X.509 distinguished name, such as the subject or issuer of a
+/// certificate.
+#[derive(Clone, Copy)]
+pub struct DistinguishedName<'a> {
+    rdns: untrusted::Input<'a>,
+}
+
+impl<'a> DistinguishedName<'a> {
+    /// The DER-encoded `RDNSequence`, without its outer SEQUENCE tag and
+    /// length. This is the same encoding as `TrustAnchor::subject`.
+    pub fn as_der(&self) -> &'a [u8] { self.rdns.as_slice_less_safe() }
+
+    /// Iterates over the relative distinguished names in the order they
+    /// are encoded, i.e. from least to most specific.
+    pub fn rdns(&self) -> RelativeDistinguishedNames<'a> {
+        RelativeDistinguishedNames {
+            input: untrusted::Reader::new(self.rdns),
+        }
+    }
+
+    /// The most specific commonName attribute, if there is one.
+    pub fn common_name(&self) -> Option<NameAttribute<'a>> {
+        let mut result = None;
+        for rdn in self.rdns() {
+            for attribute in rdn.attributes() {
+                if attribute.attribute_type == &ID_AT_COMMON_NAME[..] {
+                    result = Some(attribute);
+                }
+            }
+        }
+        result
+    }
+}
+
+pub fn distinguished_name<'a>(rdns: untrusted::Input<'a>)
+                              -> Result<DistinguishedName<'a>, Error> {
+    rdns.read_all(Error::BadDER, |rdns| {
+        while !rdns.at_end() {
+            let rdn = der::expect_raw_tag_and_get_value(rdns, SET_TAG)?;
+            rdn.read_all(Error::BadDER, |rdn| {
+                // A RelativeDistinguishedName has at least one attribute.
+                loop {
+                    let _ = name_attribute(rdn)?;
+                    if rdn.at_end() {
+                        return Ok(());
+                    }
+                }
+            })?;
+        }
+        Ok(())
+    })?;
+    Ok(DistinguishedName { rdns })
+}
+
+/// An iterator over the relative distinguished names of a
+/// `DistinguishedName`.
+pub struct RelativeDistinguishedNames<'a> {
+    input: untrusted::Reader<'a>,
+}
+
+impl<'a> Iterator for RelativeDistinguishedNames<'a> {
+    type Item = RelativeDistinguishedName<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.input.at_end() {
+            return None;
+        }
+        der::expect_raw_tag_and_get_value(&mut self.input, SET_TAG)
+            .ok()
+            .map(|attributes| RelativeDistinguishedName { attributes })
+    }
+}
+
+/// One component of a `DistinguishedName`: a set of attributes, which is
+/// almost always a single attribute.
+#[derive(Clone, Copy)]
+pub struct RelativeDistinguishedName<'a> {
+    attributes: untrusted::Input<'a>,
+}
+
+impl<'a> RelativeDistinguishedName<'a> {
+    /// Iterates over the attributes of this relative distinguished name.
+    pub fn attributes(&self) -> NameAttributes<'a> {
+        NameAttributes { input: untrusted::Reader::new(self.attributes) }
+    }
+}
+
+/// An iterator over the attributes of a `RelativeDistinguishedName`.
+pub struct NameAttributes<'a> {
+    input: untrusted::Reader<'a>,
+}
+
+impl<'a> Iterator for NameAttributes<'a> {
+    type Item = NameAttribute<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.input.at_end() {
+            return None;
+        }
+        name_attribute(&mut self.input).ok()
+    }
+}
+
+/// An attribute of a distinguished name (an `AttributeTypeAndValue`), such
+/// as its commonName or organizationName.
+#[derive(Clone, Copy)]
+pub struct NameAttribute<'a> {
+    /// The attribute type: the value of an OID, without its tag and length.
+    /// For example, commonName (2.5.4.3) is `[0x55, 0x04, 0x03]`.
+    pub attribute_type: &'a [u8],
+
+    /// The ASN.1 tag of the attribute value; e.g. 0x0c for UTF8String.
+    pub value_tag: u8,
+
+    /// The attribute value, without its tag and length.
+    pub value: &'a [u8],
+}
+
+impl<'a> NameAttribute<'a> {
+    /// The attribute value as a string, if it is encoded as a UTF8String,
+    /// PrintableString or IA5String. Values encoded any other way, such as
+    /// the legacy TeletexString and BMPString types, give `None`.
+    pub fn value_as_str(&self) -> Option<&'a str> {
+        match self.value_tag {
+            UTF8_STRING_TAG | PRINTABLE_STRING_TAG | IA5_STRING_TAG =>
+                core::str::from_utf8(self.value).ok(),
+            _ => None,
+        }
+    }
+}
+
+fn name_attribute<'a>(input: &mut untrusted::Reader<'a>)
+                      -> Result<NameAttribute<'a>, Error> {
+    der::nested(input, der::Tag::Sequence, Error::BadDER, |attribute| {
+        let attribute_type =
+            der::expect_tag_and_get_value(attribute, der::Tag::OID)?;
+        let (value_tag, value) = der::read_tag_and_get_value(attribute)?;
+        Ok(NameAttribute {
+            attribute_type: attribute_type.as_slice_less_safe(),
+            value_tag,
+            value: value.as_slice_less_safe(),
+        })
+    })
+}
+
+/// The validity period of a certificate.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Validity {
+    /// The certificate's notBefore time.
+    pub not_before: time::Time,
+
+    /// The certificate's notAfter time.
+    pub not_after: time::Time,
+}
+
+pub fn validity(input: untrusted::Input) -> Result<Validity, Error> {
+    input.read_all(Error::BadDER, |input| {
+        let not_before = der::time_choice(input)?;
+        let not_after = der::time_choice(input)?;
+        Ok(Validity { not_before, not_after })
+    })
+}
+
+/// An entry in a certificate's subjectAltName extension.
+#[derive(Clone, Copy)]
+pub enum SubjectAltName<'a> {
+    /// A dNSName, exactly as it is encoded.
+    DNSName(&'a str),
+
+    /// An iPAddress: 4 octets for an IPv4 address or 16 for IPv6.
+    IPAddress(&'a [u8]),
+
+    /// An rfc822Name (email address).
+    Rfc822Name(&'a str),
+
+    /// A uniformResourceIdentifier.
+    URI(&'a str),
+
+    /// A directoryName.
+    DirectoryName(DistinguishedName<'a>),
+
+    /// Any other kind of name: an otherName, x400Address, ediPartyName or
+    /// registeredID.
+    Other {
+        /// The context-specific tag number of the name; e.g. 0 for an
+        /// otherName.
+        tag: u8,
+
+        /// The encoded value of the name, without its tag and length.
+        value: &'a [u8],
+    },
+}
+
+/// An iterator over the entries of a certificate's subjectAltName
+/// extension.
+pub struct SubjectAltNames<'a> {
+    input: untrusted::Reader<'a>,
+}
+
+impl<'a> Iterator for SubjectAltNames<'a> {
+    type Item = SubjectAltName<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.input.at_end() {
+            return None;
+        }
+        subject_alt_name(&mut self.input).ok()
+    }
+}
+
+pub fn subject_alt_names<'a>(input: Option<untrusted::Input<'a>>)
+                             -> Result<SubjectAltNames<'a>, Error> {
+    let input = input.unwrap_or(untrusted::Input::from(&[]));
+    input.read_all(Error::BadDER, |names| {
+        while !names.at_end() {
+            let _ = subject_alt_name(names)?;
+        }
+        Ok(())
+    })?;
+    Ok(SubjectAltNames { input: untrusted::Reader::new(input) })
+}
+
+fn subject_alt_name<'a>(input: &mut untrusted::Reader<'a>)
+                        -> Result<SubjectAltName<'a>, Error> {
+    use ring::der::{CONSTRUCTED, CONTEXT_SPECIFIC};
+    const OTHER_NAME_TAG: u8 = CONTEXT_SPECIFIC | CONSTRUCTED | 0;
+    const RFC822_NAME_TAG: u8 = CONTEXT_SPECIFIC | 1;
+    const DNS_NAME_TAG: u8 = CONTEXT_SPECIFIC | 2;
+    const X400_ADDRESS_TAG : u8 = CONTEXT_SPECIFIC | CONSTRUCTED | 3;
+    const DIRECTORY_NAME_TAG: u8 = CONTEXT_SPECIFIC | CONSTRUCTED | 4;
+    const EDI_PARTY_NAME_TAG: u8 = CONTEXT_SPECIFIC | CONSTRUCTED | 5;
+    const UNIFORM_RESOURCE_IDENTIFIER_TAG: u8 = CONTEXT_SPECIFIC | 6;
+    const IP_ADDRESS_TAG: u8 = CONTEXT_SPECIFIC | 7;
+    const REGISTERED_ID_TAG: u8 = CONTEXT_SPECIFIC | 8;
+
+    let (tag, value) = der::read_tag_and_get_value(input)?;
+    let name = match tag {
+        DNS_NAME_TAG => SubjectAltName::DNSName(ia5_string(value)?),
+        RFC822_NAME_TAG => SubjectAltName::Rfc822Name(ia5_string(value)?),
+        UNIFORM_RESOURCE_IDENTIFIER_TAG =>
+            SubjectAltName::URI(ia5_string(value)?),
+
+        IP_ADDRESS_TAG => {
+            if value.len() != 4 && value.len() != 16 {
+                return Err(Error::BadDER);
+            }
+            SubjectAltName::IPAddress(value.as_slice_less_safe())
+        },
+
+        DIRECTORY_NAME_TAG => {
+            let rdns = value.read_all(Error::BadDER, |value| {
+                der::expect_tag_and_get_value(value, der::Tag::Sequence)
+            })?;
+            SubjectAltName::DirectoryName(distinguished_name(rdns)?)
+        },
+
+        OTHER_NAME_TAG |
+        X400_ADDRESS_TAG |
+        EDI_PARTY_NAME_TAG |
+        REGISTERED_ID_TAG =>
+            SubjectAltName::Other {
+                tag: tag & !(CONTEXT_SPECIFIC | CONSTRUCTED),
+                value: value.as_slice_less_safe(),
+            },
+
+        _ => return Err(Error::BadDER)
+    };
+    Ok(name)
+}
+
+fn ia5_string<'a>(value: untrusted::Input<'a>) -> Result<&'a str, Error> {
+    let value = value.as_slice_less_safe();
+    if value.iter().any(|&b| b >= 0x80) {
+        return Err(Error::BadDER);
+    }
+    core::str::from_utf8(value).map_err(|_| Error::BadDER)
+}
+
+/// The contents of a certificate's keyUsage extension.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KeyUsage {
+    // The named bits, with digitalSignature (bit 0) as the most significant
+    // bit, just as they're encoded.
+    bits: u16,
+}
+
+impl KeyUsage {
+    /// Whether the digitalSignature bit is set.
+    pub fn digital_signature(&self) -> bool { self.bit(0) }
+
+    /// Whether the nonRepudiation (a.k.a. contentCommitment) bit is set.
+    pub fn non_repudiation(&self) -> bool { self.bit(1) }
+
+    /// Whether the keyEncipherment bit is set.
+    pub fn key_encipherment(&self) -> bool { self.bit(2) }
+
+    /// Whether the dataEncipherment bit is set.
+    pub fn data_encipherment(&self) -> bool { self.bit(3) }
+
+    /// Whether the keyAgreement bit is set.
+    pub fn key_agreement(&self) -> bool { self.bit(4) }
+
+    /// Whether the keyCertSign bit is set.
+    pub fn key_cert_sign(&self) -> bool { self.bit(5) }
+
+    /// Whether the cRLSign bit is set.
+    pub fn crl_sign(&self) -> bool { self.bit(6) }
+
+    /// Whether the encipherOnly bit is set.
+    pub fn encipher_only(&self) -> bool { self.bit(7) }
+
+    /// Whether the decipherOnly bit is set.
+    pub fn decipher_only(&self) -> bool { self.bit(8) }
+
+    fn bit(&self, bit: u8) -> bool { self.bits & (0x8000 >> bit) != 0 }
+}
+
+// The keyUsage extension isn't used for validation, so `cert::Cert` doesn't
+// keep track of it; look for it among the raw extensions instead.
+pub fn key_usage(extensions: untrusted::Input)
+                 -> Result<Option<KeyUsage>, Error> {
+    let mut result = None;
+    for extension in raw_extensions(extensions) {
+        if extension.id != &ID_CE_KEY_USAGE[..] {
+            continue;
+        }
+        if result.is_some() {
+            return Err(Error::ExtensionValueInvalid);
+        }
+        let value = untrusted::Input::from(extension.value);
+        let bits = value.read_all(Error::BadDER, |value| {
+            der::nested(value, der::Tag::BitString, Error::BadDER, |bits| {
+                let unused_bits_at_end =
+                    bits.read_byte().map_err(|_| Error::BadDER)?;
+                if unused_bits_at_end > 7 {
+                    return Err(Error::BadDER);
+                }
+                // Only nine bits are defined, so there are at most two
+                // bytes.
+                let mut result = 0u16;
+                for &shift in &[8u16, 0] {
+                    if bits.at_end() {
+                        break;
+                    }
+                    let byte = bits.read_byte().map_err(|_| Error::BadDER)?;
+                    result |= u16::from(byte) << shift;
+                }
+                if !bits.at_end() {
+                    return Err(Error::BadDER);
+                }
+                Ok(result)
+            })
+        })?;
+        result = Some(KeyUsage { bits });
+    }
+    Ok(result)
+}
+
+/// An iterator over the key purposes in a certificate's extendedKeyUsage
+/// extension. Each is the value of an OID, without its tag and length;
+/// e.g. id-kp-serverAuth (1.3.6.1.5.5.7.3.1) is
+/// `[0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x03, 0x01]`.
+pub struct KeyPurposeIds<'a> {
+    input: untrusted::Reader<'a>,
+}
+
+impl<'a> Iterator for KeyPurposeIds<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.input.at_end() {
+            return None;
+        }
+        der::expect_tag_and_get_value(&mut self.input, der::Tag::OID)
+            .ok()
+            .map(|oid| oid.as_slice_less_safe())
+    }
+}
+
+pub fn extended_key_usage<'a>(eku: Option<untrusted::Input<'a>>)
+                              -> Result<Option<KeyPurposeIds<'a>>, Error> {
+    let eku = match eku {
+        Some(eku) => eku,
+        None => { return Ok(None); }
+    };
+    eku.read_all(Error::BadDER, |eku| {
+        while !eku.at_end() {
+            let _ = der::expect_tag_and_get_value(eku, der::Tag::OID)?;
+        }
+        Ok(())
+    })?;
+    Ok(Some(KeyPurposeIds { input: untrusted::Reader::new(eku) }))
+}
+
+/// An X.509v3 extension of a certificate, whether or not webpki
+/// understands it.
+#[derive(Clone, Copy)]
+pub struct Extension<'a> {
+    /// The extension's extnID: the value of an OID, without its tag and
+    /// length.
+    pub id: &'a [u8],
+
+    /// Whether the extension is marked critical.
+    pub critical: bool,
+
+    /// The contents of the extension's extnValue OCTET STRING.
+    pub value: &'a [u8],
+}
+
+/// An iterator over the extensions of a certificate, in the order they
+/// are encoded.
+pub struct Extensions<'a> {
+    input: untrusted::Reader<'a>,
+}
+
+impl<'a> Iterator for Extensions<'a> {
+    type Item = Extension<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.input.at_end() {
+            return None;
+        }
+        extension(&mut self.input).ok()
+    }
+}
+
+// `cert::parse_cert` has already checked the encoding of the extensions.
+pub fn raw_extensions<'a>(extensions: untrusted::Input<'a>) -> Extensions<'a> {
+    Extensions { input: untrusted::Reader::new(extensions) }
+}
+
+fn extension<'a>(input: &mut untrusted::Reader<'a>)
+                 -> Result<Extension<'a>, Error> {
+    der::nested(input, der::Tag::Sequence, Error::BadDER, |extension| {
+        let id = der::expect_tag_and_get_value(extension, der::Tag::OID)?;
+        let critical = der::optional_boolean(extension)?;
+        let value =
+            der::expect_tag_and_get_value(extension, der::Tag::OctetString)?;
+        Ok(Extension {
+            id: id.as_slice_less_safe(),
+            critical,
+            value: value.as_slice_less_safe(),
+        })
+    })
+}
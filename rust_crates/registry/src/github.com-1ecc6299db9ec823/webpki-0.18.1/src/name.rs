@@ -125,6 +125,329 @@ impl<'a> From<DNSNameRef<'a>> for untrusted::Input<'a> {
     fn from(DNSNameRef(dns_name): DNSNameRef<'a>) -> Self { dns_name }
 }
 
+/// An IP address, in text form, for use as the reference identifier for
+/// which to verify a certificate.
+///
+/// An `IpAddr` is guaranteed to be a syntactically-valid IPv4 address in
+/// dotted-decimal form or IPv6 address in the form given in
+/// [RFC 4291 Section 2.2].
+///
+/// `IpAddr` stores a copy of the input it was constructed from in a `String`
+/// and so it is only available when the `std` default feature is enabled.
+///
+/// [RFC 4291 Section 2.2]: https://tools.ietf.org/html/rfc4291#section-2.2
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct IpAddr(String);
+
+#[cfg(feature = "std")]
+impl IpAddr {
+    /// Returns an `IpAddrRef` that refers to this `IpAddr`.
+    pub fn as_ref(&self) -> IpAddrRef {
+        // The unwrap won't fail because `IpAddr`s are only made from valid
+        // `IpAddrRef`s.
+        IpAddrRef::try_from_ascii_str(&self.0).unwrap()
+    }
+}
+
+#[cfg(feature = "std")]
+impl AsRef<str> for IpAddr {
+    fn as_ref(&self) -> &str {
+       self.0.as_ref()
+    }
+}
+
+/// A reference to an IP address, in text form, for use as the reference
+/// identifier for which to verify a certificate.
+///
+/// An `IpAddrRef` is guaranteed to be a syntactically-valid IPv4 address in
+/// dotted-decimal form or IPv6 address in the form given in
+/// [RFC 4291 Section 2.2]. IPv6 zone identifiers and brackets are not
+/// accepted, and neither are IPv4 components with leading zeros, since they
+/// are sometimes interpreted as octal.
+///
+/// [RFC 4291 Section 2.2]: https://tools.ietf.org/html/rfc4291#section-2.2
+#[derive(Clone, Copy)]
+pub struct IpAddrRef<'a> {
+    text: untrusted::Input<'a>,
+    octets: [u8; 16],
+    len: u8,
+}
+
+impl<'a> IpAddrRef<'a> {
+    /// Constructs an `IpAddrRef` from the given input if the input is a
+    /// syntactically-valid IPv4 or IPv6 address.
+    pub fn try_from_ascii(ip_address: untrusted::Input<'a>)
+                          -> Result<Self, ()> {
+        let text = ip_address.as_slice_less_safe();
+        let mut octets = [0u8; 16];
+        let len = if parse_ipv4_address(text, &mut octets[..4]) {
+            4
+        } else if parse_ipv6_address(text, &mut octets) {
+            16
+        } else {
+            return Err(());
+        };
+
+        Ok(IpAddrRef { text: ip_address, octets, len })
+    }
+
+    /// Constructs an `IpAddrRef` from the given input if the input is a
+    /// syntactically-valid IPv4 or IPv6 address.
+    pub fn try_from_ascii_str(ip_address: &str) -> Result<IpAddrRef, ()> {
+        IpAddrRef::try_from_ascii(untrusted::Input::from(ip_address.as_bytes()))
+    }
+
+    /// The address in network byte order: 4 octets for an IPv4 address,
+    /// or 16 for IPv6.
+    pub fn octets(&self) -> &[u8] {
+        &self.octets[..usize::from(self.len)]
+    }
+
+    /// Constructs an `IpAddr` from this `IpAddrRef`.
+    #[cfg(feature = "std")]
+    pub fn to_owned(&self) -> IpAddr {
+        let s: &str = self.clone().into();
+        IpAddr(s.to_ascii_lowercase())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> core::fmt::Debug for IpAddrRef<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        let owned = self.clone().to_owned();
+        f.debug_tuple("IpAddrRef")
+            .field(&owned.0)
+            .finish()
+    }
+}
+
+impl<'a> Into<&'a str> for IpAddrRef<'a> {
+    fn into(self) -> &'a str {
+        // The unwrap won't fail because IpAddrRefs are guaranteed to be ASCII
+        // and ASCII is a subset of UTF-8.
+        core::str::from_utf8(self.text.as_slice_less_safe()).unwrap()
+    }
+}
+
+/// A reference to the name of a server: either a DNS name or an IP address.
+///
+/// This is what TLS clients verify the server's certificate against.
+#[derive(Clone, Copy)]
+pub enum SubjectNameRef<'a> {
+    /// A DNS name, to be matched against dNSName subjectAltNames.
+    DNSName(DNSNameRef<'a>),
+
+    /// An IP address, to be matched against iPAddress subjectAltNames.
+    IPAddress(IpAddrRef<'a>),
+}
+
+impl<'a> SubjectNameRef<'a> {
+    /// Constructs a `SubjectNameRef` from the given input, which may be
+    /// either an IP address or a DNS name. IP addresses take precedence.
+    pub fn try_from_ascii(name: untrusted::Input<'a>) -> Result<Self, ()> {
+        IpAddrRef::try_from_ascii(name)
+            .map(SubjectNameRef::IPAddress)
+            .or_else(|()| DNSNameRef::try_from_ascii(name)
+                .map(SubjectNameRef::DNSName))
+    }
+
+    /// Constructs a `SubjectNameRef` from the given input, which may be
+    /// either an IP address or a DNS name. IP addresses take precedence.
+    pub fn try_from_ascii_str(name: &str) -> Result<SubjectNameRef, ()> {
+        SubjectNameRef::try_from_ascii(untrusted::Input::from(name.as_bytes()))
+    }
+
+    /// Constructs a `SubjectName` from this `SubjectNameRef`.
+    #[cfg(feature = "std")]
+    pub fn to_owned(&self) -> SubjectName {
+        match *self {
+            SubjectNameRef::DNSName(ref name) =>
+                SubjectName::DNSName(name.to_owned()),
+            SubjectNameRef::IPAddress(ref addr) =>
+                SubjectName::IPAddress(addr.to_owned()),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> core::fmt::Debug for SubjectNameRef<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        match *self {
+            SubjectNameRef::DNSName(ref name) => name.fmt(f),
+            SubjectNameRef::IPAddress(ref addr) => addr.fmt(f),
+        }
+    }
+}
+
+impl<'a> From<DNSNameRef<'a>> for SubjectNameRef<'a> {
+    fn from(dns_name: DNSNameRef<'a>) -> Self {
+        SubjectNameRef::DNSName(dns_name)
+    }
+}
+
+impl<'a> From<IpAddrRef<'a>> for SubjectNameRef<'a> {
+    fn from(ip_address: IpAddrRef<'a>) -> Self {
+        SubjectNameRef::IPAddress(ip_address)
+    }
+}
+
+impl<'a> Into<&'a str> for SubjectNameRef<'a> {
+    fn into(self) -> &'a str {
+        match self {
+            SubjectNameRef::DNSName(name) => name.into(),
+            SubjectNameRef::IPAddress(addr) => addr.into(),
+        }
+    }
+}
+
+/// The name of a server: either a DNS name or an IP address.
+///
+/// `SubjectName` is only available when the `std` default feature is
+/// enabled.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum SubjectName {
+    /// A DNS name.
+    DNSName(DNSName),
+
+    /// An IP address.
+    IPAddress(IpAddr),
+}
+
+#[cfg(feature = "std")]
+impl SubjectName {
+    /// Returns a `SubjectNameRef` that refers to this `SubjectName`.
+    pub fn as_ref(&self) -> SubjectNameRef {
+        match *self {
+            SubjectName::DNSName(ref name) =>
+                SubjectNameRef::DNSName(name.as_ref()),
+            SubjectName::IPAddress(ref addr) =>
+                SubjectNameRef::IPAddress(addr.as_ref()),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> From<SubjectNameRef<'a>> for SubjectName {
+    fn from(name: SubjectNameRef<'a>) -> Self { name.to_owned() }
+}
+
+#[cfg(feature = "std")]
+impl From<DNSName> for SubjectName {
+    fn from(dns_name: DNSName) -> Self { SubjectName::DNSName(dns_name) }
+}
+
+// Parses a dotted-decimal IPv4 address into `out`, which must be 4 bytes
+// long.
+fn parse_ipv4_address(input: &[u8], out: &mut [u8]) -> bool {
+    let mut written = 0;
+    for component in input.split(|&b| b == b'.') {
+        if written == out.len() || component.is_empty() || component.len() > 3 {
+            return false;
+        }
+        // Leading zeros mean octal to some parsers; reject them rather
+        // than guess.
+        if component.len() > 1 && component[0] == b'0' {
+            return false;
+        }
+
+        let mut value: u16 = 0;
+        for &b in component {
+            if b < b'0' || b > b'9' {
+                return false;
+            }
+            value = value * 10 + u16::from(b - b'0');
+        }
+        if value > 255 {
+            return false;
+        }
+
+        out[written] = value as u8;
+        written += 1;
+    }
+
+    written == out.len()
+}
+
+// Parses an IPv6 address, in any of the forms in RFC 4291 Section 2.2, into
+// `out`.
+fn parse_ipv6_address(input: &[u8], out: &mut [u8; 16]) -> bool {
+    let elided = input.windows(2).position(|pair| pair == b"::");
+
+    let (head, tail) = match elided {
+        Some(i) => (&input[..i], &input[(i + 2)..]),
+        None => {
+            return parse_ipv6_groups(input, &mut out[..], true) == Some(16);
+        }
+    };
+
+    // "::" may appear at most once, and must stand for at least one group.
+    let mut head_octets = [0u8; 14];
+    let mut tail_octets = [0u8; 14];
+    let head_len = match parse_ipv6_groups(head, &mut head_octets, false) {
+        Some(len) => len,
+        None => { return false; }
+    };
+    let tail_len = match parse_ipv6_groups(tail, &mut tail_octets, true) {
+        Some(len) => len,
+        None => { return false; }
+    };
+    if head_len + tail_len > 14 {
+        return false;
+    }
+
+    for b in out.iter_mut() {
+        *b = 0;
+    }
+    out[..head_len].copy_from_slice(&head_octets[..head_len]);
+    out[(16 - tail_len)..].copy_from_slice(&tail_octets[..tail_len]);
+    true
+}
+
+// Parses colon-separated groups of up to four hex digits into `out`,
+// returning how many bytes were written. If `ipv4_allowed` then the last
+// group may instead be a dotted-decimal IPv4 address.
+fn parse_ipv6_groups(input: &[u8], out: &mut [u8], ipv4_allowed: bool)
+                     -> Option<usize> {
+    if input.is_empty() {
+        return Some(0);
+    }
+
+    let mut written = 0;
+    let mut groups = input.split(|&b| b == b':').peekable();
+    while let Some(group) = groups.next() {
+        let is_last = groups.peek().is_none();
+        if is_last && ipv4_allowed && group.contains(&b'.') {
+            if written + 4 > out.len() ||
+               !parse_ipv4_address(group, &mut out[written..(written + 4)]) {
+                return None;
+            }
+            written += 4;
+            continue;
+        }
+
+        if group.is_empty() || group.len() > 4 || written + 2 > out.len() {
+            return None;
+        }
+        let mut value: u16 = 0;
+        for &b in group {
+            let digit = match b {
+                b'0'...b'9' => b - b'0',
+                b'a'...b'f' => b - b'a' + 10,
+                b'A'...b'F' => b - b'A' + 10,
+                _ => { return None; }
+            };
+            value = (value << 4) | u16::from(digit);
+        }
+        out[written] = (value >> 8) as u8;
+        out[written + 1] = value as u8;
+        written += 2;
+    }
+
+    Some(written)
+}
+
 pub fn verify_cert_dns_name(cert: &super::EndEntityCert,
                             DNSNameRef(dns_name): DNSNameRef)
                             -> Result<(), Error> {
@@ -147,6 +470,42 @@ pub fn verify_cert_dns_name(cert: &super::EndEntityCert,
     })
 }
 
+pub fn verify_cert_ip_address(cert: &super::EndEntityCert,
+                              ip_address: IpAddrRef)
+                              -> Result<(), Error> {
+    let cert = &cert.inner;
+    let reference = untrusted::Input::from(ip_address.octets());
+
+    // Unlike DNS names, IP addresses are never matched against the subject
+    // CN, so only the subjectAltName is considered.
+    iterate_names(cert.subject, cert.subject_alt_name,
+                  Err(Error::CertNotValidForName), &|name| {
+        match name {
+            GeneralName::IPAddress(presented_id) => {
+                if presented_id.len() != 4 && presented_id.len() != 16 {
+                    return NameIteration::Stop(Err(Error::BadDER));
+                }
+                if presented_id == reference {
+                    return NameIteration::Stop(Ok(()));
+                }
+            },
+            _ => ()
+        }
+        NameIteration::KeepGoing
+    })
+}
+
+pub fn verify_cert_subject_name(cert: &super::EndEntityCert,
+                                subject_name: SubjectNameRef)
+                                -> Result<(), Error> {
+    match subject_name {
+        SubjectNameRef::DNSName(dns_name) =>
+            verify_cert_dns_name(cert, dns_name),
+        SubjectNameRef::IPAddress(ip_address) =>
+            verify_cert_ip_address(cert, ip_address),
+    }
+}
+
 // https://tools.ietf.org/html/rfc5280#section-4.2.1.10
 pub fn check_name_constraints<'a>(input: Option<&mut untrusted::Reader<'a>>,
                                   subordinate_certs: &Cert)
@@ -156,15 +515,17 @@ pub fn check_name_constraints<'a>(input: Option<&mut untrusted::Reader<'a>>,
         None => { return Ok(()); }
     };
 
+    // GeneralSubtrees is a SEQUENCE OF GeneralSubtree, IMPLICITly tagged, so
+    // the tagged value is the concatenation of the GeneralSubtree SEQUENCEs.
+    // They are read one at a time by
+    // `check_presented_id_conforms_to_constraints_in_subtree`.
     fn parse_subtrees<'b>(inner: &mut untrusted::Reader<'b>,
                           subtrees_tag: der::Tag)
                           -> Result<Option<untrusted::Input<'b>>, Error> {
         if !inner.peek(subtrees_tag as u8) {
             return Ok(None);
         }
-        let subtrees = der::nested(inner, subtrees_tag, Error::BadDER, |tagged| {
-            der::expect_tag_and_get_value(tagged, der::Tag::Sequence)
-        })?;
+        let subtrees = der::expect_tag_and_get_value(inner, subtrees_tag)?;
         Ok(Some(subtrees))
     }
 
@@ -239,20 +600,23 @@ fn check_presented_id_conforms_to_constraints_in_subtree(
             Err(err) => { return NameIteration::Stop(Err(err)); }
         };
 
+        // Constraints only apply to names of the same type; e.g. an
+        // iPAddress constraint says nothing about the subject's
+        // directoryName, so it mustn't count as a mismatch for it.
         let matches = match (name, base) {
             (GeneralName::DNSName(name),
              GeneralName::DNSName(base)) =>
-                presented_dns_id_matches_dns_id_constraint(name, base)
-                    .ok_or(Error::BadDER),
+                Some(presented_dns_id_matches_dns_id_constraint(name, base)
+                    .ok_or(Error::BadDER)),
 
             (GeneralName::DirectoryName(name),
              GeneralName::DirectoryName(base)) =>
-                presented_directory_name_matches_constraint(name, base,
-                                                            subtrees),
+                Some(presented_directory_name_matches_constraint(name, base,
+                                                                 subtrees)),
 
             (GeneralName::IPAddress(name),
              GeneralName::IPAddress(base)) =>
-                presented_ip_address_matches_constraint(name, base),
+                Some(presented_ip_address_matches_constraint(name, base)),
 
             // RFC 4280 says "If a name constraints extension that is marked as
             // critical imposes constraints on a particular name form, and an
@@ -264,27 +628,29 @@ fn check_presented_id_conforms_to_constraints_in_subtree(
             // considering whether the name constraint it critical.
             (GeneralName::Unsupported(name_tag),
              GeneralName::Unsupported(base_tag)) if name_tag == base_tag =>
-                Err(Error::NameConstraintViolation),
+                Some(Err(Error::NameConstraintViolation)),
 
-            _ => Ok(false)
+            _ => None
         };
 
         match (subtrees, matches) {
-            (Subtrees::PermittedSubtrees, Ok(true)) => {
+            (_, None) => (),
+
+            (Subtrees::PermittedSubtrees, Some(Ok(true))) => {
                 has_permitted_subtrees_match = true;
             },
 
-            (Subtrees::PermittedSubtrees, Ok(false)) => {
+            (Subtrees::PermittedSubtrees, Some(Ok(false))) => {
                 has_permitted_subtrees_mismatch = true;
             },
 
-            (Subtrees::ExcludedSubtrees, Ok(true)) => {
+            (Subtrees::ExcludedSubtrees, Some(Ok(true))) => {
                 return NameIteration::Stop(Err(Error::NameConstraintViolation));
             },
 
-            (Subtrees::ExcludedSubtrees, Ok(false)) => (),
+            (Subtrees::ExcludedSubtrees, Some(Ok(false))) => (),
 
-            (_, Err(err)) => {
+            (_, Some(Err(err))) => {
                 return NameIteration::Stop(Err(err));
             }
         }
@@ -345,6 +711,22 @@ fn presented_ip_address_matches_constraint(name: untrusted::Input,
             Ok((address, mask))
         })?;
 
+    // The mask must be a CIDR prefix: some one bits followed only by zero
+    // bits. Anything else can't be expressed in CIDR notation and is
+    // probably a mistake, so don't try to interpret it.
+    let mut seen_zero_bit = false;
+    for &mask_byte in constraint_mask.as_slice_less_safe() {
+        if seen_zero_bit && mask_byte != 0 {
+            return Err(Error::BadDER);
+        }
+        if mask_byte != 0xff {
+            if (!mask_byte).leading_zeros() + mask_byte.trailing_zeros() != 8 {
+                return Err(Error::BadDER);
+            }
+            seen_zero_bit = true;
+        }
+    }
+
     let mut name = untrusted::Reader::new(name);
     let mut constraint_address = untrusted::Reader::new(constraint_address);
     let mut constraint_mask = untrusted::Reader::new(constraint_mask);
@@ -0,0 +1,382 @@
+// Copyright 2018 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! OCSP response verification: https://tools.ietf.org/html/rfc6960
+
+use ring::digest;
+use untrusted;
+use {cert, der, Error, signed_data, SignatureAlgorithm, time, TrustAnchor};
+use cert::{Cert, EndEntityOrCA};
+use verify_cert;
+
+// id-pkix-ocsp-basic 1.3.6.1.5.5.7.48.1.1
+static ID_PKIX_OCSP_BASIC: [u8; 9] = oid![1, 3, 6, 1, 5, 5, 7, 48, 1, 1];
+
+// id-sha1 1.3.14.3.2.26
+static ID_SHA1: [u8; 5] = oid![1, 3, 14, 3, 2, 26];
+
+// id-sha256 2.16.840.1.101.3.4.2.1, and its siblings SHA-384 and SHA-512.
+static ID_SHA256: [u8; 9] = [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+                             0x01];
+static ID_SHA384: [u8; 9] = [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+                             0x02];
+static ID_SHA512: [u8; 9] = [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+                             0x03];
+
+// OCSPResponseStatus is an ENUMERATED, whose value successful(0) is the only
+// one that comes with a response.
+const ENUMERATED: u8 = 0x0a;
+const SUCCESSFUL: u8 = 0;
+
+// Tags of the CertStatus CHOICE, which are IMPLICIT.
+const CERT_STATUS_GOOD: u8 = der::CONTEXT_SPECIFIC | 0;
+const CERT_STATUS_REVOKED: u8 = der::CONTEXT_SPECIFIC | der::CONSTRUCTED | 1;
+const CERT_STATUS_UNKNOWN: u8 = der::CONTEXT_SPECIFIC | 2;
+
+// Like mozilla::pkix, a response without a nextUpdate is treated as if its
+// nextUpdate were one day after its thisUpdate, so that a responder can't
+// produce responses that are valid forever.
+const MAX_AGE_WITHOUT_NEXT_UPDATE_SECONDS: u64 = 24 * 60 * 60;
+
+enum CertStatus { Good, Revoked, Unknown }
+
+/// Verifies that `response`, a DER-encoded OCSPResponse, is a successful basic
+/// OCSP response that is signed by (or on behalf of) `cert`'s issuer, is
+/// current at `time`, and says that `cert` is good.
+///
+/// `cert`'s issuer is found among `trust_anchors` and `intermediate_certs`.
+/// The response may be signed by the issuer itself or by a delegated
+/// responder whose certificate is included in the response; a delegated
+/// responder must be issued directly by `cert`'s issuer and have the
+/// id-kp-OCSPSigning EKU. The ResponderID is not used to pick between these:
+/// we accept the response if either key verifies it.
+pub fn verify_ocsp_response(supported_sig_algs: &[&SignatureAlgorithm],
+                            trust_anchors: &[TrustAnchor],
+                            intermediate_certs: &[untrusted::Input],
+                            cert: &Cert, response: untrusted::Input,
+                            time: time::Time) -> Result<(), Error> {
+    verify_cert::with_issuer(supported_sig_algs, trust_anchors,
+                             intermediate_certs, cert,
+                             |issuer_subject, issuer_spki| {
+        let basic_response =
+            response.read_all(Error::BadDER, parse_ocsp_response)?;
+
+        let (tbs, signed_data, certs) =
+            basic_response.read_all(Error::BadDER, |input| {
+                der::nested(input, der::Tag::Sequence, Error::BadDER, |input| {
+                    let (tbs, signed_data) =
+                        signed_data::parse_signed_data(input)?;
+                    let certs = if input.at_end() {
+                        None
+                    } else {
+                        Some(der::expect_tag_and_get_value(
+                            input, der::Tag::ContextSpecificConstructed0)?)
+                    };
+                    Ok((tbs, signed_data, certs))
+                })
+            })?;
+
+        verify_responder_signature(supported_sig_algs, issuer_subject,
+                                   issuer_spki, &signed_data, certs, time)?;
+
+        match tbs.read_all(Error::BadDER, |tbs| {
+            parse_response_data(tbs, cert, issuer_subject, issuer_spki, time)
+        })? {
+            CertStatus::Good => Ok(()),
+            CertStatus::Revoked => Err(Error::CertRevoked),
+            CertStatus::Unknown => Err(Error::UnknownRevocationStatus),
+        }
+    })
+}
+
+// OCSPResponse ::= SEQUENCE {
+//    responseStatus         OCSPResponseStatus,
+//    responseBytes          [0] EXPLICIT ResponseBytes OPTIONAL }
+//
+// ResponseBytes ::=       SEQUENCE {
+//    responseType   OBJECT IDENTIFIER,
+//    response       OCTET STRING }
+//
+// Returns the contents of `response`, which is a BasicOCSPResponse.
+fn parse_ocsp_response<'a>(input: &mut untrusted::Reader<'a>)
+                           -> Result<untrusted::Input<'a>, Error> {
+    der::nested(input, der::Tag::Sequence, Error::BadDER, |input| {
+        let status = der::expect_raw_tag_and_get_value(input, ENUMERATED)?;
+        if status != &[SUCCESSFUL][..] {
+            // tryLater, internalError, etc. are not errors in the response
+            // itself, they just don't tell us anything.
+            let _ = input.skip_to_end();
+            return Err(Error::UnknownRevocationStatus);
+        }
+
+        der::nested(input, der::Tag::ContextSpecificConstructed0,
+                    Error::BadDER, |input| {
+            der::nested(input, der::Tag::Sequence, Error::BadDER, |input| {
+                let response_type =
+                    der::expect_tag_and_get_value(input, der::Tag::OID)?;
+                if response_type != &ID_PKIX_OCSP_BASIC[..] {
+                    let _ = input.skip_to_end();
+                    return Err(Error::UnknownRevocationStatus);
+                }
+                der::expect_tag_and_get_value(input, der::Tag::OctetString)
+            })
+        })
+    })
+}
+
+fn verify_responder_signature(supported_sig_algs: &[&SignatureAlgorithm],
+                              issuer_subject: untrusted::Input,
+                              issuer_spki: untrusted::Input,
+                              signed_data: &signed_data::SignedData,
+                              certs: Option<untrusted::Input>,
+                              time: time::Time) -> Result<(), Error> {
+    let issuer_result = signed_data::verify_signed_data(
+        supported_sig_algs, issuer_spki, signed_data);
+    let certs = match (issuer_result, certs) {
+        (Ok(()), _) => { return Ok(()); },
+        (Err(e), None) => { return Err(e); },
+        (Err(..), Some(certs)) => certs,
+    };
+
+    // certs [0] EXPLICIT SEQUENCE OF Certificate
+    certs.read_all(Error::BadDER, |certs| {
+        der::nested(certs, der::Tag::Sequence, Error::BadDER, |certs| {
+            let mut result = Err(Error::UnknownIssuer);
+            while !certs.at_end() {
+                let mark1 = certs.mark();
+                let _ = der::expect_tag_and_get_value(certs,
+                                                      der::Tag::Sequence)?;
+                let mark2 = certs.mark();
+                let cert_der = certs.get_input_between_marks(mark1, mark2)
+                                    .map_err(|_| Error::BadDER)?;
+                if result.is_ok() {
+                    continue;
+                }
+                result = verify_delegated_responder(supported_sig_algs,
+                                                    issuer_subject,
+                                                    issuer_spki, cert_der,
+                                                    time)
+                    .and_then(|responder| {
+                        signed_data::verify_signed_data(supported_sig_algs,
+                                                        responder.spki,
+                                                        signed_data)
+                    });
+            }
+            result
+        })
+    })
+}
+
+// https://tools.ietf.org/html/rfc6960#section-4.2.2.2: the delegated
+// responder's certificate must be issued directly by the CA that issued the
+// certificate in question, and must have id-kp-OCSPSigning.
+fn verify_delegated_responder<'a>(supported_sig_algs: &[&SignatureAlgorithm],
+                                  issuer_subject: untrusted::Input,
+                                  issuer_spki: untrusted::Input,
+                                  cert_der: untrusted::Input<'a>,
+                                  time: time::Time)
+                                  -> Result<Cert<'a>, Error> {
+    let responder = cert::parse_cert(cert_der, EndEntityOrCA::EndEntity)?;
+    if responder.issuer != issuer_subject {
+        return Err(Error::UnknownIssuer);
+    }
+    signed_data::verify_signed_data(supported_sig_algs, issuer_spki,
+                                    &responder.signed_data)?;
+    responder.validity.read_all(Error::BadDER, |value| {
+        verify_cert::check_validity(value, time)
+    })?;
+    untrusted::read_all_optional(responder.eku, Error::BadDER, |value| {
+        verify_cert::check_eku(value, verify_cert::EKU_OCSP_SIGNING)
+    })?;
+    Ok(responder)
+}
+
+// ResponseData ::= SEQUENCE {
+//    version              [0] EXPLICIT Version DEFAULT v1,
+//    responderID              ResponderID,
+//    producedAt               GeneralizedTime,
+//    responses                SEQUENCE OF SingleResponse,
+//    responseExtensions   [1] EXPLICIT Extensions OPTIONAL }
+fn parse_response_data(tbs: &mut untrusted::Reader, cert: &Cert,
+                       issuer_subject: untrusted::Input,
+                       issuer_spki: untrusted::Input, time: time::Time)
+                       -> Result<CertStatus, Error> {
+    // DER forbids encoding the default version, but like mozilla::pkix we
+    // tolerate an explicit v1.
+    if tbs.peek(der::Tag::ContextSpecificConstructed0 as u8) {
+        der::nested(tbs, der::Tag::ContextSpecificConstructed0,
+                    Error::BadDER, |version| {
+            if der::small_nonnegative_integer(version)? != 0 {
+                return Err(Error::UnsupportedCertVersion);
+            }
+            Ok(())
+        })?;
+    }
+
+    // ResponderID ::= CHOICE {
+    //    byName   [1] Name,
+    //    byKey    [2] KeyHash }
+    //
+    // See `verify_ocsp_response` for why this isn't used.
+    let _ = der::read_tag_and_get_value(tbs)?;
+
+    let _produced_at = der::time_choice(tbs)?;
+
+    let status = der::nested(tbs, der::Tag::Sequence, Error::BadDER,
+                             |responses| {
+        let mut status = None;
+        loop {
+            let single_status = der::nested(
+                responses, der::Tag::Sequence, Error::BadDER, |single| {
+                    parse_single_response(single, cert, issuer_subject,
+                                          issuer_spki, time)
+                })?;
+            if status.is_none() {
+                status = single_status;
+            }
+            if responses.at_end() {
+                break;
+            }
+        }
+        Ok(status)
+    })?;
+
+    // We don't understand any response extensions. The only one in common use
+    // is the nonce, which is meaningless for a stapled response.
+    let _ = tbs.skip_to_end();
+
+    status.ok_or(Error::UnknownRevocationStatus)
+}
+
+// SingleResponse ::= SEQUENCE {
+//    certID                       CertID,
+//    certStatus                   CertStatus,
+//    thisUpdate                   GeneralizedTime,
+//    nextUpdate         [0]       EXPLICIT GeneralizedTime OPTIONAL,
+//    singleExtensions   [1]       EXPLICIT Extensions OPTIONAL }
+//
+// Returns `None` if the response is for some other certificate.
+fn parse_single_response(single: &mut untrusted::Reader, cert: &Cert,
+                         issuer_subject: untrusted::Input,
+                         issuer_spki: untrusted::Input, time: time::Time)
+                         -> Result<Option<CertStatus>, Error> {
+    let matches = der::nested(single, der::Tag::Sequence, Error::BadDER,
+                              |cert_id| {
+        match_cert_id(cert_id, cert, issuer_subject, issuer_spki)
+    })?;
+    if !matches {
+        let _ = single.skip_to_end();
+        return Ok(None);
+    }
+
+    let (tag, value) = der::read_tag_and_get_value(single)?;
+    let status = match tag {
+        CERT_STATUS_GOOD if value.is_empty() => CertStatus::Good,
+        CERT_STATUS_REVOKED => CertStatus::Revoked,
+        CERT_STATUS_UNKNOWN if value.is_empty() => CertStatus::Unknown,
+        _ => { return Err(Error::BadDER); }
+    };
+
+    let this_update = der::time_choice(single)?;
+    let next_update =
+        if single.peek(der::Tag::ContextSpecificConstructed0 as u8) {
+            der::nested(single, der::Tag::ContextSpecificConstructed0,
+                        Error::BadDER, der::time_choice)?
+        } else {
+            time::add_seconds(this_update, MAX_AGE_WITHOUT_NEXT_UPDATE_SECONDS)
+        };
+
+    // We don't understand any single extensions.
+    let _ = single.skip_to_end();
+
+    if this_update > next_update {
+        return Err(Error::BadDER);
+    }
+    if time < this_update || time > next_update {
+        return Err(Error::RevocationDataNotCurrent);
+    }
+
+    Ok(Some(status))
+}
+
+// CertID ::= SEQUENCE {
+//    hashAlgorithm       AlgorithmIdentifier,
+//    issuerNameHash      OCTET STRING, -- Hash of issuer's DN
+//    issuerKeyHash       OCTET STRING, -- Hash of issuer's public key
+//    serialNumber        CertificateSerialNumber }
+fn match_cert_id(cert_id: &mut untrusted::Reader, cert: &Cert,
+                 issuer_subject: untrusted::Input,
+                 issuer_spki: untrusted::Input) -> Result<bool, Error> {
+    let hash_alg = der::nested(cert_id, der::Tag::Sequence, Error::BadDER,
+                               |alg_id| {
+        let oid = der::expect_tag_and_get_value(alg_id, der::Tag::OID)?;
+        // The parameters are absent or NULL.
+        if !alg_id.at_end() {
+            let _ = der::expect_tag_and_get_value(alg_id, der::Tag::Null)?;
+        }
+        Ok(oid)
+    })?;
+    let issuer_name_hash =
+        der::expect_tag_and_get_value(cert_id, der::Tag::OctetString)?;
+    let issuer_key_hash =
+        der::expect_tag_and_get_value(cert_id, der::Tag::OctetString)?;
+    let serial = der::positive_integer(cert_id)?;
+
+    let digest_alg = if hash_alg == &ID_SHA1[..] {
+        &digest::SHA1
+    } else if hash_alg == &ID_SHA256[..] {
+        &digest::SHA256
+    } else if hash_alg == &ID_SHA384[..] {
+        &digest::SHA384
+    } else if hash_alg == &ID_SHA512[..] {
+        &digest::SHA512
+    } else {
+        // A response for our certificate using a hash we don't know is no
+        // better than no response at all.
+        return Ok(false);
+    };
+
+    if serial != cert.serial {
+        return Ok(false);
+    }
+
+    // The name hash is calculated over the DER encoding of the issuer's name,
+    // including the SEQUENCE tag and length that `issuer_subject` lacks.
+    let mut ctx = digest::Context::new(digest_alg);
+    ctx.update(&[der::Tag::Sequence as u8]);
+    let len = issuer_subject.len();
+    if len < 0x80 {
+        ctx.update(&[len as u8]);
+    } else if len <= 0xff {
+        ctx.update(&[0x81, len as u8]);
+    } else if len <= 0xffff {
+        ctx.update(&[0x82, (len >> 8) as u8, len as u8]);
+    } else {
+        return Err(Error::BadDER);
+    }
+    ctx.update(issuer_subject.as_slice_less_safe());
+    if issuer_name_hash != ctx.finish().as_ref() {
+        return Ok(false);
+    }
+
+    // The key hash is calculated over the value of the subjectPublicKey BIT
+    // STRING, excluding the tag, length, and number of unused bits.
+    let issuer_key = issuer_spki.read_all(Error::BadDER, |spki| {
+        let _algorithm = der::expect_tag_and_get_value(spki,
+                                                       der::Tag::Sequence)?;
+        der::bit_string_with_no_unused_bits(spki)
+    })?;
+    let key_hash = digest::digest(digest_alg, issuer_key.as_slice_less_safe());
+    Ok(issuer_key_hash == key_hash.as_ref())
+}
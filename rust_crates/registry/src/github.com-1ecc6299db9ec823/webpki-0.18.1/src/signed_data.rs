@@ -267,6 +267,40 @@ pub static RSA_PSS_2048_8192_SHA512_LEGACY_KEY: SignatureAlgorithm =
     verification_alg: &signature::RSA_PSS_2048_8192_SHA512,
 };
 
+/// RSA PSS signatures using SHA-256 for keys of 2048-8192 bits and of
+/// type id-RSASSA-PSS without parameters; see
+/// https://tools.ietf.org/html/rfc4055#section-3.1
+pub static RSA_PSS_2048_8192_SHA256: SignatureAlgorithm = SignatureAlgorithm {
+    public_key_alg_id: RSA_PSS,
+    signature_alg_id: RSA_PSS_SHA256,
+    verification_alg: &signature::RSA_PSS_2048_8192_SHA256,
+};
+
+/// RSA PSS signatures using SHA-384 for keys of 2048-8192 bits and of
+/// type id-RSASSA-PSS without parameters; see
+/// https://tools.ietf.org/html/rfc4055#section-3.1
+pub static RSA_PSS_2048_8192_SHA384: SignatureAlgorithm = SignatureAlgorithm {
+    public_key_alg_id: RSA_PSS,
+    signature_alg_id: RSA_PSS_SHA384,
+    verification_alg: &signature::RSA_PSS_2048_8192_SHA384,
+};
+
+/// RSA PSS signatures using SHA-512 for keys of 2048-8192 bits and of
+/// type id-RSASSA-PSS without parameters; see
+/// https://tools.ietf.org/html/rfc4055#section-3.1
+pub static RSA_PSS_2048_8192_SHA512: SignatureAlgorithm = SignatureAlgorithm {
+    public_key_alg_id: RSA_PSS,
+    signature_alg_id: RSA_PSS_SHA512,
+    verification_alg: &signature::RSA_PSS_2048_8192_SHA512,
+};
+
+/// Ed25519 signatures; see https://tools.ietf.org/html/rfc8410
+pub static ED25519: SignatureAlgorithm = SignatureAlgorithm {
+    public_key_alg_id: ED_25519,
+    signature_alg_id: ED_25519,
+    verification_alg: &signature::ED25519,
+};
+
 struct AlgorithmIdentifier {
     asn1_id_value: &'static [u8],
 }
@@ -315,6 +349,10 @@ const RSA_PKCS1_SHA512: AlgorithmIdentifier = AlgorithmIdentifier {
     asn1_id_value: include_bytes!("data/alg-rsa-pkcs1-sha512.der"),
 };
 
+const RSA_PSS: AlgorithmIdentifier = AlgorithmIdentifier {
+    asn1_id_value: include_bytes!("data/alg-rsa-pss.der"),
+};
+
 const RSA_PSS_SHA256: AlgorithmIdentifier = AlgorithmIdentifier {
     asn1_id_value: include_bytes!("data/alg-rsa-pss-sha256.der"),
 };
@@ -327,6 +365,10 @@ const RSA_PSS_SHA512: AlgorithmIdentifier = AlgorithmIdentifier {
     asn1_id_value: include_bytes!("data/alg-rsa-pss-sha512.der"),
 };
 
+const ED_25519: AlgorithmIdentifier = AlgorithmIdentifier {
+    asn1_id_value: include_bytes!("data/alg-ed25519.der"),
+};
+
 
 #[cfg(test)]
 mod tests {
@@ -568,6 +610,20 @@ mod tests {
         "ours/rsa-pss-sha512-salt64-corrupted-data.pem",
         Err(Error::InvalidSignatureForPublicKey));
 
+    test_verify_signed_data!(
+        test_rsa_pss_sha256_salt32_using_pss_key_no_params,
+        "ours/rsa-pss-sha256-salt32-using-pss-key-no-params.pem",
+        Ok(()));
+    test_verify_signed_data!(
+        test_rsa_pss_sha256_salt32_using_pss_key_no_params_corrupted_data,
+        "ours/rsa-pss-sha256-salt32-using-pss-key-no-params-corrupted-data.pem",
+        Err(Error::InvalidSignatureForPublicKey));
+
+    test_verify_signed_data!(test_ed25519, "ours/ed25519.pem", Ok(()));
+    test_verify_signed_data!(
+        test_ed25519_corrupted_data, "ours/ed25519-corrupted-data.pem",
+        Err(Error::InvalidSignatureForPublicKey));
+
     test_verify_signed_data!(
         test_rsa_using_ec_key, "rsa-using-ec-key.pem",
         Err(Error::UnsupportedSignatureAlgorithmForPublicKey));
@@ -635,6 +691,10 @@ mod tests {
         &signed_data::RSA_PSS_2048_8192_SHA256_LEGACY_KEY,
         &signed_data::RSA_PSS_2048_8192_SHA384_LEGACY_KEY,
         &signed_data::RSA_PSS_2048_8192_SHA512_LEGACY_KEY,
+        &signed_data::RSA_PSS_2048_8192_SHA256,
+        &signed_data::RSA_PSS_2048_8192_SHA384,
+        &signed_data::RSA_PSS_2048_8192_SHA512,
+        &signed_data::ED25519,
 
         // Algorithms deprecated because they are annoying (P-521) or because
         // they are nonsensical combinations.
@@ -62,4 +62,14 @@ impl Time {
     pub fn from_seconds_since_unix_epoch(secs: u64) -> Time {
         Time(secs)
     }
+
+    /// The number of non-leap seconds since the start of 1970 that this
+    /// time represents.
+    pub fn as_seconds_since_unix_epoch(&self) -> u64 { self.0 }
+}
+
+/// Returns `time` moved `secs` seconds into the future, saturating rather
+/// than overflowing.
+pub fn add_seconds(time: Time, secs: u64) -> Time {
+    Time(time.0.saturating_add(secs))
 }
@@ -44,14 +44,15 @@ pub fn cert_der_as_trust_anchor<'a>(cert_der: untrusted::Input<'a>)
 }
 
 fn possibly_invalid_certificate_serial_number<'a>(
-        input: &mut untrusted::Reader<'a>) -> Result<(), Error> {
+        input: &mut untrusted::Reader<'a>)
+        -> Result<untrusted::Input<'a>, Error> {
     // https://tools.ietf.org/html/rfc5280#section-4.1.2.2:
     // * Conforming CAs MUST NOT use serialNumber values longer than 20 octets."
     // * "The serial number MUST be a positive integer [...]"
     //
     // However, we don't enforce these constraints on trust anchors, as there
     // are widely-deployed trust anchors that violate these constraints.
-    skip(input, der::Tag::Integer)
+    der::expect_tag_and_get_value(input, der::Tag::Integer)
 }
 
 
@@ -89,7 +90,7 @@ fn parse_cert_v1<'a>(cert_der: untrusted::Input<'a>)
             let anchor = der::nested(cert_der, der::Tag::Sequence,
                                      Error::BadDER, |tbs| {
                 // The version number field does not appear in v1 certificates.
-                certificate_serial_number(tbs)?;
+                let _ = certificate_serial_number(tbs)?;
 
                 skip(tbs, der::Tag::Sequence)?; // signature.
                 skip(tbs, der::Tag::Sequence)?; // issuer.
@@ -15,26 +15,131 @@
 use untrusted;
 use {cert, der, Error, name, signed_data, SignatureAlgorithm, time,
      TrustAnchor};
+#[cfg(feature = "std")]
+use std;
 use cert::{Cert, EndEntityOrCA};
 
+/// A certificate in a path that was tried while building a chain.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChainCert {
+    /// The certificate being verified.
+    EndEntity,
+
+    /// The intermediate certificate at this index in `intermediate_certs`.
+    Intermediate(usize),
+
+    /// The trust anchor at this index in `trust_anchors`.
+    TrustAnchor(usize),
+}
+
+/// The outcome of one path tried while building a chain.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PathAttempt {
+    /// The certificates in the path, starting with the certificate being
+    /// verified and ending with the certificate that `result` is about.
+    pub path: std::vec::Vec<ChainCert>,
+
+    /// `Ok(())` if the path ends in a trust anchor and the chain is valid.
+    /// Otherwise, why the last certificate in `path` was rejected:
+    /// `Error::UnknownIssuer` means no potential issuer was found for it.
+    pub result: Result<(), Error>,
+}
+
+/// Every path tried while building a chain, in the order they were tried.
+///
+/// Potential issuers whose subject doesn't match the issuer of the
+/// certificate being extended aren't reported.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChainReport {
+    /// The paths that were tried.
+    pub attempts: std::vec::Vec<PathAttempt>,
+}
+
+const MAX_SUB_CA_COUNT: usize = 6;
+
+// The end-entity certificate, up to `MAX_SUB_CA_COUNT` intermediates, and
+// then either one more intermediate or a trust anchor.
+const MAX_PATH_LEN: usize = MAX_SUB_CA_COUNT + 2;
+
+#[derive(Clone, Copy)]
+struct ChainPath {
+    certs: [ChainCert; MAX_PATH_LEN],
+    len: usize,
+}
+
+impl ChainPath {
+    fn new() -> ChainPath {
+        ChainPath {
+            certs: [ChainCert::EndEntity; MAX_PATH_LEN],
+            len: 1,
+        }
+    }
+
+    fn push(&self, cert: ChainCert) -> ChainPath {
+        let mut path = *self;
+        path.certs[path.len] = cert;
+        path.len += 1;
+        path
+    }
+
+    fn as_slice(&self) -> &[ChainCert] { &self.certs[..self.len] }
+}
+
+/// Calls `report` with every path that is tried, and the outcome of each.
 pub fn build_chain<'a>(required_eku_if_present: KeyPurposeId,
                        supported_sig_algs: &[&SignatureAlgorithm],
                        trust_anchors: &'a [TrustAnchor],
                        intermediate_certs: &[untrusted::Input<'a>],
-                       cert: &Cert<'a>, time: time::Time, sub_ca_count: usize)
+                       cert: &Cert<'a>, time: time::Time,
+                       report: &mut FnMut(&[ChainCert], Result<(), Error>))
                        -> Result<(), Error> {
+    build_chain_inner(required_eku_if_present, supported_sig_algs,
+                      trust_anchors, intermediate_certs, cert,
+                      ChainPath::new(), time, 0, report)
+}
+
+#[cfg(feature = "std")]
+pub fn build_chain_with_report<'a>(required_eku_if_present: KeyPurposeId,
+                                   supported_sig_algs: &[&SignatureAlgorithm],
+                                   trust_anchors: &'a [TrustAnchor],
+                                   intermediate_certs: &[untrusted::Input<'a>],
+                                   cert: &Cert<'a>, time: time::Time)
+                                   -> (Result<(), Error>, ChainReport) {
+    let mut report = ChainReport::default();
+    let result = build_chain(required_eku_if_present, supported_sig_algs,
+                             trust_anchors, intermediate_certs, cert, time,
+                             &mut |path, result| {
+        report.attempts.push(PathAttempt { path: path.to_vec(), result });
+    });
+    (result, report)
+}
+
+fn build_chain_inner<'a>(required_eku_if_present: KeyPurposeId,
+                         supported_sig_algs: &[&SignatureAlgorithm],
+                         trust_anchors: &'a [TrustAnchor],
+                         intermediate_certs: &[untrusted::Input<'a>],
+                         cert: &Cert<'a>, path: ChainPath, time: time::Time,
+                         sub_ca_count: usize,
+                         report: &mut FnMut(&[ChainCert], Result<(), Error>))
+                         -> Result<(), Error> {
     let used_as_ca = used_as_ca(&cert.ee_or_ca);
 
-    check_issuer_independent_properties(cert, time, used_as_ca, sub_ca_count,
-                                        required_eku_if_present)?;
+    if let Err(err) =
+        check_issuer_independent_properties(cert, time, used_as_ca,
+                                            sub_ca_count,
+                                            required_eku_if_present) {
+        report(path.as_slice(), Err(err));
+        return Err(err);
+    }
 
     // TODO: HPKP checks.
 
     match used_as_ca {
         UsedAsCA::Yes => {
-            const MAX_SUB_CA_COUNT: usize = 6;
-
             if sub_ca_count >= MAX_SUB_CA_COUNT {
+                report(path.as_slice(), Err(Error::UnknownIssuer));
                 return Err(Error::UnknownIssuer);
             }
         },
@@ -43,45 +148,50 @@ pub fn build_chain<'a>(required_eku_if_present: KeyPurposeId,
         }
     }
 
-    // TODO: revocation.
+    // Revocation is checked separately, once a chain has been found; see
+    // `EndEntityCert::verify_ocsp_response` and
+    // `EndEntityCert::verify_is_not_revoked`.
 
-    match loop_while_non_fatal_error(trust_anchors,
-                                     |trust_anchor: &TrustAnchor<'a>| {
+    // Whether any potential issuer's subject matched, so that we can report
+    // a dead end if none did.
+    let mut found_issuer = false;
+
+    let trust_anchor_result =
+        loop_while_non_fatal_error(trust_anchors.iter().enumerate(),
+                                   |(i, trust_anchor)| {
         let trust_anchor_subject = untrusted::Input::from(trust_anchor.subject);
         if cert.issuer != trust_anchor_subject {
             return Err(Error::UnknownIssuer);
         }
+        found_issuer = true;
+
+        let result = check_trust_anchor(supported_sig_algs, trust_anchor, cert);
+        report(path.push(ChainCert::TrustAnchor(i)).as_slice(), result);
+        result
+    });
+    if trust_anchor_result.is_ok() {
+        return Ok(());
+    }
 
-        let name_constraints =
-            trust_anchor.name_constraints.map(untrusted::Input::from);
-
-        untrusted::read_all_optional(
-            name_constraints, Error::BadDER,
-            |value| name::check_name_constraints(value, &cert))?;
-
-        let trust_anchor_spki = untrusted::Input::from(trust_anchor.spki);
-
-        // TODO: check_distrust(trust_anchor_subject, trust_anchor_spki)?;
-
-        check_signatures(supported_sig_algs, cert, trust_anchor_spki)?;
+    let result = loop_while_non_fatal_error(intermediate_certs.iter().enumerate(),
+                                            |(i, cert_der)| {
+        let issuer_path = path.push(ChainCert::Intermediate(i));
 
-        Ok(())
-    }) {
-        Ok(()) => {
-            return Ok(());
-        },
-        Err(..) => {
-            // If the error is not fatal, then keep going.
+        // Certificates that can't be the issuer aren't candidates, so they
+        // aren't reported even if they wouldn't parse.
+        match cert::parse_subject(*cert_der) {
+            Ok(subject) if subject == cert.issuer => (),
+            _ => { return Err(Error::UnknownIssuer); }
         }
-    }
 
-    loop_while_non_fatal_error(intermediate_certs, |cert_der| {
         let potential_issuer =
-            cert::parse_cert(*cert_der, EndEntityOrCA::CA(&cert))?;
-
-        if potential_issuer.subject != cert.issuer {
-            return Err(Error::UnknownIssuer)
-        }
+            match cert::parse_cert(*cert_der, EndEntityOrCA::CA(&cert)) {
+                Ok(potential_issuer) => potential_issuer,
+                Err(err) => {
+                    report(issuer_path.as_slice(), Err(err));
+                    return Err(err);
+                }
+            };
 
         // Prevent loops; see RFC 4158 section 5.2.
         let mut prev = cert;
@@ -95,20 +205,90 @@ pub fn build_chain<'a>(required_eku_if_present: KeyPurposeId,
                 &EndEntityOrCA::CA(child_cert) => { prev = child_cert; }
             }
         }
+        found_issuer = true;
 
-        untrusted::read_all_optional(
-            potential_issuer.name_constraints, Error::BadDER,
-            |value| name::check_name_constraints(value, &cert))?;
+        if let Err(err) = untrusted::read_all_optional(
+                potential_issuer.name_constraints, Error::BadDER,
+                |value| name::check_name_constraints(value, &cert)) {
+            report(issuer_path.as_slice(), Err(err));
+            return Err(err);
+        }
 
         let next_sub_ca_count = match used_as_ca {
             UsedAsCA::No => sub_ca_count,
             UsedAsCA::Yes => sub_ca_count + 1
         };
 
-        build_chain(required_eku_if_present, supported_sig_algs, trust_anchors,
-                    intermediate_certs, &potential_issuer, time,
-                    next_sub_ca_count)
-    })
+        build_chain_inner(required_eku_if_present, supported_sig_algs,
+                          trust_anchors, intermediate_certs, &potential_issuer,
+                          issuer_path, time, next_sub_ca_count, &mut *report)
+    });
+
+    if result.is_err() && !found_issuer {
+        report(path.as_slice(), Err(Error::UnknownIssuer));
+    }
+
+    match (result, trust_anchor_result) {
+        (Err(Error::UnknownIssuer), Err(err)) => Err(err),
+        (result, _) => result,
+    }
+}
+
+fn check_trust_anchor(supported_sig_algs: &[&SignatureAlgorithm],
+                      trust_anchor: &TrustAnchor, cert: &Cert)
+                      -> Result<(), Error> {
+    let name_constraints =
+        trust_anchor.name_constraints.map(untrusted::Input::from);
+
+    untrusted::read_all_optional(
+        name_constraints, Error::BadDER,
+        |value| name::check_name_constraints(value, &cert))?;
+
+    let trust_anchor_spki = untrusted::Input::from(trust_anchor.spki);
+
+    // TODO: check_distrust(trust_anchor_subject, trust_anchor_spki)?;
+
+    check_signatures(supported_sig_algs, cert, trust_anchor_spki)
+}
+
+/// Finds the issuer of `cert` among `trust_anchors` and `intermediate_certs`
+/// and calls `f` with the issuer's subject and SubjectPublicKeyInfo.
+///
+/// A candidate is only accepted as the issuer if its subject matches `cert`'s
+/// issuer *and* `cert`'s signature verifies with its key, so whatever `f`
+/// checks is bound to the key that actually issued `cert`. The result of `f`
+/// for the first accepted issuer is returned as-is.
+pub fn with_issuer<F, R>(supported_sig_algs: &[&SignatureAlgorithm],
+                         trust_anchors: &[TrustAnchor],
+                         intermediate_certs: &[untrusted::Input],
+                         cert: &Cert, f: F) -> Result<R, Error>
+                         where F: FnOnce(untrusted::Input, untrusted::Input)
+                                         -> Result<R, Error> {
+    for trust_anchor in trust_anchors {
+        let subject = untrusted::Input::from(trust_anchor.subject);
+        let spki = untrusted::Input::from(trust_anchor.spki);
+        if cert.issuer == subject &&
+           signed_data::verify_signed_data(supported_sig_algs, spki,
+                                           &cert.signed_data).is_ok() {
+            return f(subject, spki);
+        }
+    }
+
+    for cert_der in intermediate_certs {
+        let potential_issuer =
+            match cert::parse_cert(*cert_der, EndEntityOrCA::EndEntity) {
+                Ok(potential_issuer) => potential_issuer,
+                Err(..) => { continue; }
+            };
+        if cert.issuer == potential_issuer.subject &&
+           signed_data::verify_signed_data(supported_sig_algs,
+                                           potential_issuer.spki,
+                                           &cert.signed_data).is_ok() {
+            return f(potential_issuer.subject, potential_issuer.spki);
+        }
+    }
+
+    Err(Error::UnknownIssuer)
 }
 
 fn check_signatures(supported_sig_algs: &[&SignatureAlgorithm],
@@ -120,8 +300,6 @@ fn check_signatures(supported_sig_algs: &[&SignatureAlgorithm],
         signed_data::verify_signed_data(supported_sig_algs, spki_value,
                                         &cert.signed_data)?;
 
-        // TODO: check revocation
-
         match &cert.ee_or_ca {
             &EndEntityOrCA::CA(child_cert) => {
                 spki_value = cert.spki;
@@ -158,7 +336,7 @@ fn check_issuer_independent_properties<'a>(
 }
 
 // https://tools.ietf.org/html/rfc5280#section-4.1.2.5
-fn check_validity(input: &mut untrusted::Reader, time: time::Time)
+pub fn check_validity(input: &mut untrusted::Reader, time: time::Time)
                   -> Result<(), Error> {
     let not_before = der::time_choice(input)?;
     let not_after = der::time_choice(input)?;
@@ -264,7 +442,8 @@ pub static EKU_OCSP_SIGNING: KeyPurposeId = KeyPurposeId {
 //   certificates (only). Comodo has issued certificates that require this
 //   behavior that don't expire until June 2020. See
 //   https://bugzilla.mozilla.org/show_bug.cgi?id=982292.
-fn check_eku(input: Option<&mut untrusted::Reader>, required_eku_if_present: KeyPurposeId)
+pub fn check_eku(input: Option<&mut untrusted::Reader>,
+                 required_eku_if_present: KeyPurposeId)
              -> Result<(), Error> {
     match input {
         Some(input) => {
@@ -299,18 +478,25 @@ fn check_eku(input: Option<&mut untrusted::Reader>, required_eku_if_present: Key
     }
 }
 
-fn loop_while_non_fatal_error<V, F>(values: V, f: F) -> Result<(), Error>
+// Returns `Ok(())` for the first value for which `f` succeeds. Otherwise,
+// returns the first error other than `Error::UnknownIssuer`, which says why
+// a potential issuer was rejected; `Error::UnknownIssuer` only means there
+// was nothing to reject.
+fn loop_while_non_fatal_error<V, F>(values: V, mut f: F) -> Result<(), Error>
                                     where V: IntoIterator,
-                                          F: Fn(V::Item) -> Result<(), Error> {
+                                          F: FnMut(V::Item) -> Result<(), Error> {
+    let mut first_error = None;
     for v in values {
         match f(v) {
             Ok(()) => {
                 return Ok(());
             },
-            Err(..) => {
+            Err(Error::UnknownIssuer) => (),
+            Err(err) => {
                 // If the error is not fatal, then keep going.
+                first_error = first_error.or(Some(err));
             }
         }
     }
-    Err(Error::UnknownIssuer)
+    Err(first_error.unwrap_or(Error::UnknownIssuer))
 }
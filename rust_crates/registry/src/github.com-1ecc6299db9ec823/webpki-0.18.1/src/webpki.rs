@@ -63,7 +63,10 @@ extern crate untrusted;
 mod der;
 
 mod cert;
+mod crl;
+mod inspect;
 mod name;
+mod ocsp;
 mod signed_data;
 mod calendar;
 mod time;
@@ -73,10 +76,25 @@ pub mod trust_anchor_util;
 
 mod verify_cert;
 
-pub use name::DNSNameRef;
+pub use inspect::{
+    DistinguishedName,
+    Extension,
+    Extensions,
+    KeyPurposeIds,
+    KeyUsage,
+    NameAttribute,
+    NameAttributes,
+    RelativeDistinguishedName,
+    RelativeDistinguishedNames,
+    SubjectAltName,
+    SubjectAltNames,
+    Validity,
+};
+
+pub use name::{DNSNameRef, IpAddrRef, SubjectNameRef};
 
 #[cfg(feature = "std")]
-pub use name::DNSName;
+pub use name::{DNSName, IpAddr, SubjectName};
 
 
 pub use signed_data::{
@@ -93,10 +111,19 @@ pub use signed_data::{
     RSA_PSS_2048_8192_SHA256_LEGACY_KEY,
     RSA_PSS_2048_8192_SHA384_LEGACY_KEY,
     RSA_PSS_2048_8192_SHA512_LEGACY_KEY,
+    RSA_PSS_2048_8192_SHA256,
+    RSA_PSS_2048_8192_SHA384,
+    RSA_PSS_2048_8192_SHA512,
+    ED25519,
 };
 
 pub use time::Time;
 
+pub use verify_cert::ChainCert;
+
+#[cfg(feature = "std")]
+pub use verify_cert::{ChainReport, PathAttempt};
+
 /// An end-entity certificate.
 ///
 /// Server certificate processing in a TLS connection consists of several
@@ -104,7 +131,8 @@ pub use time::Time;
 ///
 /// * `EndEntityCert.verify_is_valid_tls_server_cert`: Verify that the server's
 ///   certificate is currently valid *for use by a TLS server*.
-/// * `EndEntityCert.verify_is_valid_for_dns_name`: Verify that the server's
+/// * `EndEntityCert.verify_is_valid_for_dns_name` or
+///   `EndEntityCert.verify_is_valid_for_ip_address`: Verify that the server's
 ///   certificate is valid for the host that is being connected to.
 /// * `EndEntityCert.verify_signature`: Verify that the signature of server's
 ///   `ServerKeyExchange` message is valid for the server's certificate.
@@ -132,6 +160,10 @@ pub use time::Time;
 /// deterministic, so if these tasks are done in multiple threads, it is
 /// probably best to just call `EndEntityCert::from` multiple times (before each
 /// operation) for the same DER-encoded ASN.1 certificate bytes.
+///
+/// The contents of the certificate (its subject, validity period, and so on)
+/// can also be read, e.g. to describe the certificate in an error message.
+/// None of those accessors validate anything.
 pub struct EndEntityCert<'a> {
     inner: cert::Cert<'a>,
 }
@@ -146,6 +178,52 @@ impl <'a> EndEntityCert<'a> {
         })
     }
 
+    /// The certificate's serial number, as big-endian bytes without any
+    /// leading zero byte.
+    pub fn serial(&self) -> &'a [u8] { self.inner.serial.as_slice_less_safe() }
+
+    /// The certificate's issuer.
+    pub fn issuer_dn(&self) -> Result<DistinguishedName<'a>, Error> {
+        inspect::distinguished_name(self.inner.issuer)
+    }
+
+    /// The certificate's subject.
+    pub fn subject_dn(&self) -> Result<DistinguishedName<'a>, Error> {
+        inspect::distinguished_name(self.inner.subject)
+    }
+
+    /// The certificate's validity period. This doesn't check the
+    /// certificate is valid at any particular time; use
+    /// `verify_is_valid_tls_server_cert` or `verify_is_valid_tls_client_cert`
+    /// for that.
+    pub fn validity(&self) -> Result<Validity, Error> {
+        inspect::validity(self.inner.validity)
+    }
+
+    /// The entries of the certificate's subjectAltName extension. This is
+    /// empty if the certificate doesn't have one.
+    pub fn subject_alt_names(&self) -> Result<SubjectAltNames<'a>, Error> {
+        inspect::subject_alt_names(self.inner.subject_alt_name)
+    }
+
+    /// The contents of the certificate's keyUsage extension, if it has one.
+    pub fn key_usage(&self) -> Result<Option<KeyUsage>, Error> {
+        inspect::key_usage(self.inner.extensions)
+    }
+
+    /// The key purposes in the certificate's extendedKeyUsage extension, if
+    /// it has one.
+    pub fn extended_key_usage(&self)
+                              -> Result<Option<KeyPurposeIds<'a>>, Error> {
+        inspect::extended_key_usage(self.inner.eku)
+    }
+
+    /// All of the certificate's extensions, including ones webpki doesn't
+    /// understand.
+    pub fn extensions(&self) -> Extensions<'a> {
+        inspect::raw_extensions(self.inner.extensions)
+    }
+
     /// Verifies that the end-entity certificate is valid for use by a TLS
     /// server.
     ///
@@ -163,7 +241,8 @@ impl <'a> EndEntityCert<'a> {
             -> Result<(), Error> {
         verify_cert::build_chain(verify_cert::EKU_SERVER_AUTH,
                                  supported_sig_algs, trust_anchors,
-                                 intermediate_certs, &self.inner, time, 0)
+                                 intermediate_certs, &self.inner, time,
+                                 &mut |_, _| ())
     }
 
     /// Verifies that the end-entity certificate is valid for use by a TLS
@@ -187,7 +266,45 @@ impl <'a> EndEntityCert<'a> {
             -> Result<(), Error> {
         verify_cert::build_chain(verify_cert::EKU_CLIENT_AUTH,
                                  supported_sig_algs, trust_anchors,
-                                 intermediate_certs, &self.inner, time, 0)
+                                 intermediate_certs, &self.inner, time,
+                                 &mut |_, _| ())
+    }
+
+    /// Like `verify_is_valid_tls_server_cert`, but also returns a report of
+    /// every path that was tried while looking for a chain to a trust anchor,
+    /// and why each one was rejected. This is slower, and is meant for
+    /// diagnosing why a certificate isn't trusted.
+    ///
+    /// Requires the `std` default feature; i.e. this isn't available in
+    /// `#![no_std]` configurations.
+    #[cfg(feature = "std")]
+    pub fn verify_is_valid_tls_server_cert_with_report(
+            &self, supported_sig_algs: &[&SignatureAlgorithm],
+            &TLSServerTrustAnchors(trust_anchors): &TLSServerTrustAnchors,
+            intermediate_certs: &[untrusted::Input], time: Time)
+            -> (Result<(), Error>, ChainReport) {
+        verify_cert::build_chain_with_report(verify_cert::EKU_SERVER_AUTH,
+                                             supported_sig_algs, trust_anchors,
+                                             intermediate_certs, &self.inner,
+                                             time)
+    }
+
+    /// Like `verify_is_valid_tls_client_cert`, but also returns a report of
+    /// every path that was tried while looking for a chain to a trust anchor,
+    /// and why each one was rejected.
+    ///
+    /// Requires the `std` default feature; i.e. this isn't available in
+    /// `#![no_std]` configurations.
+    #[cfg(feature = "std")]
+    pub fn verify_is_valid_tls_client_cert_with_report(
+            &self, supported_sig_algs: &[&SignatureAlgorithm],
+            &TLSClientTrustAnchors(trust_anchors): &TLSClientTrustAnchors,
+            intermediate_certs: &[untrusted::Input], time: Time)
+            -> (Result<(), Error>, ChainReport) {
+        verify_cert::build_chain_with_report(verify_cert::EKU_CLIENT_AUTH,
+                                             supported_sig_algs, trust_anchors,
+                                             intermediate_certs, &self.inner,
+                                             time)
     }
 
     /// Verifies that the certificate is valid for the given DNS host name.
@@ -196,6 +313,23 @@ impl <'a> EndEntityCert<'a> {
         name::verify_cert_dns_name(&self, dns_name)
     }
 
+    /// Verifies that the certificate is valid for the given IP address.
+    ///
+    /// Only iPAddress subjectAltNames are considered; an IP address in the
+    /// subject CN is never accepted.
+    pub fn verify_is_valid_for_ip_address(&self, ip_address: IpAddrRef)
+                                          -> Result<(), Error> {
+        name::verify_cert_ip_address(&self, ip_address)
+    }
+
+    /// Verifies that the certificate is valid for the given DNS host name
+    /// or IP address.
+    pub fn verify_is_valid_for_subject_name(&self,
+                                            subject_name: SubjectNameRef)
+                                            -> Result<(), Error> {
+        name::verify_cert_subject_name(&self, subject_name)
+    }
+
     /// Verifies that the certificate is valid for at least one of the given DNS
     /// host names.
     ///
@@ -245,8 +379,109 @@ impl <'a> EndEntityCert<'a> {
         signed_data::verify_signature(signature_alg, self.inner.spki, msg,
                                       signature)
     }
+
+    /// Verifies that the DER-encoded OCSP response `response` (for example,
+    /// one stapled by a TLS server) says that the certificate is good.
+    ///
+    /// The response must be a successful basic OCSP response, signed by the
+    /// certificate's issuer or by a responder the issuer delegated to with
+    /// the id-kp-OCSPSigning EKU, and must be current at `time`. The
+    /// certificate's issuer is found among `trust_anchors` and
+    /// `intermediate_certs`; this should be done after the certificate has
+    /// been validated using the same inputs.
+    ///
+    /// If the response says the certificate is revoked then this fails with
+    /// `Error::CertRevoked`. If the response is valid but doesn't say the
+    /// certificate is good or revoked then this fails with
+    /// `Error::UnknownRevocationStatus`.
+    pub fn verify_ocsp_response(
+            &self, supported_sig_algs: &[&SignatureAlgorithm],
+            trust_anchors: &[TrustAnchor],
+            intermediate_certs: &[untrusted::Input],
+            response: untrusted::Input, time: Time) -> Result<(), Error> {
+        ocsp::verify_ocsp_response(supported_sig_algs, trust_anchors,
+                                   intermediate_certs, &self.inner, response,
+                                   time)
+    }
+
+    /// Verifies that neither the certificate nor any of `intermediate_certs`
+    /// is revoked by any of `crls`.
+    ///
+    /// CRLs are matched to certificates by issuer, and are only used if they
+    /// are signed by the key that signed the certificate and are current at
+    /// `time`. Issuers are found among `trust_anchors` and
+    /// `intermediate_certs`; this should be done after the certificate has
+    /// been validated using the same inputs.
+    ///
+    /// If any certificate is revoked then this fails with
+    /// `Error::CertRevoked`. If none of `crls` were issued by the end-entity
+    /// certificate's issuer then this fails with
+    /// `Error::UnknownRevocationStatus`. Intermediate certificates that aren't
+    /// covered by any of `crls` are not treated as an error.
+    pub fn verify_is_not_revoked(
+            &self, supported_sig_algs: &[&SignatureAlgorithm],
+            trust_anchors: &[TrustAnchor],
+            intermediate_certs: &[untrusted::Input],
+            crls: &[CertRevocationList], time: Time) -> Result<(), Error> {
+        for cert_der in intermediate_certs {
+            let intermediate =
+                match cert::parse_cert(*cert_der,
+                                       cert::EndEntityOrCA::EndEntity) {
+                    Ok(intermediate) => intermediate,
+                    Err(..) => { continue; }
+                };
+            match crl::check_crls(supported_sig_algs, trust_anchors,
+                                  intermediate_certs, &intermediate,
+                                  crls, time) {
+                Ok(()) |
+                Err(Error::UnknownIssuer) |
+                Err(Error::UnknownRevocationStatus) => {},
+                Err(e) => { return Err(e); }
+            }
+        }
+
+        crl::check_crls(supported_sig_algs, trust_anchors, intermediate_certs,
+                        &self.inner, crls, time)
+    }
+
+    /// Finds the certificate's issuer among `trust_anchors` and
+    /// `intermediate_certs`, and calls `f` with the value of the issuer's
+    /// `subjectPublicKeyInfo` field, in the same form as `TrustAnchor::spki`.
+    ///
+    /// The issuer is the first one whose subject matches the certificate's
+    /// issuer and whose key verifies the certificate's signature, as for
+    /// `verify_ocsp_response`. If there isn't one then this fails with
+    /// `Error::UnknownIssuer`.
+    pub fn with_issuer_spki<F, R>(
+            &self, supported_sig_algs: &[&SignatureAlgorithm],
+            trust_anchors: &[TrustAnchor],
+            intermediate_certs: &[untrusted::Input], f: F)
+            -> Result<R, Error> where F: FnOnce(&[u8]) -> R {
+        verify_cert::with_issuer(supported_sig_algs, trust_anchors,
+                                 intermediate_certs, &self.inner,
+                                 |_, spki| Ok(f(spki.as_slice_less_safe())))
+    }
+}
+
+/// A certificate revocation list (CRL).
+pub struct CertRevocationList<'a> {
+    inner: crl::CRL<'a>,
 }
 
+impl <'a> CertRevocationList<'a> {
+    /// Parse the ASN.1 DER-encoded X.509 encoding of the CRL `crl_der`.
+    ///
+    /// The CRL's signature is not checked here; that happens when it is used
+    /// by `EndEntityCert::verify_is_not_revoked`. CRLs with critical
+    /// extensions, such as delta CRLs and partitioned CRLs, are rejected with
+    /// `Error::UnsupportedCriticalExtension`.
+    pub fn from(crl_der: untrusted::Input<'a>)
+                -> Result<CertRevocationList<'a>, Error> {
+        Ok(CertRevocationList {
+            inner: crl::parse_crl(crl_der)?
+        })
+    }
+}
 
 /// An error that occurs during certificate validation or name validation.
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -264,6 +499,10 @@ pub enum Error {
     /// later than the certificate's notAfter time.
     CertExpired,
 
+    /// The certificate has been revoked by its issuer, according to an OCSP
+    /// response or CRL.
+    CertRevoked,
+
     /// The certificate is not valid for the name it is being validated for.
     CertNotValidForName,
 
@@ -290,6 +529,11 @@ pub enum Error {
     /// The certificate violates one or more path length constraints.
     PathLenConstraintViolated,
 
+    /// An OCSP response or CRL is not current; i.e. the time it is being
+    /// validated for is earlier than its thisUpdate time or later than its
+    /// nextUpdate time.
+    RevocationDataNotCurrent,
+
     /// The algorithm in the TBSCertificate "signature" field of a certificate
     /// does not match the algorithm in the signature of the certificate.
     SignatureAlgorithmMismatch,
@@ -301,6 +545,12 @@ pub enum Error {
     /// A valid issuer for the certificate could not be found.
     UnknownIssuer,
 
+    /// The revocation status of the certificate could not be determined; e.g.
+    /// the OCSP responder said so, the OCSP response does not cover the
+    /// certificate, or none of the given CRLs were issued by the certificate's
+    /// issuer.
+    UnknownRevocationStatus,
+
     /// The certificate is not a v3 X.509 certificate.
     UnsupportedCertVersion,
 
@@ -343,6 +593,15 @@ pub struct TrustAnchor<'a> {
     pub name_constraints: Option<&'a [u8]>
 }
 
+impl<'a> TrustAnchor<'a> {
+    /// The trust anchor's subject. Trust anchors don't keep the other
+    /// contents of the certificates they're made from, such as the validity
+    /// period.
+    pub fn subject_dn(&self) -> Result<DistinguishedName<'a>, Error> {
+        inspect::distinguished_name(untrusted::Input::from(self.subject))
+    }
+}
+
 /// Trust anchors which may be used for authenticating servers.
 #[derive(Debug)]
 pub struct TLSServerTrustAnchors<'a>(pub &'a [TrustAnchor<'a>]);
@@ -50,7 +50,11 @@ static ALL_SIGALGS: &'static [&'static webpki::SignatureAlgorithm] = &[
     &webpki::RSA_PKCS1_2048_8192_SHA256,
     &webpki::RSA_PKCS1_2048_8192_SHA384,
     &webpki::RSA_PKCS1_2048_8192_SHA512,
-    &webpki::RSA_PKCS1_3072_8192_SHA384
+    &webpki::RSA_PKCS1_3072_8192_SHA384,
+    &webpki::RSA_PSS_2048_8192_SHA256,
+    &webpki::RSA_PSS_2048_8192_SHA384,
+    &webpki::RSA_PSS_2048_8192_SHA512,
+    &webpki::ED25519,
 ];
 
 /* Checks we can verify netflix's cert chain.  This is notable
@@ -81,6 +85,226 @@ pub fn netflix()
         .unwrap();
 }
 
+/* Checks we can verify an Ed25519 chain: the end-entity, intermediate
+ * and root all have Ed25519 keys. */
+#[cfg(feature = "trust_anchor_util")]
+#[test]
+pub fn ed25519()
+{
+    let ee = include_bytes!("ed25519/ee.der");
+    let inter = include_bytes!("ed25519/inter.der");
+    let ca = include_bytes!("ed25519/ca.der");
+
+    let ee_input = untrusted::Input::from(ee);
+    let inter_vec = vec![ untrusted::Input::from(inter) ];
+    let anchors = vec![
+        webpki::trust_anchor_util::cert_der_as_trust_anchor(
+            untrusted::Input::from(ca)
+        ).unwrap()
+    ];
+    let anchors = webpki::TLSServerTrustAnchors(&anchors);
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1800000000);
+
+    let cert = webpki::EndEntityCert::from(ee_input).unwrap();
+    let _ = cert.verify_is_valid_tls_server_cert(ALL_SIGALGS, &anchors,
+                                                 &inter_vec, time)
+        .unwrap();
+}
+
+#[cfg(feature = "trust_anchor_util")]
+fn verify_ip_test_cert(ee: &[u8]) -> Result<(), webpki::Error> {
+    let inter = include_bytes!("ip_address/inter.der");
+    let ca = include_bytes!("ip_address/ca.der");
+
+    let inter_vec = vec![ untrusted::Input::from(inter) ];
+    let anchors = vec![
+        webpki::trust_anchor_util::cert_der_as_trust_anchor(
+            untrusted::Input::from(ca)
+        ).unwrap()
+    ];
+    let anchors = webpki::TLSServerTrustAnchors(&anchors);
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(1800000000);
+
+    let cert = webpki::EndEntityCert::from(untrusted::Input::from(ee))?;
+    cert.verify_is_valid_tls_server_cert(ALL_SIGALGS, &anchors, &inter_vec,
+                                         time)
+}
+
+/* The end-entity certificate has iPAddress subjectAltNames, and is issued
+ * by an intermediate constrained to 10.0.0.0/8 and fd00::/16. */
+#[cfg(feature = "trust_anchor_util")]
+#[test]
+pub fn ip_address_san()
+{
+    let ee = include_bytes!("ip_address/ee.der");
+    assert_eq!(verify_ip_test_cert(ee), Ok(()));
+
+    let cert = webpki::EndEntityCert::from(untrusted::Input::from(ee)).unwrap();
+    let ip = |s| webpki::IpAddrRef::try_from_ascii_str(s).unwrap();
+
+    assert_eq!(cert.verify_is_valid_for_ip_address(ip("10.0.0.5")), Ok(()));
+    assert_eq!(cert.verify_is_valid_for_ip_address(ip("fd00::5")), Ok(()));
+    assert_eq!(cert.verify_is_valid_for_ip_address(ip("fd00:0:0:0:0:0:0:5")),
+               Ok(()));
+    assert_eq!(cert.verify_is_valid_for_ip_address(ip("10.0.0.4")),
+               Err(webpki::Error::CertNotValidForName));
+    assert_eq!(cert.verify_is_valid_for_ip_address(ip("::ffff:10.0.0.5")),
+               Err(webpki::Error::CertNotValidForName));
+
+    // The subject CN is 10.0.0.6, but IP addresses are only taken from
+    // the subjectAltName.
+    assert_eq!(cert.verify_is_valid_for_ip_address(ip("10.0.0.6")),
+               Err(webpki::Error::CertNotValidForName));
+
+    let name = |s| webpki::SubjectNameRef::try_from_ascii_str(s).unwrap();
+    assert_eq!(cert.verify_is_valid_for_subject_name(name("10.0.0.5")), Ok(()));
+    assert_eq!(cert.verify_is_valid_for_subject_name(name("example.com")),
+               Ok(()));
+    assert_eq!(cert.verify_is_valid_for_subject_name(name("10.0.0.4")),
+               Err(webpki::Error::CertNotValidForName));
+}
+
+#[cfg(feature = "trust_anchor_util")]
+#[test]
+pub fn ip_address_name_constraints()
+{
+    let outside = include_bytes!("ip_address/outside.der");
+    assert_eq!(verify_ip_test_cert(outside),
+               Err(webpki::Error::NameConstraintViolation));
+}
+
+#[cfg(feature = "trust_anchor_util")]
+fn ip_test_chain_report(ee: &[u8], intermediates: &[&[u8]], time: u64)
+                        -> (Result<(), webpki::Error>, webpki::ChainReport) {
+    let ca = include_bytes!("ip_address/ca.der");
+
+    let inter_vec: Vec<untrusted::Input> = intermediates.iter()
+        .map(|inter| untrusted::Input::from(inter))
+        .collect();
+    let anchors = vec![
+        webpki::trust_anchor_util::cert_der_as_trust_anchor(
+            untrusted::Input::from(ca)
+        ).unwrap()
+    ];
+    let anchors = webpki::TLSServerTrustAnchors(&anchors);
+
+    let time = webpki::Time::from_seconds_since_unix_epoch(time);
+
+    let cert = webpki::EndEntityCert::from(untrusted::Input::from(ee)).unwrap();
+    cert.verify_is_valid_tls_server_cert_with_report(ALL_SIGALGS, &anchors,
+                                                     &inter_vec, time)
+}
+
+#[cfg(feature = "trust_anchor_util")]
+#[test]
+pub fn chain_report()
+{
+    use webpki::ChainCert::{EndEntity, Intermediate, TrustAnchor};
+    use webpki::Error;
+
+    let ee = include_bytes!("ip_address/ee.der");
+    let outside = include_bytes!("ip_address/outside.der");
+    let inter = include_bytes!("ip_address/inter.der");
+
+    let attempt = |path: &[webpki::ChainCert], result| webpki::PathAttempt {
+        path: path.to_vec(),
+        result,
+    };
+
+    let (result, report) = ip_test_chain_report(ee, &[inter], 1800000000);
+    assert_eq!(result, Ok(()));
+    assert_eq!(report.attempts, vec![
+        attempt(&[EndEntity, Intermediate(0), TrustAnchor(0)], Ok(())),
+    ]);
+
+    // The root is also offered as an intermediate, as is something that
+    // isn't a certificate at all, but neither has a subject matching the
+    // end-entity certificate's issuer, so they aren't reported.
+    let (result, report) =
+        ip_test_chain_report(outside, &[include_bytes!("ip_address/ca.der"),
+                                        b"not a certificate",
+                                        inter], 1800000000);
+    assert_eq!(result, Err(Error::NameConstraintViolation));
+    assert_eq!(report.attempts, vec![
+        attempt(&[EndEntity, Intermediate(2)],
+                Err(Error::NameConstraintViolation)),
+    ]);
+
+    let (result, report) = ip_test_chain_report(ee, &[inter], 3000000000);
+    assert_eq!(result, Err(Error::CertExpired));
+    assert_eq!(report.attempts, vec![
+        attempt(&[EndEntity], Err(Error::CertExpired)),
+    ]);
+
+    let (result, report) = ip_test_chain_report(ee, &[], 1800000000);
+    assert_eq!(result, Err(Error::UnknownIssuer));
+    assert_eq!(report.attempts, vec![
+        attempt(&[EndEntity], Err(Error::UnknownIssuer)),
+    ]);
+}
+
+#[cfg(feature = "trust_anchor_util")]
+#[test]
+pub fn inspect_end_entity_cert()
+{
+    let ee = include_bytes!("ip_address/ee.der");
+    let cert = webpki::EndEntityCert::from(untrusted::Input::from(ee)).unwrap();
+
+    assert_eq!(cert.serial(), &[0x03]);
+
+    let common_name = |dn: webpki::DistinguishedName<'static>| {
+        dn.common_name().and_then(|attribute| attribute.value_as_str())
+    };
+    assert_eq!(common_name(cert.subject_dn().unwrap()), Some("10.0.0.6"));
+    assert_eq!(common_name(cert.issuer_dn().unwrap()),
+               Some("webpki IP constrained CA"));
+    assert_eq!(cert.subject_dn().unwrap().rdns().count(), 1);
+
+    let validity = cert.validity().unwrap();
+    assert_eq!(validity.not_before.as_seconds_since_unix_epoch(), 1792345218);
+    assert_eq!(validity.not_after.as_seconds_since_unix_epoch(), 2423065218);
+
+    let mut dns_names = vec![];
+    let mut ip_addresses = vec![];
+    for name in cert.subject_alt_names().unwrap() {
+        match name {
+            webpki::SubjectAltName::DNSName(name) => dns_names.push(name),
+            webpki::SubjectAltName::IPAddress(addr) => ip_addresses.push(addr),
+            _ => panic!("unexpected subjectAltName"),
+        }
+    }
+    assert_eq!(dns_names, vec!["example.com"]);
+    assert_eq!(ip_addresses, vec![
+        &[10, 0, 0, 5][..],
+        &[0xfd, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 5][..],
+    ]);
+
+    let key_usage = cert.key_usage().unwrap().unwrap();
+    assert!(key_usage.digital_signature());
+    assert!(!key_usage.key_encipherment());
+    assert!(!key_usage.key_cert_sign());
+
+    let ekus: Vec<&[u8]> = cert.extended_key_usage().unwrap().unwrap().collect();
+    assert_eq!(ekus, vec![&[0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x03, 0x01][..]]);
+
+    // basicConstraints, keyUsage, extKeyUsage, subjectAltName,
+    // subjectKeyIdentifier and authorityKeyIdentifier.
+    let extensions: Vec<(u8, bool)> = cert.extensions()
+        .map(|extension| (extension.id[2], extension.critical))
+        .collect();
+    assert_eq!(extensions, vec![(19, true), (15, true), (37, false),
+                                (17, false), (14, false), (35, false)]);
+
+    let ca = include_bytes!("ip_address/ca.der");
+    let anchor = webpki::trust_anchor_util::cert_der_as_trust_anchor(
+        untrusted::Input::from(ca)
+    ).unwrap();
+    assert_eq!(common_name(anchor.subject_dn().unwrap()),
+               Some("webpki IP test CA"));
+}
+
 #[cfg(feature = "trust_anchor_util")]
 #[test]
 fn read_root_with_zero_serial() {
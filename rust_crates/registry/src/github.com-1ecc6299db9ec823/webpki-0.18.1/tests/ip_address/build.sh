@@ -0,0 +1,57 @@
+#!/bin/sh
+#
+# Generates the certificates used by the IP address tests in
+# ../integration.rs.  Needs OpenSSL 1.1.1 or later.
+
+set -xe
+
+cat > ext.cnf <<'CNF'
+[ v3_ca ]
+basicConstraints = critical,CA:true
+keyUsage = critical, keyCertSign, cRLSign
+
+[ v3_constrained ]
+basicConstraints = critical,CA:true
+keyUsage = critical, keyCertSign, cRLSign
+nameConstraints = critical, permitted;IP:10.0.0.0/255.0.0.0, permitted;IP:fd00::/ffff::
+
+[ v3_ee ]
+basicConstraints = critical,CA:false
+keyUsage = critical, digitalSignature
+extendedKeyUsage = serverAuth
+subjectAltName = DNS:example.com, IP:10.0.0.5, IP:fd00::5
+
+[ v3_ee_outside ]
+basicConstraints = critical,CA:false
+keyUsage = critical, digitalSignature
+extendedKeyUsage = serverAuth
+subjectAltName = IP:192.168.0.5
+CNF
+
+key() {
+  openssl genpkey -algorithm ec -pkeyopt ec_paramgen_curve:P-256 -out $1.key
+}
+
+key ca
+openssl req -x509 -new -key ca.key -subj "/CN=webpki IP test CA" \
+        -days 7300 -sha256 -extensions v3_ca -config ext.cnf -out ca.pem
+
+key inter
+openssl req -new -key inter.key -subj "/CN=webpki IP constrained CA" -out inter.req
+openssl x509 -req -in inter.req -CA ca.pem -CAkey ca.key -set_serial 2 \
+        -days 7300 -sha256 -extfile ext.cnf -extensions v3_constrained -out inter.pem
+
+key ee
+openssl req -new -key ee.key -subj "/CN=10.0.0.6" -out ee.req
+openssl x509 -req -in ee.req -CA inter.pem -CAkey inter.key -set_serial 3 \
+        -days 7300 -sha256 -extfile ext.cnf -extensions v3_ee -out ee.pem
+
+openssl req -new -key ee.key -subj "/CN=webpki IP test outside" -out outside.req
+openssl x509 -req -in outside.req -CA inter.pem -CAkey inter.key -set_serial 4 \
+        -days 7300 -sha256 -extfile ext.cnf -extensions v3_ee_outside -out outside.pem
+
+for cert in ca inter ee outside ; do
+  openssl x509 -in $cert.pem -outform DER -out $cert.der
+done
+
+rm -f *.key *.req *.pem ext.cnf
@@ -0,0 +1,126 @@
+// Copyright 2018 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+// ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+// ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+extern crate untrusted;
+extern crate webpki;
+
+// (IP address, expected octets if valid)
+static IP_ADDRESS_VALIDITY: &[(&'static [u8], Option<&'static [u8]>)] = &[
+    (b"", None),
+
+    (b"1", None),
+    (b"1.2", None),
+    (b"1.2.3", None),
+    (b"1.2.3.4", Some(&[1, 2, 3, 4])),
+    (b"1.2.3.4.5", None),
+    (b"1.2.3.4a", None),
+
+    // Range
+    (b"0.0.0.0", Some(&[0, 0, 0, 0])),
+    (b"255.255.255.255", Some(&[255, 255, 255, 255])),
+    (b"256.0.0.0", None),
+    (b"0.0.0.256", None),
+    (b"9999999999999999999.0.0.0", None),
+
+    // Leading zeros, whitespace, empty components
+    (b"01.2.3.4", None),
+    (b"1.2.3.04", None),
+    (b" 1.2.3.4", None),
+    (b"1.2.3.4\n", None),
+    (b"1..3.4", None),
+    (b"1.2.3.", None),
+    (b".1.2.3.4", None),
+    (b"1.2.3.4\0", None),
+
+    // Other representations of IPv4 addresses
+    (b"0xC0.0x00.0x02.0xEB", None),
+    (b"3221226219", None),
+
+    (b"1234:5678:9abc:def0:1234:5678:9abc:def0",
+     Some(&[0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0,
+            0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0])),
+    (b"1234:5678:9abc:def0:1234:5678:9abc", None),
+    (b"1234:5678:9abc:def0:1234:5678:9abc:def0:", None),
+    (b":1234:5678:9abc:def0:1234:5678:9abc:def0", None),
+    (b"1234:5678:9abc:def0:1234:5678:9abc:def0:0000", None),
+
+    // Contractions
+    (b"::", Some(&[0; 16])),
+    (b"::1", Some(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1])),
+    (b"1234::", Some(&[0x12, 0x34, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0])),
+    (b"1234::5678",
+     Some(&[0x12, 0x34, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x56, 0x78])),
+    (b"1234:5678:9abc:def0:1234:5678:9abc::",
+     Some(&[0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0,
+            0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0, 0])),
+    (b"::1234:5678:9abc:def0:1234:5678:9abc:def0", None),
+    (b"1234:5678::9abc:def0:1234:5678:9abc:def0", None),
+    (b"::1::", None),
+    (b"1::2::", None),
+    (b":", None),
+    (b":::", None),
+    (b"1:::2", None),
+    (b":1234::", None),
+    (b"::1234:", None),
+
+    // Digits per group
+    (b"::0001", Some(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1])),
+    (b"01234::", None),
+
+    // Case
+    (b"ABCD:efab::",
+     Some(&[0xab, 0xcd, 0xef, 0xab, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0])),
+    (b"abcg::", None),
+
+    // IPv4-style
+    (b"::2.3.4.5", Some(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 3, 4, 5])),
+    (b"::ffff:2.3.4.5",
+     Some(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, 2, 3, 4, 5])),
+    (b"1234:5678:9abc:def0:1234:5678:252.253.254.255",
+     Some(&[0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0,
+            0x12, 0x34, 0x56, 0x78, 252, 253, 254, 255])),
+    (b"1234:5678:9abc:def0:1234:5678::252.253.254.255", None),
+    (b"2.3.4.5::", None),
+    (b"1234::252.253.254", None),
+    (b"::252.253.254.300", None),
+    (b"1234::252.253.254.255:5678", None),
+
+    // Zone identifiers and brackets
+    (b"fe80::1%eth0", None),
+    (b"[::1]", None),
+];
+
+#[test]
+fn ip_address_ref_try_from_ascii_test() {
+    for &(s, expected) in IP_ADDRESS_VALIDITY {
+        let actual = webpki::IpAddrRef::try_from_ascii(untrusted::Input::from(s))
+            .ok()
+            .map(|addr| addr.octets().to_vec());
+        assert_eq!(actual, expected.map(|octets| octets.to_vec()),
+                   "IpAddrRef::try_from_ascii failed for \"{:?}\"", s);
+    }
+}
+
+#[test]
+fn subject_name_ref_prefers_ip_addresses() {
+    match webpki::SubjectNameRef::try_from_ascii_str("10.0.0.5") {
+        Ok(webpki::SubjectNameRef::IPAddress(_)) => (),
+        _ => panic!("10.0.0.5 should be an IP address"),
+    }
+    match webpki::SubjectNameRef::try_from_ascii_str("example.com") {
+        Ok(webpki::SubjectNameRef::DNSName(_)) => (),
+        _ => panic!("example.com should be a DNS name"),
+    }
+    assert!(webpki::SubjectNameRef::try_from_ascii_str("not valid").is_err());
+}
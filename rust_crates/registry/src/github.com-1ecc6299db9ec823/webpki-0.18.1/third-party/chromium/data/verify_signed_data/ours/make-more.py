@@ -0,0 +1,76 @@
+# Copyright 2018 Brian Smith.
+#
+# Permission to use, copy, modify, and/or distribute this software for any
+# purpose with or without fee is hereby granted, provided that the above
+# copyright notice and this permission notice appear in all copies.
+#
+# THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+# WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+# MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR
+# ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+# WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+# ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+# OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+# Generates the Ed25519 and id-RSASSA-PSS-key test vectors.  Needs
+# OpenSSL 1.1.1 or later.
+
+import base64
+import os
+import subprocess
+import tempfile
+
+TOP = '../../../../../'
+
+def dump(bin, type):
+    return '-----BEGIN %s-----\n%s\n-----END %s-----\n' % \
+            (type, base64.encodebytes(bin).decode('ascii').strip(), type)
+
+def wrap_sequence(value):
+    assert len(value) < 128
+    return b'\x30' + bytes([len(value)]) + value
+
+def bit_string(sig):
+    body = b'\x00' + sig
+    if len(body) < 128:
+        return b'\x03' + bytes([len(body)]) + body
+    return b'\x03\x82' + len(body).to_bytes(2, 'big') + body
+
+def openssl(*args, input = None):
+    return subprocess.run(['openssl'] + list(args), input = input,
+                          stdout = subprocess.PIPE, check = True).stdout
+
+def write(outfile, pub, alg, data, sig):
+    with open(outfile, 'w') as f:
+        print(dump(pub, 'PUBLIC KEY'), file = f)
+        print(dump(alg, 'ALGORITHM'), file = f)
+        print(dump(data, 'DATA'), file = f)
+        print(dump(bit_string(sig), 'SIGNATURE'), file = f)
+
+def corrupt(data):
+    return data[:-1] + bytes([data[-1] ^ 1])
+
+def gen(name, keyfile, alg, signopts):
+    pub = openssl('pkey', '-in', keyfile, '-pubout', '-outform', 'DER')
+    data = os.urandom(64)
+    with tempfile.NamedTemporaryFile() as msg:
+        msg.write(data)
+        msg.flush()
+        sig = openssl('pkeyutl', '-sign', '-rawin', '-inkey', keyfile,
+                      '-in', msg.name, *signopts)
+    write(name + '.pem', pub, alg, data, sig)
+    write(name + '-corrupted-data.pem', pub, alg, corrupt(data), sig)
+
+if __name__ == '__main__':
+    openssl('genpkey', '-algorithm', 'ed25519', '-out', 'ed25519-priv.pem')
+    openssl('genpkey', '-algorithm', 'rsa-pss',
+            '-pkeyopt', 'rsa_keygen_bits:2048', '-out', 'pss-priv.pem')
+
+    gen('ed25519', 'ed25519-priv.pem',
+        wrap_sequence(open(TOP + 'src/data/alg-ed25519.der', 'rb').read()),
+        [])
+    gen('rsa-pss-sha256-salt32-using-pss-key-no-params', 'pss-priv.pem',
+        wrap_sequence(open(TOP + 'src/data/alg-rsa-pss-sha256.der', 'rb').read()),
+        ['-digest', 'sha256',
+         '-pkeyopt', 'rsa_padding_mode:pss',
+         '-pkeyopt', 'rsa_pss_saltlen:32'])